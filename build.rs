@@ -1,10 +1,15 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut config = prost_build::Config::new();
     config.protoc_arg("--experimental_allow_proto3_optional");
-    // Output the generated rs files to `src/proto/`
+    // Output the generated rs files to `src/proto/`, the descriptor set is served by gRPC reflection
     tonic_build::configure()
         .out_dir("src/proto/")
-        .compile_with_config(config, &["src/proto/aya_cardano.proto"], &["proto"])?;
+        .file_descriptor_set_path("src/proto/cdp_descriptor.bin")
+        .compile_with_config(
+            config,
+            &["src/proto/aya_cardano.proto", "src/proto/chain_query.proto"],
+            &["proto"],
+        )?;
 
     Ok(())
 }
@@ -6,7 +6,5 @@ pub async fn main() {
     pretty_env_logger::init();
     let config = cdp::config::ConfigRoot::new(&None).unwrap();
     config.set_as_env();
-    cdp::server::serve(Some(config.connectivity.dbsync_url))
-        .await
-        .unwrap();
+    cdp::server::serve(&config).await.unwrap();
 }
@@ -1,11 +1,14 @@
+use crate::config::ChainWellKnownInfo;
 use crate::provider::error::DataProviderError;
 use crate::models::{CDPDatum, RewardView, TokenInfoView, CardanoNativeAssetView, StakeDelegationView, DelegationView, 
-    StakeRegistrationView, StakeDeregistrationView, HoldingWalletView, TxHistoryListView, PoolView
+    StakeRegistrationView, StakeDeregistrationView, HoldingWalletView, TxHistoryListView, PoolView,
+    AddressTxView, StakeBalanceView,
 };
 
 use async_trait::async_trait;
 use blockfrost::{load, BlockFrostApi};
 use bigdecimal::BigDecimal;
+use self::error::DataProviderBlockfrostError;
 
 use ::log::debug;
 
@@ -13,34 +16,67 @@ pub mod api;
 pub mod error;
 pub mod models;
 
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// blockfrost project id, sent as `project_id` header
+    pub project_id: String,
+    /// base url of the blockfrost api, e.g. `https://cardano-mainnet.blockfrost.io/api/v0`
+    pub network_address: String,
+    /// addresses passed to the provider are checked against this network
+    pub network: ChainWellKnownInfo,
+}
+
 pub struct BlockfrostProvider {
     api: BlockFrostApi,
+    network: ChainWellKnownInfo,
 }
 
 unsafe impl Send for BlockfrostProvider {}
 unsafe impl Sync for BlockfrostProvider {}
 
 impl BlockfrostProvider {
-    pub fn new() -> Self {
-        let api = BlockfrostProvider::build_api().unwrap();
-        BlockfrostProvider { api }
+    pub fn new(config: Config) -> Self {
+        let mut blockfrost_settings = blockfrost::BlockFrostSettings::default();
+        blockfrost_settings.network_address = config.network_address;
+
+        debug!("bfs: {:?}", blockfrost_settings);
+        let api = BlockFrostApi::new(&config.project_id, blockfrost_settings);
+        BlockfrostProvider {
+            api,
+            network: config.network,
+        }
     }
 
-    fn build_api() -> blockfrost::Result<BlockFrostApi> {
+    /// Reads `project_id` and `cardano_network` the way `blockfrost::load` does,
+    /// the network is derived from the blockfrost host name
+    pub fn from_env() -> Result<Self, DataProviderBlockfrostError> {
         let configurations = load::configurations_from_env()?;
 
         debug!("configurations: {:?}", configurations);
 
-        let mut blockfrost_settings = blockfrost::BlockFrostSettings::default();
-        blockfrost_settings.network_address = configurations["cardano_network"].as_str().unwrap().to_owned();
-
-        debug!("bfs: {:?}", blockfrost_settings);
-        let api = BlockFrostApi::new(
-            configurations["project_id"].as_str().unwrap(),
-            blockfrost_settings,
-        );
-
-        Ok(api)
+        let setting = |key: &str| {
+            configurations[key]
+                .as_str()
+                .map(|s| s.to_owned())
+                .ok_or_else(|| DataProviderBlockfrostError::Custom(format!("{key} is not set")))
+        };
+
+        let network_address = setting("cardano_network")?;
+        let network = if network_address.contains("cardano-preview") {
+            ChainWellKnownInfo::preview()
+        } else if network_address.contains("cardano-preprod") {
+            ChainWellKnownInfo::preprod()
+        } else if network_address.contains("cardano-testnet") {
+            ChainWellKnownInfo::testnet()
+        } else {
+            ChainWellKnownInfo::mainnet()
+        };
+
+        Ok(BlockfrostProvider::new(Config {
+            project_id: setting("project_id")?,
+            network_address,
+            network,
+        }))
     }
 }
 
@@ -54,6 +90,7 @@ impl super::provider::CardanoDataProvider for BlockfrostProvider {
         &self,
         stake_addr: &str,
     ) -> Result<dcslc::TransactionUnspentOutputs, DataProviderError> {
+        self.network.check_address(stake_addr)?;
         Ok(api::get_stake_address_utxos(self, stake_addr).await?)
     }
 
@@ -61,6 +98,7 @@ impl super::provider::CardanoDataProvider for BlockfrostProvider {
         &self,
         addr: &str,
     ) -> Result<dcslc::TransactionUnspentOutputs, DataProviderError> {
+        self.network.check_address(addr)?;
         Ok(api::get_address_utxos(self, addr).await?)
     }
 
@@ -68,6 +106,7 @@ impl super::provider::CardanoDataProvider for BlockfrostProvider {
         &self,
         addr: &str,
     ) -> Result<dcslc::TransactionUnspentOutputs, DataProviderError> {
+        self.network.check_address(addr)?;
         Ok(api::asset_utxos_on_addr(self, addr).await?)
     }
 
@@ -75,7 +114,7 @@ impl super::provider::CardanoDataProvider for BlockfrostProvider {
         &self,
         fingerprint_in: &str,
     ) -> Result<TokenInfoView, DataProviderError> {
-        Ok(api::mint_metadata(self, fingerprint_in)?)
+        Ok(api::mint_metadata(self, fingerprint_in).await?)
     }
 
     async fn first_transaction_from_stake_addr(
@@ -85,7 +124,8 @@ impl super::provider::CardanoDataProvider for BlockfrostProvider {
         cardano_serialization_lib::address::Address,
         DataProviderError,
     > {
-        let str_addr = api::select_addr_of_first_transaction(self, stake_address_in)?;
+        self.network.check_address(stake_address_in)?;
+        let str_addr = api::select_addr_of_first_transaction(self, stake_address_in).await?;
         Ok(dcslc::addr_from_str(&str_addr)?)
     }
 
@@ -94,7 +134,8 @@ impl super::provider::CardanoDataProvider for BlockfrostProvider {
         addr: &str,
         datumhash: &Vec<u8>,
     ) -> Result<dcslc::TransactionUnspentOutput, DataProviderError> {
-        let utxo = api::utxo_by_dataumhash(self, addr, datumhash)?;
+        self.network.check_address(addr)?;
+        let utxo = api::utxo_by_dataumhash(self, addr, datumhash).await?;
         Ok(utxo)
     }
 
@@ -104,7 +145,7 @@ impl super::provider::CardanoDataProvider for BlockfrostProvider {
         txhash: &Vec<u8>,
         index: i16,
     ) -> Result<dcslc::TransactionUnspentOutput, DataProviderError> {
-        let utxo = api::utxo_by_txid(self, txhash, index)?;
+        let utxo = api::utxo_by_txid(self, txhash, index).await?;
         Ok(utxo)
     }
 
@@ -129,7 +170,7 @@ impl super::provider::CardanoDataProvider for BlockfrostProvider {
         &self,
         txid: &Vec<u8>,
     ) -> Result<Vec<CDPDatum>, DataProviderError> {
-        Ok(api::find_datums_for_tx(self, txid)?)
+        Ok(api::find_datums_for_tx(self, txid).await?)
     }
 
     async fn slot(&self) -> Result<i64, DataProviderError> {
@@ -142,7 +183,7 @@ impl super::provider::CardanoDataProvider for BlockfrostProvider {
         epoch: i32,
     ) -> Result<Vec<StakeDelegationView>, DataProviderError>
     {
-        Ok(api::pool_delegations(self, pool, epoch)?)
+        Ok(api::pool_delegations(self, pool, epoch).await?)
     }
 
     async fn deligations_per_pool_epoch_intervall(
@@ -156,7 +197,7 @@ impl super::provider::CardanoDataProvider for BlockfrostProvider {
             pool,
             start_epoch,
             end_epoch,
-        )?)
+        ).await?)
     }
 
     async fn pool_total_staked(
@@ -164,7 +205,7 @@ impl super::provider::CardanoDataProvider for BlockfrostProvider {
         pool: &str,
         epoch: i32,
     ) -> Result<u64, DataProviderError> {
-        Ok(api::pool_total_stake(self, pool, epoch)?)
+        Ok(api::pool_total_stake(self, pool, epoch).await?)
     }
 
     async fn current_epoch(&self) -> Result<i32, DataProviderError> {
@@ -183,7 +224,7 @@ impl super::provider::CardanoDataProvider for BlockfrostProvider {
         &self,
         fingerprint_in: &str,
     ) -> Result<TokenInfoView, DataProviderError> {
-        Ok(api::token_info(self, fingerprint_in)?)
+        Ok(api::token_info(self, fingerprint_in).await?)
     }
 
     async fn stake_registration(
@@ -191,7 +232,8 @@ impl super::provider::CardanoDataProvider for BlockfrostProvider {
         stake_addr_in: &str,
     ) -> Result<Vec<StakeRegistrationView>, DataProviderError>
     {
-        Ok(api::stake_registration(self, stake_addr_in)?)
+        self.network.check_address(stake_addr_in)?;
+        Ok(api::stake_registration(self, stake_addr_in).await?)
     }
 
     async fn stake_deregistration(
@@ -201,14 +243,20 @@ impl super::provider::CardanoDataProvider for BlockfrostProvider {
         Vec<StakeDeregistrationView>,
         DataProviderError,
     > {
-        Ok(api::stake_deregistration(self, stake_addr_in)?)
+        self.network.check_address(stake_addr_in)?;
+        Ok(api::stake_deregistration(self, stake_addr_in).await?)
+    }
+
+    async fn tx_included(&self, tx_hash: &str) -> Result<bool, DataProviderError> {
+        Ok(api::tx_included(self, tx_hash).await?)
     }
 
     async fn check_stakeaddr_registered(
         &self,
         stake_addr_in: &str,
     ) -> Result<bool, DataProviderError> {
-        Ok(api::check_stakeaddr_registered(self, stake_addr_in)?)
+        self.network.check_address(stake_addr_in)?;
+        Ok(api::check_stakeaddr_registered(self, stake_addr_in).await?)
     }
 
     async fn lookup_token_holders(
@@ -217,7 +265,7 @@ impl super::provider::CardanoDataProvider for BlockfrostProvider {
         min_amount: Option<&i64>,
     ) -> Result<Vec<HoldingWalletView>, DataProviderError>
     {
-        Ok(api::lookup_token_holders(self, fingerprint_in, min_amount)?)
+        Ok(api::lookup_token_holders(self, fingerprint_in, min_amount).await?)
     }
 
     async fn lookup_nft_token_holders(
@@ -225,27 +273,28 @@ impl super::provider::CardanoDataProvider for BlockfrostProvider {
         policy: &str,
     ) -> Result<Vec<HoldingWalletView>, DataProviderError>
     {
-        Ok(api::lookup_nft_token_holders(self, policy)?)
+        Ok(api::lookup_nft_token_holders(self, policy).await?)
     }
 
     async fn pool_valid(
         &self,
         pool_id: &str,
     ) -> Result<bool, DataProviderError> {
-        Ok(api::pool_valid(self, pool_id)?)
+        Ok(api::pool_valid(self, pool_id).await?)
     }
 
     async fn txhash_spent(
         &self,
         txhash: &str,
     ) -> Result<bool, DataProviderError> {
-        Ok(api::txhash_spent(self, txhash)?)
+        Ok(api::txhash_spent(self, txhash).await?)
     }
 
     async fn addresses_exist(
         &self,
         address: &Vec<&str>,
     ) -> Result<Vec<bool>, DataProviderError> {
+        self.network.check_addresses(address)?;
         Ok(api::addresses_exist(self, address).await?)
     }
 
@@ -255,14 +304,41 @@ impl super::provider::CardanoDataProvider for BlockfrostProvider {
         slot: Option<u64>,
     ) -> Result<Vec<TxHistoryListView>, DataProviderError>
     {
+        self.network.check_addresses(addresses)?;
         Ok(api::get_addresses_transactions(self, addresses, slot).await?)
     }
 
+    async fn address_tx_history(
+        &self,
+        addresses: &Vec<&str>,
+        slot: Option<u64>,
+    ) -> Result<Vec<AddressTxView>, DataProviderError> {
+        self.network.check_addresses(addresses)?;
+        Ok(api::address_tx_history(self, addresses, slot).await?)
+    }
+
+    async fn stake_address_addresses(
+        &self,
+        stake_addr: &str,
+    ) -> Result<Vec<String>, DataProviderError> {
+        self.network.check_address(stake_addr)?;
+        Ok(api::stake_address_addresses(self, stake_addr).await?)
+    }
+
+    async fn stake_address_balance(
+        &self,
+        stake_addr: &str,
+    ) -> Result<StakeBalanceView, DataProviderError> {
+        self.network.check_address(stake_addr)?;
+        Ok(api::stake_address_balance(self, stake_addr).await?)
+    }
+
     async fn retrieve_staked_amount (
         &self,
         epoch: i32,
         stake_addr: &str,
     ) -> Result<BigDecimal, DataProviderError> {
+        self.network.check_address(stake_addr)?;
         Ok(api::retrieve_staked_amount(self, epoch, stake_addr).await?)
     }
 
@@ -270,6 +346,7 @@ impl super::provider::CardanoDataProvider for BlockfrostProvider {
         &self,
         stake_addr: &str,
     ) -> Result<Vec<RewardView>, DataProviderError> {
+        self.network.check_address(stake_addr)?;
         Ok(api::retrieve_generated_rewards(self, stake_addr).await?)
     }
 }
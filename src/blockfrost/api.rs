@@ -1,182 +1,238 @@
 use blockfrost::{stream::StreamExt, AddressTransaction, Transaction, PoolMetadata, AccountHistory, AccountReward};
-use itertools::Itertools;
 
 use super::error::DataProviderBlockfrostError;
 use super::BlockfrostProvider;
 use crate::models::{
-    CDPDatum, CardanoNativeAssetView, DelegationView, HoldingWalletView, StakeDelegationView,
-    StakeDeregistrationView, StakeRegistrationView, TokenInfoView, RewardView, TxHistoryListView, PoolView
+    AddressTxView, AssetHandle, CDPDatum, CardanoNativeAssetView, DelegationView, HoldingWalletView, StakeDelegationView,
+    StakeDeregistrationView, StakeRegistrationView, TokenInfoView, RewardView, TxHistoryListView, PoolView,
+    StakeBalanceView, TxValueDelta,
 };
 
 use cardano_serialization_lib as csl;
 use crate::provider::error::DataProviderError;
 use blockfrost::{AccountAddress, AddressUtxo};
 use bigdecimal::BigDecimal;
+use std::collections::HashSet;
+use std::str::FromStr;
 use array_tool::vec::Uniq;
+use dcslc::make_fingerprint;
 
 use log::debug;
 
 const BLOCKFROST_FETCH_MAX_ITEMS: usize = 10000;
 
+fn is_not_found(err: &blockfrost::Error) -> bool {
+    matches!(err, blockfrost::Error::Response { reason, .. } if reason.status_code == 404)
+}
+
+/// Collects all pages of a blockfrost lister, an unknown resource (404) yields an empty list
+async fn collect_all<T, S>(lister: S) -> Result<Vec<T>, DataProviderBlockfrostError>
+where
+    S: futures::Stream<Item = blockfrost::Result<Vec<T>>>,
+{
+    let mut out = Vec::<T>::new();
+    // TODO: See if we could fold with take_while instead of take(BLOCKFROST_FETCH_MAX_ITEMS)
+    let mut lister = Box::pin(lister.take(BLOCKFROST_FETCH_MAX_ITEMS));
+    while let Some(n) = lister.next().await {
+        let n = match n {
+            Ok(n) => n,
+            Err(e) if is_not_found(&e) => break,
+            Err(e) => return Err(e.into()),
+        };
+        if n.is_empty() {
+            break;
+        };
+        out.extend(n);
+    }
+    Ok(out)
+}
+
+/// Blockfrost cannot resolve fingerprints, assets are identified by their unit (policy id + hex encoded asset name)
+fn asset_unit(fingerprint_in: &str) -> Result<String, DataProviderBlockfrostError> {
+    // See https://blockfrost.dev/support/cardano#poolpm-uses-fingerprints-for-querying-assets-why-dont-you-too
+    if fingerprint_in.len() < 56 || hex::decode(fingerprint_in).is_err() {
+        return Err(DataProviderBlockfrostError::Unsupported(
+            "Fingerprint unsupported, use policy/assetname instead".to_string(),
+        ));
+    }
+    Ok(fingerprint_in.to_owned())
+}
+
+/// Returns the bech32 reward address of a base address
+fn stake_address_of(address: &str) -> Option<String> {
+    let addr = csl::address::Address::from_bech32(address).ok()?;
+    let base = csl::address::BaseAddress::from_address(&addr)?;
+    csl::address::RewardAddress::new(addr.network_id().ok()?, &base.stake_cred())
+        .to_address()
+        .to_bech32(None)
+        .ok()
+}
+
+/// Returns `true` if the payment part of a shelley address is a script
+fn addr_has_script(address: &str) -> bool {
+    match csl::address::Address::from_bech32(address) {
+        Ok(addr) => {
+            let header_type = addr.to_bytes()[0] >> 4;
+            header_type < 8 && header_type & 1 == 1
+        }
+        Err(_) => false,
+    }
+}
+
 /// get all tokens of an utxo
 pub fn get_utxo_tokens(
-    bfp: &BlockfrostProvider,
-    tx_id: i64,
-    tx_index: i16,
+    _bfp: &BlockfrostProvider,
+    _tx_id: i64,
+    _tx_index: i16,
 ) -> Result<Vec<CardanoNativeAssetView>, DataProviderBlockfrostError> {
-    todo!()
+    // tx_id is a dbsync internal id which is not exposed by blockfrost
+    Err(DataProviderBlockfrostError::Unsupported(
+        "utxo_tokens requires dbsync transaction ids, use utxo_by_txid instead".to_string(),
+    ))
 }
 
-pub fn select_addr_of_first_transaction(
+pub async fn select_addr_of_first_transaction(
     bfp: &BlockfrostProvider,
     stake_address_in: &str,
 ) -> Result<String, DataProviderBlockfrostError> {
-    Ok("".to_owned())
+    // blockfrost lists the addresses of an account in the order of their first appearance
+    collect_all(bfp.api.accounts_addresses_all(stake_address_in))
+        .await?
+        .into_iter()
+        .map(|a: AccountAddress| a.address)
+        .next()
+        .ok_or_else(|| DataProviderBlockfrostError::RequestValueNotFound(stake_address_in.to_owned()))
 }
 
 /// get all utxos of an address
-pub fn utxo_by_dataumhash(
+pub async fn utxo_by_dataumhash(
     bfp: &BlockfrostProvider,
     addr: &str,
     datumhash: &Vec<u8>,
 ) -> Result<dcslc::TransactionUnspentOutput, DataProviderBlockfrostError> {
-    Err(DataProviderBlockfrostError::Custom(
-        "not implemented".to_string(),
-    ))
+    let datumhash = hex::encode(datumhash);
+    let address_utxos: Vec<AddressUtxo> = collect_all(bfp.api.addresses_utxos_all(addr)).await?;
+    match address_utxos
+        .iter()
+        .find(|u| u.data_hash.as_ref() == Some(&datumhash))
+    {
+        Some(u) => bf_utxo_as_csl_utxo(u),
+        None => Err(DataProviderBlockfrostError::RequestValueNotFound(datumhash)),
+    }
 }
 
 /// returns Utxo of a certain datumhash on an address
-pub fn utxo_by_txid(
+pub async fn utxo_by_txid(
     bfp: &BlockfrostProvider,
     txhash: &Vec<u8>,
     index: i16,
 ) -> Result<dcslc::TransactionUnspentOutput, DataProviderBlockfrostError> {
-    Err(DataProviderBlockfrostError::Custom(
-        "not implemented".to_string(),
-    ))
+    let txhash = hex::encode(txhash);
+    let not_found = || DataProviderBlockfrostError::RequestValueNotFound(format!("{txhash}#{index}"));
+
+    let tx_utxos = bfp.api.transactions_utxos(&txhash).await?;
+    let output = tx_utxos.outputs.get(index as usize).ok_or_else(not_found)?;
+
+    // the utxo is only contained in the utxo set of its address as long as it is unspent
+    let address_utxos: Vec<AddressUtxo> =
+        collect_all(bfp.api.addresses_utxos_all(&output.address)).await?;
+    match address_utxos
+        .iter()
+        .find(|u| u.tx_hash == txhash && u.output_index as i64 == index as i64)
+    {
+        Some(u) => bf_utxo_as_csl_utxo(u),
+        None => Err(not_found()),
+    }
 }
 
 // Convert a Blockfrost UTxO (AddressUtxo) to CSL UTxO (TransactionUnspentOutput)
-fn bf_utxo_as_csl_utxo(address_utxo: &AddressUtxo) -> csl::utils::TransactionUnspentOutput {
-    //debug!("C1: {:?}", address_utxo.tx_hash.clone());
+fn bf_utxo_as_csl_utxo(
+    address_utxo: &AddressUtxo,
+) -> Result<csl::utils::TransactionUnspentOutput, DataProviderBlockfrostError> {
     let input = csl::TransactionInput::new(
-        &csl::crypto::TransactionHash::from_bytes(hex::decode(&address_utxo.tx_hash).unwrap()).unwrap(),
-        address_utxo.output_index,
+        &csl::crypto::TransactionHash::from_bytes(hex::decode(&address_utxo.tx_hash)?)?,
+        address_utxo.output_index as u32,
     );
 
-    let caddr = csl::address::Address::from_bech32(&address_utxo.address).unwrap();
+    let caddr = csl::address::Address::from_bech32(&address_utxo.address)?;
     let mut cvalue = csl::utils::Value::zero();
     let mut ma = csl::MultiAsset::new();
     for amount in address_utxo.amount.iter() {
-        let qty = csl::utils::BigNum::from_str(&amount.quantity).unwrap();
-        //debug!("amount: {:?}", amount);
+        let qty = csl::utils::BigNum::from_str(&amount.quantity)?;
         if amount.unit == "lovelace" {
             cvalue.set_coin(&qty);
         } else {
+            if amount.unit.len() < 56 {
+                return Err(DataProviderBlockfrostError::Custom(format!(
+                    "invalid asset unit: {}",
+                    amount.unit
+                )));
+            }
             let (hexpolicy, hexname) = amount.unit.split_at(56);
             ma.set_asset(
-                &csl::PolicyID::from_bytes(hex::decode(hexpolicy).unwrap()).unwrap(),
-                &csl::AssetName::new(hex::decode(hexname).unwrap()).unwrap(),
-                qty);
+                &csl::PolicyID::from_bytes(hex::decode(hexpolicy)?)?,
+                &csl::AssetName::new(hex::decode(hexname)?)?,
+                qty,
+            );
         }
-    };
+    }
     if ma.len() > 0 {
         cvalue.set_multiasset(&ma);
     }
 
     let mut output = csl::TransactionOutput::new(&caddr, &cvalue);
 
-    address_utxo.inline_datum.as_ref().map(|datum| {
-        let plutus_data = csl::plutus::PlutusData::from_hex(&datum).unwrap();
-        output.set_plutus_data(&plutus_data);
-    });
+    if let Some(datum) = &address_utxo.inline_datum {
+        output.set_plutus_data(&csl::plutus::PlutusData::from_hex(datum)?);
+    } else if let Some(hash) = &address_utxo.data_hash {
+        output.set_data_hash(&csl::crypto::DataHash::from_bytes(hex::decode(hash)?)?);
+    }
 
-    csl::utils::TransactionUnspentOutput::new(&input, &output)
+    Ok(csl::utils::TransactionUnspentOutput::new(&input, &output))
 }
 
-/// get all utxos of an address
-pub async fn get_address_utxos(
-    bfp: &BlockfrostProvider,
-    addr: &str,
+fn bf_utxos_as_csl_utxos(
+    address_utxos: &[AddressUtxo],
 ) -> Result<dcslc::TransactionUnspentOutputs, DataProviderBlockfrostError> {
-
-    let mut address_utxos = Vec::<AddressUtxo>::with_capacity(BLOCKFROST_FETCH_MAX_ITEMS);
-
-    // TODO: See if we could fold with take_while instead of take(BLOCKFROST_FETCH_MAX_ITEMS)
-    // utxos.extend(
-    //     bfp.api
-    //         .addresses_utxos_all(&addr)
-    //         .fold(Vec::<AddressUtxo>::new(), |mut acc, n| async move {
-    //             acc.extend(n.unwrap().iter().map(|n| n.to_owned()));
-    //             acc
-    //         })
-    //         .await,
-    // );
-    let mut lister =  bfp.api.addresses_utxos_all(&addr).take(BLOCKFROST_FETCH_MAX_ITEMS);
-    while let Some(n) = lister.next().await {
-        let n = n.map_err( |e| DataProviderBlockfrostError::GeneralError(e.to_string()))?;
-        if n.is_empty() {
-            break;
-        };
-        address_utxos.extend(n);
-    }
     let mut utxos = dcslc::TransactionUnspentOutputs::new();
     for address_utxo in address_utxos {
-        // TODO: Add error handling to bf_utxo_as_csl_utxo
-        utxos.add(&bf_utxo_as_csl_utxo(&address_utxo));
+        utxos.add(&bf_utxo_as_csl_utxo(address_utxo)?);
     }
-
     Ok(utxos)
 }
 
+/// get all utxos of an address
+pub async fn get_address_utxos(
+    bfp: &BlockfrostProvider,
+    addr: &str,
+) -> Result<dcslc::TransactionUnspentOutputs, DataProviderBlockfrostError> {
+    let address_utxos: Vec<AddressUtxo> = collect_all(bfp.api.addresses_utxos_all(addr)).await?;
+    bf_utxos_as_csl_utxos(&address_utxos)
+}
+
 /// Get all utxos of a stake address
 pub async fn get_stake_address_utxos(
     bfp: &BlockfrostProvider,
     stake_addr: &str,
 ) -> Result<dcslc::TransactionUnspentOutputs, DataProviderBlockfrostError> {
-    let addresses = bfp
-        .api
-        .accounts_addresses_all(stake_addr)
-        .fold(Vec::<AccountAddress>::new(), |mut acc, n| async move {
-            acc.extend(n.unwrap().iter().map(|n| n.to_owned()));
-            acc
-        })
-        .await;
+    let addresses: Vec<AccountAddress> =
+        collect_all(bfp.api.accounts_addresses_all(stake_addr)).await?;
 
     let mut utxos = Vec::<AddressUtxo>::new();
     for address in addresses {
-        utxos.extend(
-            bfp.api
-                .addresses_utxos_all(&address.address)
-                .fold(Vec::<AddressUtxo>::new(), |mut acc, n| async move {
-                    acc.extend(n.unwrap().iter().map(|n| n.to_owned()));
-                    acc
-                })
-                .await,
-        );
+        utxos.extend(collect_all(bfp.api.addresses_utxos_all(&address.address)).await?);
     }
 
-    Ok(dcslc::TransactionUnspentOutputs::new())
+    bf_utxos_as_csl_utxos(&utxos)
 }
 
 pub async fn asset_utxos_on_addr(
     bfp: &BlockfrostProvider,
     addr: &str,
 ) -> Result<dcslc::TransactionUnspentOutputs, DataProviderBlockfrostError> {
-    let mut utxos = Vec::<AddressUtxo>::new();
-    utxos.extend(
-        bfp.api
-            .addresses_utxos_all(&addr)
-            .fold(Vec::<AddressUtxo>::new(), |mut acc, n| async move {
-                acc.extend(n.unwrap().iter().map(|n| n.to_owned()));
-                acc
-            })
-            .await,
-    );
-
-    // ToDo: Filter asset utxos and transform
-
-    Ok(dcslc::TransactionUnspentOutputs::new())
+    let mut utxos: Vec<AddressUtxo> = collect_all(bfp.api.addresses_utxos_all(addr)).await?;
+    utxos.retain(|u| u.amount.iter().any(|a| a.unit != "lovelace"));
+    bf_utxos_as_csl_utxos(&utxos)
 }
 
 /// Check if addresses exist
@@ -186,7 +242,11 @@ pub async fn addresses_exist(
 ) -> Result<Vec<bool>, DataProviderBlockfrostError> {
     let mut exists = Vec::with_capacity(addresses.len());
     for addr in addresses {
-        exists.push(bfp.api.addresses(&addr).await.is_ok())
+        match bfp.api.addresses(addr).await {
+            Ok(_) => exists.push(true),
+            Err(e) if is_not_found(&e) => exists.push(false),
+            Err(e) => return Err(e.into()),
+        }
     };
     Ok(exists)
 }
@@ -196,55 +256,168 @@ pub async fn addresses_exist(
 pub async fn get_addresses_transactions(
     bfp: &BlockfrostProvider,
     addresses: &Vec<&str>,
-    _slot: Option<u64>,
+    slot: Option<u64>,
 ) -> Result<Vec<TxHistoryListView>, DataProviderBlockfrostError> {
-
     let mut address_txs = Vec::<AddressTransaction>::with_capacity(BLOCKFROST_FETCH_MAX_ITEMS);
-
-    // TODO: See if we could fold with take_while instead of take(BLOCKFROST_FETCH_MAX_ITEMS)
     for addr in addresses {
-        let mut lister =  bfp.api.addresses_transactions_all(&addr).take(BLOCKFROST_FETCH_MAX_ITEMS);
-        while let Some(n) = lister.next().await {
-            let n = n.map_err( |e| DataProviderBlockfrostError::GeneralError(e.to_string()))?;
-            if n.is_empty() {
-                break;
-            };
-            address_txs.extend(n);
-        }
+        address_txs.extend(collect_all(bfp.api.addresses_transactions_all(addr)).await?);
     }
 
     let tx_hashes = address_txs.iter().map(|t| t.tx_hash.to_owned()).collect::<Vec<_>>().unique();
 
     let mut transactions = Vec::<Transaction>::new();
     for tx_hash in tx_hashes {
-        let tx = bfp.api
-            .transaction_by_hash(&tx_hash)
-            .await
-            .map_err( |e| DataProviderBlockfrostError::GeneralError(e.to_string()))?;
+        let tx = bfp.api.transaction_by_hash(&tx_hash).await?;
+        if let Some(slot) = slot {
+            if tx.slot as u64 > slot {
+                continue;
+            }
+        }
         transactions.push(tx);
-        
     }
 
     let r =  transactions.iter().map(|tx| TxHistoryListView::from_blockfrost_tx(tx)).collect::<Vec<_>>();
     Ok(r)
 }
 
+/// Adds the value of an output of the queried addresses, `amounts` are units and quantities
+fn add_amounts<'a>(
+    delta: &mut TxValueDelta,
+    spent: bool,
+    amounts: impl Iterator<Item = (&'a str, &'a str)>,
+) -> Result<(), DataProviderBlockfrostError> {
+    for (unit, quantity) in amounts {
+        let quantity = quantity.parse::<i128>()?;
+        if unit == "lovelace" {
+            delta.lovelace(spent, quantity);
+        } else if unit.len() < 56 {
+            return Err(DataProviderBlockfrostError::Custom(format!("invalid asset unit: {unit}")));
+        } else {
+            let (policy, name) = unit.split_at(56);
+            delta.asset(spent, policy.to_owned(), name.to_owned(), quantity);
+        }
+    }
+    Ok(())
+}
+
+/// Net change of value of the `own` addresses in each of the transactions, newest first
+async fn address_tx_views(
+    bfp: &BlockfrostProvider,
+    own: &HashSet<&str>,
+    tx_hashes: Vec<String>,
+    slot: Option<u64>,
+) -> Result<Vec<AddressTxView>, DataProviderBlockfrostError> {
+    let mut out = Vec::<AddressTxView>::new();
+    for tx_hash in tx_hashes {
+        let tx = bfp.api.transaction_by_hash(&tx_hash).await?;
+        if let Some(slot) = slot {
+            if tx.slot as u64 > slot {
+                continue;
+            }
+        }
+
+        let utxos = bfp.api.transactions_utxos(&tx_hash).await?;
+        let mut delta = TxValueDelta::default();
+        for input in utxos.inputs.iter() {
+            if !input.collateral && own.contains(input.address.as_str()) {
+                let amounts = input.amount.iter().map(|a| (a.unit.as_str(), a.quantity.as_str()));
+                add_amounts(&mut delta, true, amounts)?;
+            }
+        }
+        for output in utxos.outputs.iter() {
+            if own.contains(output.address.as_str()) {
+                let amounts = output.amount.iter().map(|a| (a.unit.as_str(), a.quantity.as_str()));
+                add_amounts(&mut delta, false, amounts)?;
+            }
+        }
+
+        let mut labels = Vec::<u64>::new();
+        for metadata in bfp.api.transactions_metadata(&tx_hash).await? {
+            labels.push(metadata.label.parse::<u64>()?);
+        }
+        out.push(delta.into_view(
+            tx.hash,
+            tx.slot as i64,
+            tx.block_time as i64,
+            tx.fees.parse::<u64>()?,
+            labels,
+        ));
+    }
+    out.sort_by(|a, b| b.slot.cmp(&a.slot));
+    Ok(out)
+}
+
+/// Transactions of the addresses with the net change of their value
+pub async fn address_tx_history(
+    bfp: &BlockfrostProvider,
+    addresses: &Vec<&str>,
+    slot: Option<u64>,
+) -> Result<Vec<AddressTxView>, DataProviderBlockfrostError> {
+    let mut tx_hashes = Vec::<String>::new();
+    for addr in addresses {
+        let address_txs = collect_all(bfp.api.addresses_transactions_all(addr)).await?;
+        tx_hashes.extend(address_txs.into_iter().map(|t: AddressTransaction| t.tx_hash));
+    }
+    tx_hashes.sort();
+    tx_hashes.dedup();
+
+    let own = addresses.iter().copied().collect();
+    address_tx_views(bfp, &own, tx_hashes, slot).await
+}
+
+/// All payment addresses associated with the account
+pub async fn stake_address_addresses(
+    bfp: &BlockfrostProvider,
+    stake_addr: &str,
+) -> Result<Vec<String>, DataProviderBlockfrostError> {
+    Ok(collect_all(bfp.api.accounts_addresses_all(stake_addr))
+        .await?
+        .into_iter()
+        .map(|a: AccountAddress| a.address)
+        .collect())
+}
+
+/// Controlled amount of the account split into the unspent lovelace and the withdrawable rewards
+pub async fn stake_address_balance(
+    bfp: &BlockfrostProvider,
+    stake_addr: &str,
+) -> Result<StakeBalanceView, DataProviderBlockfrostError> {
+    let account = bfp.api.accounts(stake_addr).await?;
+    let controlled = account.controlled_amount.parse::<u64>()?;
+    let withdrawable_rewards = account.withdrawable_amount.parse::<u64>()?;
+
+    let mut assets = Vec::<AssetHandle>::new();
+    for amount in collect_all(bfp.api.accounts_addresses_assets_all(stake_addr)).await? {
+        if amount.unit.len() < 56 {
+            return Err(DataProviderBlockfrostError::Custom(format!(
+                "invalid asset unit: {}",
+                amount.unit
+            )));
+        }
+        let (policy, tokenname) = amount.unit.split_at(56);
+        assets.push(AssetHandle {
+            fingerprint: make_fingerprint(policy, tokenname).ok(),
+            policy: Some(policy.to_owned()),
+            tokenname: Some(tokenname.to_owned()),
+            amount: amount.quantity.parse::<u64>()?,
+            metadata: None,
+        });
+    }
+
+    Ok(StakeBalanceView {
+        stake_address: stake_addr.to_owned(),
+        lovelace: controlled.saturating_sub(withdrawable_rewards),
+        assets,
+        withdrawable_rewards,
+    })
+}
+
 /// Get paged list of active pools with details
 pub async fn active_pools(
     bfp: &BlockfrostProvider,
     page: usize,
 ) -> Result<Vec<PoolView>, DataProviderBlockfrostError> {
-    let mut pool_ids = Vec::<String>::with_capacity(BLOCKFROST_FETCH_MAX_ITEMS);
-
-    // TODO: See if we could fold with take_while instead of take(BLOCKFROST_FETCH_MAX_ITEMS)
-    let mut lister =  bfp.api.pools_all().take(BLOCKFROST_FETCH_MAX_ITEMS);
-    while let Some(n) = lister.next().await {
-        let n = n.map_err( |e| DataProviderBlockfrostError::GeneralError(e.to_string()))?;
-        if n.is_empty() {
-            break;
-        };
-        pool_ids.extend(n);
-    }
+    let pool_ids: Vec<String> = collect_all(bfp.api.pools_all()).await?;
 
     let pool_ids_page = pool_ids.chunks(50).nth(page)
         .unwrap_or_default();
@@ -252,11 +425,8 @@ pub async fn active_pools(
     let mut pools = Vec::<PoolMetadata>::new();
 
     for pool_id in pool_ids_page {
-        let pool_metadata = bfp.api.pools_metadata(&pool_id)
-            .await
-            .map_err( |e| DataProviderBlockfrostError::GeneralError(e.to_string()))?;
+        let pool_metadata = bfp.api.pools_metadata(pool_id).await?;
         pools.push(pool_metadata);
-        
     }
 
     let r =  pools.iter().map(|pm|
@@ -274,125 +444,324 @@ pub async fn active_pools(
     Ok(r)
 }
 
-
-pub fn find_datums_for_tx(
+/// Datums which are only contained in the witness set are not returned, blockfrost does not provide their bytes
+pub async fn find_datums_for_tx(
     bfp: &BlockfrostProvider,
     txid: &Vec<u8>,
-) -> Result<Vec<CDPDatum>, DataProviderError> {
-    todo!();
+) -> Result<Vec<CDPDatum>, DataProviderBlockfrostError> {
+    let tx_utxos = bfp.api.transactions_utxos(&hex::encode(txid)).await?;
+
+    let mut datums = Vec::<CDPDatum>::new();
+    for output in tx_utxos.outputs.iter() {
+        if let Some(datum) = &output.inline_datum {
+            let plutus_data = csl::plutus::PlutusData::from_hex(datum)?;
+            let hash = match &output.data_hash {
+                Some(h) => hex::decode(h)?,
+                None => csl::utils::hash_plutus_data(&plutus_data).to_bytes(),
+            };
+            datums.push(CDPDatum {
+                hash,
+                json: None,
+                bytes: plutus_data.to_bytes(),
+                address: output.address.clone(),
+                addr_has_script: addr_has_script(&output.address),
+            });
+        }
+    }
+    Ok(datums)
 }
 
 pub async fn slot(bfp: &BlockfrostProvider) -> Result<i64, DataProviderBlockfrostError> {
-    let block = bfp.api.blocks_latest().await.unwrap();
-    Ok(block.slot.unwrap() as i64)
+    let block = bfp.api.blocks_latest().await?;
+    block
+        .slot
+        .map(|s| s as i64)
+        .ok_or_else(|| DataProviderBlockfrostError::RequestValueNotFound("slot".to_owned()))
 }
 
-pub fn pool_delegations(
+pub async fn pool_delegations(
     bfp: &BlockfrostProvider,
     pool: &str,
     epoch: i32,
 ) -> Result<Vec<StakeDelegationView>, DataProviderBlockfrostError> {
-    todo!();
+    let stakes = collect_all(bfp.api.epochs_stakes_by_pool_all(epoch.into(), pool)).await?;
+    stakes
+        .into_iter()
+        .map(|s| {
+            Ok(StakeDelegationView {
+                stake_address: s.stake_address,
+                amount: BigDecimal::from_str(&s.amount)
+                    .map_err(|e| DataProviderBlockfrostError::Custom(e.to_string()))?,
+            })
+        })
+        .collect()
 }
 
-pub fn deligations_per_pool_for_epochs(
+/// Only accounts which are currently delegated to the pool are considered
+pub async fn deligations_per_pool_for_epochs(
     bfp: &BlockfrostProvider,
     pool: &str,
     start_epoch: i64,
     end_epoch: i64,
 ) -> Result<Vec<DelegationView>, DataProviderBlockfrostError> {
-    todo!();
+    let delegators = collect_all(bfp.api.pools_delegators_all(pool)).await?;
+
+    let mut deleg = Vec::<DelegationView>::new();
+    for delegator in delegators {
+        let delegations = collect_all(bfp.api.accounts_delegations_all(&delegator.address)).await?;
+        deleg.extend(
+            delegations
+                .into_iter()
+                .filter(|d| d.pool_id == pool)
+                .filter(|d| d.active_epoch as i64 >= start_epoch && d.active_epoch as i64 <= end_epoch)
+                .map(|d| DelegationView {
+                    stake_address: delegator.address.clone(),
+                    amount: d.amount.parse::<i64>().unwrap_or(0),
+                    // blockfrost does not expose the certificate index
+                    cert_index: 0,
+                    active_epoch_no: d.active_epoch as i64,
+                }),
+        );
+    }
+    Ok(deleg)
 }
 
-pub fn pool_total_stake(
+pub async fn pool_total_stake(
     bfp: &BlockfrostProvider,
     pool: &str,
     epoch: i32,
 ) -> Result<u64, DataProviderBlockfrostError> {
-    Ok(0)
+    let history = collect_all(bfp.api.pools_history_all(pool)).await?;
+    match history.iter().find(|h| h.epoch as i64 == epoch as i64) {
+        Some(h) => Ok(h.active_stake.parse::<u64>()?),
+        None => Ok(0),
+    }
 }
 
 pub async fn current_epoch(bfp: &BlockfrostProvider) -> Result<i32, DataProviderBlockfrostError> {
-    let epoch = bfp.api.epochs_latest().await.unwrap();
+    let epoch = bfp.api.epochs_latest().await?;
     Ok(epoch.epoch as i32)
 }
 
 pub fn fingerprint(
-    bfp: &BlockfrostProvider,
+    _bfp: &BlockfrostProvider,
     policy: &str,
     tokenname: &str,
 ) -> Result<String, DataProviderBlockfrostError> {
-    Ok("".to_owned())
+    Ok(dcslc::make_fingerprint(policy, &hex::encode(tokenname))?)
 }
 
-pub fn token_info(
+/// `fingerprint_in` has to be the asset unit, see `asset_unit`
+pub async fn token_info(
     bfp: &BlockfrostProvider,
     fingerprint_in: &str,
 ) -> Result<TokenInfoView, DataProviderBlockfrostError> {
-    todo!();
+    let asset = bfp.api.assets_by_id(&asset_unit(fingerprint_in)?).await?;
+
+    Ok(TokenInfoView {
+        fingerprint: asset.fingerprint,
+        policy: asset.policy_id,
+        tokenname: asset.asset_name.unwrap_or_default(),
+        quantity: None,
+        meta_key: None,
+        json: None,
+        mint_slot: None,
+        txhash: None,
+    })
 }
 
-pub fn stake_registration(
+/// Returns stake address, tx hash and epoch of the registration certificates of an account
+async fn account_registrations(
+    bfp: &BlockfrostProvider,
+    stake_addr_in: &str,
+    action: &str,
+) -> Result<Vec<(String, Vec<u8>, i32)>, DataProviderBlockfrostError> {
+    let registrations = collect_all(bfp.api.accounts_registrations_all(stake_addr_in)).await?;
+
+    let mut out = Vec::new();
+    for r in registrations {
+        if serde_json::to_value(&r.action).ok() != Some(serde_json::json!(action)) {
+            continue;
+        }
+        let tx = bfp.api.transaction_by_hash(&r.tx_hash).await?;
+        let block = bfp.api.blocks_by_id(&tx.block).await?;
+        let epoch = block
+            .epoch
+            .ok_or_else(|| DataProviderBlockfrostError::RequestValueNotFound(tx.block.clone()))?;
+        out.push((stake_addr_in.to_owned(), hex::decode(&r.tx_hash)?, epoch as i32));
+    }
+    out.sort_by(|a, b| b.2.cmp(&a.2));
+    Ok(out)
+}
+
+pub async fn stake_registration(
     bfp: &BlockfrostProvider,
     stake_addr_in: &str,
 ) -> Result<Vec<StakeRegistrationView>, DataProviderBlockfrostError> {
-    todo!();
+    Ok(account_registrations(bfp, stake_addr_in, "registered")
+        .await?
+        .into_iter()
+        .map(|(stake_address, tx_hash, epoch)| StakeRegistrationView {
+            stake_address,
+            tx_hash,
+            // blockfrost does not expose the certificate index
+            cert_index: 0,
+            epoch,
+        })
+        .collect())
 }
 
-#[allow(clippy::type_complexity)]
-pub fn stake_deregistration(
+pub async fn stake_deregistration(
     bfp: &BlockfrostProvider,
     stake_addr_in: &str,
 ) -> Result<Vec<StakeDeregistrationView>, DataProviderBlockfrostError> {
-    todo!();
+    Ok(account_registrations(bfp, stake_addr_in, "deregistered")
+        .await?
+        .into_iter()
+        .map(|(stake_address, tx_hash, epoch)| StakeDeregistrationView {
+            stake_address,
+            tx_hash,
+            // blockfrost does not expose the certificate index
+            cert_index: 0,
+            epoch,
+        })
+        .collect())
 }
 
-pub fn check_stakeaddr_registered(
+/// Blockfrost only knows transactions which are included in a block
+pub async fn tx_included(
+    bfp: &BlockfrostProvider,
+    tx_hash: &str,
+) -> Result<bool, DataProviderBlockfrostError> {
+    match bfp.api.transactions(tx_hash).await {
+        Ok(_) => Ok(true),
+        Err(e) if is_not_found(&e) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn check_stakeaddr_registered(
     bfp: &BlockfrostProvider,
     stake_addr_in: &str,
 ) -> Result<bool, DataProviderBlockfrostError> {
-    Ok(false)
+    match bfp.api.accounts(stake_addr_in).await {
+        Ok(account) => Ok(account.active),
+        Err(e) if is_not_found(&e) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
 }
 
-pub fn lookup_token_holders(
+/// `fingerprint_in` has to be the asset unit, see `asset_unit`
+pub async fn lookup_token_holders(
     bfp: &BlockfrostProvider,
     fingerprint_in: &str,
     min_amount: Option<&i64>,
 ) -> Result<Vec<HoldingWalletView>, DataProviderBlockfrostError> {
-    todo!();
+    let unit = asset_unit(fingerprint_in)?;
+    let holders = collect_all(bfp.api.assets_addresses_all(&unit)).await?;
+
+    let mut ret = Vec::<HoldingWalletView>::new();
+    for holder in holders {
+        let amount = holder.quantity.parse::<u64>()?;
+        if let Some(min) = min_amount {
+            if (amount as i128) < *min as i128 {
+                continue;
+            }
+        }
+        if let Some(stake_address) = stake_address_of(&holder.address) {
+            ret.push(HoldingWalletView {
+                stake_address,
+                amount,
+                policy: unit[..56].to_owned(),
+                tokenname: Some(unit[56..].to_owned()),
+                fingerprint: None,
+            });
+        }
+    }
+    Ok(ret)
 }
 
-pub fn lookup_nft_token_holders(
+pub async fn lookup_nft_token_holders(
     bfp: &BlockfrostProvider,
     policy: &str,
 ) -> Result<Vec<HoldingWalletView>, DataProviderBlockfrostError> {
-    todo!();
+    let assets = collect_all(bfp.api.assets_policy_by_id_all(policy)).await?;
+
+    let mut ret = Vec::<HoldingWalletView>::new();
+    for asset in assets.iter().filter(|a| a.quantity == "1") {
+        let holders = collect_all(bfp.api.assets_addresses_all(&asset.asset)).await?;
+        for holder in holders.iter().filter(|h| h.quantity == "1") {
+            if let Some(stake_address) = stake_address_of(&holder.address) {
+                ret.push(HoldingWalletView {
+                    stake_address,
+                    amount: 1,
+                    policy: policy.to_owned(),
+                    tokenname: asset.asset.get(56..).map(|n| n.to_owned()),
+                    fingerprint: None,
+                });
+            }
+        }
+    }
+    Ok(ret)
 }
 
-pub fn mint_metadata(
-    _bfp: &BlockfrostProvider,
-    _fingerprint_in: &str,
+/// `fingerprint_in` has to be the asset unit, see `asset_unit`
+pub async fn mint_metadata(
+    bfp: &BlockfrostProvider,
+    fingerprint_in: &str,
 ) -> Result<TokenInfoView, DataProviderBlockfrostError> {
-    // Impossible only with blockfrost, not supported querying any asset info from fingerprint
-    // (and is not possible to get policy+assetname from fingerprint)
-    // See https://blockfrost.dev/support/cardano#poolpm-uses-fingerprints-for-querying-assets-why-dont-you-too
-    Err(DataProviderBlockfrostError::Custom("Fingerprint unsupported, use policy/assetname instead".to_string()))
+    let asset = bfp.api.assets_by_id(&asset_unit(fingerprint_in)?).await?;
+
+    let metadata = bfp
+        .api
+        .transactions_metadata(&asset.initial_mint_tx_hash)
+        .await?
+        .into_iter()
+        .find_map(|m| m.label.parse::<i64>().ok().map(|l| (l, m.json_metadata)));
+    let tx = bfp.api.transaction_by_hash(&asset.initial_mint_tx_hash).await?;
+
+    Ok(TokenInfoView {
+        fingerprint: asset.fingerprint,
+        policy: asset.policy_id,
+        tokenname: asset.asset_name.unwrap_or_default(),
+        quantity: asset.quantity.parse::<u64>().ok(),
+        meta_key: metadata.as_ref().map(|m| m.0),
+        json: metadata.map(|m| m.1),
+        mint_slot: Some(tx.slot as i64),
+        txhash: Some(asset.initial_mint_tx_hash),
+    })
 }
 
-pub fn pool_valid(
+pub async fn pool_valid(
     bfp: &BlockfrostProvider,
     pool_id: &str,
 ) -> Result<bool, DataProviderBlockfrostError> {
-    Ok(true)
+    let pool = bfp.api.pools_by_id(pool_id).await?;
+    Ok(pool.retirement.is_empty())
 }
 
-pub fn txhash_spent(
+/// Returns `true` if any output of the transaction has been spent
+pub async fn txhash_spent(
     bfp: &BlockfrostProvider,
     txhash: &str,
 ) -> Result<bool, DataProviderBlockfrostError> {
-    let txh_b = hex::decode(txhash)?;
+    let tx_utxos = bfp.api.transactions_utxos(txhash).await?;
 
-    Ok(false)
+    let addresses = tx_utxos
+        .outputs
+        .iter()
+        .map(|o| o.address.to_owned())
+        .collect::<Vec<_>>()
+        .unique();
+
+    let mut unspent = 0;
+    for address in addresses {
+        let address_utxos: Vec<AddressUtxo> =
+            collect_all(bfp.api.addresses_utxos_all(&address)).await?;
+        unspent += address_utxos.iter().filter(|u| u.tx_hash == txhash).count();
+    }
+
+    Ok(unspent < tx_utxos.outputs.len())
 }
 
 pub async fn retrieve_staked_amount (
@@ -401,16 +770,7 @@ pub async fn retrieve_staked_amount (
     stake_addr: &str,
 ) -> Result<BigDecimal, DataProviderError> {
 
-    let mut account_history = Vec::<AccountHistory>::with_capacity(BLOCKFROST_FETCH_MAX_ITEMS);
-    // TODO: See if we could fold with take_while instead of take(BLOCKFROST_FETCH_MAX_ITEMS)
-    let mut lister =  bfp.api.accounts_history_all(stake_addr).take(BLOCKFROST_FETCH_MAX_ITEMS);
-    while let Some(n) = lister.next().await {
-        let n = n.map_err( |e| DataProviderBlockfrostError::GeneralError(e.to_string()))?;
-        if n.is_empty() {
-            break;
-        };
-        account_history.extend(n);
-    }
+    let account_history: Vec<AccountHistory> = collect_all(bfp.api.accounts_history_all(stake_addr)).await?;
 
     debug!("AC: {:?}", account_history);
 
@@ -428,17 +788,7 @@ pub async fn retrieve_generated_rewards (
     stake_addr: &str,
 ) -> Result<Vec<RewardView>, DataProviderError> {
 
-    let mut account_rewards = Vec::<AccountReward>::with_capacity(BLOCKFROST_FETCH_MAX_ITEMS);
-
-    // TODO: See if we could fold with take_while instead of take(BLOCKFROST_FETCH_MAX_ITEMS)
-    let mut lister =  bfp.api.accounts_rewards_all(stake_addr).take(BLOCKFROST_FETCH_MAX_ITEMS);
-    while let Some(n) = lister.next().await {
-        let n = n.map_err( |e| DataProviderBlockfrostError::GeneralError(e.to_string()))?;
-        if n.is_empty() {
-            break;
-        };
-        account_rewards.extend(n);
-    }
+    let account_rewards: Vec<AccountReward> = collect_all(bfp.api.accounts_rewards_all(stake_addr)).await?;
 
     debug!("AC: {:?}", account_rewards);
 
@@ -452,3 +802,506 @@ pub async fn retrieve_generated_rewards (
 
     Ok(r)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::blockfrost::{BlockfrostProvider, Config};
+    use crate::provider::CardanoDataProvider;
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    const ADDR: &str = "addr1vyqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qavsj8u";
+    const BASE_ADDR: &str = "addr1qyqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8ryv4nxw6rfdf4kcmtwdac8zunnw36hvamc09a8klra0elshjpp08";
+    const STAKE_ADDR: &str = "stake1u9jx2en8dp5k56mvd4hx7ur3wfehgatkwau8j7nm037hulctderj8";
+    const TX_HASH: &str = "1b07f1152e52ce0a9dbb561aa2e2d1750ca3a1a4141150a8bad342947a66a3a6";
+    const BLOCK_HASH: &str = "4ea1ba291e8eef538635a53e59fddba7810d1679631cc3aed7c8e6c4091a516a";
+    const POLICY: &str = "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c";
+    const DATUM_HASH: &str = "923918e403bf43c34b4ef6b48eb2ee04babed17320d8d1b9ff9ad086e86f44ec";
+
+    fn provider(server: &MockServer) -> crate::DataProvider<BlockfrostProvider> {
+        crate::DataProvider::new(BlockfrostProvider::new(Config {
+            project_id: "mainnetTestProject".to_owned(),
+            network_address: server.base_url(),
+            network: crate::config::ChainWellKnownInfo::mainnet(),
+        }))
+    }
+
+    fn block(slot: i64, epoch: i32) -> serde_json::Value {
+        json!({
+            "time": 1641338934,
+            "height": 15243593,
+            "hash": BLOCK_HASH,
+            "slot": slot,
+            "epoch": epoch,
+            "epoch_slot": 12,
+            "slot_leader": "pool1pu5jlj4q9w9jlxeu370a3c9myx47md5j5m2str0naunn2qnikdy",
+            "size": 3,
+            "tx_count": 1,
+            "output": "128314491794",
+            "fees": "592661",
+            "block_vrf": "vrf_vk1wf2k6lhujezqcfe00l6zetxpnmh9n6mwhpmhm0dvfh3fxgmdnrfqkms8ty",
+            "op_cert": "da905277534faf75dae41732650568af545134ee08a3c0392dbefc8096ae177c",
+            "op_cert_counter": "18",
+            "previous_block": "43ebccb3ac72c7cebd0d9b755a4b08412c9f5dcb81b8a0ad1e3c197d29d47b05",
+            "next_block": null,
+            "confirmations": 0
+        })
+    }
+
+    fn transaction(slot: i64) -> serde_json::Value {
+        json!({
+            "hash": TX_HASH,
+            "block": BLOCK_HASH,
+            "block_height": 123456,
+            "block_time": 1635505891,
+            "slot": slot,
+            "index": 1,
+            "output_amount": [{ "unit": "lovelace", "quantity": "42000000" }],
+            "fees": "182485",
+            "deposit": "2000000",
+            "size": 433,
+            "invalid_before": null,
+            "invalid_hereafter": "13885913",
+            "utxo_count": 4,
+            "withdrawal_count": 0,
+            "mir_cert_count": 0,
+            "delegation_count": 0,
+            "stake_cert_count": 1,
+            "pool_update_count": 0,
+            "pool_retire_count": 0,
+            "asset_mint_or_burn_count": 0,
+            "redeemer_count": 0,
+            "valid_contract": true
+        })
+    }
+
+    #[tokio::test]
+    async fn test_slot_and_epoch() {
+        let server = MockServer::start_async().await;
+        let blocks = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/blocks/latest")
+                    .header("project_id", "mainnetTestProject");
+                then.status(200).json_body(block(95654321, 412));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/epochs/latest");
+                then.status(200).json_body(json!({
+                    "epoch": 412,
+                    "start_time": 1603403091,
+                    "end_time": 1603835086,
+                    "first_block_time": 1603403092,
+                    "last_block_time": 1603835084,
+                    "block_count": 21298,
+                    "tx_count": 17856,
+                    "output": "7849943934049314",
+                    "fees": "4203312194",
+                    "active_stake": "784953934049314"
+                }));
+            })
+            .await;
+
+        let dp = provider(&server);
+        assert_eq!(dp.slot().await.unwrap(), 95654321);
+        assert_eq!(dp.current_epoch().await.unwrap(), 412);
+        blocks.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_address_utxos() {
+        let server = MockServer::start_async().await;
+        let page = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path(format!("/addresses/{ADDR}/utxos"))
+                    .query_param("page", "1");
+                then.status(200).json_body(json!([
+                    {
+                        "address": ADDR,
+                        "tx_hash": TX_HASH,
+                        "tx_index": 0,
+                        "output_index": 0,
+                        "amount": [
+                            { "unit": "lovelace", "quantity": "1500000" },
+                            { "unit": format!("{POLICY}746f6b656e"), "quantity": "42" }
+                        ],
+                        "block": BLOCK_HASH,
+                        "data_hash": DATUM_HASH,
+                        "inline_datum": null,
+                        "reference_script_hash": null
+                    },
+                    {
+                        "address": ADDR,
+                        "tx_hash": TX_HASH,
+                        "tx_index": 1,
+                        "output_index": 1,
+                        "amount": [{ "unit": "lovelace", "quantity": "2000000" }],
+                        "block": BLOCK_HASH,
+                        "data_hash": null,
+                        "inline_datum": null,
+                        "reference_script_hash": null
+                    }
+                ]));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/addresses/{ADDR}/utxos"));
+                then.status(200).json_body(json!([]));
+            })
+            .await;
+
+        let dp = provider(&server);
+        assert_eq!(dp.script_utxos(ADDR).await.unwrap().len(), 2);
+
+        let assets = dp.asset_utxos_on_addr(ADDR).await.unwrap();
+        assert_eq!(assets.len(), 1);
+        assert_eq!(assets.get(0).output().amount().multiasset().unwrap().len(), 1);
+
+        let utxo = dp
+            .utxo_by_dataumhash(ADDR, &hex::decode(DATUM_HASH).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(utxo.input().index(), 0);
+        assert_eq!(utxo.output().amount().coin().to_str(), "1500000");
+        page.assert_hits_async(3).await;
+    }
+
+    #[tokio::test]
+    async fn test_tx_included() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/txs/{TX_HASH}"));
+                then.status(404).json_body(json!({
+                    "status_code": 404,
+                    "error": "Not Found",
+                    "message": "The requested component has not been found."
+                }));
+            })
+            .await;
+
+        let dp = provider(&server);
+        assert!(!dp.tx_included(TX_HASH).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_utxos_by_refs() {
+        let server = MockServer::start_async().await;
+        let output = |index: u32| {
+            json!({
+                "address": ADDR,
+                "amount": [{ "unit": "lovelace", "quantity": "2000000" }],
+                "output_index": index,
+                "data_hash": null,
+                "inline_datum": null,
+                "reference_script_hash": null
+            })
+        };
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/txs/{TX_HASH}/utxos"));
+                then.status(200).json_body(json!({
+                    "hash": TX_HASH,
+                    "inputs": [],
+                    "outputs": [output(0), output(1)]
+                }));
+            })
+            .await;
+        // the second output is already spent
+        server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path(format!("/addresses/{ADDR}/utxos"))
+                    .query_param("page", "1");
+                then.status(200).json_body(json!([{
+                    "address": ADDR,
+                    "tx_hash": TX_HASH,
+                    "tx_index": 0,
+                    "output_index": 0,
+                    "amount": [{ "unit": "lovelace", "quantity": "2000000" }],
+                    "block": BLOCK_HASH,
+                    "data_hash": null,
+                    "inline_datum": null,
+                    "reference_script_hash": null
+                }]));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/addresses/{ADDR}/utxos"));
+                then.status(200).json_body(json!([]));
+            })
+            .await;
+
+        let dp = provider(&server);
+        let hash = crate::models::TxHash::from_hex(TX_HASH).unwrap();
+        let resolved = dp
+            .utxos_by_refs(&[(hash.clone(), 0), (hash.clone(), 1), (hash, 7)])
+            .await
+            .unwrap();
+        assert_eq!(resolved.found.len(), 1);
+        assert_eq!(resolved.found[0].input().index(), 0);
+        let missing: Vec<u32> = resolved.missing.iter().map(|(_, index)| *index).collect();
+        assert_eq!(missing, vec![1, 7]);
+    }
+
+    #[tokio::test]
+    async fn test_stake_registration() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path(format!("/accounts/{STAKE_ADDR}/registrations"))
+                    .query_param("page", "1");
+                then.status(200).json_body(json!([
+                    { "tx_hash": TX_HASH, "action": "registered" },
+                    { "tx_hash": TX_HASH, "action": "deregistered" }
+                ]));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path(format!("/accounts/{STAKE_ADDR}/registrations"));
+                then.status(200).json_body(json!([]));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/txs/{TX_HASH}"));
+                then.status(200).json_body(transaction(95654321));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/blocks/{BLOCK_HASH}"));
+                then.status(200).json_body(block(95654321, 412));
+            })
+            .await;
+
+        let dp = provider(&server);
+        let registrations = dp.stake_registration(STAKE_ADDR).await.unwrap();
+        assert_eq!(registrations.len(), 1);
+        assert_eq!(registrations[0].stake_address, STAKE_ADDR);
+        assert_eq!(registrations[0].tx_hash, hex::decode(TX_HASH).unwrap());
+        assert_eq!(registrations[0].epoch, 412);
+        assert_eq!(dp.stake_deregistration(STAKE_ADDR).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_lookup_token_holders() {
+        let server = MockServer::start_async().await;
+        let unit = format!("{POLICY}746f6b656e");
+        server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path(format!("/assets/{unit}/addresses"))
+                    .query_param("page", "1");
+                then.status(200).json_body(json!([
+                    { "address": BASE_ADDR, "quantity": "100" },
+                    { "address": BASE_ADDR, "quantity": "5" },
+                    { "address": ADDR, "quantity": "1000" }
+                ]));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/assets/{unit}/addresses"));
+                then.status(200).json_body(json!([]));
+            })
+            .await;
+
+        let dp = provider(&server);
+        let holders = dp.lookup_token_holders(&unit, Some(&10)).await.unwrap();
+        // the enterprise address has no stake address
+        assert_eq!(holders.len(), 1);
+        assert_eq!(holders[0].stake_address, STAKE_ADDR);
+        assert_eq!(holders[0].amount, 100);
+        assert_eq!(holders[0].policy, POLICY);
+
+        assert!(dp
+            .lookup_token_holders("asset1notsupported", None)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_check_stakeaddr_registered() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/accounts/{STAKE_ADDR}"));
+                then.status(404).json_body(json!({
+                    "status_code": 404,
+                    "error": "Not Found",
+                    "message": "The requested component has not been found."
+                }));
+            })
+            .await;
+
+        let dp = provider(&server);
+        assert!(!dp.check_stakeaddr_registered(STAKE_ADDR).await.unwrap());
+        assert_eq!(
+            dp.fingerprint(POLICY, "token").await.unwrap(),
+            dcslc::make_fingerprint(POLICY, "746f6b656e").unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_address_tx_history() {
+        let server = MockServer::start_async().await;
+        let unit = format!("{POLICY}746f6b656e");
+        server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path(format!("/addresses/{ADDR}/transactions"))
+                    .query_param("page", "1");
+                then.status(200).json_body(json!([
+                    { "tx_hash": TX_HASH, "tx_index": 1, "block_height": 123456, "block_time": 1635505891 }
+                ]));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path(format!("/addresses/{ADDR}/transactions"));
+                then.status(200).json_body(json!([]));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/txs/{TX_HASH}"));
+                then.status(200).json_body(transaction(95654321));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/txs/{TX_HASH}/utxos"));
+                then.status(200).json_body(json!({
+                    "hash": TX_HASH,
+                    "inputs": [{
+                        "address": ADDR,
+                        "amount": [
+                            { "unit": "lovelace", "quantity": "5000000" },
+                            { "unit": unit, "quantity": "42" }
+                        ],
+                        "tx_hash": BLOCK_HASH,
+                        "output_index": 0,
+                        "data_hash": null,
+                        "inline_datum": null,
+                        "reference_script_hash": null,
+                        "collateral": false,
+                        "reference": false
+                    }],
+                    "outputs": [
+                        {
+                            "address": BASE_ADDR,
+                            "amount": [
+                                { "unit": "lovelace", "quantity": "2817515" },
+                                { "unit": unit, "quantity": "40" }
+                            ],
+                            "output_index": 0,
+                            "data_hash": null,
+                            "inline_datum": null,
+                            "reference_script_hash": null
+                        },
+                        {
+                            "address": ADDR,
+                            "amount": [
+                                { "unit": "lovelace", "quantity": "2000000" },
+                                { "unit": unit, "quantity": "2" }
+                            ],
+                            "output_index": 1,
+                            "data_hash": null,
+                            "inline_datum": null,
+                            "reference_script_hash": null
+                        }
+                    ]
+                }));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/txs/{TX_HASH}/metadata"));
+                then.status(200).json_body(json!([
+                    { "label": "674", "json_metadata": { "msg": ["payment"] } }
+                ]));
+            })
+            .await;
+
+        let dp = provider(&server);
+        let history = dp.address_tx_history(&vec![ADDR], None).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].hash, TX_HASH);
+        assert_eq!(history[0].block_time, 1635505891);
+        assert_eq!(history[0].fee, 182485);
+        assert_eq!(history[0].direction, crate::models::TxDirection::Outgoing);
+        assert_eq!(history[0].lovelace, -3000000);
+        assert_eq!(history[0].assets.len(), 1);
+        assert_eq!(history[0].assets[0].policy, POLICY);
+        assert_eq!(history[0].assets[0].quantity, -40);
+        assert_eq!(history[0].metadata_labels, vec![674]);
+
+        assert!(dp
+            .address_tx_history(&vec![ADDR], Some(95654320))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stake_address_balance() {
+        let server = MockServer::start_async().await;
+        let unit = format!("{POLICY}746f6b656e");
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/accounts/{STAKE_ADDR}"));
+                then.status(200).json_body(json!({
+                    "stake_address": STAKE_ADDR,
+                    "active": true,
+                    "active_epoch": 412,
+                    "controlled_amount": "12000000",
+                    "rewards_sum": "3000000",
+                    "withdrawals_sum": "1000000",
+                    "reserves_sum": "0",
+                    "treasury_sum": "0",
+                    "withdrawable_amount": "2000000",
+                    "pool_id": null
+                }));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path(format!("/accounts/{STAKE_ADDR}/addresses/assets"))
+                    .query_param("page", "1");
+                then.status(200)
+                    .json_body(json!([{ "unit": unit, "quantity": "42" }]));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path(format!("/accounts/{STAKE_ADDR}/addresses/assets"));
+                then.status(200).json_body(json!([]));
+            })
+            .await;
+
+        let dp = provider(&server);
+        let balance = dp.stake_address_balance(STAKE_ADDR).await.unwrap();
+        assert_eq!(balance.lovelace, 10000000);
+        assert_eq!(balance.withdrawable_rewards, 2000000);
+        assert_eq!(balance.assets.len(), 1);
+        assert_eq!(balance.assets[0].policy.as_deref(), Some(POLICY));
+        assert_eq!(balance.assets[0].tokenname.as_deref(), Some("746f6b656e"));
+        assert_eq!(balance.assets[0].amount, 42);
+        assert_eq!(
+            balance.assets[0].fingerprint,
+            dcslc::make_fingerprint(POLICY, "746f6b656e").ok()
+        );
+    }
+}
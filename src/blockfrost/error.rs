@@ -7,6 +7,12 @@ pub enum DataProviderBlockfrostError {
     GeneralError(String),
     #[error("Custom Error: {:?}", self)]
     Custom(String),
+    #[error("Requested value could not be found: {0}")]
+    RequestValueNotFound(String),
+    #[error("Not supported by Blockfrost: {0}")]
+    Unsupported(String),
+    #[error(transparent)]
+    BlockfrostError(#[from] blockfrost::Error),
     #[error(transparent)]
     ParseIntError(#[from] core::num::ParseIntError),
     #[error(transparent)]
@@ -1,200 +1,463 @@
 use super::error::DataProviderCarbError;
+use super::models::{Account, AddressesRequest, Address, Amount, Datum, Pool, Spent, Tip, Utxo};
 use super::CarbProvider;
 use crate::models::{
-    CDPDatum, CardanoNativeAssetView, DelegationView, HoldingWalletView, StakeDelegationView,
-    StakeDeregistrationView, StakeRegistrationView, TokenInfoView, RewardView,
+    CDPDatum, CardanoNativeAssetView, DelegationView, HoldingWalletView, PoolView, RewardView,
+    StakeDelegationView, StakeDeregistrationView, StakeRegistrationView, TokenInfoView,
+    TxHistoryListView,
 };
-use crate::provider::error::DataProviderError;
 
 use bigdecimal::BigDecimal;
+use std::str::FromStr;
+
+fn utxos_to_txuos(utxos: &[Utxo]) -> Result<dcslc::TransactionUnspentOutputs, DataProviderCarbError> {
+    let mut out = dcslc::TransactionUnspentOutputs::new();
+    for u in utxos {
+        out.add(&u.to_txuo()?);
+    }
+    Ok(out)
+}
+
+/// GET `tip`
+pub async fn tip(cp: &CarbProvider) -> Result<Tip, DataProviderCarbError> {
+    cp.get("tip", &[]).await
+}
 
 /// get all tokens of an utxo
-pub fn get_utxo_tokens(
-    bfp: &CarbProvider,
-    tx_id: i64,
-    tx_index: i16,
+pub async fn get_utxo_tokens(
+    _cp: &CarbProvider,
+    _tx_id: i64,
+    _tx_index: i16,
 ) -> Result<Vec<CardanoNativeAssetView>, DataProviderCarbError> {
-    todo!()
+    // tx_id is a dbsync internal id which is not exposed by carb
+    Err(DataProviderCarbError::Unsupported(
+        "utxo_tokens requires dbsync transaction ids, use utxo_by_txid instead".to_string(),
+    ))
 }
 
-pub fn select_addr_of_first_transaction(
-    bfp: &CarbProvider,
+/// GET `accounts/{stake_address}/first_address`
+pub async fn select_addr_of_first_transaction(
+    cp: &CarbProvider,
     stake_address_in: &str,
 ) -> Result<String, DataProviderCarbError> {
-    Ok("".to_owned())
+    let address: Address = cp
+        .get(&format!("accounts/{stake_address_in}/first_address"), &[])
+        .await?;
+    Ok(address.address)
 }
 
-/// get all utxos of an address
-pub fn utxo_by_dataumhash(
-    bfp: &CarbProvider,
+/// GET `addresses/{address}/utxos?datum_hash={hash}`
+pub async fn utxo_by_dataumhash(
+    cp: &CarbProvider,
     addr: &str,
     datumhash: &Vec<u8>,
 ) -> Result<dcslc::TransactionUnspentOutput, DataProviderCarbError> {
-    Err(DataProviderCarbError::Custom("not implemented".to_string()))
+    let datumhash = hex::encode(datumhash);
+    let utxos: Vec<Utxo> = cp
+        .get(
+            &format!("addresses/{addr}/utxos"),
+            &[("datum_hash", &datumhash)],
+        )
+        .await?;
+    match utxos.first() {
+        Some(u) => u.to_txuo(),
+        None => Err(DataProviderCarbError::RequestValueNotFound(datumhash)),
+    }
 }
 
-/// returns Utxo of a certain datumhash on an address
-pub fn utxo_by_txid(
-    bfp: &CarbProvider,
+/// GET `utxos/{tx_hash}/{index}`, only unspent outputs are returned
+pub async fn utxo_by_txid(
+    cp: &CarbProvider,
     txhash: &Vec<u8>,
     index: i16,
 ) -> Result<dcslc::TransactionUnspentOutput, DataProviderCarbError> {
-    Err(DataProviderCarbError::Custom("not implemented".to_string()))
+    let utxo: Utxo = cp
+        .get(&format!("utxos/{}/{}", hex::encode(txhash), index), &[])
+        .await?;
+    utxo.to_txuo()
 }
 
-/// get all utxos of an address
-pub fn get_address_utxos(
-    bfp: &CarbProvider,
+/// GET `addresses/{address}/utxos`
+pub async fn get_address_utxos(
+    cp: &CarbProvider,
     addr: &str,
 ) -> Result<dcslc::TransactionUnspentOutputs, DataProviderCarbError> {
-    Ok(dcslc::TransactionUnspentOutputs::new())
+    let utxos: Vec<Utxo> = cp.get(&format!("addresses/{addr}/utxos"), &[]).await?;
+    utxos_to_txuos(&utxos)
 }
 
-/// Get all utxos of a stake address
-pub fn get_stake_address_utxos(
-    bfp: &CarbProvider,
+/// GET `accounts/{stake_address}/utxos`
+pub async fn get_stake_address_utxos(
+    cp: &CarbProvider,
     stake_addr: &str,
 ) -> Result<dcslc::TransactionUnspentOutputs, DataProviderCarbError> {
-    Ok(dcslc::TransactionUnspentOutputs::new())
+    let utxos: Vec<Utxo> = cp.get(&format!("accounts/{stake_addr}/utxos"), &[]).await?;
+    utxos_to_txuos(&utxos)
 }
 
-/// Get all utxos of a stake address
-pub fn asset_utxos_on_addr(
-    bfp: &CarbProvider,
+/// GET `addresses/{address}/utxos?assets_only=true`
+pub async fn asset_utxos_on_addr(
+    cp: &CarbProvider,
     addr: &str,
 ) -> Result<dcslc::TransactionUnspentOutputs, DataProviderCarbError> {
-    Ok(dcslc::TransactionUnspentOutputs::new())
+    let utxos: Vec<Utxo> = cp
+        .get(&format!("addresses/{addr}/utxos"), &[("assets_only", "true")])
+        .await?;
+    utxos_to_txuos(&utxos)
 }
 
-pub fn find_datums_for_tx(
-    bfp: &CarbProvider,
+/// GET `pools?page={page}`
+pub async fn active_pools(
+    cp: &CarbProvider,
+    page: usize,
+) -> Result<Vec<PoolView>, DataProviderCarbError> {
+    cp.get("pools", &[("page", &page.to_string())]).await
+}
+
+/// GET `txs/{tx_hash}/datums`
+pub async fn find_datums_for_tx(
+    cp: &CarbProvider,
     txid: &Vec<u8>,
-) -> Result<Vec<CDPDatum>, DataProviderError> {
-    todo!();
+) -> Result<Vec<CDPDatum>, DataProviderCarbError> {
+    let datums: Vec<Datum> = cp
+        .get(&format!("txs/{}/datums", hex::encode(txid)), &[])
+        .await?;
+    datums.iter().map(|d| d.to_cdp_datum()).collect()
 }
 
-pub fn slot(bfp: &CarbProvider) -> Result<i64, DataProviderCarbError> {
-    Ok(0)
+pub async fn slot(cp: &CarbProvider) -> Result<i64, DataProviderCarbError> {
+    Ok(tip(cp).await?.slot)
 }
 
-pub fn stakers_on_pool(
-    bfp: &CarbProvider,
+/// GET `pools/{pool}/stakers?epoch={epoch}`
+pub async fn stakers_on_pool(
+    cp: &CarbProvider,
     pool: &str,
     epoch: i32,
 ) -> Result<Vec<StakeDelegationView>, DataProviderCarbError> {
-    todo!();
+    cp.get(
+        &format!("pools/{pool}/stakers"),
+        &[("epoch", &epoch.to_string())],
+    )
+    .await
 }
 
-pub fn deligations_per_pool_for_epochs(
-    bfp: &CarbProvider,
+/// GET `pools/{pool}/delegations?start_epoch={start_epoch}&end_epoch={end_epoch}`
+pub async fn deligations_per_pool_for_epochs(
+    cp: &CarbProvider,
     pool: &str,
     start_epoch: i64,
     end_epoch: i64,
 ) -> Result<Vec<DelegationView>, DataProviderCarbError> {
-    todo!();
+    cp.get(
+        &format!("pools/{pool}/delegations"),
+        &[
+            ("start_epoch", &start_epoch.to_string()),
+            ("end_epoch", &end_epoch.to_string()),
+        ],
+    )
+    .await
 }
 
-pub fn pool_total_stake(
-    bfp: &CarbProvider,
+/// GET `pools/{pool}/stake?epoch={epoch}`
+pub async fn pool_total_stake(
+    cp: &CarbProvider,
     pool: &str,
     epoch: i32,
 ) -> Result<u64, DataProviderCarbError> {
-    Ok(0)
+    let stake: Amount = cp
+        .get(&format!("pools/{pool}/stake"), &[("epoch", &epoch.to_string())])
+        .await?;
+    Ok(stake.amount.parse::<u64>()?)
 }
 
-pub fn current_epoch(bfp: &CarbProvider) -> Result<i32, DataProviderCarbError> {
-    Ok(0)
+pub async fn current_epoch(cp: &CarbProvider) -> Result<i32, DataProviderCarbError> {
+    Ok(tip(cp).await?.epoch)
 }
 
-pub fn fingerprint(
-    bfp: &CarbProvider,
+pub async fn fingerprint(
+    _cp: &CarbProvider,
     policy: &str,
     tokenname: &str,
 ) -> Result<String, DataProviderCarbError> {
-    Ok("".to_owned())
+    Ok(dcslc::make_fingerprint(policy, &hex::encode(tokenname))?)
 }
 
-pub fn token_info(
-    bfp: &CarbProvider,
+/// GET `assets/{fingerprint}`
+pub async fn token_info(
+    cp: &CarbProvider,
     fingerprint_in: &str,
 ) -> Result<TokenInfoView, DataProviderCarbError> {
-    todo!();
+    cp.get(&format!("assets/{fingerprint_in}"), &[]).await
 }
 
-pub fn stake_registration(
-    bfp: &CarbProvider,
+/// GET `accounts/{stake_address}/registrations`
+pub async fn stake_registration(
+    cp: &CarbProvider,
     stake_addr_in: &str,
 ) -> Result<Vec<StakeRegistrationView>, DataProviderCarbError> {
-    todo!();
+    cp.get(&format!("accounts/{stake_addr_in}/registrations"), &[])
+        .await
 }
 
-#[allow(clippy::type_complexity)]
-pub fn stake_deregistration(
-    bfp: &CarbProvider,
+/// GET `accounts/{stake_address}/deregistrations`
+pub async fn stake_deregistration(
+    cp: &CarbProvider,
     stake_addr_in: &str,
 ) -> Result<Vec<StakeDeregistrationView>, DataProviderCarbError> {
-    todo!();
+    cp.get(&format!("accounts/{stake_addr_in}/deregistrations"), &[])
+        .await
 }
 
-pub fn check_stakeaddr_registered(
-    bfp: &CarbProvider,
+/// GET `accounts/{stake_address}`, unknown accounts are not registered
+pub async fn check_stakeaddr_registered(
+    cp: &CarbProvider,
     stake_addr_in: &str,
 ) -> Result<bool, DataProviderCarbError> {
-    Ok(false)
+    match cp
+        .get::<Account>(&format!("accounts/{stake_addr_in}"), &[])
+        .await
+    {
+        Ok(account) => Ok(account.registered),
+        Err(DataProviderCarbError::RequestValueNotFound(_)) => Ok(false),
+        Err(e) => Err(e),
+    }
 }
 
-pub fn lookup_token_holders(
-    bfp: &CarbProvider,
+/// GET `assets/{fingerprint}/holders?min_amount={min_amount}`
+pub async fn lookup_token_holders(
+    cp: &CarbProvider,
     fingerprint_in: &str,
     min_amount: Option<&i64>,
 ) -> Result<Vec<HoldingWalletView>, DataProviderCarbError> {
-    todo!();
+    let min_amount = min_amount.map(|m| m.to_string());
+    let mut query = Vec::<(&str, &str)>::new();
+    if let Some(m) = &min_amount {
+        query.push(("min_amount", m));
+    }
+    cp.get(&format!("assets/{fingerprint_in}/holders"), &query)
+        .await
 }
 
-pub fn lookup_nft_token_holders(
-    bfp: &CarbProvider,
+/// GET `policies/{policy}/nft_holders`
+pub async fn lookup_nft_token_holders(
+    cp: &CarbProvider,
     policy: &str,
 ) -> Result<Vec<HoldingWalletView>, DataProviderCarbError> {
-    todo!();
+    cp.get(&format!("policies/{policy}/nft_holders"), &[]).await
 }
 
-pub fn mint_metadata(
-    bfp: &CarbProvider,
+/// GET `assets/{fingerprint}/mint_metadata`
+pub async fn mint_metadata(
+    cp: &CarbProvider,
     fingerprint_in: &str,
 ) -> Result<TokenInfoView, DataProviderCarbError> {
-    Ok(TokenInfoView {
-        fingerprint: "".to_owned(),
-        policy: hex::encode(""),
-        tokenname: "".to_owned(),
-        meta_key: None,
-        json: None,
-        txhash: None,
-        quantity: None,
-        mint_slot: None,
-    })
+    cp.get(&format!("assets/{fingerprint_in}/mint_metadata"), &[])
+        .await
 }
 
-pub fn pool_valid(bfp: &CarbProvider, pool_id: &str) -> Result<bool, DataProviderCarbError> {
-    Ok(true)
+/// GET `pools/{pool_id}`
+pub async fn pool_valid(cp: &CarbProvider, pool_id: &str) -> Result<bool, DataProviderCarbError> {
+    let pool: Pool = cp.get(&format!("pools/{pool_id}"), &[]).await?;
+    Ok(!pool.retired)
 }
 
-pub fn txhash_spent(bfp: &CarbProvider, txhash: &str) -> Result<bool, DataProviderCarbError> {
-    let txh_b = hex::decode(txhash)?;
+/// GET `txs/{tx_hash}/spent`
+pub async fn txhash_spent(cp: &CarbProvider, txhash: &str) -> Result<bool, DataProviderCarbError> {
+    hex::decode(txhash)?;
+    let spent: Spent = cp.get(&format!("txs/{txhash}/spent"), &[]).await?;
+    Ok(spent.spent)
+}
 
-    Ok(false)
+/// GET `txs/{tx_hash}/spent`, which is answered with 404 until the transaction is on-chain
+pub async fn tx_included(cp: &CarbProvider, tx_hash: &str) -> Result<bool, DataProviderCarbError> {
+    hex::decode(tx_hash)?;
+    match cp.get::<Spent>(&format!("txs/{tx_hash}/spent"), &[]).await {
+        Ok(_) => Ok(true),
+        Err(DataProviderCarbError::RequestValueNotFound(_)) => Ok(false),
+        Err(e) => Err(e),
+    }
 }
 
-pub fn retrieve_staked_amount (
-    bfp: &CarbProvider,
+/// POST `addresses/exist`
+pub async fn addresses_exist(
+    cp: &CarbProvider,
+    addresses: &Vec<&str>,
+) -> Result<Vec<bool>, DataProviderCarbError> {
+    cp.post(
+        "addresses/exist",
+        &AddressesRequest {
+            addresses,
+            slot: None,
+        },
+    )
+    .await
+}
+
+/// POST `addresses/transactions`
+pub async fn tx_history(
+    cp: &CarbProvider,
+    addresses: &Vec<&str>,
+    slot: Option<u64>,
+) -> Result<Vec<TxHistoryListView>, DataProviderCarbError> {
+    cp.post(
+        "addresses/transactions",
+        &AddressesRequest { addresses, slot },
+    )
+    .await
+}
+
+/// GET `accounts/{stake_address}/staked?epoch={epoch}`
+pub async fn retrieve_staked_amount(
+    cp: &CarbProvider,
     epoch: i32,
     stake_addr: &str,
-) -> Result<BigDecimal, DataProviderError> {
-    Ok(BigDecimal::from(0))
+) -> Result<BigDecimal, DataProviderCarbError> {
+    let staked: Amount = cp
+        .get(
+            &format!("accounts/{stake_addr}/staked"),
+            &[("epoch", &epoch.to_string())],
+        )
+        .await?;
+    BigDecimal::from_str(&staked.amount).map_err(|e| DataProviderCarbError::Custom(e.to_string()))
 }
 
-pub fn retrieve_generated_rewards (
-    bfp: &CarbProvider,
+/// GET `accounts/{stake_address}/rewards`
+pub async fn retrieve_generated_rewards(
+    cp: &CarbProvider,
     stake_addr: &str,
-) -> Result<Vec<RewardView>, DataProviderError> {
-    Ok(vec![])
+) -> Result<Vec<RewardView>, DataProviderCarbError> {
+    cp.get(&format!("accounts/{stake_addr}/rewards"), &[]).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::carb::{CarbProvider, Config};
+    use crate::provider::CardanoDataProvider;
+    use cardano_serialization_lib as csl;
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    const ADDR: &str = "addr1vyqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qavsj8u";
+    const STAKE_ADDR: &str = "stake1u9jx2en8dp5k56mvd4hx7ur3wfehgatkwau8j7nm037hulctderj8";
+    const TX_HASH: &str = "1b07f1152e52ce0a9dbb561aa2e2d1750ca3a1a4141150a8bad342947a66a3a6";
+
+    fn provider(server: &MockServer) -> crate::DataProvider<CarbProvider> {
+        crate::DataProvider::new(CarbProvider::new(Config {
+            url: server.url("/api/v1"),
+            api_token: "token".to_owned(),
+            network: crate::config::ChainWellKnownInfo::mainnet(),
+        }))
+    }
+
+    fn utxo_cbor(index: u32, lovelace: &str) -> String {
+        let input = csl::TransactionInput::new(
+            &csl::crypto::TransactionHash::from_bytes(hex::decode(TX_HASH).unwrap()).unwrap(),
+            index,
+        );
+        let output = csl::TransactionOutput::new(
+            &csl::address::Address::from_bech32(ADDR).unwrap(),
+            &csl::utils::Value::new(&csl::utils::BigNum::from_str(lovelace).unwrap()),
+        );
+        hex::encode(csl::utils::TransactionUnspentOutput::new(&input, &output).to_bytes())
+    }
+
+    #[tokio::test]
+    async fn test_tip() {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/api/v1/tip")
+                    .header("authorization", "Bearer token");
+                then.status(200)
+                    .json_body(json!({ "slot": 95654321, "epoch": 412 }));
+            })
+            .await;
+
+        let dp = provider(&server);
+        assert!(dp.alive().await);
+        assert_eq!(dp.slot().await.unwrap(), 95654321);
+        assert_eq!(dp.current_epoch().await.unwrap(), 412);
+        mock.assert_hits_async(3).await;
+    }
+
+    #[tokio::test]
+    async fn test_address_utxos() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/api/v1/addresses/{ADDR}/utxos"));
+                then.status(200).json_body(json!([
+                    { "cbor": utxo_cbor(0, "1500000") },
+                    { "cbor": utxo_cbor(1, "2000000") }
+                ]));
+            })
+            .await;
+
+        let dp = provider(&server);
+        let utxos = dp.script_utxos(ADDR).await.unwrap();
+        assert_eq!(utxos.len(), 2);
+        assert_eq!(utxos.get(1).input().index(), 1);
+        assert_eq!(utxos.get(1).output().amount().coin().to_str(), "2000000");
+    }
+
+    #[tokio::test]
+    async fn test_check_stakeaddr_registered() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/api/v1/accounts/{STAKE_ADDR}"));
+                then.status(404);
+            })
+            .await;
+
+        let dp = provider(&server);
+        assert!(!dp.check_stakeaddr_registered(STAKE_ADDR).await.unwrap());
+        assert!(dp.pool_valid("pool1unknown").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tx_included() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/api/v1/txs/{TX_HASH}/spent"));
+                then.status(200).json_body(json!({ "spent": false }));
+            })
+            .await;
+        let pending = "0000000000000000000000000000000000000000000000000000000000000000";
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/api/v1/txs/{pending}/spent"));
+                then.status(404);
+            })
+            .await;
+
+        let dp = provider(&server);
+        assert!(dp.tx_included(TX_HASH).await.unwrap());
+        assert!(!dp.tx_included(pending).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_tx_history() {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/api/v1/addresses/transactions")
+                    .json_body(json!({ "addresses": [ADDR], "slot": 1000 }));
+                then.status(200).json_body(json!([
+                    { "hash": TX_HASH, "slot": 900, "assets": [] }
+                ]));
+            })
+            .await;
+
+        let dp = provider(&server);
+        let history = dp.tx_history(&vec![ADDR], Some(1000)).await.unwrap();
+        mock.assert_async().await;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].hash, TX_HASH);
+        assert_eq!(history[0].slot, 900);
+    }
 }
@@ -3,10 +3,18 @@ use thiserror::Error;
 #[allow(clippy::enum_variant_names)]
 #[derive(Error, Debug)]
 pub enum DataProviderCarbError {
-    #[error("blockfrost provider general error")]
+    #[error("carb provider general error")]
     GeneralError(String),
     #[error("Custom Error: {:?}", self)]
     Custom(String),
+    #[error("Carb responded with status {status}: {message}")]
+    ResponseError { status: u16, message: String },
+    #[error("Requested value could not be found: {0}")]
+    RequestValueNotFound(String),
+    #[error("Not supported by Carb: {0}")]
+    Unsupported(String),
+    #[error(transparent)]
+    RequestError(#[from] reqwest::Error),
     #[error(transparent)]
     ParseIntError(#[from] core::num::ParseIntError),
     #[error(transparent)]
@@ -0,0 +1,83 @@
+use cardano_serialization_lib as csl;
+use serde::{Deserialize, Serialize};
+
+use super::error::DataProviderCarbError;
+use crate::models::CDPDatum;
+
+// Carb responses which are not covered by the views in `crate::models`
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Tip {
+    pub slot: i64,
+    pub epoch: i32,
+}
+
+/// Unspent output as hex encoded CBOR of a `TransactionUnspentOutput`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Utxo {
+    pub cbor: String,
+}
+
+impl Utxo {
+    pub fn to_txuo(&self) -> Result<dcslc::TransactionUnspentOutput, DataProviderCarbError> {
+        Ok(csl::utils::TransactionUnspentOutput::from_bytes(
+            hex::decode(&self.cbor)?,
+        )?)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Datum {
+    pub hash: String,
+    pub json: Option<serde_json::Value>,
+    pub bytes: String,
+    pub address: String,
+    pub addr_has_script: bool,
+}
+
+impl Datum {
+    pub fn to_cdp_datum(&self) -> Result<CDPDatum, DataProviderCarbError> {
+        Ok(CDPDatum {
+            hash: hex::decode(&self.hash)?,
+            json: self.json.clone(),
+            bytes: hex::decode(&self.bytes)?,
+            address: self.address.clone(),
+            addr_has_script: self.addr_has_script,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Address {
+    pub address: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Account {
+    pub stake_address: String,
+    pub registered: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Pool {
+    pub pool_id: String,
+    pub retired: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Amount {
+    /// lovelace as decimal string
+    pub amount: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Spent {
+    pub spent: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AddressesRequest<'a> {
+    pub addresses: &'a [&'a str],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u64>,
+}
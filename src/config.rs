@@ -1,7 +1,9 @@
 use serde::{de::Deserializer, Deserialize, Serialize};
 
+use crate::provider::config::{BlockfrostConfig, CarbConfig, DbsyncConfig, KoiosConfig};
 use crate::provider::error::DataProviderError;
-use crate::provider::ProviderType;
+use crate::provider::{CardanoDataProvider, ProviderType};
+use std::sync::Arc;
 
 /// Well-known magic for testnet
 pub const TESTNET_MAGIC: u64 = 1097911063;
@@ -83,20 +85,20 @@ impl ChainWellKnownInfo {
         }
     }
 
+    /// Hardcoded values for the preview testnet, it starts directly in the Shelley era
     pub fn preview() -> Self {
         ChainWellKnownInfo {
             magic: PREVIEW_MAGIC,
-            byron_epoch_length: 432000,
+            byron_epoch_length: 86400,
             byron_slot_length: 20,
             byron_known_slot: 0,
             byron_known_hash: "".to_string(),
-            byron_known_time: 1660003200,
-            shelley_epoch_length: 432000,
+            byron_known_time: 1666656000,
+            shelley_epoch_length: 86400,
             shelley_slot_length: 1,
-            shelley_known_slot: 25260,
-            shelley_known_hash: "cac921895ef5f2e85f7e6e6b51b663ab81b3605cd47d6b6d66e8e785e5c65011"
-                .to_string(),
-            shelley_known_time: 1660003200,
+            shelley_known_slot: 0,
+            shelley_known_hash: "".to_string(),
+            shelley_known_time: 1666656000,
             address_network_id: 0,
             adahandle_policy: "".to_string(),
         }
@@ -117,12 +119,36 @@ impl ChainWellKnownInfo {
             shelley_known_slot: 86400,
             shelley_known_hash: "c4a1595c5cc7a31eda9e544986fe9387af4e3491afe0ca9a80714f01951bbd5c"
                 .to_string(),
-            shelley_known_time: 1654041600,
+            shelley_known_time: 1655769600,
             address_network_id: 0,
             adahandle_policy: "".to_string(),
         }
     }
 
+    /// Fails with `WrongNetwork` if a bech32 address or stake address belongs to another
+    /// network. Strings which are not bech32 addresses are left to the provider to reject.
+    pub fn check_address(&self, address: &str) -> Result<(), DataProviderError> {
+        let found = match cardano_serialization_lib::address::Address::from_bech32(address)
+            .ok()
+            .and_then(|a| a.network_id().ok())
+        {
+            Some(network_id) => network_id,
+            None => return Ok(()),
+        };
+        if found != self.address_network_id {
+            return Err(DataProviderError::WrongNetwork {
+                expected: self.address_network_id,
+                found,
+                address: address.to_owned(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn check_addresses(&self, addresses: &[&str]) -> Result<(), DataProviderError> {
+        addresses.iter().try_for_each(|a| self.check_address(a))
+    }
+
     /// Uses the value of the magic to return either mainnet or testnet
     /// hardcoded values.
     pub fn try_from_magic(magic: u64) -> Result<ChainWellKnownInfo, DataProviderError> {
@@ -144,7 +170,7 @@ impl Default for ChainWellKnownInfo {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(tag = "type")]
 pub enum ChainConfig {
     Mainnet,
@@ -188,7 +214,9 @@ impl DeserializeWith for ProviderType {
         match s.as_ref() {
             "dbsync" => Ok(ProviderType::Dbsync),
             "blockfrost" => Ok(ProviderType::Blockfrost),
-            _ => Err(serde::de::Error::custom("error trying to deserialize connectivity.provider, should be one of [dbsync, blockfrost]"))
+            "koios" => Ok(ProviderType::Koios),
+            "carb" => Ok(ProviderType::Carb),
+            _ => Err(serde::de::Error::custom("error trying to deserialize connectivity.provider, should be one of [dbsync, blockfrost, koios, carb]"))
         }
     }
 }
@@ -197,6 +225,105 @@ impl DeserializeWith for ProviderType {
 pub struct ConfigRoot {
     pub appconfigs: appconfigs::Config,
     pub connectivity: connectivity::Config,
+    /// network the providers are connected to, required so a testnet deployment can not
+    /// silently validate addresses against mainnet
+    pub network: ChainConfig,
+    pub dbsync: Option<DbsyncConfig>,
+    pub blockfrost: Option<BlockfrostConfig>,
+    pub koios: Option<KoiosConfig>,
+    pub carb: Option<CarbConfig>,
+    pub aya: Option<AyaConfig>,
+    #[serde(default)]
+    pub grpc: GrpcConfig,
+}
+
+/// Contracts of the Aya validator network followed by the gRPC service
+#[derive(Deserialize, Debug, Clone)]
+pub struct AyaConfig {
+    /// script address holding the delegator stake and unbonding outputs
+    pub staking_address: String,
+    /// script address validators register at, needed to stream registration events
+    pub registration_address: Option<String>,
+    /// layout of the datums at `staking_address`
+    #[serde(default)]
+    pub stake_datum: StakeDatumLayout,
+}
+
+/// Constructor indices and field positions of the stake datum. The defaults are the layout of
+/// [`crate::datum::AYA_STAKE`], a contract with a different layout is followed by setting them
+/// in `[aya.stake_datum]`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct StakeDatumLayout {
+    /// constructor of a staked position
+    pub staked_constructor: u64,
+    /// constructor of an unstaked position which is locked until its release epoch
+    pub unstaked_constructor: u64,
+    /// field holding the stake key hash of the delegator
+    pub delegator_field: usize,
+    /// field holding the utf-8 operator address of the validator
+    pub validator_field: usize,
+    /// field of the unstaked constructor holding the release epoch
+    pub release_epoch_field: usize,
+}
+
+impl Default for StakeDatumLayout {
+    fn default() -> Self {
+        StakeDatumLayout {
+            staked_constructor: 0,
+            unstaked_constructor: 1,
+            delegator_field: 0,
+            validator_field: 1,
+            release_epoch_field: 2,
+        }
+    }
+}
+
+/// gRPC listener, without the section it listens on `0.0.0.0:50051` without TLS and requires
+/// the same JWTs as the REST api
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct GrpcConfig {
+    /// socket address, `0.0.0.0:50051` if omitted
+    pub address: Option<String>,
+    /// PEM certificate chain file, TLS is enabled if it is set together with `tls_key`
+    pub tls_cert: Option<String>,
+    /// PEM private key file of `tls_cert`
+    pub tls_key: Option<String>,
+    /// set to false to serve the gRPC services without authentication
+    pub auth: Option<bool>,
+}
+
+impl GrpcConfig {
+    pub const DEFAULT_ADDRESS: &'static str = "0.0.0.0:50051";
+
+    pub fn address(&self) -> Result<std::net::SocketAddr, DataProviderError> {
+        let address = self.address.as_deref().unwrap_or(Self::DEFAULT_ADDRESS);
+        address
+            .parse()
+            .map_err(|_| DataProviderError::Custom(format!("invalid grpc.address {address}")))
+    }
+
+    /// true if clients have to send a JWT
+    pub fn auth(&self) -> bool {
+        self.auth.unwrap_or(true)
+    }
+
+    /// Reads the certificate and key, `None` if TLS is not configured
+    pub fn tls(&self) -> Result<Option<tonic::transport::ServerTlsConfig>, DataProviderError> {
+        let read = |path: &str| {
+            std::fs::read(path)
+                .map_err(|e| DataProviderError::Custom(format!("could not read {path}: {e}")))
+        };
+        match (&self.tls_cert, &self.tls_key) {
+            (None, None) => Ok(None),
+            (Some(cert), Some(key)) => Ok(Some(tonic::transport::ServerTlsConfig::new().identity(
+                tonic::transport::Identity::from_pem(read(cert)?, read(key)?),
+            ))),
+            _ => Err(DataProviderError::Custom(
+                "grpc.tls_cert and grpc.tls_key have to be set together".to_string(),
+            )),
+        }
+    }
 }
 
 impl ConfigRoot {
@@ -217,15 +344,41 @@ impl ConfigRoot {
         s.build()?.try_deserialize()
     }
 
+    pub fn network(&self) -> Result<ChainWellKnownInfo, DataProviderError> {
+        match self.network {
+            ChainConfig::Custom(magic) => ChainWellKnownInfo::try_from_magic(magic),
+            ref other => Ok(other.clone().into()),
+        }
+    }
+
+    /// Builds the provider selected by `connectivity.provider` from its section
+    pub fn provider(&self) -> Result<Arc<dyn CardanoDataProvider>, DataProviderError> {
+        crate::provider::config::build_provider(self)
+    }
+
+    /// DBSync provider for the queries only DBSync can answer, `None` if no database is configured
+    pub fn dbsync_provider(&self) -> Result<Option<crate::DBSyncProvider>, DataProviderError> {
+        crate::provider::config::build_dbsync(self)
+    }
+
+    /// The configured submit-api endpoints, empty entries are skipped
+    pub fn submit_endpoints(&self) -> Vec<String> {
+        [
+            &self.connectivity.submit_endpoint_1,
+            &self.connectivity.submit_endpoint_2,
+            &self.connectivity.submit_endpoint_3,
+        ]
+        .into_iter()
+        .filter(|e| !e.is_empty())
+        .cloned()
+        .collect()
+    }
+
     pub fn set_as_env(&self) {
         match self.appconfigs.clone() {
             appconfigs::Config::Nft(x) => std::env::set_var("ENNFT_POLICY", x.policy_id),
             appconfigs::Config::None => {}
         }
-        std::env::set_var("PROVIDER", &self.connectivity.provider.as_str());
-        std::env::set_var("DBSYNC_URL", &self.connectivity.dbsync_url);
-        //std::env::set_var("BLOCKFROST_API_URL", &self.connectivity.blockfrost_api_url);
-        //std::env::set_var("BLOCKFROST_PROJECT_ID", &self.connectivity.blockfrost_project_id);
         std::env::set_var("TX_SUBMIT_ENDPOINT1", &self.connectivity.submit_endpoint_1);
         std::env::set_var("TX_SUBMIT_ENDPOINT2", &self.connectivity.submit_endpoint_2);
         std::env::set_var("TX_SUBMIT_ENDPOINT3", &self.connectivity.submit_endpoint_3);
@@ -241,15 +394,17 @@ mod connectivity {
     pub struct Config {
         #[serde(deserialize_with = "ProviderType::deserialize_with")]
         pub provider: ProviderType,
-        pub dbsync_url: String,
-        // pub blockfrost_api_url: String,
-        // pub blockfrost_project_id: String,
+        /// superseded by the `[dbsync]` section
+        pub dbsync_url: Option<String>,
         pub submit_endpoint_1: String,
         pub submit_endpoint_2: String,
         pub submit_endpoint_3: String,
         pub protocol_parameter_path: String,
         pub cert_private_key: Option<String>,
         pub cert_pub_key: String,
+        /// seconds after which transactions submitted through cdp are dropped from the
+        /// pending transactions if they are not on-chain, 20 minutes if omitted
+        pub tx_cache_ttl: Option<u64>,
     }
 }
 
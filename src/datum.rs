@@ -0,0 +1,540 @@
+//! Schema-driven decoding of Plutus datums
+//!
+//! A [`DatumSchema`] declares the constructors of a datum and the names and types of their
+//! fields once. [`DatumSchema::decode`] checks a datum against it and returns the named fields,
+//! which typed structs are built from or which are returned as JSON. Additional fields after
+//! the declared ones are ignored.
+//!
+//! ```
+//! use cdp::datum::{Constructor, DatumSchema, Field, FieldType};
+//!
+//! const VOTE: DatumSchema = DatumSchema {
+//!     name: "vote",
+//!     constructors: &[Constructor {
+//!         index: 0,
+//!         name: "vote",
+//!         fields: &[
+//!             Field::new("voter", FieldType::KeyHash),
+//!             Field::new("choice", FieldType::Int),
+//!         ],
+//!     }],
+//! };
+//! ```
+
+use crate::provider::error::DataProviderError;
+use cardano_serialization_lib as csl;
+use csl::address::{BaseAddress, EnterpriseAddress, StakeCredential};
+use csl::plutus::{PlutusData, PlutusList};
+use csl::utils::from_bignum;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DatumError {
+    #[error("unknown datum schema {0}, known schemas are {}", schema_names().join(", "))]
+    UnknownSchema(String),
+    #[error("{schema} datum is not plutus data")]
+    NotPlutusData { schema: &'static str },
+    #[error("{schema} datum is not a constructor")]
+    NotConstr { schema: &'static str },
+    #[error("{schema} datum has the unknown constructor {index}")]
+    UnknownConstructor { schema: &'static str, index: u64 },
+    #[error("{schema} datum field {field} {reason}")]
+    Field {
+        schema: &'static str,
+        field: &'static str,
+        reason: String,
+    },
+}
+
+impl From<DatumError> for DataProviderError {
+    fn from(err: DatumError) -> Self {
+        DataProviderError::InvalidInput(err.to_string())
+    }
+}
+
+/// Encoding of a datum field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    /// byte string, hex encoded in JSON
+    Bytes,
+    /// byte string holding utf-8 text
+    Utf8,
+    /// integer which fits into an `i64`
+    Int,
+    /// 28 byte key or script hash, hex encoded in JSON
+    KeyHash,
+    /// plutus `Address`, bech32 encoded in JSON
+    Address,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Field {
+    pub name: &'static str,
+    pub field_type: FieldType,
+}
+
+impl Field {
+    pub const fn new(name: &'static str, field_type: FieldType) -> Self {
+        Self { name, field_type }
+    }
+}
+
+/// One constructor of a datum and its fields in order
+#[derive(Debug, Clone, Copy)]
+pub struct Constructor {
+    pub index: u64,
+    pub name: &'static str,
+    pub fields: &'static [Field],
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DatumSchema {
+    pub name: &'static str,
+    pub constructors: &'static [Constructor],
+}
+
+/// Payment and staking credential of a plutus `Address`, it only gets a network once it is
+/// encoded
+#[derive(Debug, Clone)]
+pub struct PlutusAddress {
+    pub payment: StakeCredential,
+    pub stake: Option<StakeCredential>,
+}
+
+impl PlutusAddress {
+    pub fn to_bech32(&self, network_id: u8) -> Result<String, DataProviderError> {
+        let address = match &self.stake {
+            Some(stake) => BaseAddress::new(network_id, &self.payment, stake).to_address(),
+            None => EnterpriseAddress::new(network_id, &self.payment).to_address(),
+        };
+        Ok(address.to_bech32(None)?)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum FieldValue {
+    Bytes(Vec<u8>),
+    Utf8(String),
+    Int(i64),
+    KeyHash(Vec<u8>),
+    Address(PlutusAddress),
+}
+
+impl FieldValue {
+    fn to_json(&self, network_id: u8) -> Result<serde_json::Value, DataProviderError> {
+        Ok(match self {
+            FieldValue::Bytes(bytes) | FieldValue::KeyHash(bytes) => hex::encode(bytes).into(),
+            FieldValue::Utf8(text) => text.clone().into(),
+            FieldValue::Int(int) => (*int).into(),
+            FieldValue::Address(address) => address.to_bech32(network_id)?.into(),
+        })
+    }
+}
+
+/// Fields of a datum decoded with a schema, in the order of the schema
+#[derive(Debug, Clone)]
+pub struct DecodedDatum {
+    pub schema: &'static str,
+    pub constructor: Constructor,
+    pub fields: Vec<(&'static str, FieldValue)>,
+}
+
+impl DecodedDatum {
+    fn error(&self, field: &'static str, reason: &str) -> DatumError {
+        DatumError::Field {
+            schema: self.schema,
+            field,
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn get(&self, field: &'static str) -> Result<&FieldValue, DatumError> {
+        self.fields
+            .iter()
+            .find(|(name, _)| *name == field)
+            .map(|(_, value)| value)
+            .ok_or_else(|| {
+                self.error(
+                    field,
+                    &format!("is not a field of constructor {}", self.constructor.name),
+                )
+            })
+    }
+
+    /// value of a `Bytes` or `KeyHash` field
+    pub fn bytes(&self, field: &'static str) -> Result<&[u8], DatumError> {
+        match self.get(field)? {
+            FieldValue::Bytes(bytes) | FieldValue::KeyHash(bytes) => Ok(bytes),
+            _ => Err(self.error(field, "is not a byte string")),
+        }
+    }
+
+    pub fn utf8(&self, field: &'static str) -> Result<&str, DatumError> {
+        match self.get(field)? {
+            FieldValue::Utf8(text) => Ok(text),
+            _ => Err(self.error(field, "is not text")),
+        }
+    }
+
+    pub fn int(&self, field: &'static str) -> Result<i64, DatumError> {
+        match self.get(field)? {
+            FieldValue::Int(int) => Ok(*int),
+            _ => Err(self.error(field, "is not an integer")),
+        }
+    }
+
+    /// value of an `Int` field which must not be negative
+    pub fn uint(&self, field: &'static str) -> Result<u64, DatumError> {
+        u64::try_from(self.int(field)?).map_err(|_| self.error(field, "is negative"))
+    }
+
+    pub fn address(&self, field: &'static str) -> Result<&PlutusAddress, DatumError> {
+        match self.get(field)? {
+            FieldValue::Address(address) => Ok(address),
+            _ => Err(self.error(field, "is not an address")),
+        }
+    }
+
+    /// `{"schema", "constructor", "fields": {name: value}}`, addresses are encoded for
+    /// `network_id`
+    pub fn to_json(&self, network_id: u8) -> Result<serde_json::Value, DataProviderError> {
+        let mut fields = serde_json::Map::new();
+        for (name, value) in &self.fields {
+            fields.insert(name.to_string(), value.to_json(network_id)?);
+        }
+        Ok(serde_json::json!({
+            "schema": self.schema,
+            "constructor": self.constructor.name,
+            "fields": fields,
+        }))
+    }
+}
+
+pub(crate) fn constr_fields(data: &PlutusData) -> Option<(u64, PlutusList)> {
+    data.as_constr_plutus_data()
+        .map(|constr| (from_bignum(&constr.alternative()), constr.data()))
+}
+
+fn credential(data: &PlutusData) -> Result<StakeCredential, String> {
+    let (index, fields) = constr_fields(data).ok_or("has no credential constructor")?;
+    let hash = (fields.len() == 1)
+        .then(|| fields.get(0).as_bytes())
+        .flatten()
+        .ok_or("has no credential hash")?;
+    match index {
+        0 => csl::crypto::Ed25519KeyHash::from_bytes(hash)
+            .map(|hash| StakeCredential::from_keyhash(&hash))
+            .map_err(|_| "has an invalid key hash".to_string()),
+        1 => csl::crypto::ScriptHash::from_bytes(hash)
+            .map(|hash| StakeCredential::from_scripthash(&hash))
+            .map_err(|_| "has an invalid script hash".to_string()),
+        other => Err(format!("has the unknown credential constructor {other}")),
+    }
+}
+
+/// `Address { credential, Maybe StakingCredential }`
+fn plutus_address(data: &PlutusData) -> Result<PlutusAddress, String> {
+    let (_, fields) = constr_fields(data).ok_or("is not an address")?;
+    if fields.len() != 2 {
+        return Err("is not an address".to_string());
+    }
+    let payment = credential(&fields.get(0))?;
+    let stake = match constr_fields(&fields.get(1)) {
+        // Just (StakingHash credential)
+        Some((0, maybe)) if maybe.len() == 1 => match constr_fields(&maybe.get(0)) {
+            Some((0, staking)) if staking.len() == 1 => Some(credential(&staking.get(0))?),
+            _ => return Err("has an unsupported staking pointer".to_string()),
+        },
+        // Nothing
+        Some((1, _)) => None,
+        _ => return Err("has an invalid staking credential".to_string()),
+    };
+    Ok(PlutusAddress { payment, stake })
+}
+
+pub(crate) fn field_value(data: &PlutusData, field_type: FieldType) -> Result<FieldValue, String> {
+    let bytes = || data.as_bytes().ok_or("is not a byte string");
+    Ok(match field_type {
+        FieldType::Bytes => FieldValue::Bytes(bytes()?),
+        FieldType::Utf8 => {
+            FieldValue::Utf8(String::from_utf8(bytes()?).map_err(|_| "is not utf-8".to_string())?)
+        }
+        FieldType::Int => FieldValue::Int(
+            data.as_integer()
+                .ok_or("is not an integer")?
+                .to_str()
+                .parse()
+                .map_err(|_| "does not fit into 64 bits".to_string())?,
+        ),
+        FieldType::KeyHash => {
+            let hash = bytes()?;
+            if hash.len() != 28 {
+                return Err(format!("has {} bytes instead of 28", hash.len()));
+            }
+            FieldValue::KeyHash(hash)
+        }
+        FieldType::Address => FieldValue::Address(plutus_address(data)?),
+    })
+}
+
+impl DatumSchema {
+    pub fn decode(&self, data: &PlutusData) -> Result<DecodedDatum, DatumError> {
+        let schema = self.name;
+        let (index, values) = constr_fields(data).ok_or(DatumError::NotConstr { schema })?;
+        let constructor = self
+            .constructors
+            .iter()
+            .find(|c| c.index == index)
+            .copied()
+            .ok_or(DatumError::UnknownConstructor { schema, index })?;
+
+        let mut fields = Vec::with_capacity(constructor.fields.len());
+        for (i, field) in constructor.fields.iter().enumerate() {
+            let error = |reason: String| DatumError::Field {
+                schema,
+                field: field.name,
+                reason,
+            };
+            if i >= values.len() {
+                return Err(error("is missing".to_string()));
+            }
+            fields.push((
+                field.name,
+                field_value(&values.get(i), field.field_type).map_err(error)?,
+            ));
+        }
+        Ok(DecodedDatum {
+            schema,
+            constructor,
+            fields,
+        })
+    }
+
+    /// Decodes CBOR encoded plutus data
+    pub fn decode_bytes(&self, bytes: &[u8]) -> Result<DecodedDatum, DatumError> {
+        let data = PlutusData::from_bytes(bytes.to_vec())
+            .map_err(|_| DatumError::NotPlutusData { schema: self.name })?;
+        self.decode(&data)
+    }
+}
+
+/// Registration of a validator at the Aya registration contract
+pub const AYA_REGISTRATION: DatumSchema = DatumSchema {
+    name: "aya_registration",
+    constructors: &[Constructor {
+        index: 0,
+        name: "registration",
+        fields: &[
+            Field::new("operator_address", FieldType::Utf8),
+            Field::new("consensus_pub_key", FieldType::Utf8),
+            Field::new("merkle_tree_root", FieldType::Utf8),
+            Field::new("cce_address", FieldType::Utf8),
+            Field::new("en_nft_name", FieldType::Utf8),
+            Field::new("en_owner", FieldType::KeyHash),
+            Field::new("signature", FieldType::Bytes),
+        ],
+    }],
+};
+
+/// Delegator position at the Aya staking contract, the stake key hash of the delegator and
+/// the operator address of the validator. This is the default layout followed by the gRPC
+/// service, it can be changed with [`crate::config::StakeDatumLayout`].
+pub const AYA_STAKE: DatumSchema = DatumSchema {
+    name: "aya_stake",
+    constructors: &[
+        Constructor {
+            index: 0,
+            name: "staked",
+            fields: &[
+                Field::new("delegator", FieldType::KeyHash),
+                Field::new("validator", FieldType::Utf8),
+            ],
+        },
+        Constructor {
+            index: 1,
+            name: "unstaked",
+            fields: &[
+                Field::new("delegator", FieldType::KeyHash),
+                Field::new("validator", FieldType::Utf8),
+                Field::new("release_epoch", FieldType::Int),
+            ],
+        },
+    ],
+};
+
+/// Schemas which can be selected by name, e.g. by the REST api
+pub const SCHEMAS: &[&DatumSchema] = &[&AYA_REGISTRATION, &AYA_STAKE];
+
+fn schema_names() -> Vec<&'static str> {
+    SCHEMAS.iter().map(|s| s.name).collect()
+}
+
+pub fn schema(name: &str) -> Result<&'static DatumSchema, DatumError> {
+    SCHEMAS
+        .iter()
+        .find(|s| s.name == name)
+        .copied()
+        .ok_or_else(|| DatumError::UnknownSchema(name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use csl::plutus::ConstrPlutusData;
+
+    const PAYMENT: [u8; 28] = [1; 28];
+    const STAKE: [u8; 28] = [2; 28];
+
+    fn constr(index: u64, fields: Vec<PlutusData>) -> PlutusData {
+        let mut list = PlutusList::new();
+        for field in &fields {
+            list.add(field);
+        }
+        PlutusData::new_constr_plutus_data(&ConstrPlutusData::new(
+            &csl::utils::to_bignum(index),
+            &list,
+        ))
+    }
+
+    fn bytes(bytes: &[u8]) -> PlutusData {
+        PlutusData::new_bytes(bytes.to_vec())
+    }
+
+    fn int(int: &str) -> PlutusData {
+        PlutusData::new_integer(&csl::utils::BigInt::from_str(int).unwrap())
+    }
+
+    const ADDRESS_SCHEMA: DatumSchema = DatumSchema {
+        name: "payout",
+        constructors: &[Constructor {
+            index: 0,
+            name: "payout",
+            fields: &[
+                Field::new("to", FieldType::Address),
+                Field::new("amount", FieldType::Int),
+            ],
+        }],
+    };
+
+    #[test]
+    fn test_decode_stake() {
+        let staked = AYA_STAKE
+            .decode(&constr(0, vec![bytes(&STAKE), bytes(b"validator1")]))
+            .unwrap();
+        assert_eq!(staked.constructor.name, "staked");
+        assert_eq!(staked.bytes("delegator").unwrap(), STAKE);
+        assert_eq!(staked.utf8("validator").unwrap(), "validator1");
+        assert!(staked.uint("release_epoch").is_err());
+
+        let unstaked = AYA_STAKE
+            .decode(&constr(
+                1,
+                vec![bytes(&STAKE), bytes(b"validator1"), int("412")],
+            ))
+            .unwrap();
+        assert_eq!(unstaked.uint("release_epoch").unwrap(), 412);
+        assert_eq!(
+            unstaked.to_json(0).unwrap(),
+            serde_json::json!({
+                "schema": "aya_stake",
+                "constructor": "unstaked",
+                "fields": {
+                    "delegator": hex::encode(STAKE),
+                    "validator": "validator1",
+                    "release_epoch": 412,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let err = |data: PlutusData| AYA_STAKE.decode(&data).unwrap_err();
+        assert_eq!(
+            err(bytes(&STAKE)),
+            DatumError::NotConstr {
+                schema: "aya_stake"
+            }
+        );
+        assert_eq!(
+            err(constr(2, vec![])),
+            DatumError::UnknownConstructor {
+                schema: "aya_stake",
+                index: 2
+            }
+        );
+        assert_eq!(
+            err(constr(1, vec![bytes(&STAKE), bytes(b"validator1")])).to_string(),
+            "aya_stake datum field release_epoch is missing"
+        );
+        assert_eq!(
+            err(constr(0, vec![bytes(&[7; 3]), bytes(b"validator1")])).to_string(),
+            "aya_stake datum field delegator has 3 bytes instead of 28"
+        );
+        assert_eq!(
+            err(constr(0, vec![bytes(&STAKE), bytes(&[0xff])])).to_string(),
+            "aya_stake datum field validator is not utf-8"
+        );
+        assert!(matches!(
+            err(constr(
+                1,
+                vec![
+                    bytes(&STAKE),
+                    bytes(b"validator1"),
+                    int("18446744073709551616")
+                ]
+            )),
+            DatumError::Field {
+                field: "release_epoch",
+                ..
+            }
+        ));
+        assert!(AYA_STAKE.decode_bytes(&[0xff]).is_err());
+    }
+
+    #[test]
+    fn test_decode_address() {
+        let key = |hash: &[u8]| constr(0, vec![bytes(hash)]);
+        let base = constr(
+            0,
+            vec![key(&PAYMENT), constr(0, vec![constr(0, vec![key(&STAKE)])])],
+        );
+        let decoded = ADDRESS_SCHEMA
+            .decode(&constr(0, vec![base, int("-5")]))
+            .unwrap();
+        let expected = BaseAddress::new(
+            1,
+            &StakeCredential::from_keyhash(
+                &csl::crypto::Ed25519KeyHash::from_bytes(PAYMENT.to_vec()).unwrap(),
+            ),
+            &StakeCredential::from_keyhash(
+                &csl::crypto::Ed25519KeyHash::from_bytes(STAKE.to_vec()).unwrap(),
+            ),
+        )
+        .to_address()
+        .to_bech32(None)
+        .unwrap();
+        assert_eq!(
+            decoded.address("to").unwrap().to_bech32(1).unwrap(),
+            expected
+        );
+        assert_eq!(decoded.int("amount").unwrap(), -5);
+        assert!(decoded.uint("amount").is_err());
+
+        let script = constr(0, vec![constr(1, vec![bytes(&PAYMENT)]), constr(1, vec![])]);
+        let decoded = ADDRESS_SCHEMA
+            .decode(&constr(0, vec![script, int("1")]))
+            .unwrap();
+        let address = decoded.address("to").unwrap().to_bech32(0).unwrap();
+        assert!(address.starts_with("addr_test1w"));
+    }
+
+    #[test]
+    fn test_schema() {
+        assert_eq!(schema("aya_registration").unwrap().name, "aya_registration");
+        assert_eq!(
+            schema("cip68").unwrap_err().to_string(),
+            "unknown datum schema cip68, known schemas are aya_registration, aya_stake"
+        );
+    }
+}
@@ -6,36 +6,313 @@ pub mod error;
 pub mod models;
 mod schema;
 
-use diesel::pg::PgConnection;
-use diesel::prelude::*;
-use bigdecimal::BigDecimal;
+use diesel::connection::SimpleConnection;
+use diesel::pg::{Pg, PgConnection};
+use diesel::r2d2::{ConnectionManager, CustomizeConnection, Pool, PooledConnection};
+use diesel::{Connection, QueryableByName, RunQueryDsl};
+use bigdecimal::{BigDecimal, ToPrimitive};
+use dcslc::TransactionUnspentOutputs;
+use futures::executor::block_on;
+use futures::stream::{self, BoxStream, StreamExt};
+use std::time::Duration;
+use tokio_stream::wrappers::ReceiverStream;
 
 use crate::models::{CDPDatum, RewardView, TokenInfoView, CardanoNativeAssetView, StakeDelegationView,
     DelegationView, StakeRegistrationView, StakeDeregistrationView, HoldingWalletView,
-    TxHistoryListView, PoolView
+    TxHistoryListView, PoolView, AddressTxView, StakeBalanceView, ResolvedUtxos, TxHash,
+    TransactionView,
 };
+use crate::config::ChainWellKnownInfo;
 use crate::provider::error::DataProviderError;
+use crate::provider::{Page, PageRequest};
 
 extern crate pretty_env_logger;
 
+type DBSyncPool = Pool<ConnectionManager<PgConnection>>;
+
+/// cursors of paginated queries hold the id of the last returned row
+const PAGE_KEY: &str = "dbsync";
+/// rows fetched from a server-side cursor at once
+const STREAM_BATCH_SIZE: usize = 1000;
+type DBSyncConnection = PooledConnection<ConnectionManager<PgConnection>>;
+
 #[derive(Debug, Clone)]
 pub struct Config {
     pub db_path: String,
+    /// maximum number of connections the pool keeps open, must be greater than zero
+    pub max_connections: u32,
+    /// connections idle for longer than this are closed, `None` keeps them open
+    pub idle_timeout: Option<Duration>,
+    /// how long a query waits for a free connection before it fails, must be greater than zero
+    pub connection_timeout: Duration,
+    /// upper bound for a single provider call, also set as `statement_timeout` on every connection
+    pub query_timeout: Duration,
+    /// addresses passed to the provider are checked against this network
+    pub network: ChainWellKnownInfo,
 }
 
-#[derive(Debug, Clone)]
+impl Config {
+    pub fn new(db_path: String) -> Self {
+        Config {
+            db_path,
+            max_connections: 10,
+            idle_timeout: Some(Duration::from_secs(600)),
+            connection_timeout: Duration::from_secs(30),
+            query_timeout: Duration::from_secs(60),
+            network: ChainWellKnownInfo::mainnet(),
+        }
+    }
+}
+
+/// DBSync backed data provider
+///
+/// Connections are taken from a pool which is created lazily, no connection is
+/// established before the first query. Clones share the same pool.
+#[derive(Clone)]
 pub struct DBSyncProvider {
-    //connection: PgConnection,
     config: Config,
+    pool: DBSyncPool,
 }
 
 impl DBSyncProvider {
-    pub fn new(config: Config) -> Self {
-        DBSyncProvider { config }
+    /// Fails with `InvalidInput` on a pool size or connection timeout of zero
+    pub fn new(config: Config) -> Result<Self, DataProviderError> {
+        if config.max_connections == 0 {
+            return Err(DataProviderError::InvalidInput(
+                "dbsync max_connections must be greater than zero".to_owned(),
+            ));
+        }
+        if config.connection_timeout.is_zero() {
+            return Err(DataProviderError::InvalidInput(
+                "dbsync connection_timeout must be greater than zero".to_owned(),
+            ));
+        }
+        let pool = Pool::builder()
+            .max_size(config.max_connections)
+            .idle_timeout(config.idle_timeout)
+            .connection_timeout(config.connection_timeout)
+            .min_idle(Some(0))
+            .connection_customizer(Box::new(StatementTimeout(config.query_timeout)))
+            .build_unchecked(ConnectionManager::<PgConnection>::new(&config.db_path));
+        Ok(DBSyncProvider { config, pool })
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    fn connect(&self) -> Result<DBSyncConnection, DataProviderDBSyncError> {
+        Ok(self.pool.get()?)
+    }
+
+    /// Runs blocking diesel queries on tokio's blocking thread pool, so a slow query
+    /// does not stall the runtime. Fails with `Timeout` after `query_timeout`.
+    async fn query<F, R>(&self, f: F) -> Result<R, DataProviderDBSyncError>
+    where
+        F: FnOnce(&DBSyncProvider) -> Result<R, DataProviderDBSyncError> + Send + 'static,
+        R: Send + 'static,
+    {
+        let dbs = self.clone();
+        let timeout = self.config.query_timeout;
+        match tokio::time::timeout(timeout, tokio::task::spawn_blocking(move || f(&dbs))).await {
+            Ok(result) => result?,
+            Err(_) => Err(DataProviderDBSyncError::Timeout(timeout)),
+        }
+    }
+
+    /// Streams the rows of `query` through a server-side cursor, so only one batch is held
+    /// in memory. The cursor lives in a transaction on a dedicated blocking thread which
+    /// stops as soon as the stream is dropped. `statement_timeout` applies to every batch,
+    /// the query timeout of `query` does not apply to the whole export. `map` converts a
+    /// whole batch on the cursor's connection, so lookups for its rows take one query.
+    fn cursor_stream<R, T, F>(
+        &self,
+        query: Result<String, DataProviderError>,
+        map: F,
+    ) -> BoxStream<'static, Result<T, DataProviderError>>
+    where
+        R: QueryableByName<Pg> + Send + 'static,
+        T: Send + 'static,
+        F: Fn(&mut DBSyncConnection, Vec<R>) -> Result<Vec<T>, DataProviderDBSyncError>
+            + Send
+            + 'static,
+    {
+        let query = match query {
+            Ok(query) => query,
+            Err(e) => return stream::once(async move { Err(e) }).boxed(),
+        };
+        let (tx, rx) = tokio::sync::mpsc::channel(STREAM_BATCH_SIZE);
+        let dbs = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let result = dbs.connect().and_then(|mut conn| {
+                conn.transaction::<_, DataProviderDBSyncError, _>(|conn| {
+                    conn.batch_execute(&format!(
+                        "DECLARE cdp_export NO SCROLL CURSOR FOR {query}"
+                    ))?;
+                    let fetch = format!("FETCH {STREAM_BATCH_SIZE} FROM cdp_export");
+                    loop {
+                        let rows: Vec<R> = diesel::sql_query(&fetch).load(conn)?;
+                        if rows.is_empty() {
+                            return Ok(());
+                        }
+                        for item in map(conn, rows)? {
+                            // the receiver is gone, ending the transaction closes the cursor
+                            if tx.blocking_send(Ok(item)).is_err() {
+                                return Ok(());
+                            }
+                        }
+                    }
+                })
+            });
+            if let Err(e) = result {
+                let _ = tx.blocking_send(Err(e.into()));
+            }
+        });
+        ReceiverStream::new(rx).boxed()
+    }
+
+    /// Circulating supply of a token, `None` if it is unknown
+    pub async fn token_supply(
+        &self,
+        fingerprint: &str,
+    ) -> Result<Option<BigDecimal>, DataProviderError> {
+        let fingerprint = fingerprint.to_owned();
+        Ok(self
+            .query(move |dbs| block_on(api::token_supply(dbs, &fingerprint)))
+            .await?)
+    }
+
+    /// `true` if the token has a supply of one
+    pub async fn check_nft_supply(&self, fingerprint: &str) -> Result<bool, DataProviderError> {
+        let fingerprint = fingerprint.to_owned();
+        Ok(self
+            .query(move |dbs| block_on(api::check_nft_supply(dbs, &fingerprint)))
+            .await?)
+    }
+
+    /// [`Self::check_nft_supply`] of every fingerprint
+    pub async fn is_nft(&self, fingerprints: &[&str]) -> Result<Vec<bool>, DataProviderError> {
+        let fingerprints: Vec<String> = fingerprints.iter().map(|f| f.to_string()).collect();
+        Ok(self
+            .query(move |dbs| {
+                let fingerprints: Vec<&str> = fingerprints.iter().map(String::as_str).collect();
+                block_on(api::is_nft(dbs, &fingerprints))
+            })
+            .await?)
+    }
+
+    pub async fn tx_metadata(
+        &self,
+        tx_hash: &str,
+    ) -> Result<Option<serde_json::Value>, DataProviderError> {
+        let tx_hash = tx_hash.to_owned();
+        Ok(self
+            .query(move |dbs| block_on(api::tx_metadata(dbs, &tx_hash)))
+            .await?)
+    }
+
+    /// Outputs spent by a transaction
+    pub async fn tx_inputs(
+        &self,
+        tx_hash: &str,
+    ) -> Result<TransactionUnspentOutputs, DataProviderError> {
+        let tx_hash = tx_hash.to_owned();
+        Ok(self
+            .query(move |dbs| block_on(api::get_tx_inputs(dbs, &tx_hash)))
+            .await?)
+    }
+
+    /// Outputs created by a transaction
+    pub async fn tx_outputs(
+        &self,
+        tx_hash: &str,
+    ) -> Result<TransactionUnspentOutputs, DataProviderError> {
+        let tx_hash = tx_hash.to_owned();
+        Ok(self
+            .query(move |dbs| block_on(api::get_tx_outputs(dbs, &tx_hash)))
+            .await?)
+    }
+
+    /// Unspent outputs of the payment key addresses of a stake address
+    pub async fn stake_address_utxos(
+        &self,
+        stake_address: &str,
+    ) -> Result<TransactionUnspentOutputs, DataProviderError> {
+        let stake_address = stake_address.to_owned();
+        Ok(self
+            .query(move |dbs| api::get_stake_address_utxos_dep(dbs, &stake_address))
+            .await?)
     }
 
-    fn connect(&self) -> Result<PgConnection, DataProviderDBSyncError> {
-        Ok(PgConnection::establish(&self.config.db_path)?)
+    /// Slot of the block including the transaction
+    pub async fn tx_slot(&self, tx_hash: &str) -> Result<i64, DataProviderError> {
+        let tx_hash = tx_hash.to_owned();
+        Ok(self
+            .query(move |dbs| api::get_tx_slot(dbs, &tx_hash))
+            .await?)
+    }
+
+    pub async fn discover_transaction(
+        &self,
+        tx_hash: &str,
+    ) -> Result<TransactionView, DataProviderError> {
+        let tx_hash = tx_hash.to_owned();
+        Ok(self
+            .query(move |dbs| block_on(api::discover_transaction(dbs, &tx_hash)))
+            .await?)
+    }
+
+    /// Nonce and extra entropy of an epoch
+    pub async fn epoch_nonce(
+        &self,
+        epoch: i32,
+    ) -> Result<(Vec<u8>, Option<Vec<u8>>), DataProviderError> {
+        Ok(self
+            .query(move |dbs| block_on(api::epoch_nonce(dbs, epoch)))
+            .await?)
+    }
+
+    /// Last block of the previous epoch and first block of `epoch` with its nonce
+    pub async fn epoch_change(
+        &self,
+        epoch: Option<i32>,
+    ) -> Result<EpochChangeResponse, DataProviderError> {
+        Ok(self
+            .query(move |dbs| block_on(api::epoch_change(dbs, epoch)))
+            .await?)
+    }
+
+    /// Hashes and slots of the transactions paying to `address` in or after `slot`, oldest first
+    pub async fn txs_to_address_since(
+        &self,
+        address: &str,
+        slot: i64,
+    ) -> Result<Vec<(String, i64)>, DataProviderError> {
+        let address = address.to_owned();
+        Ok(self
+            .query(move |dbs| api::txs_to_address_since(dbs, &address, slot))
+            .await?)
+    }
+}
+
+/// Lets Postgres cancel statements running longer than the query timeout, otherwise
+/// a timed out query would keep its connection busy until it finishes.
+#[derive(Debug)]
+struct StatementTimeout(Duration);
+
+impl CustomizeConnection<PgConnection, diesel::r2d2::Error> for StatementTimeout {
+    fn on_acquire(&self, conn: &mut PgConnection) -> Result<(), diesel::r2d2::Error> {
+        conn.batch_execute(&format!("SET statement_timeout = {}", self.0.as_millis()))
+            .map_err(diesel::r2d2::Error::QueryError)
+    }
+}
+
+impl std::fmt::Debug for DBSyncProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DBSyncProvider")
+            .field("config", &self.config)
+            .field("pool", &self.pool.state())
+            .finish()
     }
 }
 
@@ -45,35 +322,46 @@ unsafe impl Sync for DBSyncProvider {}
 #[async_trait]
 impl super::provider::CardanoDataProvider for DBSyncProvider {
     async fn alive(&self) -> bool {
-        self.connect().is_ok()
+        self.query(|dbs| dbs.connect().map(|_| ())).await.is_ok()
     }
 
     async fn wallet_utxos(
         &self,
         stake_addr: &str,
     ) -> Result<dcslc::TransactionUnspentOutputs, DataProviderError> {
-        Ok(api::get_stake_address_utxos(self, stake_addr)?)
+        self.config.network.check_address(stake_addr)?;
+        let stake_addr = stake_addr.to_owned();
+        Ok(self
+            .query(move |dbs| api::get_stake_address_utxos(dbs, &stake_addr))
+            .await?)
     }
 
     async fn script_utxos(
         &self,
         addr: &str,
     ) -> Result<dcslc::TransactionUnspentOutputs, DataProviderError> {
-        Ok(api::get_address_utxos(self, addr)?)
+        self.config.network.check_address(addr)?;
+        let addr = addr.to_owned();
+        Ok(self.query(move |dbs| api::get_address_utxos(dbs, &addr)).await?)
     }
 
     async fn asset_utxos_on_addr(
         &self,
         addr: &str,
     ) -> Result<dcslc::TransactionUnspentOutputs, DataProviderError> {
-        Ok(api::asset_utxos_on_addr(self, addr)?)
+        self.config.network.check_address(addr)?;
+        let addr = addr.to_owned();
+        Ok(self.query(move |dbs| api::asset_utxos_on_addr(dbs, &addr)).await?)
     }
 
     async fn mint_metadata(
         &self,
         fingerprint_in: &str,
     ) -> Result<TokenInfoView, DataProviderError> {
-        Ok(api::mint_metadata(self, fingerprint_in)?)
+        let fingerprint_in = fingerprint_in.to_owned();
+        Ok(self
+            .query(move |dbs| api::mint_metadata(dbs, &fingerprint_in))
+            .await?)
     }
 
     async fn first_transaction_from_stake_addr(
@@ -83,7 +371,11 @@ impl super::provider::CardanoDataProvider for DBSyncProvider {
         cardano_serialization_lib::address::Address,
         DataProviderError,
     > {
-        let str_addr = api::select_addr_of_first_transaction(self, stake_address_in)?;
+        self.config.network.check_address(stake_address_in)?;
+        let stake_address_in = stake_address_in.to_owned();
+        let str_addr = self
+            .query(move |dbs| api::select_addr_of_first_transaction(dbs, &stake_address_in))
+            .await?;
         Ok(dcslc::addr_from_str(&str_addr)?)
     }
 
@@ -93,7 +385,12 @@ impl super::provider::CardanoDataProvider for DBSyncProvider {
         addr: &str,
         datumhash: &Vec<u8>,
     ) -> Result<dcslc::TransactionUnspentOutput, DataProviderError> {
-        let utxo = api::utxo_by_dataumhash(self, addr, datumhash)?;
+        self.config.network.check_address(addr)?;
+        let addr = addr.to_owned();
+        let datumhash = datumhash.to_owned();
+        let utxo = self
+            .query(move |dbs| api::utxo_by_dataumhash(dbs, &addr, &datumhash))
+            .await?;
         Ok(utxo)
     }
 
@@ -103,17 +400,47 @@ impl super::provider::CardanoDataProvider for DBSyncProvider {
         txhash: &Vec<u8>,
         index: i16,
     ) -> Result<dcslc::TransactionUnspentOutput, DataProviderError> {
-        let utxo = api::utxo_by_txid(self, txhash, index)?;
+        let txhash = txhash.to_owned();
+        let utxo = self
+            .query(move |dbs| api::utxo_by_txid(dbs, &txhash, index))
+            .await?;
         Ok(utxo)
     }
 
+    async fn tx_included(&self, tx_hash: &str) -> Result<bool, DataProviderError> {
+        let tx_hash = hex::decode(tx_hash)?;
+        Ok(self
+            .query(move |dbs| api::tx_included(dbs, &tx_hash))
+            .await?)
+    }
+
+    async fn transaction(&self, tx_hash: &str) -> Result<TransactionView, DataProviderError> {
+        let tx_hash = tx_hash.to_owned();
+        // discover_transaction only runs blocking queries
+        Ok(self
+            .query(move |dbs| block_on(api::discover_transaction(dbs, &tx_hash)))
+            .await?)
+    }
+
+    async fn utxos_by_refs(
+        &self,
+        refs: &[(TxHash, u32)],
+    ) -> Result<ResolvedUtxos, DataProviderError> {
+        let refs = refs.to_vec();
+        Ok(self
+            .query(move |dbs| api::utxos_by_refs(dbs, &refs))
+            .await?)
+    }
+
     async fn utxo_tokens(
         &self,
         utxo_id: i64,
         index: i16,
     ) -> Result<Vec<CardanoNativeAssetView>, DataProviderError>
     {
-        Ok(api::get_utxo_tokens(self, utxo_id, index)?)
+        Ok(self
+            .query(move |dbs| api::get_utxo_tokens(dbs, utxo_id, index))
+            .await?)
     }
 
     async fn active_pools(
@@ -121,7 +448,7 @@ impl super::provider::CardanoDataProvider for DBSyncProvider {
         page: usize,
     ) -> Result<Vec<PoolView>, DataProviderError>
     {
-        let pools = api::get_pools(&self).await?;
+        let pools = self.query(api::get_pools).await?;
         let pools_paged: Vec<Vec<PoolView>> = pools.chunks(100).map(|s| s.into()).collect();
         let pools_page = match pools_paged.get(page) {
             Some(page) => page.to_vec(),
@@ -134,11 +461,12 @@ impl super::provider::CardanoDataProvider for DBSyncProvider {
         &self,
         txid: &Vec<u8>,
     ) -> Result<Vec<CDPDatum>, DataProviderError> {
-        Ok(api::find_datums_for_tx(self, txid)?)
+        let txid = txid.to_owned();
+        Ok(self.query(move |dbs| api::find_datums_for_tx(dbs, &txid)).await?)
     }
 
     async fn slot(&self) -> Result<i64, DataProviderError> {
-        Ok(api::slot(self)?)
+        Ok(self.query(api::slot).await?)
     }
 
     async fn stakers_on_pool(
@@ -147,7 +475,10 @@ impl super::provider::CardanoDataProvider for DBSyncProvider {
         epoch: i32,
     ) -> Result<Vec<StakeDelegationView>, DataProviderError>
     {
-        Ok(api::stakers_on_pool(self, pool, epoch)?)
+        let pool = pool.to_owned();
+        Ok(self
+            .query(move |dbs| api::stakers_on_pool(dbs, &pool, epoch))
+            .await?)
     }
 
     async fn deligations_per_pool_epoch_intervall(
@@ -156,12 +487,12 @@ impl super::provider::CardanoDataProvider for DBSyncProvider {
         start_epoch: i64,
         end_epoch: i64,
     ) -> Result<Vec<DelegationView>, DataProviderError> {
-        Ok(api::deligations_per_pool_for_epochs(
-            self,
-            pool,
-            start_epoch,
-            end_epoch,
-        )?)
+        let pool = pool.to_owned();
+        Ok(self
+            .query(move |dbs| {
+                api::deligations_per_pool_for_epochs(dbs, &pool, start_epoch, end_epoch)
+            })
+            .await?)
     }
 
     async fn pool_total_staked(
@@ -169,11 +500,14 @@ impl super::provider::CardanoDataProvider for DBSyncProvider {
         pool: &str,
         epoch: i32,
     ) -> Result<u64, DataProviderError> {
-        Ok(api::pool_total_stake(self, pool, epoch)?)
+        let pool = pool.to_owned();
+        Ok(self
+            .query(move |dbs| api::pool_total_stake(dbs, &pool, epoch))
+            .await?)
     }
 
     async fn current_epoch(&self) -> Result<i32, DataProviderError> {
-        Ok(api::current_epoch(self)?)
+        Ok(self.query(api::current_epoch).await?)
     }
 
     async fn fingerprint(
@@ -181,14 +515,21 @@ impl super::provider::CardanoDataProvider for DBSyncProvider {
         policy: &str,
         tokenname: &str,
     ) -> Result<String, DataProviderError> {
-        Ok(api::fingerprint(self, policy, tokenname)?)
+        let policy = policy.to_owned();
+        let tokenname = tokenname.to_owned();
+        Ok(self
+            .query(move |dbs| api::fingerprint(dbs, &policy, &tokenname))
+            .await?)
     }
 
     async fn token_info(
         &self,
         fingerprint_in: &str,
     ) -> Result<TokenInfoView, DataProviderError> {
-        Ok(api::token_info(self, fingerprint_in)?)
+        let fingerprint_in = fingerprint_in.to_owned();
+        Ok(self
+            .query(move |dbs| api::token_info(dbs, &fingerprint_in))
+            .await?)
     }
 
     async fn stake_registration(
@@ -196,7 +537,11 @@ impl super::provider::CardanoDataProvider for DBSyncProvider {
         stake_addr_in: &str,
     ) -> Result<Vec<StakeRegistrationView>, DataProviderError>
     {
-        Ok(api::stake_registration(self, stake_addr_in)?)
+        self.config.network.check_address(stake_addr_in)?;
+        let stake_addr_in = stake_addr_in.to_owned();
+        Ok(self
+            .query(move |dbs| api::stake_registration(dbs, &stake_addr_in))
+            .await?)
     }
 
     async fn stake_deregistration(
@@ -206,14 +551,22 @@ impl super::provider::CardanoDataProvider for DBSyncProvider {
         Vec<StakeDeregistrationView>,
         DataProviderError,
     > {
-        Ok(api::stake_deregistration(self, stake_addr_in)?)
+        self.config.network.check_address(stake_addr_in)?;
+        let stake_addr_in = stake_addr_in.to_owned();
+        Ok(self
+            .query(move |dbs| api::stake_deregistration(dbs, &stake_addr_in))
+            .await?)
     }
 
     async fn check_stakeaddr_registered(
         &self,
         stake_addr_in: &str,
     ) -> Result<bool, DataProviderError> {
-        Ok(api::check_stakeaddr_registered(self, stake_addr_in)?)
+        self.config.network.check_address(stake_addr_in)?;
+        let stake_addr_in = stake_addr_in.to_owned();
+        Ok(self
+            .query(move |dbs| api::check_stakeaddr_registered(dbs, &stake_addr_in))
+            .await?)
     }
 
     async fn lookup_token_holders(
@@ -222,7 +575,13 @@ impl super::provider::CardanoDataProvider for DBSyncProvider {
         min_amount: Option<&i64>,
     ) -> Result<Vec<HoldingWalletView>, DataProviderError>
     {
-        Ok(api::lookup_token_holders(self, fingerprint_in, min_amount)?)
+        let fingerprint_in = fingerprint_in.to_owned();
+        let min_amount = min_amount.copied();
+        Ok(self
+            .query(move |dbs| {
+                api::lookup_token_holders(dbs, &fingerprint_in, min_amount.as_ref())
+            })
+            .await?)
     }
 
     async fn lookup_nft_token_holders(
@@ -230,28 +589,40 @@ impl super::provider::CardanoDataProvider for DBSyncProvider {
         policy: &str,
     ) -> Result<Vec<HoldingWalletView>, DataProviderError>
     {
-        Ok(api::lookup_nft_token_holders(self, policy)?)
+        let policy = policy.to_owned();
+        Ok(self
+            .query(move |dbs| api::lookup_nft_token_holders(dbs, &policy))
+            .await?)
     }
 
     async fn pool_valid(
         &self,
         pool_id: &str,
     ) -> Result<bool, DataProviderError> {
-        Ok(api::pool_valid(self, pool_id)?)
+        let pool_id = pool_id.to_owned();
+        Ok(self.query(move |dbs| api::pool_valid(dbs, &pool_id)).await?)
     }
 
     async fn txhash_spent(
         &self,
         txhash: &str,
     ) -> Result<bool, DataProviderError> {
-        Ok(api::txhash_spent(self, txhash)?)
+        let txhash = txhash.to_owned();
+        Ok(self.query(move |dbs| api::txhash_spent(dbs, &txhash)).await?)
     }
 
     async fn addresses_exist(
         &self,
         address: &Vec<&str>,
     ) -> Result<Vec<bool>, DataProviderError> {
-        Ok(api::addresses_exist(self, address)?)
+        self.config.network.check_addresses(address)?;
+        let address: Vec<String> = address.iter().map(|a| a.to_string()).collect();
+        Ok(self
+            .query(move |dbs| {
+                let address: Vec<&str> = address.iter().map(|a| a.as_str()).collect();
+                api::addresses_exist(dbs, &address)
+            })
+            .await?)
     }
 
     async fn tx_history(
@@ -260,7 +631,75 @@ impl super::provider::CardanoDataProvider for DBSyncProvider {
         slot: Option<u64>,
     ) -> Result<Vec<TxHistoryListView>, DataProviderError>
     {
-        Ok(api::tx_history(self, addresses, slot)?)
+        self.config.network.check_addresses(addresses)?;
+        let addresses: Vec<String> = addresses.iter().map(|a| a.to_string()).collect();
+        Ok(self
+            .query(move |dbs| {
+                let addresses: Vec<&str> = addresses.iter().map(|a| a.as_str()).collect();
+                api::tx_history(dbs, &addresses, slot)
+            })
+            .await?)
+    }
+
+    async fn address_tx_history(
+        &self,
+        addresses: &Vec<&str>,
+        slot: Option<u64>,
+    ) -> Result<Vec<AddressTxView>, DataProviderError> {
+        self.config.network.check_addresses(addresses)?;
+        let addresses: Vec<String> = addresses.iter().map(|a| a.to_string()).collect();
+        Ok(self
+            .query(move |dbs| {
+                let addresses: Vec<&str> = addresses.iter().map(|a| a.as_str()).collect();
+                api::address_tx_history(dbs, &addresses, slot)
+            })
+            .await?)
+    }
+
+    async fn stake_address_tx_history(
+        &self,
+        stake_addr: &str,
+        slot: Option<u64>,
+    ) -> Result<Vec<AddressTxView>, DataProviderError> {
+        self.config.network.check_address(stake_addr)?;
+        let stake_addr = stake_addr.to_owned();
+        Ok(self
+            .query(move |dbs| api::stake_address_tx_history(dbs, &stake_addr, slot))
+            .await?)
+    }
+
+    async fn stake_address_addresses(
+        &self,
+        stake_addr: &str,
+    ) -> Result<Vec<String>, DataProviderError> {
+        self.config.network.check_address(stake_addr)?;
+        let stake_addr = stake_addr.to_owned();
+        Ok(self
+            .query(move |dbs| api::stake_address_addresses(dbs, &stake_addr))
+            .await?)
+    }
+
+    async fn stake_address_balance(
+        &self,
+        stake_addr: &str,
+    ) -> Result<StakeBalanceView, DataProviderError> {
+        self.config.network.check_address(stake_addr)?;
+        let stake_addr = stake_addr.to_owned();
+        Ok(self
+            .query(move |dbs| api::stake_address_balance(dbs, &stake_addr))
+            .await?)
+    }
+
+    async fn stake_address_history(
+        &self,
+        stake_addr: &str,
+        slot: Option<u64>,
+    ) -> Result<Vec<TxHistoryListView>, DataProviderError> {
+        self.config.network.check_address(stake_addr)?;
+        let stake_addr = stake_addr.to_owned();
+        Ok(self
+            .query(move |dbs| api::stake_address_history(dbs, &stake_addr, slot))
+            .await?)
     }
 
     async fn retrieve_staked_amount (
@@ -268,13 +707,178 @@ impl super::provider::CardanoDataProvider for DBSyncProvider {
         epoch: i32,
         stake_addr: &str,
     ) -> Result<BigDecimal, DataProviderError> {
-        Ok(api::retrieve_staked_amount(self, epoch, stake_addr)?)
+        self.config.network.check_address(stake_addr)?;
+        let stake_addr = stake_addr.to_owned();
+        Ok(self
+            .query(move |dbs| api::retrieve_staked_amount(dbs, epoch, &stake_addr))
+            .await?)
     }
 
     async fn retrieve_generated_rewards (
         &self,
         stake_addr: &str,
     ) -> Result<Vec<RewardView>, DataProviderError> {
-        Ok(api::retrieve_generated_rewards(self, stake_addr)?)
+        self.config.network.check_address(stake_addr)?;
+        let stake_addr = stake_addr.to_owned();
+        Ok(self
+            .query(move |dbs| api::retrieve_generated_rewards(dbs, &stake_addr))
+            .await?)
+    }
+
+    async fn lookup_token_holders_page(
+        &self,
+        fingerprint_in: &str,
+        min_amount: Option<&i64>,
+        page: &PageRequest,
+    ) -> Result<Page<HoldingWalletView>, DataProviderError> {
+        let after = page.key::<i64>(PAGE_KEY)?.unwrap_or(0);
+        let limit = page.limit();
+        let fingerprint_in = fingerprint_in.to_owned();
+        let min_amount = min_amount.copied();
+        let rows = self
+            .query(move |dbs| {
+                api::lookup_token_holders_after(
+                    dbs,
+                    &fingerprint_in,
+                    min_amount,
+                    after,
+                    limit as i64 + 1,
+                )
+            })
+            .await?;
+        Ok(Page::from_rows(rows, limit, PAGE_KEY, |row| row.0).map(|row| row.1))
+    }
+
+    async fn lookup_nft_token_holders_page(
+        &self,
+        policy: &str,
+        page: &PageRequest,
+    ) -> Result<Page<HoldingWalletView>, DataProviderError> {
+        let after = page.key::<i64>(PAGE_KEY)?.unwrap_or(0);
+        let limit = page.limit();
+        let policy = policy.to_owned();
+        let rows = self
+            .query(move |dbs| {
+                api::lookup_nft_token_holders_after(dbs, &policy, after, limit as i64 + 1)
+            })
+            .await?;
+        Ok(Page::from_rows(rows, limit, PAGE_KEY, |row| row.0).map(|row| row.1))
+    }
+
+    async fn tx_history_page(
+        &self,
+        addresses: &Vec<&str>,
+        slot: Option<u64>,
+        page: &PageRequest,
+    ) -> Result<Page<TxHistoryListView>, DataProviderError> {
+        self.config.network.check_addresses(addresses)?;
+        let before = page.key::<i64>(PAGE_KEY)?.unwrap_or(i64::MAX);
+        let limit = page.limit();
+        let addresses: Vec<String> = addresses.iter().map(|a| a.to_string()).collect();
+        let rows = self
+            .query(move |dbs| {
+                let addresses: Vec<&str> = addresses.iter().map(|a| a.as_str()).collect();
+                api::tx_history_before(dbs, &addresses, slot, before, limit as i64 + 1)
+            })
+            .await?;
+        Ok(Page::from_rows(rows, limit, PAGE_KEY, |row| row.0).map(|row| row.1))
+    }
+
+    async fn stakers_on_pool_page(
+        &self,
+        pool: &str,
+        epoch: i32,
+        page: &PageRequest,
+    ) -> Result<Page<StakeDelegationView>, DataProviderError> {
+        let after = page.key::<i64>(PAGE_KEY)?.unwrap_or(0);
+        let limit = page.limit();
+        let pool = pool.to_owned();
+        let rows = self
+            .query(move |dbs| {
+                api::stakers_on_pool_after(dbs, &pool, epoch, after, limit as i64 + 1)
+            })
+            .await?;
+        Ok(Page::from_rows(rows, limit, PAGE_KEY, |row| row.0).map(|row| row.1))
+    }
+
+    async fn retrieve_generated_rewards_page(
+        &self,
+        stake_addr: &str,
+        page: &PageRequest,
+    ) -> Result<Page<RewardView>, DataProviderError> {
+        self.config.network.check_address(stake_addr)?;
+        let after = page.key::<i64>(PAGE_KEY)?.unwrap_or(0);
+        let limit = page.limit();
+        let stake_addr = stake_addr.to_owned();
+        let rows = self
+            .query(move |dbs| {
+                api::retrieve_generated_rewards_after(dbs, &stake_addr, after, limit as i64 + 1)
+            })
+            .await?;
+        Ok(Page::from_rows(rows, limit, PAGE_KEY, |row| row.0).map(|row| row.1))
+    }
+
+    fn script_utxos_stream<'a>(
+        &'a self,
+        addr: &'a str,
+    ) -> BoxStream<'a, Result<dcslc::TransactionUnspentOutput, DataProviderError>> {
+        let query = self
+            .config
+            .network
+            .check_address(addr)
+            .and_then(|_| Ok(api::address_utxos_cursor_query(addr)?));
+        self.cursor_stream(query, |conn, rows: Vec<models::UtxoByRefQuery>| {
+            api::cursor_utxos(conn, rows)
+        })
+    }
+
+    fn policy_holders_stream<'a>(
+        &'a self,
+        policy: &'a str,
+    ) -> BoxStream<'a, Result<HoldingWalletView, DataProviderError>> {
+        let query = api::policy_holders_cursor_query(policy).map_err(DataProviderError::from);
+        let policy = policy.to_owned();
+        self.cursor_stream(query, move |_, rows: Vec<models::PolicyHolderQuery>| {
+            rows.into_iter()
+                .map(|holder| {
+                    Ok(HoldingWalletView {
+                        amount: holder.quantity.to_u64().ok_or_else(|| {
+                            DataProviderDBSyncError::Custom(format!(
+                                "quantity out of range: {}",
+                                holder.quantity
+                            ))
+                        })?,
+                        stake_address: holder.stake_address,
+                        policy: policy.clone(),
+                        tokenname: Some(hex::encode(holder.name)),
+                        fingerprint: Some(holder.fingerprint),
+                    })
+                })
+                .collect()
+        })
+    }
+
+    fn tx_history_stream<'a>(
+        &'a self,
+        addresses: &'a Vec<&'a str>,
+    ) -> BoxStream<'a, Result<TxHistoryListView, DataProviderError>> {
+        let query = self
+            .config
+            .network
+            .check_addresses(addresses)
+            .and_then(|_| Ok(api::tx_history_cursor_query(addresses)?));
+        self.cursor_stream(
+            query,
+            |_, rows: Vec<crate::models::TxHistoryListQueryLight>| {
+                Ok(rows
+                    .into_iter()
+                    .map(|t| TxHistoryListView {
+                        slot: t.slot,
+                        hash: hex::encode(t.hash),
+                        assets: vec![],
+                    })
+                    .collect())
+            },
+        )
     }
 }
@@ -1,11 +1,15 @@
 use super::error::DataProviderDBSyncError;
-use super::models::{PoolHash, PoolRetire, Rewardtype, UnspentUtxo, UtxoView};
+use super::models::{
+    OutputAssetQuery, PoolHash, PoolRetire, Rewardtype, TxMovementQuery, UnspentUtxo,
+    UtxoByRefQuery, UtxoView,
+};
 use super::schema::*;
 use crate::models::{
-    CDPDatum, CardanoNativeAssetView, DelegationView, HoldingWalletView, PoolView, RewardView,
-    ScriptView, StakeDelegationView, StakeDeregistrationView, StakeRegistrationView, TokenInfoView,
-    TransactionView, TxHistoryListQuery, TxHistoryListQueryLight, TxHistoryListView, UTxOView,
-    WithdrawalView,
+    AddressTxView, AssetHandle, CDPDatum, CardanoNativeAssetView, DelegationView,
+    HoldingWalletView, PoolView, ResolvedUtxos, RewardView, ScriptView, StakeBalanceView,
+    StakeDelegationView, StakeDeregistrationView, StakeRegistrationView, TokenInfoView,
+    TransactionView, TxHash, TxHistoryListQuery, TxHistoryListQueryLight, TxHistoryListView,
+    TxHistoryPageQuery, TxValueDelta, UTxOView, WithdrawalView,
 };
 use crate::DBSyncProvider;
 use bigdecimal::{BigDecimal, FromPrimitive, ToPrimitive};
@@ -13,6 +17,26 @@ use dcslc::TransactionUnspentOutputs;
 use diesel::prelude::*;
 use log::debug;
 use std::str::FromStr;
+
+/// Converts a quantity, dbsync stores lovelace and token amounts as `numeric`
+pub(crate) fn to_u64(value: &BigDecimal) -> Result<u64, DataProviderDBSyncError> {
+    value
+        .to_u64()
+        .ok_or_else(|| DataProviderDBSyncError::Custom(format!("invalid quantity {value}")))
+}
+
+/// Divides `dividend` by `divisor`, a zero divisor means the data `what` is missing in dbsync
+fn checked_div(
+    dividend: BigDecimal,
+    divisor: BigDecimal,
+    what: &str,
+) -> Result<BigDecimal, DataProviderDBSyncError> {
+    if divisor == BigDecimal::from(0) {
+        return Err(DataProviderDBSyncError::RequestValueNotFound(what.to_owned()));
+    }
+    Ok(dividend / divisor)
+}
+
 /// get all tokens of an utxo
 
 pub fn get_utxo_tokens(
@@ -114,6 +138,100 @@ pub fn get_address_utxos(
     Ok(utxos)
 }
 
+/// Cursor query for all utxos of an address with their datums and reference scripts, rows are
+/// `UtxoByRefQuery`s to be converted by `cursor_utxos`
+pub fn address_utxos_cursor_query(addr: &str) -> Result<String, DataProviderDBSyncError> {
+    Ok(format!(
+        "SELECT o.id AS ord, t.hash, o.index, o.address, o.value, o.data_hash,
+            d.bytes AS inline_datum, s.hash AS script_hash, s.type AS script_type,
+            s.json AS script_json, s.bytes AS script_bytes,
+            NULL::bytea AS policy, NULL::bytea AS name, NULL::numeric AS quantity
+        FROM utxo_view o
+        JOIN tx t ON t.id = o.tx_id
+        LEFT JOIN datum d ON d.id = o.inline_datum_id
+        LEFT JOIN script s ON s.id = o.reference_script_id
+        WHERE o.address = {}
+        ORDER BY o.id",
+        address_literal(addr)?
+    ))
+}
+
+/// Converts a batch of `address_utxos_cursor_query` rows, the assets of all outputs of the
+/// batch are loaded with one query
+pub fn cursor_utxos(
+    conn: &mut PgConnection,
+    rows: Vec<UtxoByRefQuery>,
+) -> Result<Vec<dcslc::TransactionUnspentOutput>, DataProviderDBSyncError> {
+    use cardano_serialization_lib as csl;
+    use diesel::sql_types::{Array, BigInt};
+
+    let ids: Vec<i64> = rows.iter().map(|row| row.ord).collect();
+    let assets: Vec<OutputAssetQuery> = diesel::sql_query(
+        "select mto.tx_out_id, ma.policy, ma.name, mto.quantity
+        from ma_tx_out mto
+        join multi_asset ma on ma.id = mto.ident
+        where mto.tx_out_id = any($1)",
+    )
+    .bind::<Array<BigInt>, _>(ids)
+    .load(conn)?;
+
+    let mut by_output = std::collections::HashMap::<i64, csl::MultiAsset>::new();
+    for asset in assets {
+        by_output
+            .entry(asset.tx_out_id)
+            .or_insert_with(csl::MultiAsset::new)
+            .set_asset(
+                &csl::PolicyID::from_bytes(asset.policy)?,
+                &csl::AssetName::new(asset.name)?,
+                csl::utils::to_bignum(to_u64(&asset.quantity)?),
+            );
+    }
+    let none = csl::MultiAsset::new();
+    rows.iter()
+        .map(|row| row.to_txuo(by_output.get(&row.ord).unwrap_or(&none)))
+        .collect()
+}
+
+/// Cursor query for the holders of all assets of a policy, rows are `PolicyHolderQuery`s
+pub fn policy_holders_cursor_query(policy: &str) -> Result<String, DataProviderDBSyncError> {
+    let policy = hex::encode(hex::decode(policy)?);
+    Ok(format!(
+        "SELECT u.stake_address, ma.name, ma.fingerprint, mto.quantity FROM unspent_utxos u
+        JOIN ma_tx_out mto ON mto.tx_out_id = u.id
+        JOIN multi_asset ma ON ma.id = mto.ident
+        WHERE ma.policy = decode('{policy}', 'hex') AND u.stake_address IS NOT NULL
+        ORDER BY mto.id"
+    ))
+}
+
+/// Cursor query for the transactions paying to the addresses, newest first,
+/// rows are `TxHistoryListQueryLight`s
+pub fn tx_history_cursor_query(addresses: &[&str]) -> Result<String, DataProviderDBSyncError> {
+    let addresses = addresses
+        .iter()
+        .map(|a| address_literal(a))
+        .collect::<Result<Vec<_>, _>>()?
+        .join(",");
+    if addresses.is_empty() {
+        return Err(DataProviderDBSyncError::Custom("no addresses given".to_owned()));
+    }
+    Ok(format!(
+        "SELECT t.hash, b.slot_no AS slot FROM tx t
+        JOIN block b ON b.id = t.block_id
+        WHERE t.id IN (SELECT tx_id FROM tx_out WHERE address IN ({addresses}))
+        ORDER BY t.id DESC"
+    ))
+}
+
+/// Quotes an address for a cursor query, a cursor can not be declared with bind parameters.
+/// Bech32 and base58 addresses only consist of ascii alphanumerics and `_`, anything else is rejected.
+fn address_literal(addr: &str) -> Result<String, DataProviderDBSyncError> {
+    if addr.is_empty() || !addr.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(DataProviderDBSyncError::Custom(format!("invalid address: {addr}")));
+    }
+    Ok(format!("'{addr}'"))
+}
+
 /// get all utxos of an address
 pub fn utxo_by_dataumhash(
     dbs: &DBSyncProvider,
@@ -182,6 +300,70 @@ fn utxo_by_txid_db(
     unspent.to_txuo(dbs)
 }
 
+/// Resolves the unspent outputs of the references with a single query, references to unknown
+/// or spent outputs are returned as missing
+pub fn utxos_by_refs(
+    dbs: &DBSyncProvider,
+    refs: &[(TxHash, u32)],
+) -> Result<ResolvedUtxos, DataProviderDBSyncError> {
+    use cardano_serialization_lib as csl;
+    use diesel::sql_types::{Array, BigInt, Bytea};
+
+    let hashes: Vec<Vec<u8>> = refs.iter().map(|(hash, _)| hash.to_bytes()).collect();
+    let indices: Vec<i64> = refs.iter().map(|(_, index)| *index as i64).collect();
+    let rows: Vec<UtxoByRefQuery> = diesel::sql_query(
+        "select r.ord, t.hash, o.index, o.address, o.value, o.data_hash,
+            d.bytes as inline_datum, s.hash as script_hash, s.type as script_type,
+            s.json as script_json, s.bytes as script_bytes, ma.policy, ma.name, mto.quantity
+        from unnest($1::bytea[], $2::bigint[]) with ordinality as r(hash, index, ord)
+        join tx t on t.hash = r.hash
+        join tx_out o on o.tx_id = t.id and o.index = r.index
+        left join tx_in i on i.tx_out_id = o.tx_id and i.tx_out_index = o.index
+        left join datum d on d.id = o.inline_datum_id
+        left join script s on s.id = o.reference_script_id
+        left join ma_tx_out mto on mto.tx_out_id = o.id
+        left join multi_asset ma on ma.id = mto.ident
+        where i.id is null
+        order by r.ord",
+    )
+    .bind::<Array<Bytea>, _>(hashes)
+    .bind::<Array<BigInt>, _>(indices)
+    .load(&mut dbs.connect()?)?;
+
+    // first row of every output with the assets of all its rows
+    let mut outputs = Vec::<(UtxoByRefQuery, csl::MultiAsset)>::new();
+    for row in rows {
+        let asset = match (&row.policy, &row.name, &row.quantity) {
+            (Some(policy), Some(name), Some(quantity)) => Some((
+                csl::PolicyID::from_bytes(policy.clone())?,
+                csl::AssetName::new(name.clone())?,
+                csl::utils::to_bignum(to_u64(quantity)?),
+            )),
+            _ => None,
+        };
+        if outputs.last().map(|(o, _)| o.ord) != Some(row.ord) {
+            outputs.push((row, csl::MultiAsset::new()));
+        }
+        if let (Some((policy, name, quantity)), Some((_, assets))) = (asset, outputs.last_mut()) {
+            assets.set_asset(&policy, &name, quantity);
+        }
+    }
+
+    let mut resolved = ResolvedUtxos {
+        found: Vec::with_capacity(outputs.len()),
+        missing: Vec::new(),
+    };
+    let mut outputs = outputs.into_iter().peekable();
+    // `with ordinality` counts from 1
+    for (ord, (hash, index)) in (1_i64..).zip(refs) {
+        match outputs.next_if(|(o, _)| o.ord == ord) {
+            Some((output, assets)) => resolved.found.push(output.to_txuo(&assets)?),
+            None => resolved.missing.push((hash.clone(), *index)),
+        }
+    }
+    Ok(resolved)
+}
+
 /// get all utxos of an address
 pub fn get_stake_address_utxos(
     dbs: &DBSyncProvider,
@@ -239,12 +421,9 @@ pub fn asset_utxos_on_addr(
         .load::<UtxoView>(&mut dbs.connect()?)?;
 
     let mut utxos = dcslc::TransactionUnspentOutputs::new();
-    unspent_assets.iter().for_each(|n| {
-        utxos.add(
-            &n.to_txuo(dbs)
-                .expect("Could not convert into TransactionUnspentOutput"),
-        )
-    });
+    for n in unspent_assets.iter() {
+        utxos.add(&n.to_txuo(dbs)?);
+    }
 
     Ok(utxos)
 }
@@ -307,12 +486,9 @@ pub fn asset_utxos_on_addr_dep(
         .load::<UnspentUtxo>(&mut dbs.connect()?)?;
 
     let mut utxos = dcslc::TransactionUnspentOutputs::new();
-    unspent_assets.iter().for_each(|n| {
-        utxos.add(
-            &n.to_txuo(dbs)
-                .expect("Could not convert into TransactionUnspentOutput"),
-        )
-    });
+    for n in unspent_assets.iter() {
+        utxos.add(&n.to_txuo(dbs)?);
+    }
 
     Ok(utxos)
 }
@@ -372,8 +548,8 @@ pub fn find_datums_for_tx(
                 .or(tx_out::inline_datum_id.eq(datum::id.nullable()))),
         )
         .filter(diesel::BoolExpressionMethods::or(
-            datum::hash.eq(t.0.unwrap()),
-            datum::id.eq(t.1.unwrap()),
+            datum::hash.nullable().eq(t.0),
+            datum::id.nullable().eq(t.1),
         ))
         .select((
             datum::hash,
@@ -394,7 +570,7 @@ pub fn slot(dbs: &DBSyncProvider) -> Result<i64, DataProviderDBSyncError> {
         .order(block::slot_no.desc())
         .limit(1)
         .load::<Option<i64>>(&mut dbs.connect()?)?;
-    match slot[0] {
+    match slot.first().copied().flatten() {
         Some(s) => Ok(s),
         None => Err(DataProviderDBSyncError::Custom(
             "ERROR: Could not find slot number in DBsync".to_string(),
@@ -402,6 +578,12 @@ pub fn slot(dbs: &DBSyncProvider) -> Result<i64, DataProviderDBSyncError> {
     }
 }
 
+/// true if a transaction with this hash is on-chain
+pub fn tx_included(dbs: &DBSyncProvider, tx_hash: &[u8]) -> Result<bool, DataProviderDBSyncError> {
+    Ok(diesel::select(diesel::dsl::exists(tx::table.filter(tx::hash.eq(tx_hash))))
+        .get_result::<bool>(&mut dbs.connect()?)?)
+}
+
 pub fn get_tx_slot(dbs: &DBSyncProvider, txhash: &str) -> Result<i64, DataProviderDBSyncError> {
     let slot = block::table
         .inner_join(tx::table.on(tx::block_id.eq(block::id)))
@@ -417,6 +599,28 @@ pub fn get_tx_slot(dbs: &DBSyncProvider, txhash: &str) -> Result<i64, DataProvid
     }
 }
 
+/// hashes and slots of the transactions with an output to `address` in or after `slot`,
+/// oldest first
+pub fn txs_to_address_since(
+    dbs: &DBSyncProvider,
+    address: &str,
+    slot: i64,
+) -> Result<Vec<(String, i64)>, DataProviderDBSyncError> {
+    let txs = tx_out::table
+        .inner_join(tx::table.on(tx::id.eq(tx_out::tx_id)))
+        .inner_join(block::table.on(block::id.eq(tx::block_id)))
+        .filter(tx_out::address.eq(address))
+        .filter(block::slot_no.ge(slot))
+        .select((tx::id, tx::hash, block::slot_no))
+        .distinct()
+        .order_by(tx::id.asc())
+        .load::<(i64, Vec<u8>, Option<i64>)>(&mut dbs.connect()?)?;
+    Ok(txs
+        .into_iter()
+        .filter_map(|(_, hash, slot)| slot.map(|slot| (hex::encode(hash), slot)))
+        .collect())
+}
+
 pub fn stakers_on_pool(
     dbs: &DBSyncProvider,
     pool: &str,
@@ -432,6 +636,38 @@ pub fn stakers_on_pool(
     Ok(pool_stake)
 }
 
+/// at most `limit` stakers of the pool with an `epoch_stake` id greater than `after`
+pub fn stakers_on_pool_after(
+    dbs: &DBSyncProvider,
+    pool: &str,
+    epoch: i32,
+    after: i64,
+    limit: i64,
+) -> Result<Vec<(i64, StakeDelegationView)>, DataProviderDBSyncError> {
+    let pool_stake = epoch_stake::table
+        .inner_join(pool_hash::table.on(pool_hash::id.eq(epoch_stake::pool_id)))
+        .inner_join(stake_address::table.on(epoch_stake::addr_id.eq(stake_address::id)))
+        .filter(pool_hash::view.eq(pool))
+        .filter(epoch_stake::epoch_no.eq(epoch))
+        .filter(epoch_stake::id.gt(after))
+        .order(epoch_stake::id.asc())
+        .limit(limit)
+        .select((epoch_stake::id, stake_address::view, epoch_stake::amount))
+        .load::<(i64, String, BigDecimal)>(&mut dbs.connect()?)?;
+    Ok(pool_stake
+        .into_iter()
+        .map(|(id, stake_address, amount)| {
+            (
+                id,
+                StakeDelegationView {
+                    stake_address,
+                    amount,
+                },
+            )
+        })
+        .collect())
+}
+
 pub fn deligations_per_pool_for_epochs(
     dbs: &DBSyncProvider,
     pool: &str,
@@ -467,9 +703,7 @@ pub fn pool_total_stake(
         .select(epoch_stake::amount)
         .load::<BigDecimal>(&mut dbs.connect()?)?;
 
-    let tot_stake: u64 = pool_stake.iter().map(|x| x.to_u64().unwrap()).sum();
-
-    Ok(tot_stake)
+    pool_stake.iter().map(to_u64).sum()
 }
 
 pub fn current_epoch(dbs: &DBSyncProvider) -> Result<i32, DataProviderDBSyncError> {
@@ -642,22 +876,67 @@ pub fn lookup_token_holders(
         .load::<(Option<String>, BigDecimal)>(&mut dbs.connect()?)?;
 
     if let Some(amt) = min_amount {
-        let a = BigDecimal::from_i64(*amt).unwrap();
-        holders.retain(|p| p.1 >= a && p.0.is_some())
-    } else {
-        holders.retain(|p| p.0.is_some())
+        let a = BigDecimal::from(*amt);
+        holders.retain(|p| p.1 >= a)
     }
 
-    let mut ret = Vec::<HoldingWalletView>::new();
-    ret.extend(holders.iter().map(|p| HoldingWalletView {
-        stake_address: p.0.as_ref().unwrap().to_string(),
-        amount: BigDecimal::to_u64(&p.1).unwrap(),
-        tokenname: None,
-        policy: "use fingerprint".to_owned(),
-        fingerprint: Some(fingerprint_in.to_owned()),
-    }));
+    holders
+        .into_iter()
+        .filter_map(|(stake_address, quantity)| Some((stake_address?, quantity)))
+        .map(|(stake_address, quantity)| {
+            Ok(HoldingWalletView {
+                stake_address,
+                amount: to_u64(&quantity)?,
+                tokenname: None,
+                policy: "use fingerprint".to_owned(),
+                fingerprint: Some(fingerprint_in.to_owned()),
+            })
+        })
+        .collect()
+}
 
-    Ok(ret)
+/// at most `limit` holders of the token with a `ma_tx_out` id greater than `after`
+pub fn lookup_token_holders_after(
+    dbs: &DBSyncProvider,
+    fingerprint_in: &str,
+    min_amount: Option<i64>,
+    after: i64,
+    limit: i64,
+) -> Result<Vec<(i64, HoldingWalletView)>, DataProviderDBSyncError> {
+    let min_amount = BigDecimal::from(min_amount.unwrap_or(0));
+    let holders = unspent_utxos::table
+        .inner_join(ma_tx_out::table.on(unspent_utxos::id.eq(ma_tx_out::tx_out_id)))
+        .left_join(multi_asset::table.on(multi_asset::id.eq(ma_tx_out::ident)))
+        .filter(multi_asset::fingerprint.eq(fingerprint_in))
+        .filter(unspent_utxos::stake_address.is_not_null())
+        .filter(ma_tx_out::quantity.ge(min_amount))
+        .filter(ma_tx_out::id.gt(after))
+        .order(ma_tx_out::id.asc())
+        .limit(limit)
+        .select((
+            ma_tx_out::id,
+            unspent_utxos::stake_address.nullable(),
+            ma_tx_out::quantity,
+        ))
+        .load::<(i64, Option<String>, BigDecimal)>(&mut dbs.connect()?)?;
+
+    holders
+        .into_iter()
+        .map(|(id, stake_address, amount)| {
+            Ok((
+                id,
+                HoldingWalletView {
+                    stake_address: stake_address.unwrap_or_default(),
+                    amount: amount.to_u64().ok_or_else(|| {
+                        DataProviderDBSyncError::Custom(format!("invalid token amount {amount}"))
+                    })?,
+                    tokenname: None,
+                    policy: "use fingerprint".to_owned(),
+                    fingerprint: Some(fingerprint_in.to_owned()),
+                },
+            ))
+        })
+        .collect()
 }
 
 pub fn lookup_nft_token_holders(
@@ -673,23 +952,59 @@ pub fn lookup_nft_token_holders(
         .filter(unspent_utxos::stake_address.is_not_null())
         .filter(ma_tx_out::quantity.eq(BigDecimal::from(1)))
         .select((unspent_utxos::stake_address.nullable(), ma_tx_out::quantity))
-        .load::<(Option<String>, BigDecimal)>(&mut dbs.connect()?)
-        .ok();
+        .load::<(Option<String>, BigDecimal)>(&mut dbs.connect()?)?;
 
-    let mut holders = if let Some(h) = holders { h } else { vec![] };
+    holders
+        .into_iter()
+        .filter_map(|(stake_address, quantity)| Some((stake_address?, quantity)))
+        .map(|(stake_address, quantity)| {
+            Ok(HoldingWalletView {
+                stake_address,
+                amount: to_u64(&quantity)?,
+                tokenname: None,
+                policy: policy.to_string(),
+                fingerprint: None,
+            })
+        })
+        .collect()
+}
 
-    holders.retain(|p| p.0.is_some());
+/// at most `limit` holders of NFTs of the policy with a `ma_tx_out` id greater than `after`
+pub fn lookup_nft_token_holders_after(
+    dbs: &DBSyncProvider,
+    policy: &str,
+    after: i64,
+    limit: i64,
+) -> Result<Vec<(i64, HoldingWalletView)>, DataProviderDBSyncError> {
+    let pbyte = hex::decode(policy)?;
 
-    let mut ret = Vec::<HoldingWalletView>::new();
-    ret.extend(holders.iter().map(|p| HoldingWalletView {
-        stake_address: p.0.as_ref().unwrap().to_string(),
-        amount: BigDecimal::to_u64(&p.1).unwrap(),
-        tokenname: None,
-        policy: policy.to_string(),
-        fingerprint: None,
-    }));
+    let holders = unspent_utxos::table
+        .inner_join(ma_tx_out::table.on(unspent_utxos::id.eq(ma_tx_out::tx_out_id)))
+        .left_join(multi_asset::table.on(multi_asset::id.eq(ma_tx_out::ident)))
+        .filter(multi_asset::policy.eq(pbyte))
+        .filter(unspent_utxos::stake_address.is_not_null())
+        .filter(ma_tx_out::quantity.eq(BigDecimal::from(1)))
+        .filter(ma_tx_out::id.gt(after))
+        .order(ma_tx_out::id.asc())
+        .limit(limit)
+        .select((ma_tx_out::id, unspent_utxos::stake_address.nullable()))
+        .load::<(i64, Option<String>)>(&mut dbs.connect()?)?;
 
-    Ok(ret)
+    Ok(holders
+        .into_iter()
+        .map(|(id, stake_address)| {
+            (
+                id,
+                HoldingWalletView {
+                    stake_address: stake_address.unwrap_or_default(),
+                    amount: 1,
+                    tokenname: None,
+                    policy: policy.to_string(),
+                    fingerprint: None,
+                },
+            )
+        })
+        .collect())
 }
 
 pub fn mint_metadata(
@@ -727,9 +1042,7 @@ pub fn mint_metadata(
             .inner_join(multi_asset::table.on(ma_tx_mint::ident.eq(multi_asset::id)))
             .filter(multi_asset::fingerprint.eq(m.0.clone()))
             .select(ma_tx_mint::quantity)
-            .load::<BigDecimal>(&mut dbs.connect()?)
-            .ok()
-            .unwrap()
+            .load::<BigDecimal>(&mut dbs.connect()?)?
             .iter()
             .map(|n| n.to_u64())
             .sum();
@@ -738,7 +1051,7 @@ pub fn mint_metadata(
             fingerprint: m.0,
             policy: hex::encode(m.1),
             tokenname: hex::encode(m.2.clone()),
-            meta_key: Some(m.3.to_i64().unwrap()),
+            meta_key: m.3.to_i64(),
             json: m.4,
             txhash: Some(hex::encode(m.5)),
             quantity,
@@ -765,9 +1078,7 @@ pub fn mint_metadata(
                 .inner_join(multi_asset::table.on(ma_tx_mint::ident.eq(multi_asset::id)))
                 .filter(multi_asset::fingerprint.eq(m.0.clone()))
                 .select(ma_tx_mint::quantity)
-                .load::<BigDecimal>(&mut dbs.connect()?)
-                .ok()
-                .unwrap()
+                .load::<BigDecimal>(&mut dbs.connect()?)?
                 .iter()
                 .map(|n| n.to_u64())
                 .sum();
@@ -790,7 +1101,7 @@ pub fn mint_metadata(
     }
 }
 
-pub async fn get_pools(dbs: &DBSyncProvider) -> Result<Vec<PoolView>, DataProviderDBSyncError> {
+pub fn get_pools(dbs: &DBSyncProvider) -> Result<Vec<PoolView>, DataProviderDBSyncError> {
     let pools = pool_hash::table
         .left_join(pool_retire::table.on(pool_retire::hash_id.eq(pool_hash::id)))
         .inner_join(pool_offline_data::table.on(pool_offline_data::pool_id.eq(pool_hash::id)))
@@ -932,12 +1243,13 @@ pub fn addresses_exist(
     dbs: &DBSyncProvider,
     addresses: &Vec<&str>,
 ) -> Result<Vec<bool>, DataProviderDBSyncError> {
+    let conn = &mut dbs.connect()?;
     let mut out = vec![];
     for a in addresses {
         let id = tx_out::table
             .select(tx_out::id)
             .filter(tx_out::address.eq(a))
-            .load::<i64>(&mut dbs.connect()?)?;
+            .load::<i64>(conn)?;
         if id.is_empty() {
             out.push(false);
         } else {
@@ -946,82 +1258,340 @@ pub fn addresses_exist(
     }
     Ok(out)
 }
-// ToDo: Slot limitation to query just before a certain slot
+/// slot bound of the history queries, slots beyond `i64::MAX` do not exist
+fn slot_bound(slot: Option<u64>) -> Option<i64> {
+    slot.map(|s| i64::try_from(s).unwrap_or(i64::MAX))
+}
+
+/// transactions paying to the addresses up to and including `slot`, with the tokens they move
 fn _tx_history_q(
     dbs: &DBSyncProvider,
     addresses: &[&str],
-    _slot: Option<u64>,
+    slot: Option<u64>,
 ) -> Result<Vec<TxHistoryListView>, DataProviderDBSyncError> {
-    let mut addresses = addresses.iter().fold("(".to_string(), |mut acc, a| {
-        acc.push_str(&("'".to_string() + a + "',"));
-        acc
-    });
-    addresses.pop();
-    addresses.push(')');
-    let query =format!("select 
+    use diesel::sql_types::{Array, BigInt, Nullable, Text};
+
+    let addresses: Vec<String> = addresses.iter().map(|a| a.to_string()).collect();
+    log::debug!("{addresses:?}");
+    let txhistory_q: Vec<TxHistoryListQuery> = diesel::sql_query(
+        "select
     distinct t.hash,
     min(b.slot_no) as slot, asset.fingerprint,
     asset.value from tx_out to2
     join tx t on t.id = to2.tx_id
-    join block b on b.id = t.block_id 
+    join block b on b.id = t.block_id
     join lateral (
-        select array_agg(ma.fingerprint) as fingerprint, array_agg(mto.quantity) as value, mto.tx_out_id 
+        select array_agg(ma.fingerprint) as fingerprint, array_agg(mto.quantity) as value, mto.tx_out_id
         from ma_tx_out as mto
         join multi_asset ma on ma.id = mto.ident
         where mto.tx_out_id = to2.id
-        group by mto.tx_out_id 
-        ) as asset on asset.tx_out_id  = to2.id 
-    where address IN {addresses}
-    group by t.hash, b.slot_no, asset.fingerprint, asset.value");
-    log::debug!("{addresses}");
-    let txhistory_q: Vec<TxHistoryListQuery> =
-        diesel::sql_query(query).load(&mut dbs.connect()?).unwrap();
-
-    let mut txhistory: Vec<_> = txhistory_q
+        group by mto.tx_out_id
+        ) as asset on asset.tx_out_id  = to2.id
+    where address = ANY($1)
+    and ($2 is null or b.slot_no <= $2)
+    group by t.hash, b.slot_no, asset.fingerprint, asset.value",
+    )
+    .bind::<Array<Text>, _>(addresses)
+    .bind::<Nullable<BigInt>, _>(slot_bound(slot))
+    .load(&mut dbs.connect()?)?;
+
+    let mut txhistory = txhistory_q
         .iter()
         .map(TxHistoryListView::from_tx_history_list_query)
-        .collect();
+        .collect::<Result<Vec<_>, _>>()?;
     txhistory.sort_by_key(|n| n.slot);
     Ok(txhistory)
 }
 
-// ToDo: Slot limitation to query just before a certain slot
+/// transactions paying to the addresses up to and including `slot`, newest first
 pub fn tx_history(
     dbs: &DBSyncProvider,
     addresses: &[&str],
     slot: Option<u64>,
 ) -> Result<Vec<TxHistoryListView>, DataProviderDBSyncError> {
-    let mut addresses = addresses.iter().fold("(".to_string(), |mut acc, a| {
-        acc.push_str(&("'".to_string() + a + "',"));
-        acc
-    });
-    addresses.pop();
-    addresses.push(')');
-    let query = format!(
-        "select 
+    use diesel::sql_types::{Array, BigInt, Nullable, Text};
+
+    let addresses: Vec<String> = addresses.iter().map(|a| a.to_string()).collect();
+    log::trace!("{addresses:?}");
+    let txhistory: Vec<TxHistoryListQueryLight> = diesel::sql_query(
+        "select
     distinct
     t.hash, min(b.slot_no) as slot from tx_out to2
     join tx t on t.id = to2.tx_id
-    join block b on b.id = t.block_id 
-    where address IN {addresses}
-    group by b.slot_no, t.hash order by slot desc"
-    );
-    log::trace!("{addresses}");
-    let txhistory: Option<Vec<TxHistoryListQueryLight>> =
-        diesel::sql_query(query).load(&mut dbs.connect()?).ok();
+    join block b on b.id = t.block_id
+    where address = ANY($1)
+    and ($2 is null or b.slot_no <= $2)
+    group by b.slot_no, t.hash order by slot desc",
+    )
+    .bind::<Array<Text>, _>(addresses)
+    .bind::<Nullable<BigInt>, _>(slot_bound(slot))
+    .load(&mut dbs.connect()?)?;
     log::debug!("{txhistory:?}");
-    let mut out = Vec::<TxHistoryListView>::new();
-    if let Some(history) = txhistory {
-        for t in history.into_iter() {
-            out.push(TxHistoryListView {
-                slot: t.slot,
-                hash: hex::encode(t.hash),
-                assets: vec![],
-            });
+
+    Ok(txhistory
+        .into_iter()
+        .map(|t| TxHistoryListView {
+            slot: t.slot,
+            hash: hex::encode(t.hash),
+            assets: vec![],
+        })
+        .collect())
+}
+
+/// at most `limit` transactions paying to the addresses with a tx id lower than `before`
+/// and up to and including `slot`, newest first
+pub fn tx_history_before(
+    dbs: &DBSyncProvider,
+    addresses: &[&str],
+    slot: Option<u64>,
+    before: i64,
+    limit: i64,
+) -> Result<Vec<(i64, TxHistoryListView)>, DataProviderDBSyncError> {
+    use diesel::sql_types::{Array, BigInt, Nullable, Text};
+
+    let addresses: Vec<String> = addresses.iter().map(|a| a.to_string()).collect();
+    let txhistory: Vec<TxHistoryPageQuery> = diesel::sql_query(
+        "select t.id, t.hash, b.slot_no as slot from tx t
+        join block b on b.id = t.block_id
+        where t.id in (select tx_id from tx_out where address = ANY($1))
+        and t.id < $2
+        and ($4 is null or b.slot_no <= $4)
+        order by t.id desc
+        limit $3",
+    )
+    .bind::<Array<Text>, _>(addresses)
+    .bind::<BigInt, _>(before)
+    .bind::<BigInt, _>(limit)
+    .bind::<Nullable<BigInt>, _>(slot_bound(slot))
+    .load(&mut dbs.connect()?)?;
+
+    Ok(txhistory
+        .into_iter()
+        .map(|t| {
+            (
+                t.id,
+                TxHistoryListView {
+                    slot: t.slot,
+                    hash: hex::encode(t.hash),
+                    assets: vec![],
+                },
+            )
+        })
+        .collect())
+}
+
+/// Transactions creating or consuming the outputs selected by `own_outputs`, which is a
+/// condition on `tx_out o` with the keys bound to `$1`
+fn tx_value_history(
+    dbs: &DBSyncProvider,
+    own_outputs: &'static str,
+    keys: Vec<String>,
+    slot: Option<u64>,
+) -> Result<Vec<AddressTxView>, DataProviderDBSyncError> {
+    use diesel::sql_types::{Array, BigInt, Nullable, Text};
+    use std::collections::{HashMap, HashSet};
+
+    let rows: Vec<TxMovementQuery> = diesel::sql_query(format!(
+        "with own as (
+            select o.id, o.tx_id, o.value, i.tx_in_id from tx_out o
+            left join tx_in i on i.tx_out_id = o.tx_id and i.tx_out_index = o.index
+            where {own_outputs}
+        ), movement as (
+            select tx_id, id as tx_out_id, value, false as spent from own
+            union all
+            select tx_in_id, id, value, true from own where tx_in_id is not null
+        )
+        select t.id as tx_id, t.hash, b.slot_no as slot, b.time, t.fee, m.tx_out_id, m.spent,
+            m.value, ma.policy, ma.name, mto.quantity
+        from movement m
+        join tx t on t.id = m.tx_id
+        join block b on b.id = t.block_id
+        left join ma_tx_out mto on mto.tx_out_id = m.tx_out_id
+        left join multi_asset ma on ma.id = mto.ident
+        where ($2 is null or b.slot_no <= $2)
+        order by b.slot_no desc, t.id desc"
+    ))
+    .bind::<Array<Text>, _>(keys)
+    .bind::<Nullable<BigInt>, _>(slot_bound(slot))
+    .load(&mut dbs.connect()?)?;
+
+    let quantity = |q: &BigDecimal| {
+        q.to_i128()
+            .ok_or_else(|| DataProviderDBSyncError::Custom(format!("invalid quantity {q}")))
+    };
+    // (tx id, hash, slot, block time, fee) and value change, in the order of the rows
+    let mut txs = Vec::<((i64, Vec<u8>, Option<i64>, i64, BigDecimal), TxValueDelta)>::new();
+    let mut positions = HashMap::<i64, usize>::new();
+    let mut counted = HashSet::<(i64, bool)>::new();
+    for row in rows {
+        let position = *positions.entry(row.tx_id).or_insert_with(|| {
+            let tx = (row.tx_id, row.hash, row.slot, row.time.timestamp(), row.fee);
+            txs.push((tx, TxValueDelta::default()));
+            txs.len() - 1
+        });
+        let delta = &mut txs[position].1;
+        // outputs holding several assets are joined once per asset
+        if counted.insert((row.tx_out_id, row.spent)) {
+            delta.lovelace(row.spent, quantity(&row.value)?);
+        }
+        if let (Some(policy), Some(name), Some(q)) = (row.policy, row.name, row.quantity) {
+            delta.asset(row.spent, hex::encode(policy), hex::encode(name), quantity(&q)?);
         }
     }
 
-    Ok(out)
+    let tx_ids: Vec<i64> = txs.iter().map(|(tx, _)| tx.0).collect();
+    let mut labels = HashMap::<i64, Vec<u64>>::new();
+    for (tx_id, key) in tx_metadata::table
+        .filter(tx_metadata::tx_id.eq_any(tx_ids))
+        .select((tx_metadata::tx_id, tx_metadata::key))
+        .load::<(i64, BigDecimal)>(&mut dbs.connect()?)?
+    {
+        labels.entry(tx_id).or_default().push(to_u64(&key)?);
+    }
+
+    txs.into_iter()
+        .map(|((tx_id, hash, slot, block_time, fee), delta)| {
+            Ok(delta.into_view(
+                hex::encode(hash),
+                slot.unwrap_or_default(),
+                block_time,
+                to_u64(&fee)?,
+                labels.remove(&tx_id).unwrap_or_default(),
+            ))
+        })
+        .collect()
+}
+
+/// transactions of the addresses up to and including `slot` with the net change of their value
+pub fn address_tx_history(
+    dbs: &DBSyncProvider,
+    addresses: &[&str],
+    slot: Option<u64>,
+) -> Result<Vec<AddressTxView>, DataProviderDBSyncError> {
+    let addresses = addresses.iter().map(|a| a.to_string()).collect();
+    tx_value_history(dbs, "o.address = ANY($1)", addresses, slot)
+}
+
+/// `address_tx_history` of all outputs delegated to the stake address
+pub fn stake_address_tx_history(
+    dbs: &DBSyncProvider,
+    stake_addr: &str,
+    slot: Option<u64>,
+) -> Result<Vec<AddressTxView>, DataProviderDBSyncError> {
+    tx_value_history(
+        dbs,
+        "o.stake_address_id in (select id from stake_address where view = ANY($1))",
+        vec![stake_addr.to_owned()],
+        slot,
+    )
+}
+
+/// transaction history of all payment addresses delegated to the stake address
+pub fn stake_address_history(
+    dbs: &DBSyncProvider,
+    stake_addr: &str,
+    slot: Option<u64>,
+) -> Result<Vec<TxHistoryListView>, DataProviderDBSyncError> {
+    let addresses = stake_address_addresses(dbs, stake_addr)?;
+    if addresses.is_empty() {
+        return Ok(vec![]);
+    }
+    tx_history(
+        dbs,
+        &addresses.iter().map(|a| a.as_str()).collect::<Vec<_>>(),
+        slot,
+    )
+}
+
+/// payment addresses of all outputs delegated to the stake address
+pub fn stake_address_addresses(
+    dbs: &DBSyncProvider,
+    stake_addr: &str,
+) -> Result<Vec<String>, DataProviderDBSyncError> {
+    Ok(tx_out::table
+        .inner_join(
+            stake_address::table.on(tx_out::stake_address_id.eq(stake_address::id.nullable())),
+        )
+        .filter(stake_address::view.eq(stake_addr))
+        .select(tx_out::address)
+        .distinct()
+        .load::<String>(&mut dbs.connect()?)?)
+}
+
+/// unspent value of all outputs delegated to the stake address and its rewards which
+/// are spendable but not withdrawn yet
+pub fn stake_address_balance(
+    dbs: &DBSyncProvider,
+    stake_addr: &str,
+) -> Result<StakeBalanceView, DataProviderDBSyncError> {
+    use std::collections::BTreeMap;
+
+    let lovelace: BigDecimal = utxo_view::table
+        .inner_join(
+            stake_address::table.on(utxo_view::stake_address_id.eq(stake_address::id.nullable())),
+        )
+        .filter(stake_address::view.eq(stake_addr))
+        .select(utxo_view::value)
+        .load::<BigDecimal>(&mut dbs.connect()?)?
+        .into_iter()
+        .sum();
+
+    let mut assets = BTreeMap::<(Vec<u8>, Vec<u8>), (String, BigDecimal)>::new();
+    for (policy, name, fingerprint, quantity) in utxo_view::table
+        .inner_join(
+            stake_address::table.on(utxo_view::stake_address_id.eq(stake_address::id.nullable())),
+        )
+        .inner_join(ma_tx_out::table.on(ma_tx_out::tx_out_id.eq(utxo_view::id)))
+        .inner_join(multi_asset::table.on(multi_asset::id.eq(ma_tx_out::ident)))
+        .filter(stake_address::view.eq(stake_addr))
+        .select((
+            multi_asset::policy,
+            multi_asset::name,
+            multi_asset::fingerprint,
+            ma_tx_out::quantity,
+        ))
+        .load::<(Vec<u8>, Vec<u8>, String, BigDecimal)>(&mut dbs.connect()?)?
+    {
+        let (_, total) = assets
+            .entry((policy, name))
+            .or_insert_with(|| (fingerprint, BigDecimal::from(0)));
+        *total += quantity;
+    }
+
+    let epoch = current_epoch(dbs)? as i64;
+    let rewards: BigDecimal = reward::table
+        .inner_join(stake_address::table.on(reward::addr_id.eq(stake_address::id)))
+        .filter(stake_address::view.eq(stake_addr))
+        .filter(reward::spendable_epoch.le(epoch))
+        .select(reward::amount)
+        .load::<BigDecimal>(&mut dbs.connect()?)?
+        .into_iter()
+        .sum();
+    let withdrawn: BigDecimal = withdrawal::table
+        .inner_join(stake_address::table.on(withdrawal::addr_id.eq(stake_address::id)))
+        .filter(stake_address::view.eq(stake_addr))
+        .select(withdrawal::amount)
+        .load::<BigDecimal>(&mut dbs.connect()?)?
+        .into_iter()
+        .sum();
+
+    Ok(StakeBalanceView {
+        stake_address: stake_addr.to_owned(),
+        lovelace: to_u64(&lovelace)?,
+        assets: assets
+            .into_iter()
+            .map(|((policy, name), (fingerprint, quantity))| {
+                Ok(AssetHandle {
+                    fingerprint: Some(fingerprint),
+                    policy: Some(hex::encode(policy)),
+                    tokenname: Some(hex::encode(name)),
+                    amount: to_u64(&quantity)?,
+                    metadata: None,
+                })
+            })
+            .collect::<Result<_, DataProviderDBSyncError>>()?,
+        withdrawable_rewards: to_u64(&(rewards - withdrawn).max(BigDecimal::from(0)))?,
+    })
 }
 
 pub async fn txo_by_id_index(
@@ -1173,7 +1743,7 @@ pub async fn get_withdrawls(
     if let Some(withdrawals) = withdrawals {
         for w in withdrawals {
             out.push(WithdrawalView {
-                amount: w.0.to_u64().unwrap(),
+                amount: to_u64(&w.0)?,
                 stake_address: w.1,
             });
         }
@@ -1349,16 +1919,14 @@ pub fn retrieve_staked_amount(
         .filter(stake_address::view.eq(stake_addr.to_string()))
         .filter(epoch_stake::epoch_no.eq(epoch))
         .select(epoch_stake::amount)
-        .first::<BigDecimal>(&mut dbs.connect()?)
-        .ok()
-        .unwrap())
+        .first::<BigDecimal>(&mut dbs.connect()?)?)
 }
 
 pub fn retrieve_generated_rewards(
     dbs: &DBSyncProvider,
     stake_addr: &str,
 ) -> Result<Vec<RewardView>, DataProviderDBSyncError> {
-    Ok(reward::table
+    reward::table
         .inner_join(stake_address::table.on(stake_address::id.eq(reward::addr_id)))
         .filter(stake_address::view.eq(stake_addr.to_string()))
         .select((
@@ -1366,16 +1934,52 @@ pub fn retrieve_generated_rewards(
             reward::earned_epoch,
             reward::spendable_epoch,
         ))
-        .load::<(BigDecimal, i64, i64)>(&mut dbs.connect()?)
-        .ok()
-        .unwrap()
+        .load::<(BigDecimal, i64, i64)>(&mut dbs.connect()?)?
+        .into_iter()
+        .map(|t| {
+            Ok(RewardView {
+                amount: to_u64(&t.0)?,
+                earned_epoch: t.1,
+                spendable_epoch: t.2,
+            })
+        })
+        .collect()
+}
+
+/// at most `limit` rewards of the stake address with a `reward` id greater than `after`
+pub fn retrieve_generated_rewards_after(
+    dbs: &DBSyncProvider,
+    stake_addr: &str,
+    after: i64,
+    limit: i64,
+) -> Result<Vec<(i64, RewardView)>, DataProviderDBSyncError> {
+    let rewards = reward::table
+        .inner_join(stake_address::table.on(stake_address::id.eq(reward::addr_id)))
+        .filter(stake_address::view.eq(stake_addr))
+        .filter(reward::id.gt(after))
+        .order(reward::id.asc())
+        .limit(limit)
+        .select((
+            reward::id,
+            reward::amount,
+            reward::earned_epoch,
+            reward::spendable_epoch,
+        ))
+        .load::<(i64, BigDecimal, i64, i64)>(&mut dbs.connect()?)?;
+
+    rewards
         .into_iter()
-        .map(|t| RewardView {
-            amount: t.0.to_u64().unwrap(),
-            earned_epoch: t.1,
-            spendable_epoch: t.2,
+        .map(|(id, amount, earned_epoch, spendable_epoch)| {
+            Ok((
+                id,
+                RewardView {
+                    amount: to_u64(&amount)?,
+                    earned_epoch,
+                    spendable_epoch,
+                },
+            ))
         })
-        .collect::<Vec<RewardView>>())
+        .collect()
 }
 
 // R parameter in reward projection
@@ -1394,13 +1998,13 @@ pub fn pledge_influence_factor(
     dbs: &DBSyncProvider,
     current_epoch: i32,
 ) -> Result<BigDecimal, DataProviderDBSyncError> {
-    Ok(BigDecimal::from_f64(
-        epoch_param::table
-            .filter(epoch_param::epoch_no.eq(current_epoch))
-            .select(epoch_param::influence)
-            .first::<f64>(&mut dbs.connect()?)?,
-    )
-    .unwrap())
+    let influence = epoch_param::table
+        .filter(epoch_param::epoch_no.eq(current_epoch))
+        .select(epoch_param::influence)
+        .first::<f64>(&mut dbs.connect()?)?;
+    BigDecimal::from_f64(influence).ok_or_else(|| {
+        DataProviderDBSyncError::Custom(format!("invalid pledge influence {influence}"))
+    })
 }
 
 // z0 parameter in reward projection
@@ -1416,10 +2020,7 @@ pub fn relative_pool_saturation_size(
             .first::<i32>(&mut dbs.connect()?)?,
     );
 
-    let one = BigDecimal::from(1);
-    let z0 = one / k;
-
-    Ok(z0)
+    checked_div(BigDecimal::from(1), k, "optimal pool count")
 }
 
 // s parameter in reward projection
@@ -1428,17 +2029,19 @@ pub fn stake_pledged_by_owner(
     pool_addr: &str,
     current_epoch: i32,
 ) -> Result<BigDecimal, DataProviderDBSyncError> {
-    let owners_stake = pool_update::table
+    let mut owners_stake = pool_update::table
         .inner_join(pool_hash::table.on(pool_hash::id.eq(pool_update::hash_id)))
         .filter(pool_hash::view.eq(pool_addr))
         .filter(pool_update::active_epoch_no.le(current_epoch as i64))
         .select(pool_update::pledge)
         .load::<BigDecimal>(&mut dbs.connect()?)?;
-    let owners_stake = owners_stake.last().unwrap();
+    let owners_stake = owners_stake
+        .pop()
+        .ok_or_else(|| DataProviderDBSyncError::RequestValueNotFound(pool_addr.to_owned()))?;
 
     let total_stake = BigDecimal::from(pool_total_stake(dbs, pool_addr, current_epoch)?);
 
-    Ok(owners_stake / total_stake)
+    checked_div(owners_stake, total_stake, "pool stake")
 }
 
 // σ parameter in reward projection
@@ -1453,7 +2056,12 @@ pub fn stake_delegated_to_pool(
         .select(ada_pots::treasury) // 1.475 of the value of ada_pots::treasury is correct
         .first::<BigDecimal>(&mut dbs.connect()?)?;
 
-    Ok(stake_of_pool / total_stake_circulating) // 0.678 of the value is correct
+    // 0.678 of the value is correct
+    checked_div(
+        BigDecimal::from(stake_of_pool),
+        total_stake_circulating,
+        "circulating stake",
+    )
 }
 
 // input for "retrieve_rewards_next_epoch" function
@@ -1513,10 +2121,11 @@ pub fn calculate_pool_rewards_next_epoch(
     p: RewardProjectionParameters,
 ) -> Result<BigDecimal, DataProviderDBSyncError> {
     // reward formula: https://docs.cardano.org/learn/pledging-rewards/
-    let rewards = (p.r / (p.one + p.a0.clone()))
+    let pledge_factor = checked_div(p.s_.clone() * p.a0.clone(), p.z0.clone(), "z0")?;
+    let pledge_share = checked_div(p.s_, p.z0.clone(), "z0")?;
+    let rewards = checked_div(p.r, p.one + p.a0, "a0")?
         * (p.sigma_.clone()
-            + (p.s_.clone() * p.a0 / p.z0.clone())
-                * (p.sigma_.clone() - (p.s_ / p.z0.clone()) * (p.z0 - p.sigma_)));
+            + pledge_factor * (p.sigma_.clone() - pledge_share * (p.z0 - p.sigma_)));
 
     Ok(rewards)
 }
@@ -1533,9 +2142,12 @@ pub fn pool_owner_margin(
         .select(pool_update::margin)
         .load::<f64>(&mut dbs.connect()?)?;
     margin.reverse();
-    let margin = BigDecimal::from_f64(margin.last().unwrap().clone()).unwrap();
+    let margin = *margin
+        .last()
+        .ok_or_else(|| DataProviderDBSyncError::RequestValueNotFound(pool_hash.to_owned()))?;
 
-    Ok(margin)
+    BigDecimal::from_f64(margin)
+        .ok_or_else(|| DataProviderDBSyncError::Custom(format!("invalid pool margin {margin}")))
 }
 
 pub fn personal_stake(
@@ -1556,10 +2168,8 @@ pub fn personal_stake(
             .filter(stake_address::view.eq(stake_addr))
             .select(epoch_stake::amount)
             .load::<BigDecimal>(&mut dbs.connect()?)?
-            .iter()
-            .last()
-            .unwrap()
-            .clone())
+            .pop()
+            .ok_or_else(|| DataProviderDBSyncError::RequestValueNotFound(stake_addr.to_owned()))?)
     }
 }
 
@@ -1582,7 +2192,7 @@ pub fn personal_delegator_rewards_next_epoch(
 
     let total_pool_stake = BigDecimal::from(pool_total_stake(dbs, pool_hash, current_epoch)?); // correct
 
-    let personal_percentage = personal_stake / total_pool_stake; // seems right
+    let personal_percentage = checked_div(personal_stake, total_pool_stake, "pool stake")?; // seems right
 
     Ok(total_delegator_reward * personal_percentage)
 }
@@ -1709,7 +2319,7 @@ pub async fn discover_transaction(
                     }),
             )
         },
-        fee: txinfo.0.to_u64().unwrap(),
+        fee: to_u64(&txinfo.0)?,
         cbor: None,
     })
 }
@@ -1813,18 +2423,22 @@ pub async fn epoch_change(
         })
         .collect();
 
-    let out = EpochChangeResponse {
-        last_epoch: out[1].5.unwrap() as u64,
-        last_blockhash: out[1].1.clone(),
-        last_slot: out[1].2.unwrap() as u64,
-        new_epoch: out[0].5.unwrap() as u64,
-        new_slot: out[0].2.unwrap() as u64,
-        new_blockhash: out[0].1.clone(),
-        epoch_nonce: out[0].6.clone(),
-        extra_entropy: out[0].7.clone(),
+    let missing = || DataProviderDBSyncError::RequestValueNotFound("epoch change".to_owned());
+    let (new, last) = match out.as_slice() {
+        [new, last, ..] => (new, last),
+        _ => return Err(missing()),
     };
 
-    Ok(out)
+    Ok(EpochChangeResponse {
+        last_epoch: last.5.ok_or_else(missing)? as u64,
+        last_blockhash: last.1.clone(),
+        last_slot: last.2.ok_or_else(missing)? as u64,
+        new_epoch: new.5.ok_or_else(missing)? as u64,
+        new_slot: new.2.ok_or_else(missing)? as u64,
+        new_blockhash: new.1.clone(),
+        epoch_nonce: new.6.clone(),
+        extra_entropy: new.7.clone(),
+    })
 }
 
 #[cfg(test)]
@@ -1838,18 +2452,20 @@ mod tests {
     async fn test_tx_history() {
         let r = vec!["addr_test1qqt86eq9972q3qttj6ztje97llasktzfzvhmdccqjlqjaq2cer3t74yn0dm8xqnr7rtwhkqcrpsmphwcf0mlmn39ry6qy6q5t2","addr_test1qpg8ehvgj9zxrx59et72yjn2p02xwsm3l89jwj8ujcj63ujcer3t74yn0dm8xqnr7rtwhkqcrpsmphwcf0mlmn39ry6qw23emu","addr_test1qqdp3cry5vc2gfjljctdu638tvkcqfx40fjunht9hrmru5zcer3t74yn0dm8xqnr7rtwhkqcrpsmphwcf0mlmn39ry6qnaxxgs","addr_test1qr2mw080ujz0unmpn9lx5ftfuewc6htyr6v3a0svul2zgezcer3t74yn0dm8xqnr7rtwhkqcrpsmphwcf0mlmn39ry6qgryf7t","addr_test1qr7tqh7tsg4lut3jv6tsfwlv464m6knjjw90ugyz8uzgr6zcer3t74yn0dm8xqnr7rtwhkqcrpsmphwcf0mlmn39ry6qt0jxzj","addr_test1qrscurjp292sxv24sepj7ghq4ydkkekzaz53zwfswcna6ljcer3t74yn0dm8xqnr7rtwhkqcrpsmphwcf0mlmn39ry6q8pu3l5","addr_test1qqssrphse6qmp9h0ksu5vfmsx99tfl2lc6rhvy2spd5wr86cer3t74yn0dm8xqnr7rtwhkqcrpsmphwcf0mlmn39ry6qw59j4j","addr_test1qqgagc0fy6nm0qe4h8zqxsg952tqjeg7l7j0agd0cx4u25zcer3t74yn0dm8xqnr7rtwhkqcrpsmphwcf0mlmn39ry6qxvept2"];
 
-        let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-            db_path: dotenv::var("DBSYNC_DB_URL").unwrap(),
-        }));
+        let dp = crate::DataProvider::new(
+            crate::DBSyncProvider::new(crate::Config::new(dotenv::var("DBSYNC_DB_URL").unwrap()))
+                .unwrap(),
+        );
         let t = dp.tx_history(&r, None).await.unwrap();
         println!("{t:?}");
     }
 
     #[tokio::test]
     async fn test_discover_transaction() {
-        let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-            db_path: dotenv::var("DBSYNC_DB_URL").unwrap(),
-        }));
+        let dp = crate::DataProvider::new(
+            crate::DBSyncProvider::new(crate::Config::new(dotenv::var("DBSYNC_DB_URL").unwrap()))
+                .unwrap(),
+        );
         let t = crate::dbsync::discover_transaction(
             dp.provider(),
             "1b07f1152e52ce0a9dbb561aa2e2d1750ca3a1a4141150a8bad342947a66a3a6",
@@ -1860,18 +2476,20 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_pools() {
-        let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-            db_path: dotenv::var("DBSYNC_DB_URL").unwrap(),
-        }));
-        let t = crate::dbsync::get_pools(dp.provider()).await;
+        let dp = crate::DataProvider::new(
+            crate::DBSyncProvider::new(crate::Config::new(dotenv::var("DBSYNC_DB_URL").unwrap()))
+                .unwrap(),
+        );
+        let t = crate::dbsync::get_pools(dp.provider());
         println!("{t:?}");
     }
 
     #[tokio::test]
     async fn test_epoch_nonce() {
-        let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-            db_path: dotenv::var("DBSYNC_DB_URL").unwrap(),
-        }));
+        let dp = crate::DataProvider::new(
+            crate::DBSyncProvider::new(crate::Config::new(dotenv::var("DBSYNC_DB_URL").unwrap()))
+                .unwrap(),
+        );
         let t = crate::dbsync::epoch_nonce(dp.provider(), 205)
             .await
             .unwrap();
@@ -1880,9 +2498,10 @@ mod tests {
 
     #[tokio::test]
     async fn test_is_nft() {
-        let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-            db_path: dotenv::var("DBSYNC_DB_URL").unwrap(),
-        }));
+        let dp = crate::DataProvider::new(
+            crate::DBSyncProvider::new(crate::Config::new(dotenv::var("DBSYNC_DB_URL").unwrap()))
+                .unwrap(),
+        );
         let t = crate::dbsync::is_nft(
             dp.provider(),
             &[
@@ -1899,9 +2518,10 @@ mod tests {
 
     #[tokio::test]
     async fn test_supply() {
-        let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-            db_path: dotenv::var("DBSYNC_DB_URL").unwrap(),
-        }));
+        let dp = crate::DataProvider::new(
+            crate::DBSyncProvider::new(crate::Config::new(dotenv::var("DBSYNC_DB_URL").unwrap()))
+                .unwrap(),
+        );
         let t = crate::dbsync::token_supply(
             dp.provider(),
             "asset1m099azmatp3f3xehsu4sqvr45jzqafxmm0dra0",
@@ -1916,9 +2536,10 @@ mod tests {
     // make projection accurate for higher epoch values.
     #[tokio::test]
     async fn reward_projection() {
-        let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-            db_path: dotenv::var("DBSYNC_DB_URL").unwrap(),
-        }));
+        let dp = crate::DataProvider::new(
+            crate::DBSyncProvider::new(crate::Config::new(dotenv::var("DBSYNC_DB_URL").unwrap()))
+                .unwrap(),
+        );
 
         let pool_hash = "pool1ayc7a29ray6yv4hn7ge72hpjafg9vvpmtscnq9v8r0zh7azas9c";
         let stake_addr = "stake_test1upvv3c4l2jfhkannqf3lp4htmqvpscdsmhvyhalaecj3jdqtfcgvh"; // stake_test1upvv3c4l2jfhkannqf3lp4htmqvpscdsmhvyhalaecj3jdqtfcgvh
@@ -1941,9 +2562,10 @@ mod tests {
 
     #[tokio::test]
     async fn retrieve_staked_amount() {
-        let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-            db_path: dotenv::var("DBSYNC_DB_URL").unwrap(),
-        }));
+        let dp = crate::DataProvider::new(
+            crate::DBSyncProvider::new(crate::Config::new(dotenv::var("DBSYNC_DB_URL").unwrap()))
+                .unwrap(),
+        );
         let epoch = 275;
         let stake_addr = "stake_test1upvv3c4l2jfhkannqf3lp4htmqvpscdsmhvyhalaecj3jdqtfcgvh";
 
@@ -1955,9 +2577,10 @@ mod tests {
 
     #[tokio::test]
     async fn mint_metadata() {
-        let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-            db_path: dotenv::var("DBSYNC_DB_URL").unwrap(),
-        }));
+        let dp = crate::DataProvider::new(
+            crate::DBSyncProvider::new(crate::Config::new(dotenv::var("DBSYNC_DB_URL").unwrap()))
+                .unwrap(),
+        );
         let fingerprint_in = "asset1kngmwlxpfzc6pk027zvhsfpprp452gt3enhhxh";
         let func_value = super::mint_metadata(dp.provider(), fingerprint_in).unwrap();
         let real_value = super::TokenInfoView {
@@ -1987,9 +2610,10 @@ mod tests {
     #[tokio::test]
     #[allow(non_snake_case)]
     async fn mint_metadata_bug_CMW_78() {
-        let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-            db_path: dotenv::var("DBSYNC_DB_URL").unwrap(),
-        }));
+        let dp = crate::DataProvider::new(
+            crate::DBSyncProvider::new(crate::Config::new(dotenv::var("DBSYNC_DB_URL").unwrap()))
+                .unwrap(),
+        );
         let fingerprint_in = "asset162kdtwq54e5khz5y6naa55xqvk0zk5fpce8c76"; // contains non-UTF8 characters
         let func_value = super::mint_metadata(dp.provider(), fingerprint_in).unwrap();
         let real_value = super::TokenInfoView {
@@ -2017,9 +2641,10 @@ mod tests {
     #[tokio::test]
     #[allow(non_snake_case)]
     async fn get_txo_tokens_CMW_81() {
-        let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-            db_path: dotenv::var("DBSYNC_DB_URL").unwrap(),
-        }));
+        let dp = crate::DataProvider::new(
+            crate::DBSyncProvider::new(crate::Config::new(dotenv::var("DBSYNC_DB_URL").unwrap()))
+                .unwrap(),
+        );
 
         let utxo_tokens = super::get_txo_tokens(
             dp.provider(), 
@@ -2118,9 +2743,10 @@ mod tests {
     #[tokio::test]
     #[allow(non_snake_case)]
     async fn get_utxo_tokens_CMW_81() {
-        let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-            db_path: dotenv::var("DBSYNC_DB_URL").unwrap(),
-        }));
+        let dp = crate::DataProvider::new(
+            crate::DBSyncProvider::new(crate::Config::new(dotenv::var("DBSYNC_DB_URL").unwrap()))
+                .unwrap(),
+        );
 
         let utxo_tokens = super::get_utxo_tokens(
             dp.provider(), 
@@ -2170,4 +2796,257 @@ mod tests {
         assert_eq!(hex::encode(utxo_tokens[2].policy.clone()), "dfd18a815a25339777dcc80bce9c438ad632272d95f334a111711ac9");
         assert_eq!(hex::encode(utxo_tokens[2].name.clone()), "7441726b");
     }
+
+    #[test]
+    fn cursor_queries_only_take_plain_literals() {
+        let addr = "addr1vyqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qavsj8u";
+        assert!(super::address_utxos_cursor_query(addr)
+            .unwrap()
+            .contains(&format!("address = '{addr}'")));
+        assert!(super::address_utxos_cursor_query("addr1' OR '1'='1").is_err());
+        assert!(super::tx_history_cursor_query(&[addr, "x'); DROP TABLE tx; --"]).is_err());
+        assert!(super::tx_history_cursor_query(&[]).is_err());
+        assert!(super::policy_holders_cursor_query("00', 'hex') OR true --").is_err());
+        assert!(super::policy_holders_cursor_query("0A0b")
+            .unwrap()
+            .contains("decode('0a0b', 'hex')"));
+    }
+
+    const FIXTURE_SCHEMA: &str = include_str!("fixtures/schema.sql");
+    const FIXTURE_SPARSE: &str = include_str!("fixtures/sparse.sql");
+    const FIXTURE_HISTORY: &str = include_str!("fixtures/history.sql");
+
+    /// Fails on SQL errors, which mean the query does not fit the schema
+    fn valid_sql<T>(result: Result<T, DataProviderDBSyncError>) {
+        if let Err(DataProviderDBSyncError::DieselError(
+            e @ diesel::result::Error::DatabaseError(..),
+        )) = result
+        {
+            panic!("query failed: {e}");
+        }
+    }
+
+    fn not_found<T: std::fmt::Debug>(result: Result<T, DataProviderDBSyncError>) {
+        assert!(
+            matches!(
+                result,
+                Err(DataProviderDBSyncError::DieselError(
+                    diesel::result::Error::NotFound
+                ))
+            ),
+            "expected NotFound, got {result:?}"
+        );
+    }
+
+    /// Calls every query against the fixture database, `sparse` is set once the rows of
+    /// `sparse.sql` are loaded
+    async fn exercise(dbs: &crate::DBSyncProvider, sparse: bool) {
+        let addr = "addr1vyqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qavsj8u";
+        let stake = "stake_test1upjx2en8dp5k56mvd4hx7ur3wfehgatkwau8j7nm037hulcv8npk6";
+        let pool = "pool1fixture";
+        let hash = "1b07f1152e52ce0a9dbb561aa2e2d1750ca3a1a4141150a8bad342947a66a3a6";
+        let fingerprint = "asset1wn7x62hf07wp36u6nmux467nrsngrcvurwqvm2";
+        let policy = "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c";
+        let raw_hash = hex::decode(hash).unwrap();
+
+        assert!(super::get_utxo_tokens(dbs, 1, 0).unwrap().is_empty());
+        assert!(super::get_txo_tokens(dbs, 1, 0).unwrap().is_empty());
+        valid_sql(super::get_utxo_tokens_dep(dbs, 1));
+        not_found(super::select_addr_of_first_transaction(dbs, stake));
+        assert_eq!(super::get_address_utxos(dbs, addr).unwrap().len(), 0);
+        valid_sql(super::utxo_by_dataumhash(dbs, addr, &raw_hash));
+        not_found(super::utxo_by_txid(dbs, &raw_hash, 0));
+        let tx_hash = crate::models::TxHash::from_bytes(raw_hash.clone()).unwrap();
+        let refs = [(tx_hash.clone(), 0), (tx_hash, 70000)];
+        let resolved = super::utxos_by_refs(dbs, &refs).unwrap();
+        assert!(resolved.found.is_empty());
+        assert_eq!(resolved.missing, refs);
+        let mut conn = dbs.connect().unwrap();
+        assert!(super::cursor_utxos(&mut conn, vec![]).unwrap().is_empty());
+        drop(conn);
+        assert_eq!(super::get_stake_address_utxos(dbs, stake).unwrap().len(), 0);
+        valid_sql(super::asset_utxos_on_addr(dbs, addr));
+        valid_sql(super::get_address_utxos_dep(dbs, addr));
+        valid_sql(super::get_stake_address_utxos_dep(dbs, stake));
+        valid_sql(super::asset_utxos_on_addr_dep(dbs, addr));
+        not_found(super::find_datums_for_tx(dbs, &raw_hash));
+        // the fixture block has no block number
+        assert!(matches!(
+            super::slot(dbs),
+            Err(DataProviderDBSyncError::Custom(_))
+        ));
+        not_found(super::get_tx_slot(dbs, hash));
+        assert!(super::txs_to_address_since(dbs, addr, 0)
+            .unwrap()
+            .is_empty());
+        assert!(!super::tx_included(dbs, &raw_hash).unwrap());
+        assert!(super::stakers_on_pool(dbs, pool, 1).unwrap().is_empty());
+        valid_sql(super::stakers_on_pool_after(dbs, pool, 1, 0, 10));
+        valid_sql(super::deligations_per_pool_for_epochs(dbs, pool, 0, 2));
+        valid_sql(super::pool_total_stake(dbs, pool, 1));
+        // `epoch_stake` stays empty in both fixtures
+        not_found(super::current_epoch(dbs));
+        not_found(super::fingerprint(dbs, policy, "00"));
+        not_found(super::token_info(dbs, fingerprint));
+        assert!(super::stake_registration(dbs, stake).unwrap().is_empty());
+        valid_sql(super::stake_deregistration(dbs, stake));
+        assert!(!super::check_stakeaddr_registered(dbs, stake).unwrap());
+        assert!(super::lookup_token_holders(dbs, fingerprint, Some(&1))
+            .unwrap()
+            .is_empty());
+        valid_sql(super::lookup_token_holders_after(
+            dbs,
+            fingerprint,
+            None,
+            0,
+            10,
+        ));
+        valid_sql(super::lookup_nft_token_holders(dbs, policy));
+        valid_sql(super::lookup_nft_token_holders_after(dbs, policy, 0, 10));
+        valid_sql(super::mint_metadata(dbs, fingerprint));
+        assert!(super::get_pools(dbs).unwrap().is_empty());
+        assert_eq!(super::tx_metadata(dbs, hash).await.unwrap(), None);
+        if sparse {
+            assert!(super::pool_valid(dbs, pool).unwrap());
+        } else {
+            not_found(super::pool_valid(dbs, pool));
+        }
+        assert!(!super::txhash_spent(dbs, hash).unwrap());
+        assert_eq!(super::token_supply(dbs, fingerprint).await.unwrap(), None);
+        assert!(!super::check_nft_supply(dbs, fingerprint).await.unwrap());
+        assert_eq!(
+            super::is_nft(dbs, &[fingerprint]).await.unwrap(),
+            vec![false]
+        );
+        assert_eq!(
+            super::addresses_exist(dbs, &vec![addr]).unwrap(),
+            vec![false]
+        );
+        assert!(super::tx_history(dbs, &[addr], None).unwrap().is_empty());
+        assert!(super::_tx_history_q(dbs, &[addr], None).unwrap().is_empty());
+        assert!(super::tx_history_before(dbs, &[addr], None, i64::MAX, 10)
+            .unwrap()
+            .is_empty());
+        assert!(super::address_tx_history(dbs, &[addr], None)
+            .unwrap()
+            .is_empty());
+        valid_sql(super::stake_address_tx_history(dbs, stake, Some(10)));
+        assert!(super::stake_address_addresses(dbs, stake)
+            .unwrap()
+            .is_empty());
+        not_found(super::stake_address_balance(dbs, stake));
+        assert!(super::stake_address_history(dbs, stake, None)
+            .unwrap()
+            .is_empty());
+        valid_sql(super::txo_by_id_index(dbs, 1, 0).await);
+        valid_sql(super::collateral_txo_by_id_index(dbs, 1, 0).await);
+        assert_eq!(super::get_tx_inputs(dbs, hash).await.unwrap().len(), 0);
+        valid_sql(super::get_tx_reference_inputs(dbs, hash).await);
+        assert_eq!(super::get_tx_outputs(dbs, hash).await.unwrap().len(), 0);
+        valid_sql(super::get_withdrawls(dbs, hash).await);
+        valid_sql(super::tx_stake_registration(dbs, hash).await);
+        valid_sql(super::tx_stake_deregistration(dbs, hash).await);
+        valid_sql(super::tx_script(dbs, hash).await);
+        valid_sql(super::tx_collateral_in(dbs, hash).await);
+        valid_sql(super::tx_collateral_out(dbs, hash).await);
+        valid_sql(super::retrieve_staked_amount(dbs, 1, stake));
+        valid_sql(super::retrieve_generated_rewards(dbs, stake));
+        valid_sql(super::retrieve_generated_rewards_after(dbs, stake, 0, 10));
+        valid_sql(super::total_available_rewards(dbs, 1));
+        valid_sql(super::pledge_influence_factor(dbs, 1));
+        valid_sql(super::relative_pool_saturation_size(dbs, 1));
+        valid_sql(super::stake_pledged_by_owner(dbs, pool, 1));
+        valid_sql(super::stake_delegated_to_pool(dbs, pool, 1));
+        valid_sql(super::reward_projection_parameters(dbs, 1, pool));
+        valid_sql(super::pool_owner_margin(dbs, pool, 1));
+        valid_sql(super::personal_stake(dbs, stake, None));
+        valid_sql(super::personal_stake(dbs, stake, Some(1)));
+        valid_sql(super::personal_delegator_rewards_next_epoch(
+            dbs, pool, 1, stake,
+        ));
+        valid_sql(super::earned_reward(dbs, stake, 1));
+        valid_sql(super::discover_transaction(dbs, hash).await);
+        valid_sql(super::epoch_nonce(dbs, 1).await);
+        valid_sql(super::epoch_change(dbs, None).await);
+        valid_sql(super::epoch_change(dbs, Some(1)).await);
+    }
+
+    /// Runs against the database in `DBSYNC_FIXTURE_URL`, the tables are created in the
+    /// schema `cdp_fixture` which is dropped first. Run it with `cargo test -- --ignored`.
+    #[tokio::test]
+    #[ignore = "needs a PostgreSQL database in DBSYNC_FIXTURE_URL"]
+    async fn queries_handle_missing_data() {
+        use diesel::connection::SimpleConnection;
+        use diesel::Connection;
+
+        let url = dotenv::var("DBSYNC_FIXTURE_URL").expect("DBSYNC_FIXTURE_URL is not set");
+        let mut conn = diesel::PgConnection::establish(&url).unwrap();
+        conn.batch_execute(
+            "DROP SCHEMA IF EXISTS cdp_fixture CASCADE; \
+             CREATE SCHEMA cdp_fixture; \
+             SET search_path TO cdp_fixture;",
+        )
+        .unwrap();
+        conn.batch_execute(FIXTURE_SCHEMA).unwrap();
+
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let dbs = crate::DBSyncProvider::new(crate::Config::new(format!(
+            "{url}{separator}options=-csearch_path%3Dcdp_fixture"
+        )))
+        .unwrap();
+
+        exercise(&dbs, false).await;
+        conn.batch_execute(FIXTURE_SPARSE).unwrap();
+        exercise(&dbs, true).await;
+
+        // transactions at slot 10 and 20, `slot` is the last included slot
+        conn.batch_execute(FIXTURE_HISTORY).unwrap();
+        let addr = ["addr1vyqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qavsj8u"];
+        let first = "1b07f1152e52ce0a9dbb561aa2e2d1750ca3a1a4141150a8bad342947a66a3a6";
+        let slots = |history: Vec<crate::models::TxHistoryListView>| {
+            history.into_iter().map(|t| t.slot).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            slots(super::tx_history(&dbs, &addr, None).unwrap()),
+            vec![20, 10]
+        );
+        let history = super::tx_history(&dbs, &addr, Some(15)).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].hash, first);
+        assert_eq!(
+            slots(super::tx_history(&dbs, &addr, Some(20)).unwrap()),
+            vec![20, 10]
+        );
+        assert_eq!(
+            slots(super::_tx_history_q(&dbs, &addr, None).unwrap()),
+            vec![10, 20]
+        );
+        assert_eq!(
+            slots(super::_tx_history_q(&dbs, &addr, Some(15)).unwrap()),
+            vec![10]
+        );
+        let before = super::tx_history_before(&dbs, &addr, Some(15), i64::MAX, 10).unwrap();
+        assert_eq!(
+            slots(before.into_iter().map(|(_, t)| t).collect()),
+            vec![10]
+        );
+        let values = super::address_tx_history(&dbs, &addr, Some(15)).unwrap();
+        assert_eq!(
+            values
+                .iter()
+                .map(|t| (t.slot, &t.hash[..]))
+                .collect::<Vec<_>>(),
+            vec![(10, first)]
+        );
+
+        let zero = || BigDecimal::from(0);
+        let _ = super::calculate_pool_rewards_next_epoch(super::RewardProjectionParameters {
+            r: zero(),
+            one: BigDecimal::from(1),
+            a0: BigDecimal::from(-1),
+            sigma_: zero(),
+            s_: zero(),
+            z0: zero(),
+        });
+    }
 }
\ No newline at end of file
@@ -11,6 +11,8 @@ pub enum DataProviderDBSyncError {
     MintMetadataNotFound(String),
     #[error("Custom Error")]
     Custom(String),
+    #[error("DBSync query did not complete within {0:?}")]
+    Timeout(std::time::Duration),
     #[error(transparent)]
     ParseIntError(#[from] core::num::ParseIntError),
     #[error(transparent)]
@@ -20,6 +22,10 @@ pub enum DataProviderDBSyncError {
     #[error(transparent)]
     DieselConnectionError(#[from] diesel::ConnectionError),
     #[error(transparent)]
+    PoolError(#[from] diesel::r2d2::PoolError),
+    #[error(transparent)]
+    JoinError(#[from] tokio::task::JoinError),
+    #[error(transparent)]
     HexError(#[from] hex::FromHexError),
     #[error(transparent)]
     UTF8Error(#[from] std::string::FromUtf8Error),
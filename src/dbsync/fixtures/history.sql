@@ -0,0 +1,13 @@
+-- Two transactions paying to the fixture address on top of `sparse.sql`, one in the block at
+-- slot 10 and one in a block at slot 20. Every output holds one token.
+INSERT INTO block (id, hash, epoch_no, slot_no, block_no, slot_leader_id, size, time, tx_count, proto_major, proto_minor)
+    VALUES (2, '\x05', 1, 20, 2, 1, 0, '2022-01-01 00:00:10', 1, 7, 0);
+INSERT INTO tx (id, hash, block_id, block_index, out_sum, fee, deposit, size, valid_contract, script_size) VALUES
+    (1, '\x1b07f1152e52ce0a9dbb561aa2e2d1750ca3a1a4141150a8bad342947a66a3a6', 1, 0, 2000000, 170000, 0, 0, true, 0),
+    (2, '\x0202020202020202020202020202020202020202020202020202020202020202', 2, 0, 3000000, 170000, 0, 0, true, 0);
+INSERT INTO tx_out (id, tx_id, index, address, address_raw, address_has_script, value) VALUES
+    (1, 1, 0, 'addr1vyqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qavsj8u', '\x61', false, 2000000),
+    (2, 2, 0, 'addr1vyqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qavsj8u', '\x61', false, 3000000);
+INSERT INTO multi_asset (id, policy, name, fingerprint)
+    VALUES (1, '\x0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c', '\x00', 'asset1wn7x62hf07wp36u6nmux467nrsngrcvurwqvm2');
+INSERT INTO ma_tx_out (id, quantity, tx_out_id, ident) VALUES (1, 1, 1, 1), (2, 1, 2, 1);
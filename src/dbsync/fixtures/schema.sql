@@ -0,0 +1,507 @@
+-- Empty DBSync schema with the tables and columns of `schema.rs`, used by the
+-- fixture tests in `api.rs`. Views of the real database are plain tables here.
+
+CREATE TYPE syncstatetype AS ENUM ('lagging', 'following');
+CREATE TYPE scriptpurposetype AS ENUM ('spend', 'mint', 'cert', 'reward');
+CREATE TYPE rewardtype AS ENUM ('leader', 'member', 'reserves', 'treasury', 'refund');
+CREATE TYPE scripttype AS ENUM ('multisig', 'timelock', 'plutusV1', 'plutusV2');
+
+CREATE TABLE unspent_utxos (
+    id bigint PRIMARY KEY,
+    tx_id bigint NOT NULL,
+    hash bytea NOT NULL,
+    index smallint NOT NULL,
+    address varchar NOT NULL,
+    value numeric NOT NULL,
+    data_hash bytea,
+    address_has_script boolean NOT NULL,
+    stake_address varchar
+);
+
+CREATE TABLE utxo_view (
+    id bigint PRIMARY KEY,
+    tx_id bigint NOT NULL,
+    index smallint NOT NULL,
+    address varchar NOT NULL,
+    address_raw bytea NOT NULL,
+    address_has_script boolean NOT NULL,
+    payment_cred bytea NOT NULL,
+    stake_address_id bigint,
+    value numeric NOT NULL,
+    data_hash bytea,
+    inline_datum_id bigint,
+    reference_script_id bigint
+);
+
+CREATE TABLE utxo_token_view (
+    id bigint PRIMARY KEY,
+    policy bytea NOT NULL,
+    name bytea NOT NULL,
+    fingerprint varchar NOT NULL,
+    quantity numeric NOT NULL
+);
+
+CREATE TABLE ada_pots (
+    id bigint PRIMARY KEY,
+    slot_no integer NOT NULL,
+    epoch_no integer NOT NULL,
+    treasury numeric NOT NULL,
+    reserves numeric NOT NULL,
+    rewards numeric NOT NULL,
+    utxo numeric NOT NULL,
+    deposits numeric NOT NULL,
+    fees numeric NOT NULL,
+    block_id bigint NOT NULL
+);
+
+CREATE TABLE admin_user (
+    id bigint PRIMARY KEY,
+    username varchar NOT NULL,
+    password varchar NOT NULL
+);
+
+CREATE TABLE block (
+    id bigint PRIMARY KEY,
+    hash bytea NOT NULL,
+    epoch_no integer,
+    slot_no bigint,
+    epoch_slot_no integer,
+    block_no integer,
+    previous_id bigint,
+    slot_leader_id bigint NOT NULL,
+    size integer NOT NULL,
+    time timestamp NOT NULL,
+    tx_count bigint NOT NULL,
+    proto_major integer NOT NULL,
+    proto_minor integer NOT NULL,
+    vrf_key varchar,
+    op_cert bytea,
+    op_cert_counter bigint
+);
+
+CREATE TABLE collateral_tx_in (
+    id bigint PRIMARY KEY,
+    tx_in_id bigint NOT NULL,
+    tx_out_id bigint NOT NULL,
+    tx_out_index smallint NOT NULL
+);
+
+CREATE TABLE collateral_tx_out (
+    id bigint PRIMARY KEY,
+    tx_id bigint NOT NULL,
+    index smallint NOT NULL,
+    address varchar NOT NULL,
+    address_raw bytea NOT NULL,
+    address_has_script boolean NOT NULL,
+    payment_cred bytea,
+    stake_address_id bigint,
+    value numeric NOT NULL,
+    data_hash bytea,
+    multi_assets_descr varchar NOT NULL,
+    inline_datum_id bigint,
+    reference_script_id bigint
+);
+
+CREATE TABLE cost_model (
+    id bigint PRIMARY KEY,
+    costs jsonb NOT NULL,
+    block_id bigint NOT NULL
+);
+
+CREATE TABLE datum (
+    id bigint PRIMARY KEY,
+    hash bytea NOT NULL,
+    tx_id bigint NOT NULL,
+    value jsonb,
+    bytes bytea NOT NULL
+);
+
+CREATE TABLE delegation (
+    id bigint PRIMARY KEY,
+    addr_id bigint NOT NULL,
+    cert_index integer NOT NULL,
+    pool_hash_id bigint NOT NULL,
+    active_epoch_no bigint NOT NULL,
+    tx_id bigint NOT NULL,
+    slot_no integer NOT NULL,
+    redeemer_id bigint
+);
+
+CREATE TABLE delisted_pool (
+    id bigint PRIMARY KEY,
+    hash_raw bytea NOT NULL
+);
+
+CREATE TABLE epoch (
+    id bigint PRIMARY KEY,
+    out_sum numeric NOT NULL,
+    fees numeric NOT NULL,
+    tx_count integer NOT NULL,
+    blk_count integer NOT NULL,
+    no integer NOT NULL,
+    start_time timestamp NOT NULL,
+    end_time timestamp NOT NULL
+);
+
+CREATE TABLE epoch_param (
+    id bigint PRIMARY KEY,
+    epoch_no integer NOT NULL,
+    min_fee_a integer NOT NULL,
+    min_fee_b integer NOT NULL,
+    max_block_size integer NOT NULL,
+    max_tx_size integer NOT NULL,
+    max_bh_size integer NOT NULL,
+    key_deposit numeric NOT NULL,
+    pool_deposit numeric NOT NULL,
+    max_epoch integer NOT NULL,
+    optimal_pool_count integer NOT NULL,
+    influence double precision NOT NULL,
+    monetary_expand_rate double precision NOT NULL,
+    treasury_growth_rate double precision NOT NULL,
+    decentralisation double precision NOT NULL,
+    protocol_major integer NOT NULL,
+    protocol_minor integer NOT NULL,
+    min_utxo_value numeric NOT NULL,
+    min_pool_cost numeric NOT NULL,
+    nonce bytea NOT NULL,
+    cost_model_id bigint,
+    price_mem double precision,
+    price_step double precision,
+    max_tx_ex_mem numeric,
+    max_tx_ex_steps numeric,
+    max_block_ex_mem numeric,
+    max_block_ex_steps numeric,
+    max_val_size numeric,
+    collateral_percent integer,
+    max_collateral_inputs integer,
+    block_id bigint NOT NULL,
+    extra_entropy bytea,
+    coins_per_utxo_size numeric
+);
+
+CREATE TABLE epoch_reward_total_received (
+    id bigint PRIMARY KEY,
+    earned_epoch integer NOT NULL,
+    amount numeric NOT NULL
+);
+
+CREATE TABLE epoch_stake (
+    id bigint PRIMARY KEY,
+    addr_id bigint NOT NULL,
+    pool_id bigint NOT NULL,
+    amount numeric NOT NULL,
+    epoch_no integer NOT NULL
+);
+
+CREATE TABLE epoch_sync_time (
+    id bigint PRIMARY KEY,
+    no bigint NOT NULL,
+    seconds bigint NOT NULL,
+    state syncstatetype NOT NULL
+);
+
+CREATE TABLE ma_tx_mint (
+    id bigint PRIMARY KEY,
+    quantity numeric NOT NULL,
+    tx_id bigint NOT NULL,
+    ident bigint NOT NULL
+);
+
+CREATE TABLE ma_tx_out (
+    id bigint PRIMARY KEY,
+    quantity numeric NOT NULL,
+    tx_out_id bigint NOT NULL,
+    ident bigint NOT NULL
+);
+
+CREATE TABLE meta (
+    id bigint PRIMARY KEY,
+    start_time timestamp NOT NULL,
+    network_name varchar NOT NULL,
+    version varchar NOT NULL
+);
+
+CREATE TABLE multi_asset (
+    id bigint PRIMARY KEY,
+    policy bytea NOT NULL,
+    name bytea NOT NULL,
+    fingerprint varchar NOT NULL
+);
+
+CREATE TABLE param_proposal (
+    id bigint PRIMARY KEY,
+    epoch_no integer NOT NULL,
+    key bytea NOT NULL,
+    min_fee_a numeric,
+    min_fee_b numeric,
+    max_block_size numeric,
+    max_tx_size numeric,
+    max_bh_size numeric,
+    key_deposit numeric,
+    pool_deposit numeric,
+    max_epoch numeric,
+    optimal_pool_count numeric,
+    influence double precision,
+    monetary_expand_rate double precision,
+    treasury_growth_rate double precision,
+    decentralisation double precision,
+    entropy bytea,
+    protocol_major integer,
+    protocol_minor integer,
+    min_utxo_value numeric,
+    min_pool_cost numeric,
+    coins_per_utxo_word numeric,
+    cost_model_id bigint,
+    price_mem double precision,
+    price_step double precision,
+    max_tx_ex_mem numeric,
+    max_tx_ex_steps numeric,
+    max_block_ex_mem numeric,
+    max_block_ex_steps numeric,
+    max_val_size numeric,
+    collateral_percent integer,
+    max_collateral_inputs integer,
+    registered_tx_id bigint NOT NULL
+);
+
+CREATE TABLE pool_hash (
+    id bigint PRIMARY KEY,
+    hash_raw bytea NOT NULL,
+    view varchar NOT NULL
+);
+
+CREATE TABLE pool_metadata_ref (
+    id bigint PRIMARY KEY,
+    pool_id bigint NOT NULL,
+    url varchar NOT NULL,
+    hash bytea NOT NULL,
+    registered_tx_id bigint NOT NULL
+);
+
+CREATE TABLE pool_offline_data (
+    id bigint PRIMARY KEY,
+    pool_id bigint NOT NULL,
+    ticker_name varchar NOT NULL,
+    hash bytea NOT NULL,
+    json jsonb NOT NULL,
+    bytes bytea NOT NULL,
+    pmr_id bigint NOT NULL
+);
+
+CREATE TABLE pool_offline_fetch_error (
+    id bigint PRIMARY KEY,
+    pool_id bigint NOT NULL,
+    fetch_time timestamp NOT NULL,
+    pmr_id bigint NOT NULL,
+    fetch_error varchar NOT NULL,
+    retry_count integer NOT NULL
+);
+
+CREATE TABLE pool_owner (
+    id bigint PRIMARY KEY,
+    addr_id bigint NOT NULL,
+    pool_hash_id bigint NOT NULL,
+    registered_tx_id bigint NOT NULL
+);
+
+CREATE TABLE pool_relay (
+    id bigint PRIMARY KEY,
+    update_id bigint NOT NULL,
+    ipv4 varchar,
+    ipv6 varchar,
+    dns_name varchar,
+    dns_srv_name varchar,
+    port integer
+);
+
+CREATE TABLE pool_retire (
+    id bigint PRIMARY KEY,
+    hash_id bigint NOT NULL,
+    cert_index integer NOT NULL,
+    announced_tx_id bigint NOT NULL,
+    retiring_epoch integer NOT NULL
+);
+
+CREATE TABLE pool_update (
+    id bigint PRIMARY KEY,
+    hash_id bigint NOT NULL,
+    cert_index integer NOT NULL,
+    vrf_key_hash bytea NOT NULL,
+    pledge numeric NOT NULL,
+    reward_addr bytea NOT NULL,
+    active_epoch_no bigint NOT NULL,
+    meta_id bigint,
+    margin double precision NOT NULL,
+    fixed_cost numeric NOT NULL,
+    registered_tx_id bigint NOT NULL
+);
+
+CREATE TABLE pot_transfer (
+    id bigint PRIMARY KEY,
+    cert_index integer NOT NULL,
+    treasury numeric NOT NULL,
+    reserves numeric NOT NULL,
+    tx_id bigint NOT NULL
+);
+
+CREATE TABLE redeemer (
+    id bigint PRIMARY KEY,
+    tx_id bigint NOT NULL,
+    unit_mem bigint NOT NULL,
+    unit_steps bigint NOT NULL,
+    fee numeric NOT NULL,
+    purpose scriptpurposetype NOT NULL,
+    index integer NOT NULL,
+    script_hash bytea,
+    redeemer_data_id bigint NOT NULL
+);
+
+CREATE TABLE redeemer_data (
+    id bigint PRIMARY KEY,
+    hash bytea NOT NULL,
+    tx_id bigint NOT NULL,
+    value jsonb NOT NULL,
+    bytes bytea NOT NULL
+);
+
+CREATE TABLE reference_tx_in (
+    id bigint PRIMARY KEY,
+    tx_in_id bigint NOT NULL,
+    tx_out_id bigint NOT NULL,
+    tx_out_index smallint NOT NULL
+);
+
+CREATE TABLE reserve (
+    id bigint PRIMARY KEY,
+    addr_id bigint NOT NULL,
+    cert_index integer NOT NULL,
+    amount numeric NOT NULL,
+    tx_id bigint NOT NULL
+);
+
+CREATE TABLE reserved_pool_ticker (
+    id bigint PRIMARY KEY,
+    name varchar NOT NULL,
+    pool_hash bytea NOT NULL
+);
+
+CREATE TABLE reward (
+    id bigint PRIMARY KEY,
+    addr_id bigint NOT NULL,
+    type rewardtype NOT NULL,
+    amount numeric NOT NULL,
+    earned_epoch bigint NOT NULL,
+    spendable_epoch bigint NOT NULL,
+    pool_id bigint
+);
+
+CREATE TABLE schema_version (
+    id bigint PRIMARY KEY,
+    stage_one bigint NOT NULL,
+    stage_two bigint NOT NULL,
+    stage_three bigint NOT NULL
+);
+
+CREATE TABLE script (
+    id bigint PRIMARY KEY,
+    tx_id bigint NOT NULL,
+    hash bytea NOT NULL,
+    type scripttype NOT NULL,
+    json jsonb,
+    bytes bytea,
+    serialised_size integer
+);
+
+CREATE TABLE slot_leader (
+    id bigint PRIMARY KEY,
+    hash bytea NOT NULL,
+    pool_hash_id bigint,
+    description varchar NOT NULL
+);
+
+CREATE TABLE stake_address (
+    id bigint PRIMARY KEY,
+    hash_raw bytea NOT NULL,
+    view varchar NOT NULL,
+    script_hash bytea,
+    registered_tx_id bigint NOT NULL
+);
+
+CREATE TABLE stake_deregistration (
+    id bigint PRIMARY KEY,
+    addr_id bigint NOT NULL,
+    cert_index integer NOT NULL,
+    epoch_no integer NOT NULL,
+    tx_id bigint NOT NULL,
+    redeemer_id bigint
+);
+
+CREATE TABLE stake_registration (
+    id bigint PRIMARY KEY,
+    addr_id bigint NOT NULL,
+    cert_index integer NOT NULL,
+    epoch_no integer NOT NULL,
+    tx_id bigint NOT NULL
+);
+
+CREATE TABLE treasury (
+    id bigint PRIMARY KEY,
+    addr_id bigint NOT NULL,
+    cert_index integer NOT NULL,
+    amount numeric NOT NULL,
+    tx_id bigint NOT NULL
+);
+
+CREATE TABLE tx (
+    id bigint PRIMARY KEY,
+    hash bytea NOT NULL,
+    block_id bigint NOT NULL,
+    block_index integer NOT NULL,
+    out_sum numeric NOT NULL,
+    fee numeric NOT NULL,
+    deposit bigint NOT NULL,
+    size integer NOT NULL,
+    invalid_before numeric,
+    invalid_hereafter numeric,
+    valid_contract boolean NOT NULL,
+    script_size integer NOT NULL
+);
+
+CREATE TABLE tx_in (
+    id bigint PRIMARY KEY,
+    tx_in_id bigint NOT NULL,
+    tx_out_id bigint NOT NULL,
+    tx_out_index smallint NOT NULL,
+    redeemer_id bigint
+);
+
+CREATE TABLE tx_metadata (
+    id bigint PRIMARY KEY,
+    key numeric NOT NULL,
+    json jsonb,
+    bytes bytea NOT NULL,
+    tx_id bigint NOT NULL
+);
+
+CREATE TABLE tx_out (
+    id bigint PRIMARY KEY,
+    tx_id bigint NOT NULL,
+    index smallint NOT NULL,
+    address varchar NOT NULL,
+    address_raw bytea NOT NULL,
+    address_has_script boolean NOT NULL,
+    payment_cred bytea,
+    stake_address_id bigint,
+    value numeric NOT NULL,
+    data_hash bytea,
+    inline_datum_id bigint,
+    reference_script_id bigint
+);
+
+CREATE TABLE withdrawal (
+    id bigint PRIMARY KEY,
+    addr_id bigint NOT NULL,
+    amount numeric NOT NULL,
+    redeemer_id bigint,
+    tx_id bigint NOT NULL
+);
@@ -0,0 +1,13 @@
+-- Sparse rows on top of `schema.sql`: a block, a pool and a stake address without any
+-- stake or reward history, epoch parameters and pots which can not be divided by.
+INSERT INTO slot_leader (id, hash, description) VALUES (1, '\x00', 'leader');
+INSERT INTO block (id, hash, epoch_no, slot_no, slot_leader_id, size, time, tx_count, proto_major, proto_minor)
+    VALUES (1, '\x01', 1, 10, 1, 0, '2022-01-01', 0, 7, 0);
+INSERT INTO pool_hash (id, hash_raw, view) VALUES (1, '\x02', 'pool1fixture');
+INSERT INTO stake_address (id, hash_raw, view, registered_tx_id) VALUES (1, '\x03', 'stake_test1upjx2en8dp5k56mvd4hx7ur3wfehgatkwau8j7nm037hulcv8npk6', 1);
+INSERT INTO epoch_param (id, epoch_no, min_fee_a, min_fee_b, max_block_size, max_tx_size, max_bh_size,
+    key_deposit, pool_deposit, max_epoch, optimal_pool_count, influence, monetary_expand_rate,
+    treasury_growth_rate, decentralisation, protocol_major, protocol_minor, min_utxo_value,
+    min_pool_cost, nonce, block_id)
+    VALUES (1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 'NaN', 0, 0, 0, 7, 0, 0, 0, '\x04', 1);
+INSERT INTO ada_pots (id, slot_no, epoch_no, treasury, reserves, rewards, utxo, deposits, fees, block_id) VALUES (1, 10, 1, 0, 0, 0, 0, 0, 0, 1);
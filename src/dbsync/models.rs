@@ -113,7 +113,7 @@ impl UnspentUtxo {
                     asset.insert(
                         &cardano_serialization_lib::AssetName::new(tok.name)?,
                         &cardano_serialization_lib::utils::to_bignum(
-                            tok.quantity.to_u64().unwrap(),
+                            api::to_u64(&tok.quantity)?,
                         ),
                     );
                     ma.insert(
@@ -126,7 +126,7 @@ impl UnspentUtxo {
                     a.insert(
                         &cardano_serialization_lib::AssetName::new(tok.name)?,
                         &cardano_serialization_lib::utils::to_bignum(
-                            tok.quantity.to_u64().unwrap(),
+                            api::to_u64(&tok.quantity)?,
                         ),
                     );
                     ma.insert(
@@ -146,7 +146,8 @@ impl UnspentUtxo {
     }
 }
 
-#[derive(Queryable, Debug)]
+#[derive(Queryable, QueryableByName, Debug)]
+#[diesel(table_name = utxo_view)]
 pub struct UtxoView {
     pub id: i64,
     pub tx_id: i64,
@@ -205,7 +206,7 @@ impl UtxoView {
                     asset.insert(
                         &cardano_serialization_lib::AssetName::new(tok.name)?,
                         &cardano_serialization_lib::utils::to_bignum(
-                            tok.quantity.to_u64().unwrap(),
+                            api::to_u64(&tok.quantity)?,
                         ),
                     );
                     ma.insert(
@@ -218,7 +219,7 @@ impl UtxoView {
                     a.insert(
                         &cardano_serialization_lib::AssetName::new(tok.name)?,
                         &cardano_serialization_lib::utils::to_bignum(
-                            tok.quantity.to_u64().unwrap(),
+                            api::to_u64(&tok.quantity)?,
                         ),
                     );
                     ma.insert(
@@ -252,18 +253,7 @@ impl UtxoView {
                 .filter(script::id.eq(id))
                 .first::<Script>(&mut dbs.connect()?)?;
 
-            let scr = match script.type_ {
-                Scripttype::Multisig | Scripttype::Tiemlock => ScriptRef::new_native_script(
-                    &NativeScript::from_json(&script.json.unwrap().to_string())?,
-                ),
-                Scripttype::PlutusV1 => {
-                    ScriptRef::new_plutus_script(&PlutusScript::from_bytes(script.bytes.unwrap())?)
-                }
-                Scripttype::PlutusV2 => ScriptRef::new_plutus_script(&PlutusScript::from_bytes_v2(
-                    script.bytes.unwrap(),
-                )?),
-            };
-            output.set_script_ref(&scr)
+            output.set_script_ref(&script.to_script_ref()?)
         }
 
         Ok(cardano_serialization_lib::utils::TransactionUnspentOutput::new(&input, &output))
@@ -666,6 +656,34 @@ pub struct Script {
     pub serialised_size: Option<i32>,
 }
 
+impl Script {
+    pub fn to_script_ref(&self) -> Result<ScriptRef, DataProviderDBSyncError> {
+        script_ref(&self.hash, &self.type_, self.json.as_ref(), self.bytes.as_ref())
+    }
+}
+
+/// native scripts are stored as json, plutus scripts as bytes
+pub(crate) fn script_ref(
+    hash: &[u8],
+    type_: &Scripttype,
+    json: Option<&serde_json::Value>,
+    bytes: Option<&Vec<u8>>,
+) -> Result<ScriptRef, DataProviderDBSyncError> {
+    let missing =
+        || DataProviderDBSyncError::Custom(format!("script {} has no content", hex::encode(hash)));
+    Ok(match type_ {
+        Scripttype::Multisig | Scripttype::Tiemlock => ScriptRef::new_native_script(
+            &NativeScript::from_json(&json.ok_or_else(missing)?.to_string())?,
+        ),
+        Scripttype::PlutusV1 => ScriptRef::new_plutus_script(&PlutusScript::from_bytes(
+            bytes.ok_or_else(missing)?.clone(),
+        )?),
+        Scripttype::PlutusV2 => ScriptRef::new_plutus_script(&PlutusScript::from_bytes_v2(
+            bytes.ok_or_else(missing)?.clone(),
+        )?),
+    })
+}
+
 #[derive(Queryable, Debug)]
 pub struct SlotLeader {
     pub id: i64,
@@ -812,7 +830,7 @@ impl TxOut {
                     asset.insert(
                         &cardano_serialization_lib::AssetName::new(tok.name)?,
                         &cardano_serialization_lib::utils::to_bignum(
-                            tok.quantity.to_u64().unwrap(),
+                            api::to_u64(&tok.quantity)?,
                         ),
                     );
                     ma.insert(
@@ -825,7 +843,7 @@ impl TxOut {
                     a.insert(
                         &cardano_serialization_lib::AssetName::new(tok.name)?,
                         &cardano_serialization_lib::utils::to_bignum(
-                            tok.quantity.to_u64().unwrap(),
+                            api::to_u64(&tok.quantity)?,
                         ),
                     );
                     ma.insert(
@@ -859,18 +877,7 @@ impl TxOut {
                 .filter(script::id.eq(id))
                 .first::<Script>(&mut dbs.connect()?)?;
 
-            let scr = match script.type_ {
-                Scripttype::Multisig | Scripttype::Tiemlock => ScriptRef::new_native_script(
-                    &NativeScript::from_json(&script.json.unwrap().to_string())?,
-                ),
-                Scripttype::PlutusV1 => {
-                    ScriptRef::new_plutus_script(&PlutusScript::from_bytes(script.bytes.unwrap())?)
-                }
-                Scripttype::PlutusV2 => ScriptRef::new_plutus_script(&PlutusScript::from_bytes_v2(
-                    script.bytes.unwrap(),
-                )?),
-            };
-            output.set_script_ref(&scr)
+            output.set_script_ref(&script.to_script_ref()?)
         }
 
         Ok(cardano_serialization_lib::utils::TransactionUnspentOutput::new(&input, &output))
@@ -885,3 +892,132 @@ pub struct Withdrawal {
     pub redeemer_id: Option<i64>,
     pub tx_id: i64,
 }
+
+/// Row of a policy holder export, one per unspent output holding an asset of the policy
+#[derive(QueryableByName, Debug)]
+pub struct PolicyHolderQuery {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub stake_address: String,
+    #[diesel(sql_type = diesel::sql_types::Bytea)]
+    pub name: Vec<u8>,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub fingerprint: String,
+    #[diesel(sql_type = Numeric)]
+    pub quantity: BigDecimal,
+}
+
+/// Asset held by an output of a streamed batch
+#[derive(QueryableByName, Debug)]
+pub struct OutputAssetQuery {
+    #[diesel(sql_type = BigInt)]
+    pub tx_out_id: i64,
+    #[diesel(sql_type = diesel::sql_types::Bytea)]
+    pub policy: Vec<u8>,
+    #[diesel(sql_type = diesel::sql_types::Bytea)]
+    pub name: Vec<u8>,
+    #[diesel(sql_type = Numeric)]
+    pub quantity: BigDecimal,
+}
+
+/// Lovelace or asset of an output of the queried addresses which a transaction creates
+/// or, if `spent`, consumes. Outputs holding several assets produce one row per asset.
+#[derive(QueryableByName, Debug)]
+pub struct TxMovementQuery {
+    #[diesel(sql_type = BigInt)]
+    pub tx_id: i64,
+    #[diesel(sql_type = diesel::sql_types::Bytea)]
+    pub hash: Vec<u8>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<BigInt>)]
+    pub slot: Option<i64>,
+    #[diesel(sql_type = diesel::sql_types::Timestamp)]
+    pub time: NaiveDateTime,
+    #[diesel(sql_type = Numeric)]
+    pub fee: BigDecimal,
+    #[diesel(sql_type = BigInt)]
+    pub tx_out_id: i64,
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    pub spent: bool,
+    #[diesel(sql_type = Numeric)]
+    pub value: BigDecimal,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Bytea>)]
+    pub policy: Option<Vec<u8>>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Bytea>)]
+    pub name: Option<Vec<u8>>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<Numeric>)]
+    pub quantity: Option<BigDecimal>,
+}
+
+/// Unspent output requested by `utxos_by_refs`, `ord` is the position of its reference in
+/// the request. Outputs holding several assets produce one row per asset.
+/// Rows of `address_utxos_cursor_query` hold the output id in `ord` and no asset, the assets
+/// of a batch are loaded at once by `cursor_utxos`.
+#[derive(QueryableByName, Debug)]
+pub struct UtxoByRefQuery {
+    #[diesel(sql_type = BigInt)]
+    pub ord: i64,
+    #[diesel(sql_type = diesel::sql_types::Bytea)]
+    pub hash: Vec<u8>,
+    #[diesel(sql_type = diesel::sql_types::SmallInt)]
+    pub index: i16,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub address: String,
+    #[diesel(sql_type = Numeric)]
+    pub value: BigDecimal,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Bytea>)]
+    pub data_hash: Option<Vec<u8>>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Bytea>)]
+    pub inline_datum: Option<Vec<u8>>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Bytea>)]
+    pub script_hash: Option<Vec<u8>>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<crate::dbsync::schema::sql_types::Scripttype>)]
+    pub script_type: Option<Scripttype>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<Jsonb>)]
+    pub script_json: Option<serde_json::Value>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Bytea>)]
+    pub script_bytes: Option<Vec<u8>>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Bytea>)]
+    pub policy: Option<Vec<u8>>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Bytea>)]
+    pub name: Option<Vec<u8>>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<Numeric>)]
+    pub quantity: Option<BigDecimal>,
+}
+
+impl UtxoByRefQuery {
+    /// `assets` are collected from all rows of the output
+    pub fn to_txuo(
+        &self,
+        assets: &cardano_serialization_lib::MultiAsset,
+    ) -> Result<dcslc::TransactionUnspentOutput, DataProviderDBSyncError> {
+        let input = cardano_serialization_lib::TransactionInput::new(
+            &cardano_serialization_lib::crypto::TransactionHash::from_bytes(self.hash.clone())?,
+            self.index as u32,
+        );
+        let mut amount = cardano_serialization_lib::utils::Value::new(
+            &cardano_serialization_lib::utils::to_bignum(api::to_u64(&self.value)?),
+        );
+        if assets.len() > 0 {
+            amount.set_multiasset(assets)
+        }
+
+        let address = dcslc::addr_from_str(&self.address)?;
+        let mut output = cardano_serialization_lib::TransactionOutput::new(&address, &amount);
+        if let Some(datum) = &self.inline_datum {
+            output.set_plutus_data(&cardano_serialization_lib::plutus::PlutusData::from_bytes(
+                datum.clone(),
+            )?);
+        } else if let Some(hash) = &self.data_hash {
+            output.set_data_hash(&DataHash::from_bytes(hash.clone())?);
+        }
+        if let (Some(hash), Some(type_)) = (&self.script_hash, &self.script_type) {
+            output.set_script_ref(&script_ref(
+                hash,
+                type_,
+                self.script_json.as_ref(),
+                self.script_bytes.as_ref(),
+            )?);
+        }
+
+        Ok(cardano_serialization_lib::utils::TransactionUnspentOutput::new(&input, &output))
+    }
+}
@@ -1,21 +1,33 @@
 use tonic::{transport::Server, Request, Response, Status};
 
-use crate::dbsync::{epoch_change, get_tx_slot};
+pub(crate) mod auth;
+pub mod chain_query;
+mod events;
+mod staking;
+
+use crate::config::StakeDatumLayout;
+use crate::datum::AYA_REGISTRATION;
+use crate::models::TxHash;
+use crate::provider::error::DataProviderError;
 use crate::provider::CardanoDataProvider;
 use aya_cardano::chain_follower_request_service_server::{
     ChainFollowerRequestService, ChainFollowerRequestServiceServer,
 };
 use aya_cardano::{
     event_response::Message, CurrentEpochResponse, EpochChangeResponse as ProtoEpochChangeResponse,
-    EpochRequest, EpochRequestType, EventResponse, EventResponseType, StakeRequest,
-    StakeRequestType, StateResponse, StateResponseType, ValidatorRequest, ValidatorRequestType,
+    EpochRequest, EpochRequestType, EventResponse, EventResponseType, OutputRef,
+    StakeEventResponse, StakeRequest, StakeRequestType, StakeStatus, StateResponse,
+    StateResponseType, SubmitOutcome, SubmitTxRequest, SubmitTxResponse, SubscribeRequest,
+    UtxosByRefsRequest, UtxosByRefsResponse, ValidatorRequest, ValidatorRequestType,
 };
-use cardano_serialization_lib::crypto::Ed25519KeyHash;
-use cardano_serialization_lib::AssetName;
+use staking::StakePosition;
+use std::collections::HashMap;
 pub mod aya_cardano {
     include!("proto/aya_cardano.rs");
     //tonic::include_proto!("aya_cardano"); // The string specified here must match the proto package name
 }
+/// Encoded descriptors of the cdp protos, served by gRPC reflection
+pub const FILE_DESCRIPTOR_SET: &[u8] = include_bytes!("proto/cdp_descriptor.bin");
 use base64::{
     alphabet,
     engine::{self, general_purpose},
@@ -24,6 +36,24 @@ use base64::{
 #[derive(Debug, Default)]
 pub struct AyaCardanoRPCServer {}
 
+/// gRPC status of a provider error
+pub(crate) fn status(err: impl Into<DataProviderError>) -> Status {
+    let err = err.into();
+    let message = err.to_string();
+    match err {
+        DataProviderError::NotFound(_) => Status::not_found(message),
+        DataProviderError::InvalidInput(_)
+        | DataProviderError::WrongNetwork { .. }
+        | DataProviderError::InvalidCursor(_)
+        | DataProviderError::HexDecoderError(_) => Status::invalid_argument(message),
+        DataProviderError::Unsupported(_) => Status::unimplemented(message),
+        DataProviderError::RateLimited(_) => Status::resource_exhausted(message),
+        DataProviderError::Timeout(_) => Status::deadline_exceeded(message),
+        DataProviderError::BackendUnavailable(_) => Status::unavailable(message),
+        _ => Status::internal(message),
+    }
+}
+
 #[tonic::async_trait]
 impl ChainFollowerRequestService for AyaCardanoRPCServer {
     async fn epoch_change_event(
@@ -32,39 +62,23 @@ impl ChainFollowerRequestService for AyaCardanoRPCServer {
     ) -> Result<Response<EventResponse>, Status> {
         println!("Got a request: {request:?}");
 
-        let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-            db_path: std::env::var("DBSYNC_URL").unwrap(),
-        }));
+        let dp = crate::server::dbsync().map_err(status)?;
 
         //select * from block b where b.block_no = (select min(block_no-1) from block b where b.epoch_no = 209) or b.block_no = (select max(block_no) from block b where b.epoch_no = 209) order by block_no DESC;
         let rtype = request.into_inner();
         let output = match rtype.r#type() {
             EpochRequestType::LatestEpochChange => {
-                let current_epoch = dp.current_epoch().await.unwrap();
+                let current_epoch = dp.current_epoch().await.map_err(status)?;
                 println!("Current epoch: {current_epoch}");
-                let resp = epoch_change(dp.provider(), Some(current_epoch))
+                let resp = dp
+                    .provider()
+                    .epoch_change(Some(current_epoch))
                     .await
-                    .unwrap();
-                EventResponse {
-                    message_type: EventResponseType::EpochChangeEvent.into(),
-                    message: Some(Message::EpochChange(ProtoEpochChangeResponse {
-                        last_epoch: resp.last_epoch,
-                        last_blockhash: resp.last_blockhash,
-                        last_slot: resp.last_slot,
-                        new_epoch: resp.new_epoch,
-                        new_slot: resp.new_slot,
-                        new_blockhash: resp.new_blockhash,
-                        epoch_nonce: resp.epoch_nonce,
-                        extra_entropy: if let Some(s) = resp.extra_entropy {
-                            s
-                        } else {
-                            "".to_string()
-                        },
-                    })),
-                }
+                    .map_err(status)?;
+                epoch_change_response(resp)
             }
             EpochRequestType::CurrentEpoch => {
-                let resp = dp.current_epoch().await.unwrap();
+                let resp = dp.current_epoch().await.map_err(status)?;
                 EventResponse {
                     message_type: EventResponseType::EpochChangeEvent.into(),
                     message: Some(Message::CurrentEpoch(CurrentEpochResponse {
@@ -73,26 +87,12 @@ impl ChainFollowerRequestService for AyaCardanoRPCServer {
                 }
             }
             EpochRequestType::SpecificEpochChange => {
-                let resp = epoch_change(dp.provider(), Some(rtype.epoch))
+                let resp = dp
+                    .provider()
+                    .epoch_change(Some(rtype.epoch))
                     .await
-                    .unwrap();
-                EventResponse {
-                    message_type: EventResponseType::EpochChangeEvent.into(),
-                    message: Some(Message::EpochChange(ProtoEpochChangeResponse {
-                        last_epoch: resp.last_epoch,
-                        last_blockhash: resp.last_blockhash,
-                        last_slot: resp.last_slot,
-                        new_epoch: resp.new_epoch,
-                        new_slot: resp.new_slot,
-                        new_blockhash: resp.new_blockhash,
-                        epoch_nonce: resp.epoch_nonce,
-                        extra_entropy: if let Some(s) = resp.extra_entropy {
-                            s
-                        } else {
-                            "".to_string()
-                        },
-                    })),
-                }
+                    .map_err(status)?;
+                epoch_change_response(resp)
             }
         };
         println!("Output: {output:?}");
@@ -104,12 +104,32 @@ impl ChainFollowerRequestService for AyaCardanoRPCServer {
         request: Request<ValidatorRequest>,
     ) -> Result<Response<StateResponse>, Status> {
         println!("Got a request: {request:?}");
+        let vr = request.into_inner();
+        let dp = crate::server::provider();
+        let mut reply = tip_state(StateResponseType::NoStateFound).await?;
 
-        let reply = StateResponse {
-            r#type: StateResponseType::ValidatorStatusState.into(),
-        };
+        for d in find_registration_event(&vr.txhash).await? {
+            if let Ok(datum) = restore_wmreg_datum(&d.bytes) {
+                // the validator is registered as long as its registration output is unspent
+                let utxos = dp.script_utxos(&d.address).await.map_err(status)?;
+                reply.r#type = StateResponseType::ValidatorStatusState.into();
+                reply.validator = datum.into_inner().operator_address;
+                reply.utxos = utxos
+                    .into_iter()
+                    .filter(|u| u.input().transaction_id().to_hex() == vr.txhash)
+                    .map(|u| output_ref(&u))
+                    .collect();
+                reply.status = if reply.utxos.is_empty() {
+                    StakeStatus::Unregistered
+                } else {
+                    StakeStatus::Registered
+                }
+                .into();
+                break;
+            }
+        }
 
-        Ok(Response::new(reply)) // Send back our formatted greeting
+        Ok(Response::new(reply))
     }
 
     async fn aya_validator_stake(
@@ -117,12 +137,21 @@ impl ChainFollowerRequestService for AyaCardanoRPCServer {
         request: Request<StakeRequest>,
     ) -> Result<Response<StateResponse>, Status> {
         println!("Got a request: {request:?}");
-
-        let reply = StateResponse {
-            r#type: StateResponseType::ValidatorStakeState.into(),
-        };
-
-        Ok(Response::new(reply)) // Send back our formatted greeting
+        let sr = request.into_inner();
+        if sr.validator.is_empty() {
+            return Err(Status::invalid_argument("validator is required"));
+        }
+        let delegator = delegator(&sr)?;
+        let positions = staking::select(
+            contract_positions().await?,
+            false,
+            delegator.as_deref(),
+            Some(&sr.validator),
+        );
+
+        Ok(Response::new(
+            stake_state(StateResponseType::ValidatorStakeState, &sr, &positions).await?,
+        ))
     }
 
     async fn aya_validator_registration_event(
@@ -131,32 +160,21 @@ impl ChainFollowerRequestService for AyaCardanoRPCServer {
     ) -> Result<Response<EventResponse>, Status> {
         println!("Got a request: {request:?}");
         let vr = request.into_inner();
-        let datums = find_registration_event(&vr.txhash).await;
+        let datums = find_registration_event(&vr.txhash).await?;
 
         for d in datums {
             println!("Datum: {d:?}");
             if let Ok(datum) = restore_wmreg_datum(&d.bytes) {
                 let reg_datum: aya_cardano::RegistrationDatum = datum.into_inner();
                 println!("Registration Datum: {:?}", reg_datum);
-                let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-                    db_path: std::env::var("DBSYNC_URL").unwrap(),
-                }));
-                let reply = EventResponse {
-                    message_type: EventResponseType::ValidatorRegistrationEvent.into(),
-                    message: Some(Message::ValidatorRegistration(
-                        aya_cardano::ValidatorRegistrationResponse {
-                            tx_hash: vr.txhash.clone(),
-                            slot: get_tx_slot(dp.provider(), &vr.txhash).unwrap() as u64,
-                            operator_address: reg_datum.operator_address,
-                            consensus_pub_key: reg_datum.consensus_pub_key,
-                            merkle_tree_root: reg_datum.merkle_tree_root,
-                            cce_address: reg_datum.cce_address,
-                            en_nft_name: reg_datum.en_nft_name,
-                            en_owner: reg_datum.en_owner,
-                            signature: reg_datum.signature,
-                        },
-                    )),
-                };
+                let dp = crate::server::dbsync().map_err(status)?;
+                let slot = dp.provider().tx_slot(&vr.txhash).await.map_err(status)? as u64;
+                let reply = registration_event(
+                    EventResponseType::ValidatorRegistrationEvent,
+                    vr.txhash.clone(),
+                    slot,
+                    reg_datum,
+                );
 
                 return Ok(Response::new(reply)); // Send back our formatted greeting
             }
@@ -178,29 +196,18 @@ impl ChainFollowerRequestService for AyaCardanoRPCServer {
     ) -> Result<Response<EventResponse>, Status> {
         println!("Got a request: {request:?}");
         let vr = request.into_inner();
-        let datums = find_registration_event(&vr.txhash).await;
+        let datums = find_registration_event(&vr.txhash).await?;
         for d in datums {
             if let Ok(datum) = restore_wmreg_datum(&d.bytes) {
                 let reg_datum: aya_cardano::RegistrationDatum = datum.into_inner();
-                let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-                    db_path: std::env::var("DBSYNC_URL").unwrap(),
-                }));
-                let reply = EventResponse {
-                    message_type: EventResponseType::ValidatorUnregistrationEvent.into(),
-                    message: Some(Message::ValidatorRegistration(
-                        aya_cardano::ValidatorRegistrationResponse {
-                            tx_hash: vr.txhash.clone(),
-                            slot: get_tx_slot(dp.provider(), &vr.txhash).unwrap() as u64,
-                            operator_address: reg_datum.operator_address,
-                            consensus_pub_key: reg_datum.consensus_pub_key,
-                            merkle_tree_root: reg_datum.merkle_tree_root,
-                            cce_address: reg_datum.cce_address,
-                            en_nft_name: reg_datum.en_nft_name,
-                            en_owner: reg_datum.en_owner,
-                            signature: reg_datum.signature,
-                        },
-                    )),
-                };
+                let dp = crate::server::dbsync().map_err(status)?;
+                let slot = dp.provider().tx_slot(&vr.txhash).await.map_err(status)? as u64;
+                let reply = registration_event(
+                    EventResponseType::ValidatorUnregistrationEvent,
+                    vr.txhash.clone(),
+                    slot,
+                    reg_datum,
+                );
 
                 return Ok(Response::new(reply)); // Send back our formatted greeting
             }
@@ -220,13 +227,17 @@ impl ChainFollowerRequestService for AyaCardanoRPCServer {
         request: Request<StakeRequest>,
     ) -> Result<Response<EventResponse>, Status> {
         println!("Got a request: {request:?}");
-
-        let reply = EventResponse {
-            message_type: EventResponseType::DelegatorStakeEvent.into(),
-            message: Some(Message::String("DelegatorStakeEvent".to_string())),
-        };
-
-        Ok(Response::new(reply)) // Send back our formatted greeting
+        let sr = request.into_inner();
+        let positions = tx_positions(&sr, false).await?;
+
+        Ok(Response::new(
+            stake_event(
+                EventResponseType::DelegatorStakeEvent,
+                &sr,
+                staking::select(positions, false, None, None),
+            )
+            .await?,
+        ))
     }
 
     async fn deligator_staked(
@@ -234,12 +245,19 @@ impl ChainFollowerRequestService for AyaCardanoRPCServer {
         request: Request<StakeRequest>,
     ) -> Result<Response<StateResponse>, Status> {
         println!("Got a request: {request:?}");
-
-        let reply = StateResponse {
-            r#type: StateResponseType::DelegatorStakeState.into(),
-        };
-
-        Ok(Response::new(reply)) // Send back our formatted greeting
+        let sr = request.into_inner();
+        let delegator =
+            delegator(&sr)?.ok_or_else(|| Status::invalid_argument("stake_address is required"))?;
+        let positions = staking::select(
+            contract_positions().await?,
+            false,
+            Some(delegator.as_slice()),
+            validator(&sr),
+        );
+
+        Ok(Response::new(
+            stake_state(StateResponseType::DelegatorStakeState, &sr, &positions).await?,
+        ))
     }
 
     async fn deligator_unstake_event(
@@ -247,13 +265,18 @@ impl ChainFollowerRequestService for AyaCardanoRPCServer {
         request: Request<StakeRequest>,
     ) -> Result<Response<EventResponse>, Status> {
         println!("Got a request: {request:?}");
-
-        let reply = EventResponse {
-            message_type: EventResponseType::DelegatorUnstakeEvent.into(),
-            message: Some(Message::String("DelegatorUnstakeEvent".to_string())),
-        };
-
-        Ok(Response::new(reply)) // Send back our formatted greeting
+        let sr = request.into_inner();
+        // unstaking spends the staked position
+        let positions = tx_positions(&sr, true).await?;
+
+        Ok(Response::new(
+            stake_event(
+                EventResponseType::DelegatorUnstakeEvent,
+                &sr,
+                staking::select(positions, false, None, None),
+            )
+            .await?,
+        ))
     }
 
     async fn deligator_unbonding(
@@ -261,12 +284,19 @@ impl ChainFollowerRequestService for AyaCardanoRPCServer {
         request: Request<StakeRequest>,
     ) -> Result<Response<StateResponse>, Status> {
         println!("Got a request: {request:?}");
-
-        let reply = StateResponse {
-            r#type: StateResponseType::DelegatorUnbondingState.into(),
-        };
-
-        Ok(Response::new(reply)) // Send back our formatted greeting
+        let sr = request.into_inner();
+        let delegator =
+            delegator(&sr)?.ok_or_else(|| Status::invalid_argument("stake_address is required"))?;
+        let positions = staking::select(
+            contract_positions().await?,
+            true,
+            Some(delegator.as_slice()),
+            validator(&sr),
+        );
+
+        Ok(Response::new(
+            stake_state(StateResponseType::DelegatorUnbondingState, &sr, &positions).await?,
+        ))
     }
 
     async fn deligator_unbonding_event(
@@ -274,128 +304,339 @@ impl ChainFollowerRequestService for AyaCardanoRPCServer {
         request: Request<StakeRequest>,
     ) -> Result<Response<EventResponse>, Status> {
         println!("Got a request: {request:?}");
+        let sr = request.into_inner();
+        // the unbonding position is created by the unstake transaction
+        let positions = tx_positions(&sr, false).await?;
+
+        Ok(Response::new(
+            stake_event(
+                EventResponseType::DelegatorUnbondingEvent,
+                &sr,
+                staking::select(positions, true, None, None),
+            )
+            .await?,
+        ))
+    }
 
-        let reply = EventResponse {
-            message_type: EventResponseType::DelegatorUnbondingEvent.into(),
-            message: Some(Message::String("DelegatorUnbondingEvent".to_string())),
-        };
+    async fn utxos_by_refs(
+        &self,
+        request: Request<UtxosByRefsRequest>,
+    ) -> Result<Response<UtxosByRefsResponse>, Status> {
+        let request = request.into_inner();
+        let refs = request
+            .refs
+            .into_iter()
+            .map(|r| {
+                TxHash::from_hex(&r.tx_hash)
+                    .map(|hash| (hash, r.index))
+                    .map_err(|_| Status::invalid_argument(format!("invalid tx hash {}", r.tx_hash)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let dp = crate::server::provider();
+        let mut resolved = dp.utxos_by_refs(&refs).await.map_err(status)?;
+        if request.pending {
+            let pending = crate::server::tx_cache::pending(&crate::server::tx_cache()).await;
+            resolved = crate::server::tx_cache::apply_pending_refs(&pending, &refs, resolved);
+        }
 
-        Ok(Response::new(reply)) // Send back our formatted greeting
+        Ok(Response::new(UtxosByRefsResponse {
+            found: resolved
+                .found
+                .iter()
+                .map(|utxo| hex::encode(utxo.to_bytes()))
+                .collect(),
+            missing: resolved
+                .missing
+                .iter()
+                .map(|(hash, index)| OutputRef {
+                    tx_hash: hash.to_hex(),
+                    index: *index,
+                })
+                .collect(),
+        }))
+    }
+
+    async fn submit_tx(
+        &self,
+        request: Request<SubmitTxRequest>,
+    ) -> Result<Response<SubmitTxResponse>, Status> {
+        let result = crate::server::submit::submit_tx(&request.into_inner().cbor)
+            .await
+            .map_err(status)?;
+
+        Ok(Response::new(SubmitTxResponse {
+            tx_hash: result.tx_hash,
+            accepted: result.accepted,
+            outcomes: result
+                .outcomes
+                .into_iter()
+                .map(|o| SubmitOutcome {
+                    endpoint: o.endpoint,
+                    accepted: o.accepted,
+                    status: o.status.unwrap_or_default() as u32,
+                    message: o.message,
+                })
+                .collect(),
+        }))
+    }
+
+    type SubscribeEventsStream = events::EventStream;
+
+    async fn subscribe_events(
+        &self,
+        request: Request<SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeEventsStream>, Status> {
+        println!("Got a request: {request:?}");
+        Ok(Response::new(
+            events::subscribe(request.into_inner()).await?,
+        ))
     }
 }
 
 fn restore_wmreg_datum(bytes: &[u8]) -> Result<Response<aya_cardano::RegistrationDatum>, Status> {
-    let datum = cardano_serialization_lib::plutus::PlutusData::from_bytes(bytes.to_vec())
-        .expect("Could not deserialize PlutusData");
-    log::debug!("Restored PlutusData: {:?}", datum);
-    let d_str = datum
-        .to_json(cardano_serialization_lib::plutus::PlutusDatumSchema::DetailedSchema)
-        .expect("Could not transform PlutusData to JSON");
-    log::debug!("Restored PlutusData Str: {:?}", d_str);
-    let d_svalue = serde_json::from_str::<serde_json::Value>(&d_str)
-        .expect("Could not transform PlutusDataJson to serde_json::Value");
-    log::debug!("Deserialized Datum: \n{:?}", &d_str);
-    let fields = d_svalue.get("fields").unwrap().as_array().unwrap();
-
-    let operator_address = hex::decode(
-        fields[0]
-            .as_object()
-            .unwrap()
-            .get("bytes")
-            .unwrap()
-            .as_str()
-            .unwrap(),
-    )
-    .unwrap();
-
-    let consensus_pub_key = hex::decode(
-        fields[1]
-            .as_object()
-            .unwrap()
-            .get("bytes")
-            .unwrap()
-            .as_str()
-            .unwrap(),
-    )
-    .unwrap();
-
-    let merkle_tree_root = hex::decode(
-        fields[2]
-            .as_object()
-            .unwrap()
-            .get("bytes")
-            .unwrap()
-            .as_str()
-            .unwrap(),
-    )
-    .unwrap();
-
-    let cce_address = hex::decode(
-        fields[3]
-            .as_object()
-            .unwrap()
-            .get("bytes")
-            .unwrap()
-            .as_str()
-            .unwrap(),
-    )
-    .unwrap();
-
-    let en_nft_name = AssetName::new(
-        hex::decode(
-            fields[4]
-                .as_object()
-                .unwrap()
-                .get("bytes")
-                .unwrap()
-                .as_str()
-                .unwrap(),
-        )
-        .unwrap(),
-    )
-    .unwrap();
-
-    let en_owner = Ed25519KeyHash::from_bytes(
-        hex::decode(
-            fields[5]
-                .as_object()
-                .unwrap()
-                .get("bytes")
-                .unwrap()
-                .as_str()
-                .unwrap(),
-        )
-        .unwrap(),
-    )
-    .unwrap();
-
-    let enSignature = hex::decode(
-        fields[6]
-            .as_object()
-            .unwrap()
-            .get("bytes")
-            .unwrap()
-            .as_str()
-            .unwrap(),
-    )
-    .unwrap();
-
-    Ok(Response::new(aya_cardano::RegistrationDatum {
-        operator_address: std::str::from_utf8(&operator_address).unwrap().to_owned(),
-        consensus_pub_key: std::str::from_utf8(&consensus_pub_key).unwrap().to_owned(),
-        merkle_tree_root: std::str::from_utf8(&merkle_tree_root).unwrap().to_owned(),
-        cce_address: std::str::from_utf8(&cce_address).unwrap().to_owned(),
-        en_nft_name: std::str::from_utf8(&en_nft_name.name()).unwrap().to_owned(),
-        en_owner: hex::encode(en_owner.to_bytes()),
-        signature: general_purpose::STANDARD.encode(enSignature),
-    }))
+    let decode = || -> Result<_, crate::datum::DatumError> {
+        let datum = AYA_REGISTRATION.decode_bytes(bytes)?;
+        log::debug!("Restored registration datum: {:?}", datum);
+        Ok(aya_cardano::RegistrationDatum {
+            operator_address: datum.utf8("operator_address")?.to_owned(),
+            consensus_pub_key: datum.utf8("consensus_pub_key")?.to_owned(),
+            merkle_tree_root: datum.utf8("merkle_tree_root")?.to_owned(),
+            cce_address: datum.utf8("cce_address")?.to_owned(),
+            en_nft_name: datum.utf8("en_nft_name")?.to_owned(),
+            en_owner: hex::encode(datum.bytes("en_owner")?),
+            signature: general_purpose::STANDARD.encode(datum.bytes("signature")?),
+        })
+    };
+    decode().map(Response::new).map_err(status)
+}
+
+fn epoch_change_response(resp: crate::dbsync::EpochChangeResponse) -> EventResponse {
+    EventResponse {
+        message_type: EventResponseType::EpochChangeEvent.into(),
+        message: Some(Message::EpochChange(ProtoEpochChangeResponse {
+            last_epoch: resp.last_epoch,
+            last_blockhash: resp.last_blockhash,
+            last_slot: resp.last_slot,
+            new_epoch: resp.new_epoch,
+            new_slot: resp.new_slot,
+            new_blockhash: resp.new_blockhash,
+            epoch_nonce: resp.epoch_nonce,
+            extra_entropy: resp.extra_entropy.unwrap_or_default(),
+        })),
+    }
+}
+
+fn registration_event(
+    message_type: EventResponseType,
+    tx_hash: String,
+    slot: u64,
+    reg_datum: aya_cardano::RegistrationDatum,
+) -> EventResponse {
+    EventResponse {
+        message_type: message_type.into(),
+        message: Some(Message::ValidatorRegistration(
+            aya_cardano::ValidatorRegistrationResponse {
+                tx_hash,
+                slot,
+                operator_address: reg_datum.operator_address,
+                consensus_pub_key: reg_datum.consensus_pub_key,
+                merkle_tree_root: reg_datum.merkle_tree_root,
+                cce_address: reg_datum.cce_address,
+                en_nft_name: reg_datum.en_nft_name,
+                en_owner: reg_datum.en_owner,
+                signature: reg_datum.signature,
+            },
+        )),
+    }
+}
+
+fn output_ref(utxo: &dcslc::TransactionUnspentOutput) -> OutputRef {
+    OutputRef {
+        tx_hash: utxo.input().transaction_id().to_hex(),
+        index: utxo.input().index(),
+    }
+}
+
+/// Stake key hash of the requested delegator, `None` if no stake address is given
+fn delegator(request: &StakeRequest) -> Result<Option<Vec<u8>>, Status> {
+    if request.stake_address.is_empty() {
+        return Ok(None);
+    }
+    crate::server::network()
+        .check_address(&request.stake_address)
+        .map_err(status)?;
+    staking::stake_key_hash(&request.stake_address)
+        .map(Some)
+        .map_err(status)
+}
+
+fn validator(request: &StakeRequest) -> Option<&str> {
+    Some(request.validator.as_str()).filter(|v| !v.is_empty())
+}
+
+fn staking_contract() -> Result<cardano_serialization_lib::address::Address, Status> {
+    let aya = crate::server::aya().map_err(status)?;
+    cardano_serialization_lib::address::Address::from_bech32(&aya.staking_address).map_err(|e| {
+        Status::internal(format!(
+            "invalid staking address {}: {e}",
+            aya.staking_address
+        ))
+    })
+}
+
+/// Layout of the datums at the staking contract
+fn stake_datum_layout() -> Result<StakeDatumLayout, Status> {
+    Ok(crate::server::aya().map_err(status)?.stake_datum)
+}
+
+/// Positions currently locked in the staking contract, datum hashes are resolved with the
+/// datums of the transactions which created the outputs
+async fn contract_positions() -> Result<Vec<StakePosition>, Status> {
+    let contract = staking_contract()?;
+    let layout = stake_datum_layout()?;
+    let dp = crate::server::provider();
+    let utxos = dp
+        .script_utxos(&contract.to_bech32(None).map_err(status)?)
+        .await
+        .map_err(status)?;
+    let mut datums = HashMap::new();
+    for tx_hash in staking::hashed_datum_txs(&utxos, &contract) {
+        datums.extend(
+            dp.find_datums_for_tx(&tx_hash)
+                .await
+                .map_err(status)?
+                .into_iter()
+                .map(|d| (d.hash, d.bytes)),
+        );
+    }
+    Ok(staking::positions(utxos, &contract, &datums, &layout))
+}
+
+/// Positions created (or with `spent` consumed) by the requested transaction, optionally only
+/// those of the requested delegator
+async fn tx_positions(request: &StakeRequest, spent: bool) -> Result<Vec<StakePosition>, Status> {
+    if request.tx_hash.is_empty() {
+        return Err(Status::invalid_argument("tx_hash is required"));
+    }
+    let contract = staking_contract()?;
+    let layout = stake_datum_layout()?;
+    let delegator = delegator(request)?;
+    let dp = crate::server::dbsync().map_err(status)?;
+    let datums: HashMap<Vec<u8>, Vec<u8>> = dp
+        .find_datums_for_tx(&hex::decode(&request.tx_hash).map_err(status)?)
+        .await
+        .unwrap_or_else(|e| {
+            log::debug!("no datums for {}: {e}", request.tx_hash);
+            Vec::new()
+        })
+        .into_iter()
+        .map(|d| (d.hash, d.bytes))
+        .collect();
+    let utxos = if spent {
+        dp.provider().tx_inputs(&request.tx_hash).await
+    } else {
+        dp.provider().tx_outputs(&request.tx_hash).await
+    }
+    .map_err(status)?;
+
+    Ok(staking::positions(utxos, &contract, &datums, &layout)
+        .into_iter()
+        .filter(|p| delegator.as_ref().map_or(true, |d| &p.datum.delegator == d))
+        .collect())
+}
+
+/// Empty state at the current tip
+async fn tip_state(r#type: StateResponseType) -> Result<StateResponse, Status> {
+    let dp = crate::server::provider();
+    Ok(StateResponse {
+        r#type: r#type.into(),
+        slot: dp.slot().await.map_err(status)? as u64,
+        epoch: dp.current_epoch().await.map_err(status)? as u64,
+        ..Default::default()
+    })
+}
+
+/// State of the selected `positions` at the current tip
+async fn stake_state(
+    r#type: StateResponseType,
+    request: &StakeRequest,
+    positions: &[StakePosition],
+) -> Result<StateResponse, Status> {
+    let mut state = tip_state(if positions.is_empty() {
+        StateResponseType::NoStateFound
+    } else {
+        r#type
+    })
+    .await?;
+    state.stake_address = request.stake_address.clone();
+    state.validator = validator(request)
+        .or_else(|| positions.first().map(|p| p.datum.validator.as_str()))
+        .unwrap_or_default()
+        .to_owned();
+    state.amount = positions.iter().map(StakePosition::amount).sum();
+    state.status = staking::status(positions, state.epoch).into();
+    state.release_epoch = positions
+        .iter()
+        .filter_map(|p| p.datum.release_epoch)
+        .max()
+        .unwrap_or_default();
+    state.utxos = positions.iter().map(|p| output_ref(&p.utxo)).collect();
+    Ok(state)
+}
+
+/// Event of the first delegator in `positions`, their positions in the transaction are summed up
+async fn stake_event(
+    message_type: EventResponseType,
+    request: &StakeRequest,
+    positions: Vec<StakePosition>,
+) -> Result<EventResponse, Status> {
+    let datum = match positions.first() {
+        Some(p) => p.datum.clone(),
+        None => {
+            return Ok(EventResponse {
+                message_type: message_type.into(),
+                message: Some(Message::String(format!(
+                    "Could not find a matching staking contract output in transaction {}",
+                    request.tx_hash
+                ))),
+            })
+        }
+    };
+    let positions: Vec<_> = positions
+        .into_iter()
+        .filter(|p| p.datum.delegator == datum.delegator && p.datum.validator == datum.validator)
+        .collect();
+
+    let dp = crate::server::dbsync().map_err(status)?;
+    let network = crate::server::network();
+    let slot = dp
+        .provider()
+        .tx_slot(&request.tx_hash)
+        .await
+        .map_err(status)? as u64;
+    Ok(EventResponse {
+        message_type: message_type.into(),
+        message: Some(Message::StakeEvent(StakeEventResponse {
+            tx_hash: request.tx_hash.clone(),
+            slot,
+            epoch: crate::time::slot_to_epoch(&network, slot).0,
+            stake_address: staking::stake_address(network.address_network_id, &datum.delegator)
+                .map_err(status)?,
+            validator: datum.validator,
+            amount: positions.iter().map(StakePosition::amount).sum(),
+            release_epoch: positions
+                .iter()
+                .filter_map(|p| p.datum.release_epoch)
+                .max()
+                .unwrap_or_default(),
+        })),
+    })
 }
 
-async fn find_registration_event(txhash: &str) -> Vec<crate::models::CDPDatum> {
-    let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-        db_path: std::env::var("DBSYNC_URL").unwrap(),
-    }));
-    dp.find_datums_for_tx(&hex::decode(txhash).unwrap())
+async fn find_registration_event(txhash: &str) -> Result<Vec<crate::models::CDPDatum>, Status> {
+    let dp = crate::server::provider();
+    dp.find_datums_for_tx(&hex::decode(txhash).map_err(status)?)
         .await
-        .unwrap()
+        .map_err(status)
 }
@@ -0,0 +1,365 @@
+//! gRPC mirror of [`CardanoDataProvider`]: the same queries the REST api answers, with typed
+//! responses. It works over any provider, the server runs it over the configured one.
+
+use super::status;
+use crate::models::{AssetHandle, TokenInfoView, TransactionView, TxHistoryListView, UTxOView};
+use crate::provider::CardanoDataProvider;
+use dcslc::TransactionUnspentOutputs;
+use proto::chain_query_server::ChainQuery;
+use proto::{
+    AddressRequest, Asset, HistoryAsset, PoolInfoRequest, PoolInfoResponse, StakeAddressRequest,
+    StakeHistoryEntry, StakeHistoryRequest, StakeHistoryResponse, TipRequest, TipResponse,
+    TokenInfoRequest, TokenInfoResponse, TransactionRequest, TransactionResponse, Utxo,
+    UtxosResponse, Withdrawal,
+};
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+
+pub mod proto {
+    include!("../proto/chain_query.rs");
+}
+
+pub struct ChainQueryRPCServer<P: CardanoDataProvider + ?Sized> {
+    provider: Arc<P>,
+}
+
+impl<P: CardanoDataProvider + ?Sized> ChainQueryRPCServer<P> {
+    pub fn new(provider: Arc<P>) -> Self {
+        Self { provider }
+    }
+}
+
+fn required<'a>(value: &'a str, name: &str) -> Result<&'a str, Status> {
+    if value.is_empty() {
+        Err(Status::invalid_argument(format!("{name} is required")))
+    } else {
+        Ok(value)
+    }
+}
+
+impl From<AssetHandle> for Asset {
+    fn from(asset: AssetHandle) -> Self {
+        Asset {
+            policy: asset.policy.unwrap_or_default(),
+            name: asset.tokenname.unwrap_or_default(),
+            fingerprint: asset.fingerprint.unwrap_or_default(),
+            quantity: asset.amount,
+        }
+    }
+}
+
+impl From<UTxOView> for Utxo {
+    fn from(utxo: UTxOView) -> Self {
+        Utxo {
+            tx_hash: utxo.hash,
+            index: utxo.index as u32,
+            address: utxo.address,
+            lovelace: utxo.amount.coin,
+            assets: utxo
+                .amount
+                .multiasset
+                .unwrap_or_default()
+                .into_iter()
+                .map(Asset::from)
+                .collect(),
+            plutus_data: utxo.plutus_data.unwrap_or_default(),
+            script_ref: utxo.script_ref.unwrap_or_default(),
+        }
+    }
+}
+
+fn utxos(views: Vec<UTxOView>) -> Vec<Utxo> {
+    views.into_iter().map(Utxo::from).collect()
+}
+
+fn utxos_response(utxos: TransactionUnspentOutputs) -> UtxosResponse {
+    UtxosResponse {
+        utxos: utxos
+            .into_iter()
+            .map(|utxo| UTxOView::from_txuo(&utxo).into())
+            .collect(),
+    }
+}
+
+impl From<TransactionView> for TransactionResponse {
+    fn from(tx: TransactionView) -> Self {
+        TransactionResponse {
+            tx_hash: tx.hash,
+            block_hash: tx.block,
+            slot: tx.slot.unwrap_or_default() as u64,
+            fee: tx.fee,
+            inputs: utxos(tx.inputs),
+            reference_inputs: utxos(tx.reference_inputs.unwrap_or_default()),
+            outputs: utxos(tx.outputs),
+            collateral_inputs: utxos(tx.collateral_tx_in.unwrap_or_default()),
+            collateral_outputs: utxos(tx.collateral_tx_out.unwrap_or_default()),
+            withdrawals: tx
+                .withdrawals
+                .unwrap_or_default()
+                .into_iter()
+                .map(|w| Withdrawal {
+                    stake_address: w.stake_address,
+                    amount: w.amount,
+                })
+                .collect(),
+            metadata_json: tx.metadata.map(|m| m.to_string()).unwrap_or_default(),
+        }
+    }
+}
+
+impl From<TokenInfoView> for TokenInfoResponse {
+    fn from(token: TokenInfoView) -> Self {
+        TokenInfoResponse {
+            fingerprint: token.fingerprint,
+            policy: token.policy,
+            name: token.tokenname,
+            quantity: token.quantity.unwrap_or_default(),
+            metadata_json: token.json.map(|j| j.to_string()).unwrap_or_default(),
+            mint_slot: token.mint_slot.unwrap_or_default() as u64,
+            mint_tx_hash: token.txhash.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<TxHistoryListView> for StakeHistoryEntry {
+    fn from(tx: TxHistoryListView) -> Self {
+        StakeHistoryEntry {
+            slot: tx.slot as u64,
+            assets: tx
+                .assets
+                .iter()
+                .map(|a| HistoryAsset {
+                    fingerprint: a.fingerprint().to_owned(),
+                    quantity: a.amount(),
+                })
+                .collect(),
+            tx_hash: tx.hash,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl<P: CardanoDataProvider + ?Sized + 'static> ChainQuery for ChainQueryRPCServer<P> {
+    async fn tip(&self, _request: Request<TipRequest>) -> Result<Response<TipResponse>, Status> {
+        let slot = self.provider.slot().await.map_err(status)?;
+        let epoch = self.provider.current_epoch().await.map_err(status)?;
+        Ok(Response::new(TipResponse {
+            slot: slot as u64,
+            epoch: epoch as u64,
+        }))
+    }
+
+    async fn utxos_by_address(
+        &self,
+        request: Request<AddressRequest>,
+    ) -> Result<Response<UtxosResponse>, Status> {
+        let request = request.into_inner();
+        let address = required(&request.address, "address")?;
+        let utxos = self.provider.script_utxos(address).await.map_err(status)?;
+        Ok(Response::new(utxos_response(utxos)))
+    }
+
+    async fn utxos_by_stake_address(
+        &self,
+        request: Request<StakeAddressRequest>,
+    ) -> Result<Response<UtxosResponse>, Status> {
+        let request = request.into_inner();
+        let stake_address = required(&request.stake_address, "stake_address")?;
+        let utxos = self
+            .provider
+            .wallet_utxos(stake_address)
+            .await
+            .map_err(status)?;
+        Ok(Response::new(utxos_response(utxos)))
+    }
+
+    async fn transaction(
+        &self,
+        request: Request<TransactionRequest>,
+    ) -> Result<Response<TransactionResponse>, Status> {
+        let request = request.into_inner();
+        let tx_hash = required(&request.tx_hash, "tx_hash")?;
+        let tx = self.provider.transaction(tx_hash).await.map_err(status)?;
+        Ok(Response::new(tx.into()))
+    }
+
+    async fn token_info(
+        &self,
+        request: Request<TokenInfoRequest>,
+    ) -> Result<Response<TokenInfoResponse>, Status> {
+        let request = request.into_inner();
+        let fingerprint = required(&request.fingerprint, "fingerprint")?;
+        let token = self
+            .provider
+            .token_info(fingerprint)
+            .await
+            .map_err(status)?;
+        Ok(Response::new(token.into()))
+    }
+
+    async fn pool_info(
+        &self,
+        request: Request<PoolInfoRequest>,
+    ) -> Result<Response<PoolInfoResponse>, Status> {
+        let request = request.into_inner();
+        let pool_id = required(&request.pool_id, "pool_id")?;
+        let epoch = match request.epoch {
+            0 => self.provider.current_epoch().await.map_err(status)?,
+            epoch => epoch,
+        };
+        let valid = self.provider.pool_valid(pool_id).await.map_err(status)?;
+        let total_stake = self
+            .provider
+            .pool_total_staked(pool_id, epoch)
+            .await
+            .map_err(status)?;
+        Ok(Response::new(PoolInfoResponse {
+            pool_id: pool_id.to_owned(),
+            valid,
+            epoch,
+            total_stake,
+        }))
+    }
+
+    async fn stake_history(
+        &self,
+        request: Request<StakeHistoryRequest>,
+    ) -> Result<Response<StakeHistoryResponse>, Status> {
+        let request = request.into_inner();
+        let stake_address = required(&request.stake_address, "stake_address")?;
+        let slot = Some(request.slot).filter(|slot| *slot > 0);
+        let history = self
+            .provider
+            .stake_address_history(stake_address, slot)
+            .await
+            .map_err(status)?;
+        Ok(Response::new(StakeHistoryResponse {
+            transactions: history.into_iter().map(StakeHistoryEntry::from).collect(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockfrost::{BlockfrostProvider, Config};
+    use cardano_serialization_lib as csl;
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    const ADDR: &str = "addr1vyqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qavsj8u";
+
+    fn server(mock: &MockServer) -> ChainQueryRPCServer<crate::DataProvider<BlockfrostProvider>> {
+        ChainQueryRPCServer::new(Arc::new(crate::DataProvider::new(BlockfrostProvider::new(
+            Config {
+                project_id: "mainnetTestProject".to_owned(),
+                network_address: mock.base_url(),
+                network: crate::config::ChainWellKnownInfo::mainnet(),
+            },
+        ))))
+    }
+
+    #[test]
+    fn test_utxo_from_view() {
+        let mut assets = csl::Assets::new();
+        assets.insert(
+            &csl::AssetName::new(b"token".to_vec()).unwrap(),
+            &csl::utils::to_bignum(42),
+        );
+        let mut multiasset = csl::MultiAsset::new();
+        multiasset.insert(
+            &csl::crypto::ScriptHash::from_bytes(vec![1; 28]).unwrap(),
+            &assets,
+        );
+        let mut value = csl::utils::Value::new(&csl::utils::to_bignum(1_500_000));
+        value.set_multiasset(&multiasset);
+        let output =
+            csl::TransactionOutput::new(&csl::address::Address::from_bech32(ADDR).unwrap(), &value);
+        let input = csl::TransactionInput::new(
+            &csl::crypto::TransactionHash::from_bytes(vec![2; 32]).unwrap(),
+            3,
+        );
+
+        let utxo = Utxo::from(UTxOView::from_txuo(&dcslc::TransactionUnspentOutput::new(
+            &input, &output,
+        )));
+        assert_eq!(utxo.tx_hash, hex::encode([2; 32]));
+        assert_eq!(utxo.index, 3);
+        assert_eq!(utxo.address, ADDR);
+        assert_eq!(utxo.lovelace, 1_500_000);
+        assert_eq!(utxo.assets.len(), 1);
+        assert_eq!(utxo.assets[0].policy, hex::encode([1; 28]));
+        assert_eq!(utxo.assets[0].name, hex::encode("token"));
+        assert!(utxo.assets[0].fingerprint.starts_with("asset1"));
+        assert_eq!(utxo.assets[0].quantity, 42);
+        assert!(utxo.plutus_data.is_empty());
+        assert!(utxo.script_ref.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tip() {
+        let mock = MockServer::start_async().await;
+        mock.mock_async(|when, then| {
+            when.method(GET).path("/blocks/latest");
+            then.status(200).json_body(json!({
+                "time": 1641338934,
+                "height": 15243593,
+                "hash": "4ea1ba291e8eef538635a53e59fddba7810d1679631cc3aed7c8e6c4091a516a",
+                "slot": 95654321,
+                "epoch": 412,
+                "epoch_slot": 12,
+                "slot_leader": "pool1pu5jlj4q9w9jlxeu370a3c9myx47md5j5m2str0naunn2qnikdy",
+                "size": 3,
+                "tx_count": 1,
+                "output": "128314491794",
+                "fees": "592661",
+                "block_vrf": "vrf_vk1wf2k6lhujezqcfe00l6zetxpnmh9n6mwhpmhm0dvfh3fxgmdnrfqkms8ty",
+                "op_cert": "da905277534faf75dae41732650568af545134ee08a3c0392dbefc8096ae177c",
+                "op_cert_counter": "18",
+                "previous_block": "43ebccb3ac72c7cebd0d9b755a4b08412c9f5dcb81b8a0ad1e3c197d29d47b05",
+                "next_block": null,
+                "confirmations": 0
+            }));
+        })
+        .await;
+        mock.mock_async(|when, then| {
+            when.method(GET).path("/epochs/latest");
+            then.status(200).json_body(json!({
+                "epoch": 412,
+                "start_time": 1603403091,
+                "end_time": 1603835086,
+                "first_block_time": 1603403092,
+                "last_block_time": 1603835084,
+                "block_count": 21298,
+                "tx_count": 17856,
+                "output": "7849943934049314",
+                "fees": "4203312194",
+                "active_stake": "784953934049314"
+            }));
+        })
+        .await;
+
+        let tip = server(&mock)
+            .tip(Request::new(TipRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(
+            tip,
+            TipResponse {
+                slot: 95654321,
+                epoch: 412
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_required_fields() {
+        let mock = MockServer::start_async().await;
+        let err = server(&mock)
+            .utxos_by_address(Request::new(AddressRequest::default()))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+    }
+}
@@ -0,0 +1,231 @@
+//! Server-streaming subscription to epoch changes and validator registrations. db-sync is
+//! polled about once per block and new events are sent in slot order.
+
+use super::aya_cardano::{
+    event_response::Message, EventResponse, EventResponseType, SubscribeRequest,
+};
+use super::{epoch_change_response, registration_event, restore_wmreg_datum, status};
+use crate::provider::error::DataProviderError;
+use crate::provider::CardanoDataProvider;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::Status;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// events buffered for a slow client before polling pauses
+const BUFFER: usize = 64;
+
+pub(crate) type EventStream = ReceiverStream<Result<EventResponse, Status>>;
+
+/// What a client subscribed to and how far it has been served
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cursor {
+    /// epoch changes before this slot are not sent
+    from_slot: u64,
+    /// next epoch whose change is sent, `None` if epoch changes are not subscribed
+    epoch: Option<u64>,
+    /// registration contract and the slot registrations are looked up from, `None` if
+    /// registrations are not subscribed
+    registrations: Option<(String, u64)>,
+}
+
+/// Starts streaming the requested events
+pub(crate) async fn subscribe(request: SubscribeRequest) -> Result<EventStream, Status> {
+    let cursor = cursor(&request).await?;
+    let (tx, rx) = mpsc::channel(BUFFER);
+    tokio::spawn(run(cursor, tx));
+    Ok(ReceiverStream::new(rx))
+}
+
+/// Whether epoch changes and registrations are subscribed to
+fn subscribed(types: &[i32]) -> Result<(bool, bool), Status> {
+    if types.is_empty() {
+        return Ok((true, true));
+    }
+    let (mut epoch_changes, mut registrations) = (false, false);
+    for t in types {
+        match EventResponseType::from_i32(*t) {
+            Some(EventResponseType::EpochChangeEvent) => epoch_changes = true,
+            Some(EventResponseType::ValidatorRegistrationEvent) => registrations = true,
+            _ => {
+                return Err(Status::invalid_argument(format!(
+                    "event type {t} can not be subscribed to"
+                )))
+            }
+        }
+    }
+    Ok((epoch_changes, registrations))
+}
+
+async fn cursor(request: &SubscribeRequest) -> Result<Cursor, Status> {
+    let (epoch_changes, registrations) = subscribed(&request.types)?;
+    let registration_address = if registrations {
+        let address = crate::server::aya()
+            .ok()
+            .and_then(|aya| aya.registration_address);
+        if address.is_none() && !request.types.is_empty() {
+            return Err(status(DataProviderError::Unsupported(
+                "registration events need aya.registration_address".to_string(),
+            )));
+        }
+        address
+    } else {
+        None
+    };
+
+    let dp = crate::server::dbsync().map_err(status)?;
+    let (from_slot, epoch) = if request.from_slot == 0 {
+        let tip = dp.slot().await.map_err(status)? as u64;
+        let current_epoch = dp.current_epoch().await.map_err(status)? as u64;
+        (tip + 1, current_epoch + 1)
+    } else {
+        let epoch = crate::time::slot_to_epoch(&crate::server::network(), request.from_slot).0;
+        (request.from_slot, epoch)
+    };
+
+    Ok(Cursor {
+        from_slot,
+        epoch: Some(epoch).filter(|_| epoch_changes),
+        registrations: registration_address.map(|address| (address, from_slot)),
+    })
+}
+
+async fn run(mut cursor: Cursor, tx: mpsc::Sender<Result<EventResponse, Status>>) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        if tx.is_closed() {
+            return;
+        }
+        let events = match poll(&mut cursor).await {
+            Ok(events) => events,
+            Err(e) => {
+                log::warn!("could not poll events: {e}");
+                continue;
+            }
+        };
+        for event in events {
+            if tx.send(Ok(event)).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+fn slot(event: &EventResponse) -> u64 {
+    match &event.message {
+        Some(Message::EpochChange(change)) => change.new_slot,
+        Some(Message::ValidatorRegistration(registration)) => registration.slot,
+        _ => 0,
+    }
+}
+
+/// Events which happened since the last poll, advances the cursor past them. On an error the
+/// cursor is left as it was, so the next poll looks up the same events again.
+async fn poll(cursor: &mut Cursor) -> Result<Vec<EventResponse>, DataProviderError> {
+    let dp = crate::server::dbsync()?;
+    let mut next_cursor = cursor.clone();
+    let mut events = Vec::new();
+
+    if let Some(next) = next_cursor.epoch.as_mut() {
+        let current = dp.current_epoch().await? as u64;
+        while *next <= current {
+            match dp.provider().epoch_change(Some(*next as i32)).await {
+                Ok(change) if change.new_slot < cursor.from_slot => {}
+                Ok(change) => events.push(epoch_change_response(change)),
+                // the nonce of a new epoch is ingested a bit after its first block
+                Err(e) if *next == current => {
+                    log::debug!("change to epoch {next} is not complete yet: {e}");
+                    break;
+                }
+                Err(e) => return Err(e),
+            }
+            *next += 1;
+        }
+    }
+
+    if let Some((address, from)) = next_cursor.registrations.as_mut() {
+        let txs = dp
+            .provider()
+            .txs_to_address_since(address, *from as i64)
+            .await?;
+        for (tx_hash, slot) in txs {
+            // blocks are ingested in slot order, nothing new appears before this slot
+            *from = (*from).max(slot as u64 + 1);
+            let hash = match hex::decode(&tx_hash) {
+                Ok(hash) => hash,
+                Err(e) => {
+                    log::warn!("skipping registration tx {tx_hash}: {e}");
+                    continue;
+                }
+            };
+            let datums = match dp.find_datums_for_tx(&hash).await {
+                Ok(datums) => datums,
+                Err(DataProviderError::NotFound(_)) => Vec::new(),
+                Err(e) => return Err(e),
+            };
+            match datums
+                .iter()
+                .filter(|d| d.address == *address)
+                .find_map(|d| restore_wmreg_datum(&d.bytes).ok())
+            {
+                Some(datum) => events.push(registration_event(
+                    EventResponseType::ValidatorRegistrationEvent,
+                    tx_hash,
+                    slot as u64,
+                    datum.into_inner(),
+                )),
+                None => log::debug!("{tx_hash} has no registration datum"),
+            }
+        }
+    }
+
+    *cursor = next_cursor;
+    events.sort_by_key(slot);
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscribed() {
+        assert_eq!(subscribed(&[]).unwrap(), (true, true));
+        assert_eq!(
+            subscribed(&[EventResponseType::EpochChangeEvent as i32]).unwrap(),
+            (true, false)
+        );
+        assert_eq!(
+            subscribed(&[EventResponseType::ValidatorRegistrationEvent as i32]).unwrap(),
+            (false, true)
+        );
+        assert!(subscribed(&[EventResponseType::DelegatorStakeEvent as i32]).is_err());
+        assert!(subscribed(&[42]).is_err());
+    }
+
+    #[test]
+    fn test_event_slot() {
+        let mut events = vec![
+            registration_event(
+                EventResponseType::ValidatorRegistrationEvent,
+                "aa".to_string(),
+                120,
+                Default::default(),
+            ),
+            epoch_change_response(crate::dbsync::EpochChangeResponse {
+                last_epoch: 1,
+                last_blockhash: String::new(),
+                last_slot: 99,
+                new_epoch: 2,
+                new_slot: 100,
+                new_blockhash: String::new(),
+                epoch_nonce: String::new(),
+                extra_entropy: None,
+            }),
+        ];
+        events.sort_by_key(slot);
+        assert_eq!(events.iter().map(slot).collect::<Vec<_>>(), vec![100, 120]);
+    }
+}
@@ -0,0 +1,428 @@
+//! Delegator positions in the Aya staking contract. Each output at the contract carries the
+//! datum (inline or by hash) of one position. By default it has the layout of [`AYA_STAKE`]:
+//!
+//! - `Constr 0 [delegator, validator]`: staked
+//! - `Constr 1 [delegator, validator, release_epoch]`: unstaked, locked until `release_epoch`
+//!
+//! `delegator` is the stake key hash of the delegator, `validator` the utf-8 operator address
+//! of the validator, the same encoding as in the registration datum. The constructors and field
+//! positions are configured with [`StakeDatumLayout`] in `[aya.stake_datum]`, outputs which do
+//! not match them are logged and skipped.
+
+use super::aya_cardano::StakeStatus;
+use crate::config::StakeDatumLayout;
+use crate::datum::{constr_fields, field_value, DatumError, DecodedDatum, FieldType, AYA_STAKE};
+use crate::provider::error::DataProviderError;
+use cardano_serialization_lib as csl;
+use csl::plutus::PlutusData;
+use dcslc::{TransactionUnspentOutput, TransactionUnspentOutputs};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct StakeDatum {
+    pub delegator: Vec<u8>,
+    pub validator: String,
+    /// `None` while the position is staked
+    pub release_epoch: Option<u64>,
+}
+
+impl StakeDatum {
+    pub(crate) fn from_plutus_data(
+        data: &PlutusData,
+        layout: &StakeDatumLayout,
+    ) -> Result<Self, DataProviderError> {
+        let schema = AYA_STAKE.name;
+        let (index, values) = constr_fields(data).ok_or(DatumError::NotConstr { schema })?;
+        let unstaked = match index {
+            i if i == layout.staked_constructor => false,
+            i if i == layout.unstaked_constructor => true,
+            index => return Err(DatumError::UnknownConstructor { schema, index }.into()),
+        };
+
+        let mut wanted = vec![
+            ("delegator", layout.delegator_field, FieldType::KeyHash),
+            ("validator", layout.validator_field, FieldType::Utf8),
+        ];
+        if unstaked {
+            wanted.push(("release_epoch", layout.release_epoch_field, FieldType::Int));
+        }
+        let mut fields = Vec::with_capacity(wanted.len());
+        for (field, position, field_type) in wanted {
+            let error = |reason: String| DatumError::Field {
+                schema,
+                field,
+                reason,
+            };
+            if position >= values.len() {
+                return Err(error("is missing".to_string()).into());
+            }
+            fields.push((
+                field,
+                field_value(&values.get(position), field_type).map_err(error)?,
+            ));
+        }
+        let datum = DecodedDatum {
+            schema,
+            constructor: AYA_STAKE.constructors[usize::from(unstaked)],
+            fields,
+        };
+
+        Ok(StakeDatum {
+            delegator: datum.bytes("delegator")?.to_vec(),
+            validator: datum.utf8("validator")?.to_owned(),
+            release_epoch: if unstaked {
+                Some(datum.uint("release_epoch")?)
+            } else {
+                None
+            },
+        })
+    }
+}
+
+/// An output at the staking contract and its decoded datum
+#[derive(Debug, Clone)]
+pub(crate) struct StakePosition {
+    pub utxo: TransactionUnspentOutput,
+    pub datum: StakeDatum,
+}
+
+impl StakePosition {
+    /// locked lovelace
+    pub fn amount(&self) -> u64 {
+        csl::utils::from_bignum(&self.utxo.output().amount().coin())
+    }
+}
+
+/// Positions among `utxos` which are at `contract`. Datum hashes are resolved with `datums`
+/// (hash to datum bytes), outputs without a stake datum of `layout` are skipped.
+pub(crate) fn positions(
+    utxos: TransactionUnspentOutputs,
+    contract: &csl::address::Address,
+    datums: &HashMap<Vec<u8>, Vec<u8>>,
+    layout: &StakeDatumLayout,
+) -> Vec<StakePosition> {
+    let mut out = Vec::new();
+    for utxo in utxos {
+        let output = utxo.output();
+        if output.address().to_bytes() != contract.to_bytes() {
+            continue;
+        }
+        let data = output.plutus_data().or_else(|| {
+            output
+                .data_hash()
+                .and_then(|hash| datums.get(&hash.to_bytes()))
+                .and_then(|bytes| PlutusData::from_bytes(bytes.clone()).ok())
+        });
+        match data.map(|data| StakeDatum::from_plutus_data(&data, layout)) {
+            Some(Ok(datum)) => out.push(StakePosition { utxo, datum }),
+            Some(Err(e)) => log::debug!(
+                "skipping output {}#{}: {e}",
+                utxo.input().transaction_id().to_hex(),
+                utxo.input().index()
+            ),
+            None => log::debug!(
+                "skipping output {}#{} without datum",
+                utxo.input().transaction_id().to_hex(),
+                utxo.input().index()
+            ),
+        }
+    }
+    out
+}
+
+/// Transactions which created outputs at `contract` that only carry a datum hash, their
+/// datums are needed to decode these positions
+pub(crate) fn hashed_datum_txs(
+    utxos: &TransactionUnspentOutputs,
+    contract: &csl::address::Address,
+) -> Vec<Vec<u8>> {
+    let mut txs = Vec::new();
+    for i in 0..utxos.len() {
+        let utxo = utxos.get(i);
+        let output = utxo.output();
+        let tx_hash = utxo.input().transaction_id().to_bytes();
+        if output.address().to_bytes() == contract.to_bytes()
+            && output.plutus_data().is_none()
+            && output.data_hash().is_some()
+            && !txs.contains(&tx_hash)
+        {
+            txs.push(tx_hash);
+        }
+    }
+    txs
+}
+
+/// Keeps the staked (`unbonding == false`) or unbonding positions, optionally only those of one
+/// delegator or validator
+pub(crate) fn select(
+    positions: Vec<StakePosition>,
+    unbonding: bool,
+    delegator: Option<&[u8]>,
+    validator: Option<&str>,
+) -> Vec<StakePosition> {
+    positions
+        .into_iter()
+        .filter(|p| p.datum.release_epoch.is_some() == unbonding)
+        .filter(|p| delegator.map_or(true, |d| p.datum.delegator == d))
+        .filter(|p| validator.map_or(true, |v| p.datum.validator == v))
+        .collect()
+}
+
+/// Status of the selected positions in `epoch`
+pub(crate) fn status(positions: &[StakePosition], epoch: u64) -> StakeStatus {
+    if positions.is_empty() {
+        StakeStatus::NotStaked
+    } else if positions.iter().any(|p| p.datum.release_epoch.is_none()) {
+        StakeStatus::Staked
+    } else if positions
+        .iter()
+        .any(|p| p.datum.release_epoch.unwrap_or_default() > epoch)
+    {
+        StakeStatus::Unbonding
+    } else {
+        StakeStatus::Unbonded
+    }
+}
+
+/// Stake key hash of a bech32 stake address
+pub(crate) fn stake_key_hash(stake_address: &str) -> Result<Vec<u8>, DataProviderError> {
+    csl::address::Address::from_bech32(stake_address)
+        .ok()
+        .and_then(|address| csl::address::RewardAddress::from_address(&address))
+        .and_then(|address| address.payment_cred().to_keyhash())
+        .map(|hash| hash.to_bytes())
+        .ok_or_else(|| {
+            DataProviderError::InvalidInput(format!("{stake_address} is not a stake key address"))
+        })
+}
+
+/// Bech32 stake address of a stake key hash
+pub(crate) fn stake_address(network_id: u8, key_hash: &[u8]) -> Result<String, DataProviderError> {
+    let key_hash = csl::crypto::Ed25519KeyHash::from_bytes(key_hash.to_vec())?;
+    Ok(csl::address::RewardAddress::new(
+        network_id,
+        &csl::address::StakeCredential::from_keyhash(&key_hash),
+    )
+    .to_address()
+    .to_bech32(None)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use csl::plutus::{ConstrPlutusData, PlutusList};
+
+    const CONTRACT: &str = "addr1vyqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qavsj8u";
+    const OTHER_ADDR: &str = "addr1vy3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygs44r503";
+    const STAKE_ADDR: &str = "stake_test1upjx2en8dp5k56mvd4hx7ur3wfehgatkwau8j7nm037hulcv8npk6";
+
+    fn datum(delegator: &[u8], validator: &str, release_epoch: Option<u64>) -> PlutusData {
+        let mut fields = PlutusList::new();
+        fields.add(&PlutusData::new_bytes(delegator.to_vec()));
+        fields.add(&PlutusData::new_bytes(validator.as_bytes().to_vec()));
+        if let Some(epoch) = release_epoch {
+            fields.add(&PlutusData::new_integer(
+                &csl::utils::BigInt::from_str(&epoch.to_string()).unwrap(),
+            ));
+        }
+        let alternative = if release_epoch.is_some() { 1 } else { 0 };
+        PlutusData::new_constr_plutus_data(&ConstrPlutusData::new(
+            &csl::utils::to_bignum(alternative),
+            &fields,
+        ))
+    }
+
+    fn output(addr: &str, coin: u64) -> csl::TransactionOutput {
+        csl::TransactionOutput::new(
+            &csl::address::Address::from_bech32(addr).unwrap(),
+            &csl::utils::Value::new(&csl::utils::to_bignum(coin)),
+        )
+    }
+
+    fn utxo(index: u32, output: &csl::TransactionOutput) -> TransactionUnspentOutput {
+        let tx = csl::crypto::TransactionHash::from_bytes(vec![1; 32]).unwrap();
+        TransactionUnspentOutput::new(&csl::TransactionInput::new(&tx, index), output)
+    }
+
+    #[test]
+    fn test_decode_stake_datum() {
+        let layout = StakeDatumLayout::default();
+        let staked =
+            StakeDatum::from_plutus_data(&datum(&[7; 28], "validator1", None), &layout).unwrap();
+        assert_eq!(
+            staked,
+            StakeDatum {
+                delegator: vec![7; 28],
+                validator: "validator1".to_string(),
+                release_epoch: None,
+            }
+        );
+        let unbonding =
+            StakeDatum::from_plutus_data(&datum(&[7; 28], "validator1", Some(412)), &layout)
+                .unwrap();
+        assert_eq!(unbonding.release_epoch, Some(412));
+
+        let unknown = PlutusData::new_constr_plutus_data(&ConstrPlutusData::new(
+            &csl::utils::to_bignum(2),
+            &PlutusList::new(),
+        ));
+        assert!(StakeDatum::from_plutus_data(&unknown, &layout).is_err());
+        assert!(StakeDatum::from_plutus_data(&PlutusData::new_bytes(vec![1]), &layout).is_err());
+    }
+
+    /// Datums as the plutus serialiser writes them on chain, with indefinite length field lists
+    #[test]
+    fn test_decode_stake_datum_cbor() {
+        let decode = |cbor: &str| {
+            let data = PlutusData::from_bytes(hex::decode(cbor).unwrap()).unwrap();
+            StakeDatum::from_plutus_data(&data, &StakeDatumLayout::default()).unwrap()
+        };
+        let staked = decode(
+            "d8799f581c07070707070707070707070707070707070707070707070707070707\
+             4a76616c696461746f7231ff",
+        );
+        assert_eq!(staked.delegator, vec![7; 28]);
+        assert_eq!(staked.validator, "validator1");
+        assert_eq!(staked.release_epoch, None);
+        let unstaked = decode(
+            "d87a9f581c07070707070707070707070707070707070707070707070707070707\
+             4a76616c696461746f723119019cff",
+        );
+        assert_eq!(unstaked.release_epoch, Some(412));
+    }
+
+    #[test]
+    fn test_decode_stake_datum_layout() {
+        // Constr 1 [validator, delegator] / Constr 2 [validator, delegator, release_epoch]
+        let layout = StakeDatumLayout {
+            staked_constructor: 1,
+            unstaked_constructor: 2,
+            delegator_field: 1,
+            validator_field: 0,
+            release_epoch_field: 2,
+        };
+        let constr = |index: u64, fields: Vec<PlutusData>| {
+            let mut list = PlutusList::new();
+            for field in &fields {
+                list.add(field);
+            }
+            PlutusData::new_constr_plutus_data(&ConstrPlutusData::new(
+                &csl::utils::to_bignum(index),
+                &list,
+            ))
+        };
+        let validator = PlutusData::new_bytes(b"validator1".to_vec());
+        let delegator = PlutusData::new_bytes(vec![7; 28]);
+        let epoch = PlutusData::new_integer(&csl::utils::BigInt::from_str("412").unwrap());
+
+        let staked = StakeDatum::from_plutus_data(
+            &constr(1, vec![validator.clone(), delegator.clone()]),
+            &layout,
+        )
+        .unwrap();
+        assert_eq!(staked.delegator, vec![7; 28]);
+        assert_eq!(staked.validator, "validator1");
+        assert_eq!(staked.release_epoch, None);
+        let unstaked = StakeDatum::from_plutus_data(
+            &constr(2, vec![validator.clone(), delegator.clone(), epoch]),
+            &layout,
+        )
+        .unwrap();
+        assert_eq!(unstaked.release_epoch, Some(412));
+
+        // the default layout is not accepted any more
+        assert!(
+            StakeDatum::from_plutus_data(&constr(0, vec![delegator, validator]), &layout).is_err()
+        );
+    }
+
+    #[test]
+    fn test_positions() {
+        let contract = csl::address::Address::from_bech32(CONTRACT).unwrap();
+        let mut utxos = TransactionUnspentOutputs::new();
+
+        let mut inline = output(CONTRACT, 10_000_000);
+        inline.set_plutus_data(&datum(&[7; 28], "validator1", None));
+        utxos.add(&utxo(0, &inline));
+
+        let by_hash = datum(&[8; 28], "validator1", Some(500));
+        let mut hashed = output(CONTRACT, 5_000_000);
+        hashed.set_data_hash(&csl::utils::hash_plutus_data(&by_hash));
+        utxos.add(&utxo(1, &hashed));
+
+        // no datum and another address
+        utxos.add(&utxo(2, &output(CONTRACT, 1_000_000)));
+        let mut elsewhere = output(OTHER_ADDR, 1_000_000);
+        elsewhere.set_plutus_data(&datum(&[7; 28], "validator1", None));
+        utxos.add(&utxo(3, &elsewhere));
+
+        let datums = HashMap::from([(
+            csl::utils::hash_plutus_data(&by_hash).to_bytes(),
+            by_hash.to_bytes(),
+        )]);
+        let found = positions(utxos, &contract, &datums, &StakeDatumLayout::default());
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].amount(), 10_000_000);
+        assert_eq!(found[1].datum.release_epoch, Some(500));
+
+        let staked = select(found.clone(), false, Some(&[7; 28]), None);
+        assert_eq!(staked.len(), 1);
+        assert_eq!(status(&staked, 400), StakeStatus::Staked);
+        let unbonding = select(found.clone(), true, None, Some("validator1"));
+        assert_eq!(status(&unbonding, 499), StakeStatus::Unbonding);
+        assert_eq!(status(&unbonding, 500), StakeStatus::Unbonded);
+        assert!(select(found, true, Some(&[7; 28]), None).is_empty());
+        assert_eq!(status(&[], 500), StakeStatus::NotStaked);
+    }
+
+    #[test]
+    fn test_hashed_datum_positions() {
+        let contract = csl::address::Address::from_bech32(CONTRACT).unwrap();
+        let by_hash = datum(&[8; 28], "validator1", None);
+        let utxos = || {
+            let mut utxos = TransactionUnspentOutputs::new();
+            let mut hashed = output(CONTRACT, 5_000_000);
+            hashed.set_data_hash(&csl::utils::hash_plutus_data(&by_hash));
+            utxos.add(&utxo(0, &hashed));
+            utxos.add(&utxo(1, &hashed));
+            let mut inline = output(CONTRACT, 10_000_000);
+            inline.set_plutus_data(&datum(&[7; 28], "validator1", None));
+            let other_tx = csl::crypto::TransactionHash::from_bytes(vec![2; 32]).unwrap();
+            utxos.add(&TransactionUnspentOutput::new(
+                &csl::TransactionInput::new(&other_tx, 0),
+                &inline,
+            ));
+            utxos
+        };
+        let layout = StakeDatumLayout::default();
+
+        assert_eq!(hashed_datum_txs(&utxos(), &contract), vec![vec![1; 32]]);
+        // without the datums of the creating transaction the hashed outputs are skipped
+        assert_eq!(
+            positions(utxos(), &contract, &HashMap::new(), &layout).len(),
+            1
+        );
+        let datums = HashMap::from([(
+            csl::utils::hash_plutus_data(&by_hash).to_bytes(),
+            by_hash.to_bytes(),
+        )]);
+        let found = positions(utxos(), &contract, &datums, &layout);
+        assert_eq!(found.len(), 3);
+        let staked = select(found, false, Some(&[8; 28]), None);
+        assert_eq!(
+            staked.iter().map(StakePosition::amount).sum::<u64>(),
+            10_000_000
+        );
+    }
+
+    #[test]
+    fn test_stake_address() {
+        let key_hash = stake_key_hash(STAKE_ADDR).unwrap();
+        assert_eq!(key_hash.len(), 28);
+        assert_eq!(stake_address(0, &key_hash).unwrap(), STAKE_ADDR);
+        assert!(matches!(
+            stake_key_hash(CONTRACT),
+            Err(DataProviderError::InvalidInput(_))
+        ));
+    }
+}
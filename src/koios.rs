@@ -3,6 +3,7 @@ use crate::models::{
     StakeDelegationView, StakeDeregistrationView, StakeRegistrationView, TokenInfoView,
     TxHistoryListView,
 };
+use crate::config::ChainWellKnownInfo;
 use crate::provider::error::DataProviderError;
 
 use self::error::DataProviderKoiosError;
@@ -11,68 +12,167 @@ pub mod api;
 pub mod error;
 pub mod models;
 use bigdecimal::BigDecimal;
+use reqwest::{Method, RequestBuilder};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
+/// Koios returns at most this many rows per request
+const KOIOS_PAGE_SIZE: usize = 1000;
+
+#[derive(Debug, Clone)]
 pub struct Config {
+    /// base url of the Koios REST API, e.g. `https://api.koios.rest/api/v1`
     pub url: String,
+    /// bearer token, requests are sent without authorization if empty
     pub api_token: String,
+    /// addresses passed to the provider are checked against this network
+    pub network: ChainWellKnownInfo,
 }
 
+#[derive(Debug, Clone)]
 pub struct KoiosProvider {
     config: Config,
+    client: reqwest::Client,
 }
 
 impl KoiosProvider {
     pub fn new(config: Config) -> Self {
-        KoiosProvider { config }
+        KoiosProvider {
+            config,
+            client: reqwest::Client::new(),
+        }
     }
 
-    fn connect(&self) -> Result<(), DataProviderKoiosError> {
-        Ok(())
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let url = format!(
+            "{}/{}",
+            self.config.url.trim_end_matches('/'),
+            path.trim_start_matches('/')
+        );
+        let request = self.client.request(method, url);
+        if self.config.api_token.is_empty() {
+            request
+        } else {
+            request.bearer_auth(&self.config.api_token)
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(request: RequestBuilder) -> Result<T, DataProviderKoiosError> {
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(DataProviderKoiosError::ResponseError {
+                status: status.as_u16(),
+                message: response.text().await.unwrap_or_default(),
+            });
+        }
+        Ok(response.json::<T>().await?)
+    }
+
+    pub(crate) async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<T, DataProviderKoiosError> {
+        Self::send(self.request(Method::GET, path).query(query)).await
+    }
+
+    pub(crate) async fn post<T: DeserializeOwned, B: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T, DataProviderKoiosError> {
+        Self::send(self.request(Method::POST, path).json(body)).await
     }
-}
 
-unsafe impl Send for KoiosProvider {}
-unsafe impl Sync for KoiosProvider {}
+    /// GET request following the offset until a page shorter than `KOIOS_PAGE_SIZE` is returned
+    pub(crate) async fn get_all<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<Vec<T>, DataProviderKoiosError> {
+        let mut out = Vec::<T>::new();
+        loop {
+            let page: Vec<T> = Self::send(
+                self.request(Method::GET, path)
+                    .query(query)
+                    .query(&[("offset", out.len()), ("limit", KOIOS_PAGE_SIZE)]),
+            )
+            .await?;
+            let len = page.len();
+            out.extend(page);
+            if len < KOIOS_PAGE_SIZE {
+                return Ok(out);
+            }
+        }
+    }
+
+    /// POST request following the offset until a page shorter than `KOIOS_PAGE_SIZE` is returned
+    pub(crate) async fn post_all<T: DeserializeOwned, B: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<Vec<T>, DataProviderKoiosError> {
+        let mut out = Vec::<T>::new();
+        loop {
+            let page: Vec<T> = Self::send(
+                self.request(Method::POST, path)
+                    .query(&[("offset", out.len()), ("limit", KOIOS_PAGE_SIZE)])
+                    .json(body),
+            )
+            .await?;
+            let len = page.len();
+            out.extend(page);
+            if len < KOIOS_PAGE_SIZE {
+                return Ok(out);
+            }
+        }
+    }
+}
 
 #[async_trait]
 impl super::provider::CardanoDataProvider for KoiosProvider {
     async fn alive(&self) -> bool {
-        self.connect().is_ok()
+        api::tip(self).await.is_ok()
     }
 
     async fn wallet_utxos(
         &self,
         stake_addr: &str,
     ) -> Result<dcslc::TransactionUnspentOutputs, DataProviderError> {
-        Ok(api::get_stake_address_utxos(self, stake_addr)?)
+        self.config.network.check_address(stake_addr)?;
+        Ok(api::get_stake_address_utxos(self, stake_addr).await?)
     }
 
     async fn script_utxos(
         &self,
         addr: &str,
     ) -> Result<dcslc::TransactionUnspentOutputs, DataProviderError> {
-        Ok(api::get_address_utxos(self, addr)?)
+        self.config.network.check_address(addr)?;
+        Ok(api::get_address_utxos(self, addr).await?)
     }
 
     async fn asset_utxos_on_addr(
         &self,
         addr: &str,
     ) -> Result<dcslc::TransactionUnspentOutputs, DataProviderError> {
-        Ok(api::asset_utxos_on_addr(self, addr)?)
+        self.config.network.check_address(addr)?;
+        Ok(api::asset_utxos_on_addr(self, addr).await?)
     }
 
     async fn mint_metadata(
         &self,
         fingerprint_in: &str,
     ) -> Result<TokenInfoView, DataProviderError> {
-        Ok(api::mint_metadata(self, fingerprint_in)?)
+        Ok(api::mint_metadata(self, fingerprint_in).await?)
     }
 
     async fn first_transaction_from_stake_addr(
         &self,
         stake_address_in: &str,
     ) -> Result<cardano_serialization_lib::address::Address, DataProviderError> {
-        let str_addr = api::select_addr_of_first_transaction(self, stake_address_in)?;
+        self.config.network.check_address(stake_address_in)?;
+        let str_addr = api::select_addr_of_first_transaction(self, stake_address_in).await?;
         Ok(dcslc::addr_from_str(&str_addr)?)
     }
 
@@ -82,7 +182,8 @@ impl super::provider::CardanoDataProvider for KoiosProvider {
         addr: &str,
         datumhash: &Vec<u8>,
     ) -> Result<dcslc::TransactionUnspentOutput, DataProviderError> {
-        let utxo = api::get_utxo_by_dataumhash(self, addr, datumhash)?;
+        self.config.network.check_address(addr)?;
+        let utxo = api::utxo_by_dataumhash(self, addr, datumhash).await?;
         Ok(utxo)
     }
 
@@ -92,7 +193,7 @@ impl super::provider::CardanoDataProvider for KoiosProvider {
         txhash: &Vec<u8>,
         index: i16,
     ) -> Result<dcslc::TransactionUnspentOutput, DataProviderError> {
-        let utxo = api::utxo_by_txid(self, txhash, index)?;
+        let utxo = api::utxo_by_txid(self, txhash, index).await?;
         Ok(utxo)
     }
 
@@ -101,21 +202,19 @@ impl super::provider::CardanoDataProvider for KoiosProvider {
         tx_id: i64,
         tx_index: i16,
     ) -> Result<Vec<CardanoNativeAssetView>, DataProviderError> {
-        Ok(api::get_utxo_tokens(self, tx_id, tx_index)?)
+        Ok(api::get_utxo_tokens(self, tx_id, tx_index).await?)
     }
 
     async fn active_pools(&self, page: usize) -> Result<Vec<PoolView>, DataProviderError> {
-        Err(DataProviderError::KoiosError(
-            DataProviderKoiosError::Custom("Not implemented".to_string()),
-        ))
+        Ok(api::active_pools(self, page).await?)
     }
 
     async fn find_datums_for_tx(&self, txid: &Vec<u8>) -> Result<Vec<CDPDatum>, DataProviderError> {
-        Ok(api::find_datums_for_tx(self, txid)?)
+        Ok(api::find_datums_for_tx(self, txid).await?)
     }
 
     async fn slot(&self) -> Result<i64, DataProviderError> {
-        Ok(api::slot(self)?)
+        Ok(api::slot(self).await?)
     }
 
     async fn stakers_on_pool(
@@ -123,7 +222,7 @@ impl super::provider::CardanoDataProvider for KoiosProvider {
         pool: &str,
         epoch: i32,
     ) -> Result<Vec<StakeDelegationView>, DataProviderError> {
-        Ok(api::stakers_on_pool(self, pool, epoch)?)
+        Ok(api::stakers_on_pool(self, pool, epoch).await?)
     }
 
     async fn deligations_per_pool_epoch_intervall(
@@ -132,20 +231,15 @@ impl super::provider::CardanoDataProvider for KoiosProvider {
         start_epoch: i64,
         end_epoch: i64,
     ) -> Result<Vec<DelegationView>, DataProviderError> {
-        Ok(api::deligations_per_pool_for_epochs(
-            self,
-            pool,
-            start_epoch,
-            end_epoch,
-        )?)
+        Ok(api::deligations_per_pool_for_epochs(self, pool, start_epoch, end_epoch).await?)
     }
 
     async fn pool_total_staked(&self, pool: &str, epoch: i32) -> Result<u64, DataProviderError> {
-        Ok(api::pool_total_stake(self, pool, epoch)?)
+        Ok(api::pool_total_stake(self, pool, epoch).await?)
     }
 
     async fn current_epoch(&self) -> Result<i32, DataProviderError> {
-        Ok(api::current_epoch(self)?)
+        Ok(api::current_epoch(self).await?)
     }
 
     async fn fingerprint(
@@ -153,32 +247,35 @@ impl super::provider::CardanoDataProvider for KoiosProvider {
         policy: &str,
         tokenname: &str,
     ) -> Result<String, DataProviderError> {
-        Ok(api::fingerprint(self, policy, tokenname)?)
+        Ok(api::fingerprint(self, policy, tokenname).await?)
     }
 
     async fn token_info(&self, fingerprint_in: &str) -> Result<TokenInfoView, DataProviderError> {
-        Ok(api::token_info(self, fingerprint_in)?)
+        Ok(api::token_info(self, fingerprint_in).await?)
     }
 
     async fn stake_registration(
         &self,
         stake_addr_in: &str,
     ) -> Result<Vec<StakeRegistrationView>, DataProviderError> {
-        Ok(api::stake_registration(self, stake_addr_in)?)
+        self.config.network.check_address(stake_addr_in)?;
+        Ok(api::stake_registration(self, stake_addr_in).await?)
     }
 
     async fn stake_deregistration(
         &self,
         stake_addr_in: &str,
     ) -> Result<Vec<StakeDeregistrationView>, DataProviderError> {
-        Ok(api::stake_deregistration(self, stake_addr_in)?)
+        self.config.network.check_address(stake_addr_in)?;
+        Ok(api::stake_deregistration(self, stake_addr_in).await?)
     }
 
     async fn check_stakeaddr_registered(
         &self,
         stake_addr_in: &str,
     ) -> Result<bool, DataProviderError> {
-        Ok(api::check_stakeaddr_registered(self, stake_addr_in)?)
+        self.config.network.check_address(stake_addr_in)?;
+        Ok(api::check_stakeaddr_registered(self, stake_addr_in).await?)
     }
 
     async fn lookup_token_holders(
@@ -186,26 +283,31 @@ impl super::provider::CardanoDataProvider for KoiosProvider {
         fingerprint_in: &str,
         min_amount: Option<&i64>,
     ) -> Result<Vec<HoldingWalletView>, DataProviderError> {
-        Ok(api::lookup_token_holders(self, fingerprint_in, min_amount)?)
+        Ok(api::lookup_token_holders(self, fingerprint_in, min_amount).await?)
     }
 
     async fn lookup_nft_token_holders(
         &self,
         policy: &str,
     ) -> Result<Vec<HoldingWalletView>, DataProviderError> {
-        Ok(api::lookup_nft_token_holders(self, policy)?)
+        Ok(api::lookup_nft_token_holders(self, policy).await?)
     }
 
     async fn pool_valid(&self, pool_id: &str) -> Result<bool, DataProviderError> {
-        Ok(api::pool_valid(self, pool_id)?)
+        Ok(api::pool_valid(self, pool_id).await?)
     }
 
     async fn txhash_spent(&self, txhash: &str) -> Result<bool, DataProviderError> {
-        Ok(api::txhash_spent(self, txhash)?)
+        Ok(api::txhash_spent(self, txhash).await?)
+    }
+
+    async fn tx_included(&self, tx_hash: &str) -> Result<bool, DataProviderError> {
+        Ok(api::tx_included(self, tx_hash).await?)
     }
 
     async fn addresses_exist(&self, address: &Vec<&str>) -> Result<Vec<bool>, DataProviderError> {
-        Ok(Vec::new())
+        self.config.network.check_addresses(address)?;
+        Ok(api::addresses_exist(self, address).await?)
     }
 
     async fn tx_history(
@@ -213,7 +315,8 @@ impl super::provider::CardanoDataProvider for KoiosProvider {
         addresses: &Vec<&str>,
         slot: Option<u64>,
     ) -> Result<Vec<TxHistoryListView>, DataProviderError> {
-        Ok(Vec::new())
+        self.config.network.check_addresses(addresses)?;
+        Ok(api::tx_history(self, addresses, slot).await?)
     }
 
     async fn retrieve_staked_amount(
@@ -221,13 +324,15 @@ impl super::provider::CardanoDataProvider for KoiosProvider {
         epoch: i32,
         stake_addr: &str,
     ) -> Result<BigDecimal, DataProviderError> {
-        Ok(api::retrieve_staked_amount(self, epoch, stake_addr)?)
+        self.config.network.check_address(stake_addr)?;
+        Ok(api::retrieve_staked_amount(self, epoch, stake_addr).await?)
     }
 
     async fn retrieve_generated_rewards(
         &self,
         stake_addr: &str,
     ) -> Result<Vec<RewardView>, DataProviderError> {
-        Ok(api::retrieve_generated_rewards(self, stake_addr)?)
+        self.config.network.check_address(stake_addr)?;
+        Ok(api::retrieve_generated_rewards(self, stake_addr).await?)
     }
 }
@@ -1,202 +1,900 @@
 use super::error::DataProviderKoiosError;
+use super::models::{
+    AccountAddresses, AccountHistory, AccountInfo, AccountRewards, AccountUpdates, AddressInfo,
+    AddressTx, AssetHolder, AssetInfo, AssetListItem, DatumInfo, PolicyAssetHolder, PoolDelegator,
+    PoolDelegatorHistory, PoolHistory, PoolInfo, PoolListItem, Tip, TxInfo, TxStatus, Utxo,
+};
 use super::KoiosProvider;
 use crate::models::{
-    CDPDatum, CardanoNativeAssetView, DelegationView, HoldingWalletView, StakeDelegationView,
-    StakeDeregistrationView, StakeRegistrationView, TokenInfoView, RewardView,
+    CDPDatum, CardanoNativeAssetView, DelegationView, HoldingWalletView, PoolView, RewardView,
+    StakeDelegationView, StakeDeregistrationView, StakeRegistrationView, TokenInfoView,
+    TxHistoryListView,
 };
 use bigdecimal::BigDecimal;
+use cardano_serialization_lib as csl;
+use serde_json::json;
+use std::str::FromStr;
+
+/// number of pools per page, same as the dbsync provider
+const POOLS_PER_PAGE: usize = 100;
+
+/// Koios accepts at most this many items in a list parameter
+const KOIOS_MAX_LIST_PARAMS: usize = 50;
+
+pub async fn tip(kp: &KoiosProvider) -> Result<Tip, DataProviderKoiosError> {
+    let tip: Vec<Tip> = kp.get("tip", &[]).await?;
+    tip.into_iter()
+        .next()
+        .ok_or_else(|| DataProviderKoiosError::RequestValueNotFound("tip".to_owned()))
+}
+
+/// Returns `true` if the payment part of a shelley address is a script
+fn addr_has_script(address: &str) -> bool {
+    match dcslc::addr_from_str(address) {
+        Ok(addr) => {
+            let header_type = addr.to_bytes()[0] >> 4;
+            header_type < 8 && header_type & 1 == 1
+        }
+        Err(_) => false,
+    }
+}
+
+fn utxos_to_txuos(utxos: &[Utxo]) -> Result<dcslc::TransactionUnspentOutputs, DataProviderKoiosError> {
+    let mut out = dcslc::TransactionUnspentOutputs::new();
+    for u in utxos.iter().filter(|u| !u.is_spent) {
+        out.add(&u.to_txuo()?);
+    }
+    Ok(out)
+}
+
+async fn address_utxos(kp: &KoiosProvider, addr: &str) -> Result<Vec<Utxo>, DataProviderKoiosError> {
+    kp.post_all(
+        "address_utxos",
+        &json!({ "_addresses": [addr], "_extended": true }),
+    )
+    .await
+}
+
+async fn asset_by_fingerprint(
+    kp: &KoiosProvider,
+    fingerprint: &str,
+) -> Result<AssetListItem, DataProviderKoiosError> {
+    let filter = format!("eq.{fingerprint}");
+    let assets: Vec<AssetListItem> = kp.get("asset_list", &[("fingerprint", &filter)]).await?;
+    assets
+        .into_iter()
+        .next()
+        .ok_or_else(|| DataProviderKoiosError::RequestValueNotFound(fingerprint.to_owned()))
+}
+
+async fn asset_info(
+    kp: &KoiosProvider,
+    asset: &AssetListItem,
+) -> Result<AssetInfo, DataProviderKoiosError> {
+    let infos: Vec<AssetInfo> = kp
+        .post(
+            "asset_info",
+            &json!({ "_asset_list": [[asset.policy_id, asset.asset_name.as_deref().unwrap_or_default()]] }),
+        )
+        .await?;
+    infos
+        .into_iter()
+        .next()
+        .ok_or_else(|| DataProviderKoiosError::RequestValueNotFound(asset.fingerprint.clone()))
+}
+
+async fn tx_infos(
+    kp: &KoiosProvider,
+    tx_hashes: &[String],
+) -> Result<Vec<TxInfo>, DataProviderKoiosError> {
+    let mut out = Vec::<TxInfo>::new();
+    for chunk in tx_hashes.chunks(KOIOS_MAX_LIST_PARAMS) {
+        let txs: Vec<TxInfo> = kp
+            .post(
+                "tx_info",
+                &json!({
+                    "_tx_hashes": chunk,
+                    "_inputs": false,
+                    "_metadata": false,
+                    "_assets": false,
+                    "_withdrawals": false,
+                    "_certs": false,
+                    "_scripts": false,
+                    "_bytecode": false,
+                }),
+            )
+            .await?;
+        out.extend(txs);
+    }
+    Ok(out)
+}
 
 /// get all tokens of an utxo
-pub fn get_utxo_tokens(
-    bfp: &KoiosProvider,
-    tx_id: i64,
-    tx_index: i16,
+pub async fn get_utxo_tokens(
+    _kp: &KoiosProvider,
+    _tx_id: i64,
+    _tx_index: i16,
 ) -> Result<Vec<CardanoNativeAssetView>, DataProviderKoiosError> {
-    todo!()
+    // tx_id is a dbsync internal id which is not exposed by koios
+    Err(DataProviderKoiosError::Unsupported(
+        "utxo_tokens requires dbsync transaction ids, use utxo_by_txid instead".to_owned(),
+    ))
 }
 
-pub fn select_addr_of_first_transaction(
-    bfp: &KoiosProvider,
+pub async fn select_addr_of_first_transaction(
+    kp: &KoiosProvider,
     stake_address_in: &str,
 ) -> Result<String, DataProviderKoiosError> {
-    Ok("".to_owned())
+    let accounts: Vec<AccountAddresses> = kp
+        .post(
+            "account_addresses",
+            &json!({ "_stake_addresses": [stake_address_in], "_first_only": true }),
+        )
+        .await?;
+    accounts
+        .into_iter()
+        .flat_map(|a| a.addresses)
+        .next()
+        .ok_or_else(|| DataProviderKoiosError::RequestValueNotFound(stake_address_in.to_owned()))
 }
 
-/// get all utxos of an address
-pub fn get_utxo_by_dataumhash(
-    bfp: &KoiosProvider,
+/// get the utxo with a certain datumhash on an address
+pub async fn utxo_by_dataumhash(
+    kp: &KoiosProvider,
     addr: &str,
     datumhash: &Vec<u8>,
 ) -> Result<dcslc::TransactionUnspentOutput, DataProviderKoiosError> {
-    Err(DataProviderKoiosError::Custom(
-        "not implemented".to_string(),
-    ))
+    let datumhash = hex::encode(datumhash);
+    let utxos = address_utxos(kp, addr).await?;
+    match utxos
+        .iter()
+        .find(|u| !u.is_spent && u.datum_hash.as_ref() == Some(&datumhash))
+    {
+        Some(u) => u.to_txuo(),
+        None => Err(DataProviderKoiosError::RequestValueNotFound(datumhash)),
+    }
 }
 
-/// get all utxos of an address
-pub fn utxo_by_txid(
-    bfp: &KoiosProvider,
+/// get utxo by hash and index
+pub async fn utxo_by_txid(
+    kp: &KoiosProvider,
     txhash: &Vec<u8>,
     index: i16,
 ) -> Result<dcslc::TransactionUnspentOutput, DataProviderKoiosError> {
-    Err(DataProviderKoiosError::Custom(
-        "not implemented".to_string(),
-    ))
+    let utxo_ref = format!("{}#{}", hex::encode(txhash), index);
+    let utxos: Vec<Utxo> = kp
+        .post(
+            "utxo_info",
+            &json!({ "_utxo_refs": [utxo_ref], "_extended": true }),
+        )
+        .await?;
+    match utxos.iter().find(|u| !u.is_spent) {
+        Some(u) => u.to_txuo(),
+        None => Err(DataProviderKoiosError::RequestValueNotFound(utxo_ref)),
+    }
 }
 
 /// get all utxos of an address
-pub fn get_address_utxos(
-    bfp: &KoiosProvider,
+pub async fn get_address_utxos(
+    kp: &KoiosProvider,
     addr: &str,
 ) -> Result<dcslc::TransactionUnspentOutputs, DataProviderKoiosError> {
-    Ok(dcslc::TransactionUnspentOutputs::new())
+    utxos_to_txuos(&address_utxos(kp, addr).await?)
 }
 
 /// Get all utxos of a stake address
-pub fn get_stake_address_utxos(
-    bfp: &KoiosProvider,
+pub async fn get_stake_address_utxos(
+    kp: &KoiosProvider,
     stake_addr: &str,
 ) -> Result<dcslc::TransactionUnspentOutputs, DataProviderKoiosError> {
-    Ok(dcslc::TransactionUnspentOutputs::new())
+    let utxos: Vec<Utxo> = kp
+        .post_all(
+            "account_utxos",
+            &json!({ "_stake_addresses": [stake_addr], "_extended": true }),
+        )
+        .await?;
+    utxos_to_txuos(&utxos)
 }
 
-/// Get all utxos of a stake address
-pub fn asset_utxos_on_addr(
-    bfp: &KoiosProvider,
+/// Get all utxos of an address holding native assets
+pub async fn asset_utxos_on_addr(
+    kp: &KoiosProvider,
     addr: &str,
 ) -> Result<dcslc::TransactionUnspentOutputs, DataProviderKoiosError> {
-    Ok(dcslc::TransactionUnspentOutputs::new())
+    let mut utxos = address_utxos(kp, addr).await?;
+    utxos.retain(|u| u.asset_list.as_ref().map(|a| !a.is_empty()).unwrap_or(false));
+    utxos_to_txuos(&utxos)
 }
 
-pub fn find_datums_for_tx(
-    bfp: &KoiosProvider,
+pub async fn find_datums_for_tx(
+    kp: &KoiosProvider,
     txid: &Vec<u8>,
-) -> Result<Vec<CDPDatum>, crate::provider::error::DataProviderError> {
-    todo!();
+) -> Result<Vec<CDPDatum>, DataProviderKoiosError> {
+    let txs = tx_infos(kp, &[hex::encode(txid)]).await?;
+    let outputs: Vec<_> = txs.into_iter().flat_map(|t| t.outputs).collect();
+
+    let mut out = Vec::<CDPDatum>::new();
+    let mut hashes = Vec::<(String, String)>::new();
+    for o in outputs {
+        match (o.inline_datum, o.datum_hash) {
+            (Some(inline), hash) => {
+                let bytes = hex::decode(&inline.bytes)?;
+                let hash = match hash {
+                    Some(h) => hex::decode(h)?,
+                    None => csl::utils::hash_plutus_data(
+                        &csl::plutus::PlutusData::from_bytes(bytes.clone())?,
+                    )
+                    .to_bytes(),
+                };
+                out.push(CDPDatum {
+                    hash,
+                    json: inline.value,
+                    bytes,
+                    addr_has_script: addr_has_script(&o.payment_addr.bech32),
+                    address: o.payment_addr.bech32,
+                });
+            }
+            (None, Some(hash)) => hashes.push((hash, o.payment_addr.bech32)),
+            (None, None) => (),
+        }
+    }
+
+    if !hashes.is_empty() {
+        let datums: Vec<DatumInfo> = kp
+            .post(
+                "datum_info",
+                &json!({ "_datum_hashes": hashes.iter().map(|h| &h.0).collect::<Vec<_>>() }),
+            )
+            .await?;
+        for (hash, address) in hashes {
+            if let Some(d) = datums.iter().find(|d| d.datum_hash == hash) {
+                out.push(CDPDatum {
+                    hash: hex::decode(&hash)?,
+                    json: d.value.clone(),
+                    bytes: hex::decode(&d.bytes)?,
+                    addr_has_script: addr_has_script(&address),
+                    address,
+                });
+            }
+        }
+    }
+
+    Ok(out)
 }
 
-pub fn slot(bfp: &KoiosProvider) -> Result<i64, DataProviderKoiosError> {
-    Ok(0)
+pub async fn slot(kp: &KoiosProvider) -> Result<i64, DataProviderKoiosError> {
+    Ok(tip(kp).await?.abs_slot)
+}
+
+/// Get paged list of active pools with details
+pub async fn active_pools(
+    kp: &KoiosProvider,
+    page: usize,
+) -> Result<Vec<PoolView>, DataProviderKoiosError> {
+    let offset = (page * POOLS_PER_PAGE).to_string();
+    let limit = POOLS_PER_PAGE.to_string();
+    let pools: Vec<PoolListItem> = kp
+        .get(
+            "pool_list",
+            &[
+                ("pool_status", "eq.registered"),
+                ("offset", &offset),
+                ("limit", &limit),
+            ],
+        )
+        .await?;
+
+    let mut out = Vec::<PoolView>::with_capacity(pools.len());
+    for chunk in pools.chunks(KOIOS_MAX_LIST_PARAMS) {
+        let infos: Vec<PoolInfo> = kp
+            .post(
+                "pool_info",
+                &json!({ "_pool_bech32_ids": chunk.iter().map(|p| &p.pool_id_bech32).collect::<Vec<_>>() }),
+            )
+            .await?;
+        out.extend(chunk.iter().map(|p| PoolView {
+            pool_hash: p.pool_id_bech32.clone(),
+            ticker: p.ticker.clone().unwrap_or_default(),
+            json: infos
+                .iter()
+                .find(|i| i.pool_id_bech32 == p.pool_id_bech32)
+                .and_then(|i| i.meta_json.clone())
+                .unwrap_or(serde_json::Value::Null),
+        }));
+    }
+    Ok(out)
 }
 
-pub fn stakers_on_pool(
-    bfp: &KoiosProvider,
+pub async fn stakers_on_pool(
+    kp: &KoiosProvider,
     pool: &str,
     epoch: i32,
 ) -> Result<Vec<StakeDelegationView>, DataProviderKoiosError> {
-    todo!();
+    let epoch = epoch.to_string();
+    let delegators: Vec<PoolDelegatorHistory> = kp
+        .get_all(
+            "pool_delegators_history",
+            &[("_pool_bech32", pool), ("_epoch_no", &epoch)],
+        )
+        .await?;
+    delegators
+        .into_iter()
+        .map(|d| {
+            Ok(StakeDelegationView {
+                stake_address: d.stake_address,
+                amount: BigDecimal::from_str(&d.amount)
+                    .map_err(|e| DataProviderKoiosError::Custom(e.to_string()))?,
+            })
+        })
+        .collect()
 }
 
-pub fn deligations_per_pool_for_epochs(
-    bfp: &KoiosProvider,
+pub async fn deligations_per_pool_for_epochs(
+    kp: &KoiosProvider,
     pool: &str,
     start_epoch: i64,
     end_epoch: i64,
 ) -> Result<Vec<DelegationView>, DataProviderKoiosError> {
-    todo!();
+    let delegators: Vec<PoolDelegator> = kp
+        .get_all("pool_delegators", &[("_pool_bech32", pool)])
+        .await?;
+    Ok(delegators
+        .into_iter()
+        .filter(|d| d.active_epoch_no >= start_epoch && d.active_epoch_no <= end_epoch)
+        .map(|d| DelegationView {
+            amount: d.amount.parse::<i64>().unwrap_or(0),
+            stake_address: d.stake_address,
+            // koios does not expose the certificate index
+            cert_index: 0,
+            active_epoch_no: d.active_epoch_no,
+        })
+        .collect())
 }
 
-pub fn pool_total_stake(
-    bfp: &KoiosProvider,
+pub async fn pool_total_stake(
+    kp: &KoiosProvider,
     pool: &str,
     epoch: i32,
 ) -> Result<u64, DataProviderKoiosError> {
-    Ok(0)
+    let epoch_str = epoch.to_string();
+    let history: Vec<PoolHistory> = kp
+        .get(
+            "pool_history",
+            &[("_pool_bech32", pool), ("_epoch_no", &epoch_str)],
+        )
+        .await?;
+    match history.iter().find(|h| h.epoch_no == epoch) {
+        Some(h) => Ok(h.active_stake.as_deref().unwrap_or("0").parse::<u64>()?),
+        None => Ok(0),
+    }
 }
 
-pub fn current_epoch(bfp: &KoiosProvider) -> Result<i32, DataProviderKoiosError> {
-    Ok(0)
+pub async fn current_epoch(kp: &KoiosProvider) -> Result<i32, DataProviderKoiosError> {
+    Ok(tip(kp).await?.epoch_no)
 }
 
-pub fn fingerprint(
-    bfp: &KoiosProvider,
+pub async fn fingerprint(
+    _kp: &KoiosProvider,
     policy: &str,
     tokenname: &str,
 ) -> Result<String, DataProviderKoiosError> {
-    Ok("".to_owned())
+    Ok(dcslc::make_fingerprint(policy, &hex::encode(tokenname))?)
 }
 
-pub fn token_info(
-    bfp: &KoiosProvider,
+pub async fn token_info(
+    kp: &KoiosProvider,
     fingerprint_in: &str,
 ) -> Result<TokenInfoView, DataProviderKoiosError> {
-    todo!();
+    let asset = asset_by_fingerprint(kp, fingerprint_in).await?;
+    Ok(TokenInfoView {
+        fingerprint: asset.fingerprint,
+        policy: asset.policy_id,
+        tokenname: asset.asset_name.unwrap_or_default(),
+        quantity: None,
+        meta_key: None,
+        json: None,
+        mint_slot: None,
+        txhash: None,
+    })
 }
 
-pub fn stake_registration(
-    bfp: &KoiosProvider,
+async fn account_updates(
+    kp: &KoiosProvider,
+    stake_addr_in: &str,
+    action_type: &str,
+) -> Result<Vec<(String, Vec<u8>, i32)>, DataProviderKoiosError> {
+    let accounts: Vec<AccountUpdates> = kp
+        .post(
+            "account_updates",
+            &json!({ "_stake_addresses": [stake_addr_in] }),
+        )
+        .await?;
+    let mut out = Vec::new();
+    for account in accounts {
+        for u in account.updates.iter().filter(|u| u.action_type == action_type) {
+            out.push((account.stake_address.clone(), hex::decode(&u.tx_hash)?, u.epoch_no));
+        }
+    }
+    out.sort_by(|a, b| b.2.cmp(&a.2));
+    Ok(out)
+}
+
+pub async fn stake_registration(
+    kp: &KoiosProvider,
     stake_addr_in: &str,
 ) -> Result<Vec<StakeRegistrationView>, DataProviderKoiosError> {
-    todo!();
+    Ok(account_updates(kp, stake_addr_in, "registration")
+        .await?
+        .into_iter()
+        .map(|(stake_address, tx_hash, epoch)| StakeRegistrationView {
+            stake_address,
+            tx_hash,
+            // koios does not expose the certificate index
+            cert_index: 0,
+            epoch,
+        })
+        .collect())
 }
 
-#[allow(clippy::type_complexity)]
-pub fn stake_deregistration(
-    bfp: &KoiosProvider,
+pub async fn stake_deregistration(
+    kp: &KoiosProvider,
     stake_addr_in: &str,
 ) -> Result<Vec<StakeDeregistrationView>, DataProviderKoiosError> {
-    todo!();
+    Ok(account_updates(kp, stake_addr_in, "deregistration")
+        .await?
+        .into_iter()
+        .map(|(stake_address, tx_hash, epoch)| StakeDeregistrationView {
+            stake_address,
+            tx_hash,
+            // koios does not expose the certificate index
+            cert_index: 0,
+            epoch,
+        })
+        .collect())
 }
 
-pub fn check_stakeaddr_registered(
-    bfp: &KoiosProvider,
+pub async fn check_stakeaddr_registered(
+    kp: &KoiosProvider,
     stake_addr_in: &str,
 ) -> Result<bool, DataProviderKoiosError> {
-    Ok(false)
+    let accounts: Vec<AccountInfo> = kp
+        .post(
+            "account_info",
+            &json!({ "_stake_addresses": [stake_addr_in] }),
+        )
+        .await?;
+    Ok(accounts
+        .iter()
+        .any(|a| a.stake_address == stake_addr_in && a.status == "registered"))
 }
 
-pub fn lookup_token_holders(
-    bfp: &KoiosProvider,
+pub async fn lookup_token_holders(
+    kp: &KoiosProvider,
     fingerprint_in: &str,
     min_amount: Option<&i64>,
 ) -> Result<Vec<HoldingWalletView>, DataProviderKoiosError> {
-    todo!();
+    let asset = asset_by_fingerprint(kp, fingerprint_in).await?;
+    let asset_name = asset.asset_name.clone().unwrap_or_default();
+    let holders: Vec<AssetHolder> = kp
+        .get_all(
+            "asset_addresses",
+            &[("_asset_policy", &asset.policy_id), ("_asset_name", &asset_name)],
+        )
+        .await?;
+
+    let mut out = Vec::<HoldingWalletView>::new();
+    for h in holders {
+        let amount = h.quantity.parse::<u64>()?;
+        if let Some(min) = min_amount {
+            if (amount as i128) < *min as i128 {
+                continue;
+            }
+        }
+        if let Some(stake_address) = h.stake_address {
+            out.push(HoldingWalletView {
+                stake_address,
+                amount,
+                policy: asset.policy_id.clone(),
+                tokenname: Some(asset_name.clone()),
+                fingerprint: Some(fingerprint_in.to_owned()),
+            });
+        }
+    }
+    Ok(out)
 }
 
-pub fn lookup_nft_token_holders(
-    bfp: &KoiosProvider,
+pub async fn lookup_nft_token_holders(
+    kp: &KoiosProvider,
     policy: &str,
 ) -> Result<Vec<HoldingWalletView>, DataProviderKoiosError> {
-    todo!();
+    let holders: Vec<PolicyAssetHolder> = kp
+        .get_all("policy_asset_addresses", &[("_asset_policy", policy)])
+        .await?;
+
+    let mut out = Vec::<HoldingWalletView>::new();
+    for h in holders {
+        let amount = h.quantity.parse::<u64>()?;
+        if amount != 1 {
+            continue;
+        }
+        if let Some(stake_address) = h.stake_address {
+            out.push(HoldingWalletView {
+                stake_address,
+                amount,
+                policy: policy.to_owned(),
+                tokenname: h.asset_name,
+                fingerprint: None,
+            });
+        }
+    }
+    Ok(out)
 }
 
-pub fn mint_metadata(
-    bfp: &KoiosProvider,
+pub async fn mint_metadata(
+    kp: &KoiosProvider,
     fingerprint_in: &str,
 ) -> Result<TokenInfoView, DataProviderKoiosError> {
+    let asset = asset_by_fingerprint(kp, fingerprint_in).await?;
+    let info = asset_info(kp, &asset).await?;
+    let metadata = info.minting_metadata();
+
+    let mint_slot = match &info.minting_tx_hash {
+        Some(h) => tx_infos(kp, &[h.clone()])
+            .await?
+            .first()
+            .map(|t| t.absolute_slot),
+        None => None,
+    };
+
     Ok(TokenInfoView {
-        fingerprint: "".to_owned(),
-        policy: hex::encode(""),
-        tokenname: "".to_owned(),
-        meta_key: None,
-        json: None,
-        txhash: None,
-        quantity: None,
-        mint_slot: None,
+        fingerprint: info.fingerprint,
+        policy: info.policy_id,
+        tokenname: info.asset_name.unwrap_or_default(),
+        quantity: info.total_supply.and_then(|q| q.parse::<u64>().ok()),
+        meta_key: metadata.as_ref().map(|m| m.0),
+        json: metadata.map(|m| m.1),
+        mint_slot,
+        txhash: info.minting_tx_hash,
     })
 }
 
-pub fn pool_valid(bfp: &KoiosProvider, pool_id: &str) -> Result<bool, DataProviderKoiosError> {
-    Ok(true)
+pub async fn pool_valid(kp: &KoiosProvider, pool_id: &str) -> Result<bool, DataProviderKoiosError> {
+    let pools: Vec<PoolInfo> = kp
+        .post("pool_info", &json!({ "_pool_bech32_ids": [pool_id] }))
+        .await?;
+    match pools.first() {
+        Some(p) => Ok(p.pool_status != "retired"),
+        None => Err(DataProviderKoiosError::RequestValueNotFound(pool_id.to_owned())),
+    }
 }
 
-pub fn txhash_spent(bfp: &KoiosProvider, txhash: &str) -> Result<bool, DataProviderKoiosError> {
-    let txh_b = hex::decode(txhash)?;
+pub async fn txhash_spent(kp: &KoiosProvider, txhash: &str) -> Result<bool, DataProviderKoiosError> {
+    let tx = tx_infos(kp, &[txhash.to_owned()]).await?;
+    let utxo_refs: Vec<String> = tx
+        .iter()
+        .flat_map(|t| t.outputs.iter())
+        .map(|o| format!("{}#{}", txhash, o.tx_index))
+        .collect();
+    if utxo_refs.is_empty() {
+        return Ok(false);
+    }
+    let utxos: Vec<Utxo> = kp
+        .post("utxo_info", &json!({ "_utxo_refs": utxo_refs }))
+        .await?;
+    Ok(utxos.iter().any(|u| u.is_spent))
+}
+
+/// `true` once the transaction is in a block
+pub async fn tx_included(kp: &KoiosProvider, tx_hash: &str) -> Result<bool, DataProviderKoiosError> {
+    let status: Vec<TxStatus> = kp
+        .post("tx_status", &json!({ "_tx_hashes": [tx_hash] }))
+        .await?;
+    Ok(status
+        .iter()
+        .any(|s| s.tx_hash == tx_hash && s.num_confirmations.is_some()))
+}
 
-    Ok(false)
+/// Check if addresses exist
+pub async fn addresses_exist(
+    kp: &KoiosProvider,
+    addresses: &Vec<&str>,
+) -> Result<Vec<bool>, DataProviderKoiosError> {
+    let mut known = Vec::<String>::new();
+    for chunk in addresses.chunks(KOIOS_MAX_LIST_PARAMS) {
+        let infos: Vec<AddressInfo> = kp
+            .post("address_info", &json!({ "_addresses": chunk }))
+            .await?;
+        known.extend(infos.into_iter().map(|i| i.address));
+    }
+    Ok(addresses.iter().map(|a| known.iter().any(|k| k == a)).collect())
 }
 
-pub fn retrieve_staked_amount (
-    bfp: &KoiosProvider,
+/// Get list of txs involving addresses
+pub async fn tx_history(
+    kp: &KoiosProvider,
+    addresses: &Vec<&str>,
+    slot: Option<u64>,
+) -> Result<Vec<TxHistoryListView>, DataProviderKoiosError> {
+    let mut tx_hashes = Vec::<String>::new();
+    for chunk in addresses.chunks(KOIOS_MAX_LIST_PARAMS) {
+        let txs: Vec<AddressTx> = kp
+            .post_all("address_txs", &json!({ "_addresses": chunk }))
+            .await?;
+        tx_hashes.extend(txs.into_iter().map(|t| t.tx_hash));
+    }
+    tx_hashes.sort();
+    tx_hashes.dedup();
+
+    let mut out: Vec<TxHistoryListView> = tx_infos(kp, &tx_hashes)
+        .await?
+        .into_iter()
+        .filter(|t| slot.map(|s| t.absolute_slot as u64 <= s).unwrap_or(true))
+        .map(|t| TxHistoryListView::new(t.tx_hash, t.absolute_slot, vec![]))
+        .collect();
+    out.sort_by(|a, b| b.slot.cmp(&a.slot));
+    Ok(out)
+}
+
+pub async fn retrieve_staked_amount(
+    kp: &KoiosProvider,
     epoch: i32,
     stake_addr: &str,
 ) -> Result<BigDecimal, DataProviderKoiosError> {
-    Ok(BigDecimal::from(0))
+    let accounts: Vec<AccountHistory> = kp
+        .post(
+            "account_history",
+            &json!({ "_stake_addresses": [stake_addr], "_epoch_no": epoch }),
+        )
+        .await?;
+    let amount = accounts
+        .iter()
+        .flat_map(|a| a.history.iter())
+        .find(|h| h.epoch_no == epoch)
+        .map(|h| h.active_stake.as_str())
+        .unwrap_or("0");
+    BigDecimal::from_str(amount).map_err(|e| DataProviderKoiosError::Custom(e.to_string()))
 }
 
-pub fn retrieve_generated_rewards (
-    bfp: &KoiosProvider,
+pub async fn retrieve_generated_rewards(
+    kp: &KoiosProvider,
     stake_addr: &str,
 ) -> Result<Vec<RewardView>, DataProviderKoiosError> {
-    Ok(vec![])
-}
\ No newline at end of file
+    let accounts: Vec<AccountRewards> = kp
+        .post(
+            "account_rewards",
+            &json!({ "_stake_addresses": [stake_addr] }),
+        )
+        .await?;
+    let mut out = Vec::<RewardView>::new();
+    for r in accounts.iter().flat_map(|a| a.rewards.iter()) {
+        out.push(RewardView {
+            amount: r.amount.parse::<u64>()?,
+            earned_epoch: r.earned_epoch,
+            spendable_epoch: r.spendable_epoch,
+        })
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::provider::CardanoDataProvider;
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    const ADDR: &str = "addr1vyqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qavsj8u";
+    const STAKE_ADDR: &str = "stake1u9jx2en8dp5k56mvd4hx7ur3wfehgatkwau8j7nm037hulctderj8";
+    const TX_HASH: &str = "1b07f1152e52ce0a9dbb561aa2e2d1750ca3a1a4141150a8bad342947a66a3a6";
+    const POLICY: &str = "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c";
+
+    fn provider(server: &MockServer) -> crate::DataProvider<crate::koios::KoiosProvider> {
+        crate::DataProvider::new(crate::koios::KoiosProvider::new(crate::koios::Config {
+            url: server.url("/api/v1"),
+            api_token: "token".to_owned(),
+            network: crate::config::ChainWellKnownInfo::mainnet(),
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_tip() {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/api/v1/tip")
+                    .header("authorization", "Bearer token");
+                then.status(200).json_body(json!([{
+                    "hash": "e3c0c1a3c5b5b2a4ac2e3a3f0e6de7b3b2a0c1fcd0cdac1d0bbf2b07d66f3d7e",
+                    "epoch_no": 412,
+                    "abs_slot": 95654321,
+                    "epoch_slot": 12345,
+                    "block_height": 8765432,
+                    "block_time": 1687220612
+                }]));
+            })
+            .await;
+
+        let dp = provider(&server);
+        assert!(dp.alive().await);
+        assert_eq!(dp.slot().await.unwrap(), 95654321);
+        assert_eq!(dp.current_epoch().await.unwrap(), 412);
+        mock.assert_hits_async(3).await;
+    }
+
+    #[tokio::test]
+    async fn test_wrong_network() {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.any_request();
+                then.status(200).json_body(json!([]));
+            })
+            .await;
+
+        let dp = provider(&server);
+        let testnet_addr = "addr_test1vqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qxyywge";
+        match dp.script_utxos(testnet_addr).await {
+            Err(crate::DataProviderError::WrongNetwork {
+                expected: 1,
+                found: 0,
+                address,
+            }) => assert_eq!(address, testnet_addr),
+            Err(e) => panic!("expected WrongNetwork, got {e}"),
+            Ok(_) => panic!("expected WrongNetwork"),
+        }
+        let stake_test = "stake_test1upjx2en8dp5k56mvd4hx7ur3wfehgatkwau8j7nm037hulcv8npk6";
+        assert!(matches!(
+            dp.addresses_exist(&vec![ADDR, stake_test]).await,
+            Err(crate::DataProviderError::WrongNetwork { .. })
+        ));
+        mock.assert_hits_async(0).await;
+    }
+
+    #[tokio::test]
+    async fn test_script_utxos() {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/api/v1/address_utxos")
+                    .query_param("offset", "0")
+                    .json_body(json!({ "_addresses": [ADDR], "_extended": true }));
+                then.status(200).json_body(json!([
+                    {
+                        "tx_hash": TX_HASH,
+                        "tx_index": 0,
+                        "address": ADDR,
+                        "value": "1500000",
+                        "stake_address": null,
+                        "datum_hash": null,
+                        "inline_datum": { "bytes": "d87980", "value": { "constructor": 0, "fields": [] } },
+                        "reference_script": null,
+                        "asset_list": [{
+                            "policy_id": POLICY,
+                            "asset_name": "746f6b656e",
+                            "fingerprint": "asset1notchecked",
+                            "decimals": 0,
+                            "quantity": "42"
+                        }],
+                        "is_spent": false
+                    },
+                    {
+                        "tx_hash": TX_HASH,
+                        "tx_index": 1,
+                        "address": ADDR,
+                        "value": "2000000",
+                        "asset_list": [],
+                        "is_spent": false
+                    }
+                ]));
+            })
+            .await;
+
+        let dp = provider(&server);
+        let utxos = dp.script_utxos(ADDR).await.unwrap();
+        mock.assert_async().await;
+        assert_eq!(utxos.len(), 2);
+
+        let assets = dp.asset_utxos_on_addr(ADDR).await.unwrap();
+        assert_eq!(assets.len(), 1);
+        let output = assets.get(0).output();
+        assert_eq!(output.amount().coin().to_str(), "1500000");
+        assert!(output.plutus_data().is_some());
+        assert_eq!(output.amount().multiasset().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stake_registration() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST).path("/api/v1/account_updates");
+                then.status(200).json_body(json!([{
+                    "stake_address": STAKE_ADDR,
+                    "updates": [
+                        { "action_type": "registration", "tx_hash": TX_HASH, "epoch_no": 300, "epoch_slot": 1, "absolute_slot": 1, "block_time": 1 },
+                        { "action_type": "delegation", "tx_hash": TX_HASH, "epoch_no": 300, "epoch_slot": 1, "absolute_slot": 1, "block_time": 1 },
+                        { "action_type": "deregistration", "tx_hash": TX_HASH, "epoch_no": 310, "epoch_slot": 1, "absolute_slot": 1, "block_time": 1 },
+                        { "action_type": "registration", "tx_hash": TX_HASH, "epoch_no": 320, "epoch_slot": 1, "absolute_slot": 1, "block_time": 1 }
+                    ]
+                }]));
+            })
+            .await;
+
+        let dp = provider(&server);
+        let registrations = dp.stake_registration(STAKE_ADDR).await.unwrap();
+        assert_eq!(registrations.len(), 2);
+        assert_eq!(registrations[0].epoch, 320);
+        assert_eq!(registrations[0].tx_hash, hex::decode(TX_HASH).unwrap());
+        let deregistrations = dp.stake_deregistration(STAKE_ADDR).await.unwrap();
+        assert_eq!(deregistrations.len(), 1);
+        assert_eq!(deregistrations[0].epoch, 310);
+    }
+
+    #[tokio::test]
+    async fn test_tx_included() {
+        let server = MockServer::start_async().await;
+        let pending = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/api/v1/tx_status")
+                    .json_body(json!({ "_tx_hashes": [TX_HASH] }));
+                then.status(200)
+                    .json_body(json!([{ "tx_hash": TX_HASH, "num_confirmations": null }]));
+            })
+            .await;
+
+        let dp = provider(&server);
+        assert!(!dp.tx_included(TX_HASH).await.unwrap());
+
+        pending.delete_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST).path("/api/v1/tx_status");
+                then.status(200)
+                    .json_body(json!([{ "tx_hash": TX_HASH, "num_confirmations": 3 }]));
+            })
+            .await;
+        assert!(dp.tx_included(TX_HASH).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_generated_rewards() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/api/v1/account_rewards")
+                    .json_body(json!({ "_stake_addresses": [STAKE_ADDR] }));
+                then.status(200).json_body(json!([{
+                    "stake_address": STAKE_ADDR,
+                    "rewards": [
+                        { "earned_epoch": 400, "spendable_epoch": 402, "amount": "1234567", "type": "member", "pool_id": null },
+                        { "earned_epoch": 401, "spendable_epoch": 403, "amount": "7654321", "type": "member", "pool_id": null }
+                    ]
+                }]));
+            })
+            .await;
+
+        let dp = provider(&server);
+        let rewards = dp.retrieve_generated_rewards(STAKE_ADDR).await.unwrap();
+        assert_eq!(rewards.len(), 2);
+        assert_eq!(rewards[1].amount, 7654321);
+        assert_eq!(rewards[1].spendable_epoch, 403);
+    }
+
+    #[tokio::test]
+    async fn test_error_status() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/api/v1/tip");
+                then.status(503).body("upstream unavailable");
+            })
+            .await;
+
+        let dp = provider(&server);
+        assert!(!dp.alive().await);
+        assert!(dp.slot().await.is_err());
+    }
+}
@@ -0,0 +1,290 @@
+use cardano_serialization_lib as csl;
+use serde::Deserialize;
+
+use super::error::DataProviderKoiosError;
+
+// Koios responses, only the fields used by the provider are deserialized
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Tip {
+    pub hash: String,
+    pub epoch_no: i32,
+    pub abs_slot: i64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Asset {
+    pub policy_id: String,
+    pub asset_name: Option<String>,
+    pub fingerprint: Option<String>,
+    pub quantity: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct InlineDatum {
+    pub bytes: String,
+    pub value: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ReferenceScript {
+    pub hash: String,
+    #[serde(rename = "type")]
+    pub script_type: String,
+    pub bytes: Option<String>,
+}
+
+impl ReferenceScript {
+    pub fn to_script_ref(&self) -> Result<csl::ScriptRef, DataProviderKoiosError> {
+        let bytes = match &self.bytes {
+            Some(b) => hex::decode(b)?,
+            None => {
+                return Err(DataProviderKoiosError::Custom(format!(
+                    "reference script {} has no bytes",
+                    self.hash
+                )))
+            }
+        };
+        let script_ref = match self.script_type.as_str() {
+            "plutusV1" => csl::ScriptRef::new_plutus_script(
+                &csl::plutus::PlutusScript::from_bytes(bytes)?,
+            ),
+            "plutusV2" => csl::ScriptRef::new_plutus_script(
+                &csl::plutus::PlutusScript::from_bytes_v2(bytes)?,
+            ),
+            _ => csl::ScriptRef::new_native_script(&csl::NativeScript::from_bytes(bytes)?),
+        };
+        Ok(script_ref)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Utxo {
+    pub tx_hash: String,
+    pub tx_index: u32,
+    pub address: String,
+    pub value: String,
+    pub stake_address: Option<String>,
+    pub datum_hash: Option<String>,
+    pub inline_datum: Option<InlineDatum>,
+    pub reference_script: Option<ReferenceScript>,
+    pub asset_list: Option<Vec<Asset>>,
+    #[serde(default)]
+    pub is_spent: bool,
+}
+
+impl Utxo {
+    pub fn to_txuo(&self) -> Result<dcslc::TransactionUnspentOutput, DataProviderKoiosError> {
+        let input = csl::TransactionInput::new(
+            &csl::crypto::TransactionHash::from_bytes(hex::decode(&self.tx_hash)?)?,
+            self.tx_index,
+        );
+
+        let mut value = csl::utils::Value::new(&csl::utils::BigNum::from_str(&self.value)?);
+        let mut ma = csl::MultiAsset::new();
+        for asset in self.asset_list.iter().flatten() {
+            ma.set_asset(
+                &csl::PolicyID::from_bytes(hex::decode(&asset.policy_id)?)?,
+                &csl::AssetName::new(hex::decode(asset.asset_name.as_deref().unwrap_or_default())?)?,
+                csl::utils::BigNum::from_str(&asset.quantity)?,
+            );
+        }
+        if ma.len() > 0 {
+            value.set_multiasset(&ma);
+        }
+
+        let mut output = csl::TransactionOutput::new(&dcslc::addr_from_str(&self.address)?, &value);
+        if let Some(datum) = &self.inline_datum {
+            output.set_plutus_data(&csl::plutus::PlutusData::from_bytes(hex::decode(&datum.bytes)?)?);
+        } else if let Some(hash) = &self.datum_hash {
+            output.set_data_hash(&csl::crypto::DataHash::from_bytes(hex::decode(hash)?)?);
+        }
+        if let Some(script) = &self.reference_script {
+            output.set_script_ref(&script.to_script_ref()?);
+        }
+
+        Ok(dcslc::TransactionUnspentOutput::new(&input, &output))
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AssetListItem {
+    pub policy_id: String,
+    pub asset_name: Option<String>,
+    pub fingerprint: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AssetInfo {
+    pub policy_id: String,
+    pub asset_name: Option<String>,
+    pub fingerprint: String,
+    pub minting_tx_hash: Option<String>,
+    pub total_supply: Option<String>,
+    pub minting_tx_metadata: Option<serde_json::Value>,
+}
+
+impl AssetInfo {
+    /// Returns label and content of the minting transaction metadata, Koios delivers either
+    /// an object keyed by label or a list of `{key, json}` entries depending on the version
+    pub fn minting_metadata(&self) -> Option<(i64, serde_json::Value)> {
+        match self.minting_tx_metadata.as_ref()? {
+            serde_json::Value::Object(map) => map
+                .iter()
+                .find_map(|(k, v)| k.parse::<i64>().ok().map(|k| (k, v.clone()))),
+            serde_json::Value::Array(list) => list.iter().find_map(|e| {
+                let key = match &e["key"] {
+                    serde_json::Value::String(s) => s.parse::<i64>().ok()?,
+                    serde_json::Value::Number(n) => n.as_i64()?,
+                    _ => return None,
+                };
+                Some((key, e["json"].clone()))
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AssetHolder {
+    pub payment_address: String,
+    pub stake_address: Option<String>,
+    pub quantity: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PolicyAssetHolder {
+    pub asset_name: Option<String>,
+    pub payment_address: String,
+    pub stake_address: Option<String>,
+    pub quantity: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PoolListItem {
+    pub pool_id_bech32: String,
+    pub ticker: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PoolInfo {
+    pub pool_id_bech32: String,
+    pub pool_status: String,
+    pub meta_json: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PoolDelegatorHistory {
+    pub stake_address: String,
+    pub amount: String,
+    pub epoch_no: i32,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PoolDelegator {
+    pub stake_address: String,
+    pub amount: String,
+    pub active_epoch_no: i64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PoolHistory {
+    pub epoch_no: i32,
+    pub active_stake: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AccountInfo {
+    pub stake_address: String,
+    pub status: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AccountUpdate {
+    pub action_type: String,
+    pub tx_hash: String,
+    pub epoch_no: i32,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AccountUpdates {
+    pub stake_address: String,
+    pub updates: Vec<AccountUpdate>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AccountAddresses {
+    pub stake_address: String,
+    pub addresses: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AccountHistoryEntry {
+    pub pool_id: Option<String>,
+    pub epoch_no: i32,
+    pub active_stake: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AccountHistory {
+    pub stake_address: String,
+    pub history: Vec<AccountHistoryEntry>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AccountReward {
+    pub earned_epoch: i64,
+    pub spendable_epoch: i64,
+    pub amount: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AccountRewards {
+    pub stake_address: String,
+    pub rewards: Vec<AccountReward>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AddressInfo {
+    pub address: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AddressTx {
+    pub tx_hash: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PaymentAddr {
+    pub bech32: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TxOutput {
+    pub payment_addr: PaymentAddr,
+    pub tx_index: u32,
+    pub datum_hash: Option<String>,
+    pub inline_datum: Option<InlineDatum>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TxInfo {
+    pub tx_hash: String,
+    pub absolute_slot: i64,
+    #[serde(default)]
+    pub outputs: Vec<TxOutput>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TxStatus {
+    pub tx_hash: String,
+    /// `None` while the transaction is not in a block
+    pub num_confirmations: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct DatumInfo {
+    pub datum_hash: String,
+    pub value: Option<serde_json::Value>,
+    pub bytes: String,
+}
@@ -1,12 +1,14 @@
 pub mod blockfrost;
 pub mod carb;
 pub mod config;
+pub mod datum;
 pub mod dbsync;
 pub mod grpc;
 pub mod koios;
 pub mod models;
 pub mod provider;
 pub mod server;
+pub mod time;
 
 #[macro_use]
 extern crate diesel;
@@ -15,3 +17,5 @@ pub use dbsync::{Config, DBSyncProvider};
 pub use crate::blockfrost::BlockfrostProvider;
 pub use provider::error::DataProviderError;
 pub use provider::DataProvider;
+pub use provider::cache::CachedProvider;
+pub use provider::fallback::FallbackProvider;
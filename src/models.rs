@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::iter::zip;
 
 use bigdecimal::{BigDecimal, ToPrimitive};
@@ -8,6 +9,7 @@ use blockfrost::Transaction;
 
 pub type Token = (ScriptHash, AssetName, BigNum);
 pub type Tokens = Vec<Token>;
+pub type TxHash = cardano_serialization_lib::crypto::TransactionHash;
 
 #[derive(Queryable, serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct TokenInfoView {
@@ -116,6 +118,14 @@ impl TxHistoryListAssetView {
             amount,
         }
     }
+
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    pub fn amount(&self) -> u64 {
+        self.amount
+    }
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, QueryableByName)]
@@ -138,6 +148,16 @@ pub struct TxHistoryListQueryLight {
     pub slot: i64,
 }
 
+#[derive(serde::Serialize, serde::Deserialize, Debug, QueryableByName)]
+pub struct TxHistoryPageQuery {
+    #[diesel(sql_type = diesel::sql_types::BigInt, column_name = id, deserialize_as = i64)]
+    pub id: i64,
+    #[diesel(sql_type = diesel::sql_types::Bytea, column_name = hash, deserialize_as = Vec<u8>)]
+    pub hash: Vec<u8>,
+    #[diesel(sql_type = diesel::sql_types::BigInt, column_name = slot, deserialize_as = i64)]
+    pub slot: i64,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct TxHistoryListViewLight {
     pub hash: Vec<u8>,
@@ -156,17 +176,20 @@ impl TxHistoryListView {
         Self { hash, slot, assets }
     }
 
-    pub fn from_tx_history_list_query(d: &TxHistoryListQuery) -> Self {
+    /// fails if a token quantity does not fit into an `u64`
+    pub fn from_tx_history_list_query(d: &TxHistoryListQuery) -> Result<Self, String> {
         let mut assets = Vec::<TxHistoryListAssetView>::new();
         for (f, a) in zip(d.fingerprint.iter(), d.value.iter()) {
-            let asset = TxHistoryListAssetView::new(f.to_string(), a.to_u64().unwrap());
-            assets.push(asset)
+            let amount = a
+                .to_u64()
+                .ok_or_else(|| format!("invalid quantity {a} of {f}"))?;
+            assets.push(TxHistoryListAssetView::new(f.to_string(), amount))
         }
-        Self {
+        Ok(Self {
             hash: hex::encode(&d.hash),
             slot: d.slot,
             assets,
-        }
+        })
     }
     pub fn from_blockfrost_tx(tx: &Transaction) -> Self {
         let mut assets = Vec::<TxHistoryListAssetView>::new();
@@ -185,6 +208,107 @@ impl TxHistoryListView {
     }
 }
 
+/// Direction of a transaction seen from the queried addresses
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TxDirection {
+    /// the addresses gain lovelace
+    Incoming,
+    /// the addresses lose lovelace, change may return to them
+    Outgoing,
+    /// the addresses only move value between each other and pay the fee
+    #[serde(rename = "self")]
+    SelfTransfer,
+}
+
+/// Net change of one asset, negative if the addresses sent more than they received
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AssetDeltaView {
+    pub policy: String,
+    pub tokenname: String,
+    pub fingerprint: Option<String>,
+    pub quantity: i128,
+}
+
+/// Transaction of an address history with the net change of value of the addresses,
+/// `lovelace` includes the fee if the addresses paid it
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct AddressTxView {
+    pub hash: String,
+    pub slot: i64,
+    pub block_time: i64,
+    pub fee: u64,
+    pub direction: TxDirection,
+    pub lovelace: i128,
+    pub assets: Vec<AssetDeltaView>,
+    pub metadata_labels: Vec<u64>,
+}
+
+/// Sums what a transaction takes from and sends to a set of addresses,
+/// policy and name of assets are hex encoded
+#[derive(Debug, Default)]
+pub struct TxValueDelta {
+    spent: bool,
+    lovelace: i128,
+    assets: BTreeMap<(String, String), i128>,
+}
+
+impl TxValueDelta {
+    /// lovelace of an output of the addresses, `spent` if the transaction consumes the output
+    pub fn lovelace(&mut self, spent: bool, quantity: i128) {
+        self.spent |= spent;
+        self.lovelace += if spent { -quantity } else { quantity };
+    }
+
+    /// asset of an output of the addresses
+    pub fn asset(&mut self, spent: bool, policy: String, name: String, quantity: i128) {
+        *self.assets.entry((policy, name)).or_default() += if spent { -quantity } else { quantity };
+    }
+
+    pub fn direction(&self, fee: u64) -> TxDirection {
+        let assets_unchanged = self.assets.values().all(|q| *q == 0);
+        if !self.spent || self.lovelace > 0 {
+            TxDirection::Incoming
+        } else if self.lovelace + fee as i128 == 0 && assets_unchanged {
+            TxDirection::SelfTransfer
+        } else {
+            TxDirection::Outgoing
+        }
+    }
+
+    pub fn into_view(
+        self,
+        hash: String,
+        slot: i64,
+        block_time: i64,
+        fee: u64,
+        mut metadata_labels: Vec<u64>,
+    ) -> AddressTxView {
+        metadata_labels.sort_unstable();
+        metadata_labels.dedup();
+        AddressTxView {
+            hash,
+            slot,
+            block_time,
+            fee,
+            direction: self.direction(fee),
+            lovelace: self.lovelace,
+            assets: self
+                .assets
+                .into_iter()
+                .filter(|(_, quantity)| *quantity != 0)
+                .map(|((policy, tokenname), quantity)| AssetDeltaView {
+                    fingerprint: make_fingerprint(&policy, &tokenname).ok(),
+                    policy,
+                    tokenname,
+                    quantity,
+                })
+                .collect(),
+            metadata_labels,
+        }
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct WithdrawalView {
     pub stake_address: String,
@@ -245,6 +369,52 @@ impl UTxOView {
         }
     }
 }
+/// Outputs resolved by `utxos_by_refs`, in the order they were requested. References to
+/// unknown or already spent outputs are listed in `missing`.
+#[derive(Debug, Clone)]
+pub struct ResolvedUtxos {
+    pub found: Vec<TransactionUnspentOutput>,
+    pub missing: Vec<(TxHash, u32)>,
+}
+
+/// Parses an output reference `txhash#index`
+pub fn parse_output_ref(output_ref: &str) -> Result<(TxHash, u32), String> {
+    let invalid = || format!("invalid output reference {output_ref}, expected txhash#index");
+    let (hash, index) = output_ref.split_once('#').ok_or_else(invalid)?;
+    let hash = TxHash::from_hex(hash).map_err(|_| invalid())?;
+    let index = index.parse::<u32>().map_err(|_| invalid())?;
+    Ok((hash, index))
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ResolvedUtxosView {
+    pub found: Vec<UTxOView>,
+    /// `txhash#index`
+    pub missing: Vec<String>,
+}
+
+impl From<&ResolvedUtxos> for ResolvedUtxosView {
+    fn from(resolved: &ResolvedUtxos) -> Self {
+        Self {
+            found: resolved.found.iter().map(UTxOView::from_txuo).collect(),
+            missing: resolved
+                .missing
+                .iter()
+                .map(|(hash, index)| format!("{}#{index}", hash.to_hex()))
+                .collect(),
+        }
+    }
+}
+
+/// Balance of all addresses of a stake address, the rewards are not part of `lovelace`
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct StakeBalanceView {
+    pub stake_address: String,
+    pub lovelace: u64,
+    pub assets: Vec<AssetHandle>,
+    pub withdrawable_rewards: u64,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct ValueView {
     pub coin: u64,
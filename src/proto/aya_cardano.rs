@@ -19,13 +19,22 @@ pub struct ValidatorRequest {
 pub struct StakeRequest {
     #[prost(enumeration = "StakeRequestType", tag = "1")]
     pub r#type: i32,
+    /// bech32 stake address of the delegator
+    #[prost(string, tag = "2")]
+    pub stake_address: ::prost::alloc::string::String,
+    /// transaction of the event
+    #[prost(string, tag = "3")]
+    pub tx_hash: ::prost::alloc::string::String,
+    /// operator address of the validator
+    #[prost(string, tag = "4")]
+    pub validator: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct EventResponse {
     #[prost(enumeration = "EventResponseType", tag = "1")]
     pub message_type: i32,
-    #[prost(oneof = "event_response::Message", tags = "2, 3, 4, 99")]
+    #[prost(oneof = "event_response::Message", tags = "2, 3, 4, 5, 99")]
     pub message: ::core::option::Option<event_response::Message>,
 }
 /// Nested message and enum types in `EventResponse`.
@@ -39,6 +48,8 @@ pub mod event_response {
         CurrentEpoch(super::CurrentEpochResponse),
         #[prost(message, tag = "4")]
         ValidatorRegistration(super::ValidatorRegistrationResponse),
+        #[prost(message, tag = "5")]
+        StakeEvent(super::StakeEventResponse),
         #[prost(string, tag = "99")]
         String(::prost::alloc::string::String),
     }
@@ -111,9 +122,119 @@ pub struct ValidatorRegistrationResponse {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StakeEventResponse {
+    #[prost(string, tag = "1")]
+    pub tx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub slot: u64,
+    #[prost(uint64, tag = "3")]
+    pub epoch: u64,
+    #[prost(string, tag = "4")]
+    pub stake_address: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub validator: ::prost::alloc::string::String,
+    /// lovelace locked in the staking contract
+    #[prost(uint64, tag = "6")]
+    pub amount: u64,
+    /// epoch in which unbonding funds can be withdrawn, 0 if not unbonding
+    #[prost(uint64, tag = "7")]
+    pub release_epoch: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StateResponse {
     #[prost(enumeration = "StateResponseType", tag = "1")]
     pub r#type: i32,
+    #[prost(string, tag = "2")]
+    pub stake_address: ::prost::alloc::string::String,
+    /// lovelace locked in the staking contract
+    #[prost(uint64, tag = "3")]
+    pub amount: u64,
+    /// epoch of the tip the state was read at
+    #[prost(uint64, tag = "4")]
+    pub epoch: u64,
+    #[prost(enumeration = "StakeStatus", tag = "5")]
+    pub status: i32,
+    #[prost(uint64, tag = "6")]
+    pub slot: u64,
+    #[prost(string, tag = "7")]
+    pub validator: ::prost::alloc::string::String,
+    /// epoch in which unbonding funds can be withdrawn, 0 if not unbonding
+    #[prost(uint64, tag = "8")]
+    pub release_epoch: u64,
+    /// outputs holding the stake or the validator registration
+    #[prost(message, repeated, tag = "9")]
+    pub utxos: ::prost::alloc::vec::Vec<OutputRef>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OutputRef {
+    #[prost(string, tag = "1")]
+    pub tx_hash: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "2")]
+    pub index: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UtxosByRefsRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub refs: ::prost::alloc::vec::Vec<OutputRef>,
+    /// apply the transactions submitted through cdp which are not on-chain yet
+    #[prost(bool, tag = "2")]
+    pub pending: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UtxosByRefsResponse {
+    /// hex encoded TransactionUnspentOutputs in the order of the request
+    #[prost(string, repeated, tag = "1")]
+    pub found: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// references to unknown or spent outputs
+    #[prost(message, repeated, tag = "2")]
+    pub missing: ::prost::alloc::vec::Vec<OutputRef>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubscribeRequest {
+    /// events in or after this slot are sent before the live ones, a reconnecting client passes
+    /// the slot of the last event it received and skips the events it already has.
+    /// 0 for live events only
+    #[prost(uint64, tag = "1")]
+    pub from_slot: u64,
+    /// EpochChangeEvent and ValidatorRegistrationEvent are supported, all of them if empty
+    #[prost(enumeration = "EventResponseType", repeated, tag = "2")]
+    pub types: ::prost::alloc::vec::Vec<i32>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubmitTxRequest {
+    /// hex encoded signed transaction
+    #[prost(string, tag = "1")]
+    pub cbor: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubmitOutcome {
+    #[prost(string, tag = "1")]
+    pub endpoint: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub accepted: bool,
+    /// HTTP status, 0 if the endpoint could not be reached
+    #[prost(uint32, tag = "3")]
+    pub status: u32,
+    #[prost(string, tag = "4")]
+    pub message: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubmitTxResponse {
+    #[prost(string, tag = "1")]
+    pub tx_hash: ::prost::alloc::string::String,
+    /// true if at least one endpoint accepted the transaction
+    #[prost(bool, tag = "2")]
+    pub accepted: bool,
+    #[prost(message, repeated, tag = "3")]
+    pub outcomes: ::prost::alloc::vec::Vec<SubmitOutcome>,
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -280,6 +401,46 @@ impl StateResponseType {
         }
     }
 }
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum StakeStatus {
+    NotStaked = 0,
+    Staked = 1,
+    /// unstaked, the funds are locked until the release epoch
+    Unbonding = 2,
+    /// the release epoch is reached, the funds can be withdrawn
+    Unbonded = 3,
+    Registered = 4,
+    Unregistered = 5,
+}
+impl StakeStatus {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            StakeStatus::NotStaked => "NotStaked",
+            StakeStatus::Staked => "Staked",
+            StakeStatus::Unbonding => "Unbonding",
+            StakeStatus::Unbonded => "Unbonded",
+            StakeStatus::Registered => "Registered",
+            StakeStatus::Unregistered => "Unregistered",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "NotStaked" => Some(Self::NotStaked),
+            "Staked" => Some(Self::Staked),
+            "Unbonding" => Some(Self::Unbonding),
+            "Unbonded" => Some(Self::Unbonded),
+            "Registered" => Some(Self::Registered),
+            "Unregistered" => Some(Self::Unregistered),
+            _ => None,
+        }
+    }
+}
 /// Generated client implementations.
 pub mod chain_follower_request_service_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
@@ -637,6 +798,96 @@ pub mod chain_follower_request_service_client {
                 );
             self.inner.unary(req, path, codec).await
         }
+        pub async fn utxos_by_refs(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UtxosByRefsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::UtxosByRefsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/aya_cardano.ChainFollowerRequestService/UtxosByRefs",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "aya_cardano.ChainFollowerRequestService",
+                        "UtxosByRefs",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn submit_tx(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SubmitTxRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SubmitTxResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/aya_cardano.ChainFollowerRequestService/SubmitTx",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "aya_cardano.ChainFollowerRequestService",
+                        "SubmitTx",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn subscribe_events(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SubscribeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::EventResponse>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/aya_cardano.ChainFollowerRequestService/SubscribeEvents",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "aya_cardano.ChainFollowerRequestService",
+                        "SubscribeEvents",
+                    ),
+                );
+            self.inner.server_streaming(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -686,6 +937,33 @@ pub mod chain_follower_request_service_server {
             &self,
             request: tonic::Request<super::StakeRequest>,
         ) -> std::result::Result<tonic::Response<super::EventResponse>, tonic::Status>;
+        async fn utxos_by_refs(
+            &self,
+            request: tonic::Request<super::UtxosByRefsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::UtxosByRefsResponse>,
+            tonic::Status,
+        >;
+        async fn submit_tx(
+            &self,
+            request: tonic::Request<super::SubmitTxRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SubmitTxResponse>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the SubscribeEvents method.
+        type SubscribeEventsStream: futures_core::Stream<
+                Item = std::result::Result<super::EventResponse, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        async fn subscribe_events(
+            &self,
+            request: tonic::Request<super::SubscribeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::SubscribeEventsStream>,
+            tonic::Status,
+        >;
     }
     #[derive(Debug)]
     pub struct ChainFollowerRequestServiceServer<T: ChainFollowerRequestService> {
@@ -1251,6 +1529,145 @@ pub mod chain_follower_request_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/aya_cardano.ChainFollowerRequestService/UtxosByRefs" => {
+                    #[allow(non_camel_case_types)]
+                    struct UtxosByRefsSvc<T: ChainFollowerRequestService>(pub Arc<T>);
+                    impl<
+                        T: ChainFollowerRequestService,
+                    > tonic::server::UnaryService<super::UtxosByRefsRequest>
+                    for UtxosByRefsSvc<T> {
+                        type Response = super::UtxosByRefsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UtxosByRefsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).utxos_by_refs(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = UtxosByRefsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/aya_cardano.ChainFollowerRequestService/SubmitTx" => {
+                    #[allow(non_camel_case_types)]
+                    struct SubmitTxSvc<T: ChainFollowerRequestService>(pub Arc<T>);
+                    impl<
+                        T: ChainFollowerRequestService,
+                    > tonic::server::UnaryService<super::SubmitTxRequest>
+                    for SubmitTxSvc<T> {
+                        type Response = super::SubmitTxResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SubmitTxRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { (*inner).submit_tx(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SubmitTxSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/aya_cardano.ChainFollowerRequestService/SubscribeEvents" => {
+                    #[allow(non_camel_case_types)]
+                    struct SubscribeEventsSvc<T: ChainFollowerRequestService>(
+                        pub Arc<T>,
+                    );
+                    impl<
+                        T: ChainFollowerRequestService,
+                    > tonic::server::ServerStreamingService<super::SubscribeRequest>
+                    for SubscribeEventsSvc<T> {
+                        type Response = super::EventResponse;
+                        type ResponseStream = T::SubscribeEventsStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SubscribeRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).subscribe_events(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SubscribeEventsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
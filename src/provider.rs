@@ -1,7 +1,13 @@
 use serde::Deserialize;
+pub mod cache;
 pub mod config;
 pub mod error;
-use crate::models::{CDPDatum, TxHistoryListView, PoolView};
+pub mod fallback;
+pub mod page;
+use crate::models::{
+    AddressTxView, CDPDatum, PoolView, ResolvedUtxos, StakeBalanceView, TransactionView, TxHash,
+    TxHistoryListView,
+};
 
 use super::models::{
     CardanoNativeAssetView, DelegationView, HoldingWalletView, StakeDelegationView,
@@ -12,19 +18,25 @@ use cardano_serialization_lib::address::Address;
 use dcslc::TransactionUnspentOutputs;
 use error::DataProviderError;
 use bigdecimal::BigDecimal;
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+pub use page::{Page, PageRequest};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(tag = "type")]
 pub enum ProviderType {
     Dbsync,
     Blockfrost,
+    Koios,
+    Carb,
 }
 
 impl ProviderType {
     pub fn as_str(&self) -> &'static str {
         match self {
             ProviderType::Dbsync => "dbsync",
-            ProviderType::Blockfrost => "blockfrost"
+            ProviderType::Blockfrost => "blockfrost",
+            ProviderType::Koios => "koios",
+            ProviderType::Carb => "carb",
         }
     }
 }
@@ -155,6 +167,192 @@ pub trait CardanoDataProvider: Send + Sync {
         stake_addr: &str,
     ) -> Result<Vec<RewardView>, DataProviderError>;
 
+    /// page of `lookup_token_holders`, by default sliced from the complete list
+    async fn lookup_token_holders_page(
+        &self,
+        fingerprint_in: &str,
+        min_amount: Option<&i64>,
+        page: &PageRequest,
+    ) -> Result<Page<HoldingWalletView>, DataProviderError> {
+        Page::from_all(self.lookup_token_holders(fingerprint_in, min_amount).await?, page)
+    }
+
+    /// page of `lookup_nft_token_holders`, by default sliced from the complete list
+    async fn lookup_nft_token_holders_page(
+        &self,
+        policy: &str,
+        page: &PageRequest,
+    ) -> Result<Page<HoldingWalletView>, DataProviderError> {
+        Page::from_all(self.lookup_nft_token_holders(policy).await?, page)
+    }
+
+    /// page of `tx_history`, by default sliced from the complete list
+    async fn tx_history_page(
+        &self,
+        addresses: &Vec<&str>,
+        slot: Option<u64>,
+        page: &PageRequest,
+    ) -> Result<Page<TxHistoryListView>, DataProviderError> {
+        Page::from_all(self.tx_history(addresses, slot).await?, page)
+    }
+
+    /// page of `stakers_on_pool`, by default sliced from the complete list
+    async fn stakers_on_pool_page(
+        &self,
+        pool: &str,
+        epoch: i32,
+        page: &PageRequest,
+    ) -> Result<Page<StakeDelegationView>, DataProviderError> {
+        Page::from_all(self.stakers_on_pool(pool, epoch).await?, page)
+    }
+
+    /// page of `retrieve_generated_rewards`, by default sliced from the complete list
+    async fn retrieve_generated_rewards_page(
+        &self,
+        stake_addr: &str,
+        page: &PageRequest,
+    ) -> Result<Page<RewardView>, DataProviderError> {
+        Page::from_all(self.retrieve_generated_rewards(stake_addr).await?, page)
+    }
+
+    /// transactions of the addresses up to and including `slot`, newest first, with the
+    /// net change of value of the addresses
+    async fn address_tx_history(
+        &self,
+        _addresses: &Vec<&str>,
+        _slot: Option<u64>,
+    ) -> Result<Vec<AddressTxView>, DataProviderError> {
+        Err(DataProviderError::Unsupported(
+            "address_tx_history is not supported by this provider".to_owned(),
+        ))
+    }
+
+    /// `address_tx_history` of all addresses of a stake address, by default of the
+    /// addresses returned by `stake_address_addresses`
+    async fn stake_address_tx_history(
+        &self,
+        stake_addr: &str,
+        slot: Option<u64>,
+    ) -> Result<Vec<AddressTxView>, DataProviderError> {
+        let addresses = self.stake_address_addresses(stake_addr).await?;
+        let addresses = addresses.iter().map(|a| a.as_str()).collect();
+        self.address_tx_history(&addresses, slot).await
+    }
+
+    /// all payment addresses which ever received an output delegated to the stake address
+    async fn stake_address_addresses(
+        &self,
+        _stake_addr: &str,
+    ) -> Result<Vec<String>, DataProviderError> {
+        Err(DataProviderError::Unsupported(
+            "stake_address_addresses is not supported by this provider".to_owned(),
+        ))
+    }
+
+    /// unspent lovelace and assets of all addresses of the stake address and its
+    /// withdrawable rewards
+    async fn stake_address_balance(
+        &self,
+        _stake_addr: &str,
+    ) -> Result<StakeBalanceView, DataProviderError> {
+        Err(DataProviderError::Unsupported(
+            "stake_address_balance is not supported by this provider".to_owned(),
+        ))
+    }
+
+    /// `tx_history` of all addresses of the stake address, by default of the addresses
+    /// returned by `stake_address_addresses`
+    async fn stake_address_history(
+        &self,
+        stake_addr: &str,
+        slot: Option<u64>,
+    ) -> Result<Vec<TxHistoryListView>, DataProviderError> {
+        let addresses = self.stake_address_addresses(stake_addr).await?;
+        if addresses.is_empty() {
+            return Ok(vec![]);
+        }
+        let addresses = addresses.iter().map(|a| a.as_str()).collect();
+        self.tx_history(&addresses, slot).await
+    }
+
+    /// returns true once the transaction is included in a block
+    async fn tx_included(&self, _tx_hash: &str) -> Result<bool, DataProviderError> {
+        Err(DataProviderError::Unsupported(
+            "tx_included is not supported by this provider".to_owned(),
+        ))
+    }
+
+    /// inputs, outputs, withdrawals, certificates and metadata of a transaction
+    async fn transaction(&self, _tx_hash: &str) -> Result<TransactionView, DataProviderError> {
+        Err(DataProviderError::Unsupported(
+            "transaction is not supported by this provider".to_owned(),
+        ))
+    }
+
+    /// resolves the unspent outputs of several output references at once, by default one
+    /// `utxo_by_txid` request per reference
+    async fn utxos_by_refs(
+        &self,
+        refs: &[(TxHash, u32)],
+    ) -> Result<ResolvedUtxos, DataProviderError> {
+        let mut resolved = ResolvedUtxos {
+            found: Vec::with_capacity(refs.len()),
+            missing: Vec::new(),
+        };
+        for (hash, index) in refs {
+            let utxo = match i16::try_from(*index) {
+                Ok(i) => self.utxo_by_txid(&hash.to_bytes(), i).await,
+                Err(_) => Err(DataProviderError::NotFound(format!(
+                    "{}#{index}",
+                    hash.to_hex()
+                ))),
+            };
+            match utxo {
+                Ok(utxo) => resolved.found.push(utxo),
+                Err(DataProviderError::NotFound(_)) => {
+                    resolved.missing.push((hash.clone(), *index))
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// streams all utxos of an address, by default loaded at once through `script_utxos`
+    fn script_utxos_stream<'a>(
+        &'a self,
+        addr: &'a str,
+    ) -> BoxStream<'a, Result<dcslc::TransactionUnspentOutput, DataProviderError>> {
+        stream::once(self.script_utxos(addr))
+            .map_ok(|utxos| stream::iter(utxos.into_iter().map(Ok)))
+            .try_flatten()
+            .boxed()
+    }
+
+    /// streams the holders of all assets of a policy
+    fn policy_holders_stream<'a>(
+        &'a self,
+        _policy: &'a str,
+    ) -> BoxStream<'a, Result<HoldingWalletView, DataProviderError>> {
+        stream::once(async {
+            Err(DataProviderError::Unsupported(
+                "policy_holders_stream is not supported by this provider".to_owned(),
+            ))
+        })
+        .boxed()
+    }
+
+    /// streams the history of the addresses, newest first, by default loaded at once through
+    /// `tx_history`
+    fn tx_history_stream<'a>(
+        &'a self,
+        addresses: &'a Vec<&'a str>,
+    ) -> BoxStream<'a, Result<TxHistoryListView, DataProviderError>> {
+        stream::once(self.tx_history(addresses, None))
+            .map_ok(|history| stream::iter(history.into_iter().map(Ok)))
+            .try_flatten()
+            .boxed()
+    }
 }
 
 pub struct DataProvider<T: CardanoDataProvider> {
@@ -367,4 +565,123 @@ impl<T: CardanoDataProvider + std::marker::Sync + std::marker::Send> CardanoData
     ) -> Result<Vec<RewardView>, DataProviderError> {
         dbg!(self.provider().retrieve_generated_rewards(stake_addr).await)
     }
+
+    async fn lookup_token_holders_page(
+        &self,
+        fingerprint_in: &str,
+        min_amount: Option<&i64>,
+        page: &PageRequest,
+    ) -> Result<Page<HoldingWalletView>, DataProviderError> {
+        self.provider()
+            .lookup_token_holders_page(fingerprint_in, min_amount, page)
+            .await
+    }
+
+    async fn lookup_nft_token_holders_page(
+        &self,
+        policy: &str,
+        page: &PageRequest,
+    ) -> Result<Page<HoldingWalletView>, DataProviderError> {
+        self.provider().lookup_nft_token_holders_page(policy, page).await
+    }
+
+    async fn tx_history_page(
+        &self,
+        addresses: &Vec<&str>,
+        slot: Option<u64>,
+        page: &PageRequest,
+    ) -> Result<Page<TxHistoryListView>, DataProviderError> {
+        self.provider().tx_history_page(addresses, slot, page).await
+    }
+
+    async fn stakers_on_pool_page(
+        &self,
+        pool: &str,
+        epoch: i32,
+        page: &PageRequest,
+    ) -> Result<Page<StakeDelegationView>, DataProviderError> {
+        self.provider().stakers_on_pool_page(pool, epoch, page).await
+    }
+
+    async fn retrieve_generated_rewards_page(
+        &self,
+        stake_addr: &str,
+        page: &PageRequest,
+    ) -> Result<Page<RewardView>, DataProviderError> {
+        self.provider().retrieve_generated_rewards_page(stake_addr, page).await
+    }
+
+    async fn address_tx_history(
+        &self,
+        addresses: &Vec<&str>,
+        slot: Option<u64>,
+    ) -> Result<Vec<AddressTxView>, DataProviderError> {
+        self.provider().address_tx_history(addresses, slot).await
+    }
+
+    async fn stake_address_tx_history(
+        &self,
+        stake_addr: &str,
+        slot: Option<u64>,
+    ) -> Result<Vec<AddressTxView>, DataProviderError> {
+        self.provider().stake_address_tx_history(stake_addr, slot).await
+    }
+
+    async fn stake_address_addresses(
+        &self,
+        stake_addr: &str,
+    ) -> Result<Vec<String>, DataProviderError> {
+        self.provider().stake_address_addresses(stake_addr).await
+    }
+
+    async fn stake_address_balance(
+        &self,
+        stake_addr: &str,
+    ) -> Result<StakeBalanceView, DataProviderError> {
+        self.provider().stake_address_balance(stake_addr).await
+    }
+
+    async fn stake_address_history(
+        &self,
+        stake_addr: &str,
+        slot: Option<u64>,
+    ) -> Result<Vec<TxHistoryListView>, DataProviderError> {
+        self.provider().stake_address_history(stake_addr, slot).await
+    }
+
+    async fn tx_included(&self, tx_hash: &str) -> Result<bool, DataProviderError> {
+        self.provider().tx_included(tx_hash).await
+    }
+
+    async fn transaction(&self, tx_hash: &str) -> Result<TransactionView, DataProviderError> {
+        self.provider().transaction(tx_hash).await
+    }
+
+    async fn utxos_by_refs(
+        &self,
+        refs: &[(TxHash, u32)],
+    ) -> Result<ResolvedUtxos, DataProviderError> {
+        self.provider().utxos_by_refs(refs).await
+    }
+
+    fn script_utxos_stream<'a>(
+        &'a self,
+        addr: &'a str,
+    ) -> BoxStream<'a, Result<dcslc::TransactionUnspentOutput, DataProviderError>> {
+        self.provider().script_utxos_stream(addr)
+    }
+
+    fn policy_holders_stream<'a>(
+        &'a self,
+        policy: &'a str,
+    ) -> BoxStream<'a, Result<HoldingWalletView, DataProviderError>> {
+        self.provider().policy_holders_stream(policy)
+    }
+
+    fn tx_history_stream<'a>(
+        &'a self,
+        addresses: &'a Vec<&'a str>,
+    ) -> BoxStream<'a, Result<TxHistoryListView, DataProviderError>> {
+        self.provider().tx_history_stream(addresses)
+    }
 }
@@ -0,0 +1,787 @@
+use super::error::DataProviderError;
+use super::{CardanoDataProvider, Page, PageRequest};
+use crate::models::{
+    AddressTxView, CDPDatum, CardanoNativeAssetView, DelegationView, HoldingWalletView, PoolView,
+    ResolvedUtxos, RewardView, StakeBalanceView, StakeDelegationView, StakeDeregistrationView,
+    StakeRegistrationView, TokenInfoView, TransactionView, TxHash, TxHistoryListView,
+};
+
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use cardano_serialization_lib::address::Address;
+use dcslc::{TransactionUnspentOutput, TransactionUnspentOutputs};
+use futures::stream::BoxStream;
+use lru::LruCache;
+use std::any::Any;
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// maximum number of cached responses
+    pub capacity: usize,
+    /// responses older than this are fetched again, `None` keeps them until they are evicted or invalidated
+    pub ttl: Option<Duration>,
+    /// how long an observed tip is trusted before `slot()` is queried again
+    pub tip_check_interval: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            capacity: 10_000,
+            ttl: None,
+            tip_check_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+/// How long a cached response stays valid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    /// the response can not change anymore
+    Immutable,
+    /// valid until the next epoch
+    Epoch(i32),
+    /// valid until a new block is observed
+    Tip(i64),
+}
+
+struct Entry {
+    value: Box<dyn Any + Send>,
+    inserted: Instant,
+    scope: Scope,
+}
+
+/// Caches the responses of the wrapped provider in an in-memory LRU
+///
+/// Lookups which can not change anymore (token infos, datums, stake of past epochs) are kept until
+/// they are evicted, lookups depending on the chain tip are dropped as soon as a new slot is observed.
+/// Errors are never cached.
+pub struct CachedProvider<T: CardanoDataProvider> {
+    config: Config,
+    provider: T,
+    cache: Mutex<LruCache<String, Entry>>,
+    tip: Mutex<Option<(Instant, i64)>>,
+    epoch: Mutex<Option<(i64, i32)>>,
+}
+
+impl<T: CardanoDataProvider> CachedProvider<T> {
+    pub fn new(provider: T, config: Config) -> Self {
+        let capacity = NonZeroUsize::new(config.capacity.max(1)).expect("capacity is not zero");
+        CachedProvider {
+            config,
+            provider,
+            cache: Mutex::new(LruCache::new(capacity)),
+            tip: Mutex::new(None),
+            epoch: Mutex::new(None),
+        }
+    }
+
+    pub fn provider(&self) -> &T {
+        &self.provider
+    }
+
+    /// Drops all cached responses
+    pub fn clear(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+
+    /// Returns the latest observed slot, the provider is only asked once per `tip_check_interval`
+    async fn tip(&self) -> Result<i64, DataProviderError> {
+        let observed = *self.tip.lock().unwrap();
+        if let Some((checked, slot)) = observed {
+            if checked.elapsed() < self.config.tip_check_interval {
+                return Ok(slot);
+            }
+        }
+        let slot = self.provider.slot().await?;
+        *self.tip.lock().unwrap() = Some((Instant::now(), slot));
+        Ok(slot)
+    }
+
+    /// Returns the epoch at the latest observed slot
+    async fn epoch(&self) -> Result<i32, DataProviderError> {
+        let slot = self.tip().await?;
+        let observed = *self.epoch.lock().unwrap();
+        if let Some((at_slot, epoch)) = observed {
+            if at_slot == slot {
+                return Ok(epoch);
+            }
+        }
+        let epoch = self.provider.current_epoch().await?;
+        *self.epoch.lock().unwrap() = Some((slot, epoch));
+        Ok(epoch)
+    }
+
+    async fn tip_scope(&self) -> Result<Scope, DataProviderError> {
+        Ok(Scope::Tip(self.tip().await?))
+    }
+
+    async fn epoch_scope(&self) -> Result<Scope, DataProviderError> {
+        Ok(Scope::Epoch(self.epoch().await?))
+    }
+
+    /// Immutable if the requested epoch is over, otherwise valid until the next block
+    async fn past_epoch_scope(&self, epoch: i64) -> Result<Scope, DataProviderError> {
+        if epoch < self.epoch().await? as i64 {
+            Ok(Scope::Immutable)
+        } else {
+            self.tip_scope().await
+        }
+    }
+
+    fn lookup<R: Clone + 'static>(&self, key: &str, scope: Scope) -> Option<R> {
+        let mut cache = self.cache.lock().unwrap();
+        let entry = cache.get(key)?;
+        let expired = self
+            .config
+            .ttl
+            .map(|ttl| entry.inserted.elapsed() >= ttl)
+            .unwrap_or(false);
+        if expired || entry.scope != scope {
+            cache.pop(key);
+            return None;
+        }
+        entry.value.downcast_ref::<R>().cloned()
+    }
+
+    async fn cached<R, F>(&self, key: String, scope: Scope, f: F) -> Result<R, DataProviderError>
+    where
+        R: Clone + Send + 'static,
+        F: Future<Output = Result<R, DataProviderError>>,
+    {
+        if let Some(value) = self.lookup::<R>(&key, scope) {
+            return Ok(value);
+        }
+        let value = f.await?;
+        self.cache.lock().unwrap().put(
+            key,
+            Entry {
+                value: Box::new(value.clone()),
+                inserted: Instant::now(),
+                scope,
+            },
+        );
+        Ok(value)
+    }
+}
+
+#[async_trait]
+impl<T: CardanoDataProvider> CardanoDataProvider for CachedProvider<T> {
+    async fn alive(&self) -> bool {
+        self.provider.alive().await
+    }
+
+    async fn wallet_utxos(
+        &self,
+        stake_addr: &str,
+    ) -> Result<TransactionUnspentOutputs, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("wallet_utxos:{stake_addr}"),
+            scope,
+            self.provider.wallet_utxos(stake_addr),
+        )
+        .await
+    }
+
+    async fn script_utxos(
+        &self,
+        addr: &str,
+    ) -> Result<TransactionUnspentOutputs, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("script_utxos:{addr}"),
+            scope,
+            self.provider.script_utxos(addr),
+        )
+        .await
+    }
+
+    async fn asset_utxos_on_addr(
+        &self,
+        addr: &str,
+    ) -> Result<TransactionUnspentOutputs, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("asset_utxos_on_addr:{addr}"),
+            scope,
+            self.provider.asset_utxos_on_addr(addr),
+        )
+        .await
+    }
+
+    async fn active_pools(&self, page: usize) -> Result<Vec<PoolView>, DataProviderError> {
+        let scope = self.epoch_scope().await?;
+        self.cached(
+            format!("active_pools:{page}"),
+            scope,
+            self.provider.active_pools(page),
+        )
+        .await
+    }
+
+    async fn mint_metadata(
+        &self,
+        fingerprint_in: &str,
+    ) -> Result<TokenInfoView, DataProviderError> {
+        self.cached(
+            format!("mint_metadata:{fingerprint_in}"),
+            Scope::Immutable,
+            self.provider.mint_metadata(fingerprint_in),
+        )
+        .await
+    }
+
+    async fn first_transaction_from_stake_addr(
+        &self,
+        stake_address_in: &str,
+    ) -> Result<Address, DataProviderError> {
+        self.cached(
+            format!("first_transaction_from_stake_addr:{stake_address_in}"),
+            Scope::Immutable,
+            self.provider.first_transaction_from_stake_addr(stake_address_in),
+        )
+        .await
+    }
+
+    async fn utxo_by_dataumhash(
+        &self,
+        addr: &str,
+        datumhash: &Vec<u8>,
+    ) -> Result<dcslc::TransactionUnspentOutput, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("utxo_by_dataumhash:{addr}:{}", hex::encode(datumhash)),
+            scope,
+            self.provider.utxo_by_dataumhash(addr, datumhash),
+        )
+        .await
+    }
+
+    async fn utxo_by_txid(
+        &self,
+        txhash: &Vec<u8>,
+        index: i16,
+    ) -> Result<dcslc::TransactionUnspentOutput, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("utxo_by_txid:{}#{index}", hex::encode(txhash)),
+            scope,
+            self.provider.utxo_by_txid(txhash, index),
+        )
+        .await
+    }
+
+    async fn utxo_tokens(
+        &self,
+        tx_id: i64,
+        tx_index: i16,
+    ) -> Result<Vec<CardanoNativeAssetView>, DataProviderError> {
+        self.cached(
+            format!("utxo_tokens:{tx_id}#{tx_index}"),
+            Scope::Immutable,
+            self.provider.utxo_tokens(tx_id, tx_index),
+        )
+        .await
+    }
+
+    async fn find_datums_for_tx(&self, txid: &Vec<u8>) -> Result<Vec<CDPDatum>, DataProviderError> {
+        self.cached(
+            format!("find_datums_for_tx:{}", hex::encode(txid)),
+            Scope::Immutable,
+            self.provider.find_datums_for_tx(txid),
+        )
+        .await
+    }
+
+    async fn slot(&self) -> Result<i64, DataProviderError> {
+        self.tip().await
+    }
+
+    async fn stakers_on_pool(
+        &self,
+        pool: &str,
+        epoch: i32,
+    ) -> Result<Vec<StakeDelegationView>, DataProviderError> {
+        let scope = self.past_epoch_scope(epoch as i64).await?;
+        self.cached(
+            format!("stakers_on_pool:{pool}:{epoch}"),
+            scope,
+            self.provider.stakers_on_pool(pool, epoch),
+        )
+        .await
+    }
+
+    async fn deligations_per_pool_epoch_intervall(
+        &self,
+        pool: &str,
+        start_epoch: i64,
+        end_epoch: i64,
+    ) -> Result<Vec<DelegationView>, DataProviderError> {
+        let scope = self.past_epoch_scope(end_epoch).await?;
+        self.cached(
+            format!("deligations_per_pool_epoch_intervall:{pool}:{start_epoch}:{end_epoch}"),
+            scope,
+            self.provider
+                .deligations_per_pool_epoch_intervall(pool, start_epoch, end_epoch),
+        )
+        .await
+    }
+
+    async fn pool_total_staked(&self, pool: &str, epoch: i32) -> Result<u64, DataProviderError> {
+        let scope = self.past_epoch_scope(epoch as i64).await?;
+        self.cached(
+            format!("pool_total_staked:{pool}:{epoch}"),
+            scope,
+            self.provider.pool_total_staked(pool, epoch),
+        )
+        .await
+    }
+
+    async fn current_epoch(&self) -> Result<i32, DataProviderError> {
+        self.epoch().await
+    }
+
+    async fn fingerprint(
+        &self,
+        policy: &str,
+        tokenname: &str,
+    ) -> Result<String, DataProviderError> {
+        self.cached(
+            format!("fingerprint:{policy}:{tokenname}"),
+            Scope::Immutable,
+            self.provider.fingerprint(policy, tokenname),
+        )
+        .await
+    }
+
+    async fn token_info(&self, fingerprint_in: &str) -> Result<TokenInfoView, DataProviderError> {
+        self.cached(
+            format!("token_info:{fingerprint_in}"),
+            Scope::Immutable,
+            self.provider.token_info(fingerprint_in),
+        )
+        .await
+    }
+
+    async fn stake_registration(
+        &self,
+        stake_addr_in: &str,
+    ) -> Result<Vec<StakeRegistrationView>, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("stake_registration:{stake_addr_in}"),
+            scope,
+            self.provider.stake_registration(stake_addr_in),
+        )
+        .await
+    }
+
+    async fn stake_deregistration(
+        &self,
+        stake_addr_in: &str,
+    ) -> Result<Vec<StakeDeregistrationView>, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("stake_deregistration:{stake_addr_in}"),
+            scope,
+            self.provider.stake_deregistration(stake_addr_in),
+        )
+        .await
+    }
+
+    async fn check_stakeaddr_registered(
+        &self,
+        stake_addr_in: &str,
+    ) -> Result<bool, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("check_stakeaddr_registered:{stake_addr_in}"),
+            scope,
+            self.provider.check_stakeaddr_registered(stake_addr_in),
+        )
+        .await
+    }
+
+    async fn lookup_token_holders(
+        &self,
+        fingerprint_in: &str,
+        min_amount: Option<&i64>,
+    ) -> Result<Vec<HoldingWalletView>, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("lookup_token_holders:{fingerprint_in}:{min_amount:?}"),
+            scope,
+            self.provider.lookup_token_holders(fingerprint_in, min_amount),
+        )
+        .await
+    }
+
+    async fn lookup_nft_token_holders(
+        &self,
+        policy: &str,
+    ) -> Result<Vec<HoldingWalletView>, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("lookup_nft_token_holders:{policy}"),
+            scope,
+            self.provider.lookup_nft_token_holders(policy),
+        )
+        .await
+    }
+
+    async fn pool_valid(&self, pool_id: &str) -> Result<bool, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("pool_valid:{pool_id}"),
+            scope,
+            self.provider.pool_valid(pool_id),
+        )
+        .await
+    }
+
+    async fn txhash_spent(&self, txhash: &str) -> Result<bool, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("txhash_spent:{txhash}"),
+            scope,
+            self.provider.txhash_spent(txhash),
+        )
+        .await
+    }
+
+    async fn addresses_exist(&self, addresses: &Vec<&str>) -> Result<Vec<bool>, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("addresses_exist:{}", addresses.join(",")),
+            scope,
+            self.provider.addresses_exist(addresses),
+        )
+        .await
+    }
+
+    async fn tx_history(
+        &self,
+        addresses: &Vec<&str>,
+        slot: Option<u64>,
+    ) -> Result<Vec<TxHistoryListView>, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("tx_history:{}:{slot:?}", addresses.join(",")),
+            scope,
+            self.provider.tx_history(addresses, slot),
+        )
+        .await
+    }
+
+    async fn retrieve_staked_amount(
+        &self,
+        epoch: i32,
+        stake_addr: &str,
+    ) -> Result<BigDecimal, DataProviderError> {
+        let scope = self.past_epoch_scope(epoch as i64).await?;
+        self.cached(
+            format!("retrieve_staked_amount:{stake_addr}:{epoch}"),
+            scope,
+            self.provider.retrieve_staked_amount(epoch, stake_addr),
+        )
+        .await
+    }
+
+    async fn retrieve_generated_rewards(
+        &self,
+        stake_addr: &str,
+    ) -> Result<Vec<RewardView>, DataProviderError> {
+        let scope = self.epoch_scope().await?;
+        self.cached(
+            format!("retrieve_generated_rewards:{stake_addr}"),
+            scope,
+            self.provider.retrieve_generated_rewards(stake_addr),
+        )
+        .await
+    }
+
+    // pages are passed through, a cursor only makes sense against the current state of the chain
+    async fn lookup_token_holders_page(
+        &self,
+        fingerprint_in: &str,
+        min_amount: Option<&i64>,
+        page: &PageRequest,
+    ) -> Result<Page<HoldingWalletView>, DataProviderError> {
+        self.provider
+            .lookup_token_holders_page(fingerprint_in, min_amount, page)
+            .await
+    }
+
+    async fn lookup_nft_token_holders_page(
+        &self,
+        policy: &str,
+        page: &PageRequest,
+    ) -> Result<Page<HoldingWalletView>, DataProviderError> {
+        self.provider.lookup_nft_token_holders_page(policy, page).await
+    }
+
+    async fn tx_history_page(
+        &self,
+        addresses: &Vec<&str>,
+        slot: Option<u64>,
+        page: &PageRequest,
+    ) -> Result<Page<TxHistoryListView>, DataProviderError> {
+        self.provider.tx_history_page(addresses, slot, page).await
+    }
+
+    async fn stakers_on_pool_page(
+        &self,
+        pool: &str,
+        epoch: i32,
+        page: &PageRequest,
+    ) -> Result<Page<StakeDelegationView>, DataProviderError> {
+        self.provider.stakers_on_pool_page(pool, epoch, page).await
+    }
+
+    async fn retrieve_generated_rewards_page(
+        &self,
+        stake_addr: &str,
+        page: &PageRequest,
+    ) -> Result<Page<RewardView>, DataProviderError> {
+        self.provider
+            .retrieve_generated_rewards_page(stake_addr, page)
+            .await
+    }
+
+    async fn address_tx_history(
+        &self,
+        addresses: &Vec<&str>,
+        slot: Option<u64>,
+    ) -> Result<Vec<AddressTxView>, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("address_tx_history:{}:{slot:?}", addresses.join(",")),
+            scope,
+            self.provider.address_tx_history(addresses, slot),
+        )
+        .await
+    }
+
+    async fn stake_address_tx_history(
+        &self,
+        stake_addr: &str,
+        slot: Option<u64>,
+    ) -> Result<Vec<AddressTxView>, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("stake_address_tx_history:{stake_addr}:{slot:?}"),
+            scope,
+            self.provider.stake_address_tx_history(stake_addr, slot),
+        )
+        .await
+    }
+
+    async fn stake_address_addresses(
+        &self,
+        stake_addr: &str,
+    ) -> Result<Vec<String>, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("stake_address_addresses:{stake_addr}"),
+            scope,
+            self.provider.stake_address_addresses(stake_addr),
+        )
+        .await
+    }
+
+    async fn stake_address_balance(
+        &self,
+        stake_addr: &str,
+    ) -> Result<StakeBalanceView, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("stake_address_balance:{stake_addr}"),
+            scope,
+            self.provider.stake_address_balance(stake_addr),
+        )
+        .await
+    }
+
+    async fn stake_address_history(
+        &self,
+        stake_addr: &str,
+        slot: Option<u64>,
+    ) -> Result<Vec<TxHistoryListView>, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("stake_address_history:{stake_addr}:{slot:?}"),
+            scope,
+            self.provider.stake_address_history(stake_addr, slot),
+        )
+        .await
+    }
+
+    async fn tx_included(&self, tx_hash: &str) -> Result<bool, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        self.cached(
+            format!("tx_included:{tx_hash}"),
+            scope,
+            self.provider.tx_included(tx_hash),
+        )
+        .await
+    }
+
+    async fn transaction(&self, tx_hash: &str) -> Result<TransactionView, DataProviderError> {
+        self.cached(
+            format!("transaction:{tx_hash}"),
+            Scope::Immutable,
+            self.provider.transaction(tx_hash),
+        )
+        .await
+    }
+
+    async fn utxos_by_refs(
+        &self,
+        refs: &[(TxHash, u32)],
+    ) -> Result<ResolvedUtxos, DataProviderError> {
+        let scope = self.tip_scope().await?;
+        let refs_key = refs
+            .iter()
+            .map(|(hash, index)| format!("{}#{index}", hash.to_hex()))
+            .collect::<Vec<_>>()
+            .join(",");
+        self.cached(
+            format!("utxos_by_refs:{refs_key}"),
+            scope,
+            self.provider.utxos_by_refs(refs),
+        )
+        .await
+    }
+
+    // exports are not cached, they are too large to hold and usually read once
+    fn script_utxos_stream<'a>(
+        &'a self,
+        addr: &'a str,
+    ) -> BoxStream<'a, Result<TransactionUnspentOutput, DataProviderError>> {
+        self.provider.script_utxos_stream(addr)
+    }
+
+    fn policy_holders_stream<'a>(
+        &'a self,
+        policy: &'a str,
+    ) -> BoxStream<'a, Result<HoldingWalletView, DataProviderError>> {
+        self.provider.policy_holders_stream(policy)
+    }
+
+    fn tx_history_stream<'a>(
+        &'a self,
+        addresses: &'a Vec<&'a str>,
+    ) -> BoxStream<'a, Result<TxHistoryListView, DataProviderError>> {
+        self.provider.tx_history_stream(addresses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CachedProvider, Config};
+    use crate::koios::KoiosProvider;
+    use crate::provider::CardanoDataProvider;
+    use httpmock::prelude::*;
+    use serde_json::json;
+    use std::time::Duration;
+
+    const ADDR: &str = "addr1vyqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qavsj8u";
+    const TX_HASH: &str = "1b07f1152e52ce0a9dbb561aa2e2d1750ca3a1a4141150a8bad342947a66a3a6";
+
+    fn provider(server: &MockServer) -> CachedProvider<KoiosProvider> {
+        CachedProvider::new(
+            KoiosProvider::new(crate::koios::Config {
+                url: server.base_url(),
+                api_token: String::new(),
+                network: crate::config::ChainWellKnownInfo::mainnet(),
+            }),
+            Config {
+                tip_check_interval: Duration::ZERO,
+                ..Config::default()
+            },
+        )
+    }
+
+    async fn tip(server: &MockServer, slot: i64) -> httpmock::Mock<'_> {
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/tip");
+                then.status(200).json_body(json!([{
+                    "hash": "e3c0c1a3c5b5b2a4ac2e3a3f0e6de7b3b2a0c1fcd0cdac1d0bbf2b07d66f3d7e",
+                    "epoch_no": 412,
+                    "abs_slot": slot
+                }]));
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_immutable_lookup_is_cached() {
+        let server = MockServer::start_async().await;
+        let asset_list = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/asset_list");
+                then.status(200).json_body(json!([{
+                    "policy_id": "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c",
+                    "asset_name": "746f6b656e",
+                    "fingerprint": "asset1token"
+                }]));
+            })
+            .await;
+
+        let cp = provider(&server);
+        let first = cp.token_info("asset1token").await.unwrap();
+        let second = cp.token_info("asset1token").await.unwrap();
+        assert_eq!(first.tokenname, second.tokenname);
+        asset_list.assert_hits_async(1).await;
+    }
+
+    #[tokio::test]
+    async fn test_tip_dependent_lookup_is_invalidated() {
+        let server = MockServer::start_async().await;
+        let old_tip = tip(&server, 1000).await;
+        let utxos = server
+            .mock_async(|when, then| {
+                when.method(POST).path("/address_utxos");
+                then.status(200).json_body(json!([{
+                    "tx_hash": TX_HASH,
+                    "tx_index": 0,
+                    "address": ADDR,
+                    "value": "1500000"
+                }]));
+            })
+            .await;
+
+        let cp = provider(&server);
+        cp.script_utxos(ADDR).await.unwrap();
+        cp.script_utxos(ADDR).await.unwrap();
+        utxos.assert_hits_async(1).await;
+
+        old_tip.delete_async().await;
+        tip(&server, 1020).await;
+        assert_eq!(cp.slot().await.unwrap(), 1020);
+        cp.script_utxos(ADDR).await.unwrap();
+        utxos.assert_hits_async(2).await;
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_not_cached() {
+        let server = MockServer::start_async().await;
+        let asset_list = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/asset_list");
+                then.status(200).json_body(json!([]));
+            })
+            .await;
+
+        let cp = provider(&server);
+        assert!(cp.token_info("asset1unknown").await.is_err());
+        assert!(cp.token_info("asset1unknown").await.is_err());
+        asset_list.assert_hits_async(2).await;
+    }
+}
@@ -0,0 +1,291 @@
+//! Typed backend sections of `ConfigRoot` and the provider factory shared by
+//! the REST server, the gRPC server and library users.
+//!
+//! ```toml
+//! [connectivity]
+//! provider = "koios"
+//!
+//! [koios]
+//! url = "https://api.koios.rest/api/v1"
+//! api_token = ""
+//! ```
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::error::DataProviderError;
+use super::{CardanoDataProvider, ProviderType};
+use crate::blockfrost::BlockfrostProvider;
+use crate::carb::CarbProvider;
+use crate::config::{ChainWellKnownInfo, ConfigRoot};
+use crate::dbsync::DBSyncProvider;
+use crate::koios::KoiosProvider;
+
+/// `[dbsync]` section, timeouts are given in seconds
+#[derive(Deserialize, Debug, Clone)]
+pub struct DbsyncConfig {
+    pub url: String,
+    pub max_connections: Option<u32>,
+    pub idle_timeout: Option<u64>,
+    pub connection_timeout: Option<u64>,
+    pub query_timeout: Option<u64>,
+}
+
+impl DbsyncConfig {
+    /// Values of zero are rejected by `DBSyncProvider::new`
+    pub fn provider_config(&self, network: ChainWellKnownInfo) -> crate::dbsync::Config {
+        let mut config = crate::dbsync::Config::new(self.url.clone());
+        config.network = network;
+
+        if let Some(max_connections) = self.max_connections {
+            config.max_connections = max_connections;
+        }
+        if let Some(idle_timeout) = self.idle_timeout {
+            config.idle_timeout = Some(Duration::from_secs(idle_timeout));
+        }
+        if let Some(connection_timeout) = self.connection_timeout {
+            config.connection_timeout = Duration::from_secs(connection_timeout);
+        }
+        if let Some(query_timeout) = self.query_timeout {
+            config.query_timeout = Duration::from_secs(query_timeout);
+        }
+        config
+    }
+}
+
+/// `[blockfrost]` section
+#[derive(Deserialize, Debug, Clone)]
+pub struct BlockfrostConfig {
+    pub project_id: String,
+    pub network_address: String,
+}
+
+impl BlockfrostConfig {
+    pub fn provider_config(&self, network: ChainWellKnownInfo) -> crate::blockfrost::Config {
+        crate::blockfrost::Config {
+            project_id: self.project_id.clone(),
+            network_address: self.network_address.clone(),
+            network,
+        }
+    }
+}
+
+/// `[koios]` section
+#[derive(Deserialize, Debug, Clone)]
+pub struct KoiosConfig {
+    pub url: String,
+    #[serde(default)]
+    pub api_token: String,
+}
+
+impl KoiosConfig {
+    pub fn provider_config(&self, network: ChainWellKnownInfo) -> crate::koios::Config {
+        crate::koios::Config {
+            url: self.url.clone(),
+            api_token: self.api_token.clone(),
+            network,
+        }
+    }
+}
+
+/// `[carb]` section
+#[derive(Deserialize, Debug, Clone)]
+pub struct CarbConfig {
+    pub url: String,
+    #[serde(default)]
+    pub api_token: String,
+}
+
+impl CarbConfig {
+    pub fn provider_config(&self, network: ChainWellKnownInfo) -> crate::carb::Config {
+        crate::carb::Config {
+            url: self.url.clone(),
+            api_token: self.api_token.clone(),
+            network,
+        }
+    }
+}
+
+fn missing_section(name: &str) -> DataProviderError {
+    DataProviderError::Custom(format!(
+        "provider '{name}' is selected but the [{name}] section is missing"
+    ))
+}
+
+/// Builds the provider selected by `connectivity.provider`
+pub fn build_provider(
+    config: &ConfigRoot,
+) -> Result<Arc<dyn CardanoDataProvider>, DataProviderError> {
+    let network = config.network()?;
+    let provider: Arc<dyn CardanoDataProvider> = match config.connectivity.provider {
+        ProviderType::Dbsync => Arc::new(
+            build_dbsync(config)?.ok_or_else(|| missing_section(ProviderType::Dbsync.as_str()))?,
+        ),
+        ProviderType::Blockfrost => {
+            let section = config
+                .blockfrost
+                .as_ref()
+                .ok_or_else(|| missing_section(ProviderType::Blockfrost.as_str()))?;
+            Arc::new(BlockfrostProvider::new(section.provider_config(network)))
+        }
+        ProviderType::Koios => {
+            let section = config
+                .koios
+                .as_ref()
+                .ok_or_else(|| missing_section(ProviderType::Koios.as_str()))?;
+            Arc::new(KoiosProvider::new(section.provider_config(network)))
+        }
+        ProviderType::Carb => {
+            let section = config
+                .carb
+                .as_ref()
+                .ok_or_else(|| missing_section(ProviderType::Carb.as_str()))?;
+            Arc::new(CarbProvider::new(section.provider_config(network)))
+        }
+    };
+    Ok(provider)
+}
+
+/// Builds a DBSync provider from the `[dbsync]` section, or from `connectivity.dbsync_url`
+/// for configurations written before the section existed. Some endpoints are only
+/// served by DBSync, so this is independent of the selected provider.
+pub fn build_dbsync(config: &ConfigRoot) -> Result<Option<DBSyncProvider>, DataProviderError> {
+    let network = config.network()?;
+    let dbsync_config = match (&config.dbsync, &config.connectivity.dbsync_url) {
+        (Some(section), _) => section.provider_config(network),
+        (None, Some(url)) if !url.is_empty() => {
+            let mut dbsync_config = crate::dbsync::Config::new(url.clone());
+            dbsync_config.network = network;
+            dbsync_config
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(DBSyncProvider::new(dbsync_config)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_root(toml: &str) -> ConfigRoot {
+        config::Config::builder()
+            .add_source(config::File::from_str(toml, config::FileFormat::Toml))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap()
+    }
+
+    const CONNECTIVITY: &str = r#"
+        submit_endpoint_1 = ""
+        submit_endpoint_2 = ""
+        submit_endpoint_3 = ""
+        protocol_parameter_path = ""
+        cert_pub_key = ""
+    "#;
+
+    #[test]
+    fn dbsync_section_takes_precedence_over_dbsync_url() {
+        let config = config_root(&format!(
+            r#"
+            [appconfigs]
+            None = {{}}
+
+            [network]
+            type = "Mainnet"
+
+            [connectivity]
+            provider = "dbsync"
+            dbsync_url = "postgres://legacy@localhost/db"
+            {CONNECTIVITY}
+
+            [dbsync]
+            url = "postgres://section@localhost/db"
+            max_connections = 3
+            query_timeout = 5
+            "#
+        ));
+
+        let dbsync = build_dbsync(&config).unwrap().unwrap();
+        assert_eq!(dbsync.config().db_path, "postgres://section@localhost/db");
+        assert_eq!(dbsync.config().max_connections, 3);
+        assert_eq!(dbsync.config().query_timeout, Duration::from_secs(5));
+        assert!(build_provider(&config).is_ok());
+    }
+
+    #[test]
+    fn zero_max_connections_is_rejected() {
+        let config = config_root(&format!(
+            r#"
+            [appconfigs]
+            None = {{}}
+
+            [network]
+            type = "Mainnet"
+
+            [connectivity]
+            provider = "dbsync"
+            {CONNECTIVITY}
+
+            [dbsync]
+            url = "postgres://section@localhost/db"
+            max_connections = 0
+            "#
+        ));
+
+        assert!(matches!(
+            build_dbsync(&config),
+            Err(DataProviderError::InvalidInput(_))
+        ));
+        assert!(build_provider(&config).is_err());
+
+        let mut dbsync_config = crate::dbsync::Config::new("postgres://localhost/db".to_owned());
+        dbsync_config.connection_timeout = Duration::ZERO;
+        assert!(matches!(
+            DBSyncProvider::new(dbsync_config),
+            Err(DataProviderError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn selected_provider_requires_its_section() {
+        let config = config_root(&format!(
+            r#"
+            [appconfigs]
+            None = {{}}
+
+            [network]
+            type = "Mainnet"
+
+            [connectivity]
+            provider = "koios"
+            {CONNECTIVITY}
+            "#
+        ));
+
+        assert!(build_dbsync(&config).unwrap().is_none());
+        assert!(build_provider(&config).is_err());
+    }
+
+    #[test]
+    fn network_section_is_required() {
+        let config = config::Config::builder()
+            .add_source(config::File::from_str(
+                &format!(
+                    r#"
+                    [appconfigs]
+                    None = {{}}
+
+                    [connectivity]
+                    provider = "koios"
+                    {CONNECTIVITY}
+                    "#
+                ),
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize::<ConfigRoot>();
+        assert!(config.is_err());
+    }
+}
@@ -1,5 +1,15 @@
 use thiserror::Error;
 
+use crate::blockfrost::error::DataProviderBlockfrostError;
+use crate::carb::error::DataProviderCarbError;
+use crate::dbsync::error::DataProviderDBSyncError;
+use crate::koios::error::DataProviderKoiosError;
+
+/// Errors of the data providers
+///
+/// Backend errors are sorted into the structured variants (`NotFound` to `RateLimited`) when
+/// they are converted, so callers can react to them independent of the backend. Errors which
+/// do not fit any of them keep the backend specific wrapper.
 #[allow(clippy::enum_variant_names)]
 #[derive(Error, Debug)]
 pub enum DataProviderError {
@@ -7,14 +17,34 @@ pub enum DataProviderError {
     Custom(String),
     #[error("error in data provider")]
     General,
+    #[error("requested value could not be found: {0}")]
+    NotFound(String),
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+    #[error("backend unavailable: {0}")]
+    BackendUnavailable(String),
+    #[error("timeout: {0}")]
+    Timeout(String),
+    #[error("not supported: {0}")]
+    Unsupported(String),
+    #[error("rate limited: {0}")]
+    RateLimited(String),
+    #[error("address {address} belongs to network {found}, the provider is connected to network {expected}")]
+    WrongNetwork {
+        expected: u8,
+        found: u8,
+        address: String,
+    },
+    #[error("invalid cursor: {0}")]
+    InvalidCursor(String),
     #[error(transparent)]
-    DBsyncError(#[from] crate::dbsync::error::DataProviderDBSyncError),
+    DBsyncError(DataProviderDBSyncError),
     #[error(transparent)]
-    BlockFrostError(#[from] crate::blockfrost::error::DataProviderBlockfrostError),
+    BlockFrostError(DataProviderBlockfrostError),
     #[error(transparent)]
-    KoiosError(#[from] crate::koios::error::DataProviderKoiosError),
+    KoiosError(DataProviderKoiosError),
     #[error(transparent)]
-    CarbError(#[from] crate::carb::error::DataProviderCarbError),
+    CarbError(DataProviderCarbError),
     #[error(transparent)]
     HexDecoderError(#[from] hex::FromHexError),
     #[error(transparent)]
@@ -40,3 +70,163 @@ impl From<cardano_serialization_lib::error::JsError> for DataProviderError {
         DataProviderError::Custom(err.to_string())
     }
 }
+
+/// Sorts an unsuccessful HTTP response of a backend, `None` for statuses without a matching variant
+fn from_status(status: u16, message: String) -> Option<DataProviderError> {
+    match status {
+        404 => Some(DataProviderError::NotFound(message)),
+        400 | 422 => Some(DataProviderError::InvalidInput(message)),
+        408 | 504 => Some(DataProviderError::Timeout(message)),
+        429 => Some(DataProviderError::RateLimited(message)),
+        401 | 402 | 403 | 500..=599 => Some(DataProviderError::BackendUnavailable(message)),
+        _ => None,
+    }
+}
+
+/// Sorts transport errors, also if the `reqwest::Error` is wrapped by another error
+fn from_request_error(err: &(dyn std::error::Error + 'static)) -> Option<DataProviderError> {
+    let mut source = Some(err);
+    while let Some(err) = source {
+        if let Some(e) = err.downcast_ref::<reqwest::Error>() {
+            return if e.is_timeout() {
+                Some(DataProviderError::Timeout(e.to_string()))
+            } else if e.is_connect() {
+                Some(DataProviderError::BackendUnavailable(e.to_string()))
+            } else {
+                e.status().and_then(|s| from_status(s.as_u16(), e.to_string()))
+            };
+        }
+        source = err.source();
+    }
+    None
+}
+
+impl From<DataProviderDBSyncError> for DataProviderError {
+    fn from(err: DataProviderDBSyncError) -> Self {
+        use DataProviderDBSyncError as DB;
+        match err {
+            DB::RequestValueNotFound(v) | DB::MintMetadataNotFound(v) => {
+                DataProviderError::NotFound(v)
+            }
+            DB::DieselError(diesel::result::Error::NotFound) => {
+                DataProviderError::NotFound("no matching record in dbsync".to_owned())
+            }
+            DB::Timeout(_) => DataProviderError::Timeout(err.to_string()),
+            // postgres cancelled the query after the `statement_timeout` of the pool,
+            // diesel has no error kind for SQLSTATE 57014
+            DB::DieselError(diesel::result::Error::DatabaseError(_, ref info))
+                if info
+                    .message()
+                    .contains("canceling statement due to statement timeout") =>
+            {
+                DataProviderError::Timeout(err.to_string())
+            }
+            DB::PoolError(_) | DB::DieselConnectionError(_) => {
+                DataProviderError::BackendUnavailable(err.to_string())
+            }
+            DB::HexError(_) | DB::ParseIntError(_) => {
+                DataProviderError::InvalidInput(err.to_string())
+            }
+            err => DataProviderError::DBsyncError(err),
+        }
+    }
+}
+
+impl From<DataProviderBlockfrostError> for DataProviderError {
+    fn from(err: DataProviderBlockfrostError) -> Self {
+        use DataProviderBlockfrostError as BF;
+        let sorted = match &err {
+            BF::RequestValueNotFound(v) => Some(DataProviderError::NotFound(v.clone())),
+            BF::Unsupported(v) => Some(DataProviderError::Unsupported(v.clone())),
+            BF::HexError(_) => Some(DataProviderError::InvalidInput(err.to_string())),
+            BF::BlockfrostError(e @ blockfrost::Error::Response { reason, .. }) => {
+                from_status(reason.status_code, e.to_string())
+            }
+            BF::BlockfrostError(e) => from_request_error(e),
+            _ => None,
+        };
+        sorted.unwrap_or(DataProviderError::BlockFrostError(err))
+    }
+}
+
+impl From<DataProviderKoiosError> for DataProviderError {
+    fn from(err: DataProviderKoiosError) -> Self {
+        use DataProviderKoiosError as KO;
+        let sorted = match &err {
+            KO::RequestValueNotFound(v) => Some(DataProviderError::NotFound(v.clone())),
+            KO::Unsupported(v) => Some(DataProviderError::Unsupported(v.clone())),
+            KO::HexError(_) => Some(DataProviderError::InvalidInput(err.to_string())),
+            KO::ResponseError { status, message } => from_status(*status, message.clone()),
+            KO::RequestError(e) => from_request_error(e),
+            _ => None,
+        };
+        sorted.unwrap_or(DataProviderError::KoiosError(err))
+    }
+}
+
+impl From<DataProviderCarbError> for DataProviderError {
+    fn from(err: DataProviderCarbError) -> Self {
+        use DataProviderCarbError as CB;
+        let sorted = match &err {
+            CB::RequestValueNotFound(v) => Some(DataProviderError::NotFound(v.clone())),
+            CB::Unsupported(v) => Some(DataProviderError::Unsupported(v.clone())),
+            CB::HexError(_) => Some(DataProviderError::InvalidInput(err.to_string())),
+            CB::ResponseError { status, message } => from_status(*status, message.clone()),
+            CB::RequestError(e) => from_request_error(e),
+            _ => None,
+        };
+        sorted.unwrap_or(DataProviderError::CarbError(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backend_errors_are_sorted() {
+        let not_found: DataProviderError = DataProviderKoiosError::ResponseError {
+            status: 404,
+            message: "unknown".to_owned(),
+        }
+        .into();
+        assert!(matches!(not_found, DataProviderError::NotFound(_)));
+
+        let limited: DataProviderError = DataProviderCarbError::ResponseError {
+            status: 429,
+            message: "slow down".to_owned(),
+        }
+        .into();
+        assert!(matches!(limited, DataProviderError::RateLimited(_)));
+
+        let down: DataProviderError = DataProviderKoiosError::ResponseError {
+            status: 503,
+            message: String::new(),
+        }
+        .into();
+        assert!(matches!(down, DataProviderError::BackendUnavailable(_)));
+
+        let timeout: DataProviderError =
+            DataProviderDBSyncError::Timeout(std::time::Duration::from_secs(1)).into();
+        assert!(matches!(timeout, DataProviderError::Timeout(_)));
+
+        let cancelled: DataProviderError =
+            DataProviderDBSyncError::DieselError(diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::Unknown,
+                Box::new("canceling statement due to statement timeout".to_owned()),
+            ))
+            .into();
+        assert!(matches!(cancelled, DataProviderError::Timeout(_)));
+
+        let unsupported: DataProviderError =
+            DataProviderBlockfrostError::Unsupported("fingerprints".to_owned()).into();
+        assert!(matches!(unsupported, DataProviderError::Unsupported(_)));
+
+        let other: DataProviderError = DataProviderKoiosError::ResponseError {
+            status: 302,
+            message: String::new(),
+        }
+        .into();
+        assert!(matches!(other, DataProviderError::KoiosError(_)));
+    }
+}
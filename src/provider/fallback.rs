@@ -0,0 +1,685 @@
+use super::error::DataProviderError;
+use super::{CardanoDataProvider, Page, PageRequest};
+use crate::models::{
+    AddressTxView, CDPDatum, CardanoNativeAssetView, DelegationView, HoldingWalletView, PoolView,
+    ResolvedUtxos, RewardView, StakeBalanceView, StakeDelegationView, StakeDeregistrationView,
+    StakeRegistrationView, TokenInfoView, TransactionView, TxHash, TxHistoryListView,
+};
+
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use cardano_serialization_lib::address::Address;
+use dcslc::{TransactionUnspentOutput, TransactionUnspentOutputs};
+use futures::future::join_all;
+use futures::stream::{self, BoxStream, StreamExt};
+use log::{debug, warn};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// providers whose tip is more than this many slots behind the most recent tip are skipped,
+    /// `None` disables the comparison
+    pub max_slot_lag: Option<i64>,
+    /// how long the result of a health check is reused before `alive()` and `slot()` are queried again
+    pub health_check_interval: Duration,
+    /// providers not answering the health check within this time are considered dead
+    pub health_check_timeout: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            max_slot_lag: None,
+            health_check_interval: Duration::from_secs(10),
+            health_check_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+struct Health {
+    checked: Instant,
+    usable: Vec<bool>,
+}
+
+/// Routes every call to the first usable provider of an ordered list
+///
+/// A provider is usable if it is `alive()` and, if `max_slot_lag` is set, its tip is not lagging.
+/// Calls failing for reasons another backend might not share (connection problems, timeouts,
+/// unsupported queries) are retried on the next provider. A provider failing this way is skipped
+/// until the next health check, unless it merely does not support the query. Errors like unknown
+/// values or invalid input are returned as they are.
+pub struct FallbackProvider {
+    config: Config,
+    providers: Vec<Box<dyn CardanoDataProvider>>,
+    health: Mutex<Option<Health>>,
+}
+
+impl FallbackProvider {
+    pub fn new(providers: Vec<Box<dyn CardanoDataProvider>>, config: Config) -> Self {
+        FallbackProvider {
+            config,
+            providers,
+            health: Mutex::new(None),
+        }
+    }
+
+    pub fn providers(&self) -> &[Box<dyn CardanoDataProvider>] {
+        &self.providers
+    }
+
+    /// `alive()` and, if the slot lag is checked, the tip of a provider
+    async fn probe(&self, provider: &dyn CardanoDataProvider) -> (bool, Option<i64>) {
+        let probe = async {
+            if !provider.alive().await {
+                return (false, None);
+            }
+            match self.config.max_slot_lag {
+                Some(_) => (true, provider.slot().await.ok()),
+                None => (true, None),
+            }
+        };
+        tokio::time::timeout(self.config.health_check_timeout, probe)
+            .await
+            .unwrap_or((false, None))
+    }
+
+    async fn check_health(&self) -> Vec<bool> {
+        let probes = join_all(self.providers.iter().map(|p| self.probe(p.as_ref()))).await;
+        let (alive, slots): (Vec<bool>, Vec<Option<i64>>) = probes.into_iter().unzip();
+
+        let usable = match (self.config.max_slot_lag, slots.iter().flatten().max()) {
+            (Some(lag), Some(tip)) => alive
+                .iter()
+                .zip(slots.iter())
+                .map(|(a, s)| *a && s.map(|s| tip - s <= lag).unwrap_or(false))
+                .collect(),
+            _ => alive,
+        };
+        debug!("fallback provider health: {:?}", usable);
+        usable
+    }
+
+    /// Indices of the providers to try in order, if no provider is usable all of them are tried
+    async fn candidates(&self) -> Vec<usize> {
+        let cached = self
+            .health
+            .lock()
+            .await
+            .as_ref()
+            .filter(|h| h.checked.elapsed() < self.config.health_check_interval)
+            .map(|h| h.usable.clone());
+        // the lock is not held while probing, so calls are not queued behind a slow provider
+        let usable = match cached {
+            Some(usable) => usable,
+            None => {
+                let usable = self.check_health().await;
+                *self.health.lock().await = Some(Health {
+                    checked: Instant::now(),
+                    usable: usable.clone(),
+                });
+                usable
+            }
+        };
+        let candidates: Vec<usize> = (0..self.providers.len())
+            .filter(|i| usable.get(*i).copied().unwrap_or(true))
+            .collect();
+        if candidates.is_empty() {
+            (0..self.providers.len()).collect()
+        } else {
+            candidates
+        }
+    }
+
+    /// A stream can not be retried once it yielded items, so it is served by the first candidate only
+    fn stream_first<'a, T: Send + 'a>(
+        &'a self,
+        open: impl FnOnce(&'a dyn CardanoDataProvider) -> BoxStream<'a, Result<T, DataProviderError>>
+            + Send
+            + 'a,
+    ) -> BoxStream<'a, Result<T, DataProviderError>> {
+        stream::once(async move {
+            match self.candidates().await.first() {
+                Some(&i) => open(self.providers[i].as_ref()),
+                None => stream::once(async {
+                    Err(DataProviderError::Custom("no data provider configured".to_owned()))
+                })
+                .boxed(),
+            }
+        })
+        .flatten()
+        .boxed()
+    }
+
+    async fn mark_unusable(&self, index: usize) {
+        if let Some(h) = self.health.lock().await.as_mut() {
+            if let Some(u) = h.usable.get_mut(index) {
+                *u = false;
+            }
+        }
+    }
+}
+
+/// Returns `false` for errors every backend would answer the same way
+fn should_fall_back(err: &DataProviderError) -> bool {
+    use crate::blockfrost::error::DataProviderBlockfrostError as BF;
+    use crate::carb::error::DataProviderCarbError as CB;
+    use crate::dbsync::error::DataProviderDBSyncError as DB;
+    use crate::koios::error::DataProviderKoiosError as KO;
+
+    !matches!(
+        err,
+        DataProviderError::NotFound(_)
+            | DataProviderError::InvalidInput(_)
+            | DataProviderError::WrongNetwork { .. }
+            | DataProviderError::InvalidCursor(_)
+            | DataProviderError::HexDecoderError(_)
+            | DataProviderError::CSLCommonError(_)
+            | DataProviderError::DBsyncError(DB::CSLCommonError(_))
+            | DataProviderError::BlockFrostError(BF::CSLCommonError(_))
+            | DataProviderError::KoiosError(KO::CSLCommonError(_))
+            | DataProviderError::CarbError(CB::CSLCommonError(_))
+    )
+}
+
+/// Calls the method on the candidates until one succeeds or fails with an error which is not retried
+macro_rules! route {
+    ($self:ident, $method:ident ( $($arg:expr),* )) => {{
+        let mut last_err = None;
+        for i in $self.candidates().await {
+            match $self.providers[i].$method($($arg),*).await {
+                Ok(r) => return Ok(r),
+                Err(e @ DataProviderError::Unsupported(_)) => {
+                    debug!("provider {} does not support {}", i, stringify!($method));
+                    last_err = Some(e);
+                }
+                Err(e) if should_fall_back(&e) => {
+                    warn!("provider {} failed on {}: {}", i, stringify!($method), e);
+                    $self.mark_unusable(i).await;
+                    last_err = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| {
+            DataProviderError::Custom("no data provider configured".to_owned())
+        }))
+    }};
+}
+
+#[async_trait]
+impl CardanoDataProvider for FallbackProvider {
+    async fn alive(&self) -> bool {
+        for provider in &self.providers {
+            if provider.alive().await {
+                return true;
+            }
+        }
+        false
+    }
+
+    async fn wallet_utxos(
+        &self,
+        stake_addr: &str,
+    ) -> Result<TransactionUnspentOutputs, DataProviderError> {
+        route!(self, wallet_utxos(stake_addr))
+    }
+
+    async fn script_utxos(
+        &self,
+        addr: &str,
+    ) -> Result<TransactionUnspentOutputs, DataProviderError> {
+        route!(self, script_utxos(addr))
+    }
+
+    async fn asset_utxos_on_addr(
+        &self,
+        addr: &str,
+    ) -> Result<TransactionUnspentOutputs, DataProviderError> {
+        route!(self, asset_utxos_on_addr(addr))
+    }
+
+    async fn active_pools(&self, page: usize) -> Result<Vec<PoolView>, DataProviderError> {
+        route!(self, active_pools(page))
+    }
+
+    async fn mint_metadata(
+        &self,
+        fingerprint_in: &str,
+    ) -> Result<TokenInfoView, DataProviderError> {
+        route!(self, mint_metadata(fingerprint_in))
+    }
+
+    async fn first_transaction_from_stake_addr(
+        &self,
+        stake_address_in: &str,
+    ) -> Result<Address, DataProviderError> {
+        route!(self, first_transaction_from_stake_addr(stake_address_in))
+    }
+
+    async fn utxo_by_dataumhash(
+        &self,
+        addr: &str,
+        datumhash: &Vec<u8>,
+    ) -> Result<dcslc::TransactionUnspentOutput, DataProviderError> {
+        route!(self, utxo_by_dataumhash(addr, datumhash))
+    }
+
+    async fn utxo_by_txid(
+        &self,
+        txhash: &Vec<u8>,
+        index: i16,
+    ) -> Result<dcslc::TransactionUnspentOutput, DataProviderError> {
+        route!(self, utxo_by_txid(txhash, index))
+    }
+
+    async fn utxo_tokens(
+        &self,
+        tx_id: i64,
+        tx_index: i16,
+    ) -> Result<Vec<CardanoNativeAssetView>, DataProviderError> {
+        route!(self, utxo_tokens(tx_id, tx_index))
+    }
+
+    async fn find_datums_for_tx(&self, txid: &Vec<u8>) -> Result<Vec<CDPDatum>, DataProviderError> {
+        route!(self, find_datums_for_tx(txid))
+    }
+
+    async fn slot(&self) -> Result<i64, DataProviderError> {
+        route!(self, slot())
+    }
+
+    async fn stakers_on_pool(
+        &self,
+        pool: &str,
+        epoch: i32,
+    ) -> Result<Vec<StakeDelegationView>, DataProviderError> {
+        route!(self, stakers_on_pool(pool, epoch))
+    }
+
+    async fn deligations_per_pool_epoch_intervall(
+        &self,
+        pool: &str,
+        start_epoch: i64,
+        end_epoch: i64,
+    ) -> Result<Vec<DelegationView>, DataProviderError> {
+        route!(self, deligations_per_pool_epoch_intervall(pool, start_epoch, end_epoch))
+    }
+
+    async fn pool_total_staked(&self, pool: &str, epoch: i32) -> Result<u64, DataProviderError> {
+        route!(self, pool_total_staked(pool, epoch))
+    }
+
+    async fn current_epoch(&self) -> Result<i32, DataProviderError> {
+        route!(self, current_epoch())
+    }
+
+    async fn fingerprint(
+        &self,
+        policy: &str,
+        tokenname: &str,
+    ) -> Result<String, DataProviderError> {
+        route!(self, fingerprint(policy, tokenname))
+    }
+
+    async fn token_info(&self, fingerprint_in: &str) -> Result<TokenInfoView, DataProviderError> {
+        route!(self, token_info(fingerprint_in))
+    }
+
+    async fn stake_registration(
+        &self,
+        stake_addr_in: &str,
+    ) -> Result<Vec<StakeRegistrationView>, DataProviderError> {
+        route!(self, stake_registration(stake_addr_in))
+    }
+
+    async fn stake_deregistration(
+        &self,
+        stake_addr_in: &str,
+    ) -> Result<Vec<StakeDeregistrationView>, DataProviderError> {
+        route!(self, stake_deregistration(stake_addr_in))
+    }
+
+    async fn check_stakeaddr_registered(
+        &self,
+        stake_addr_in: &str,
+    ) -> Result<bool, DataProviderError> {
+        route!(self, check_stakeaddr_registered(stake_addr_in))
+    }
+
+    async fn lookup_token_holders(
+        &self,
+        fingerprint_in: &str,
+        min_amount: Option<&i64>,
+    ) -> Result<Vec<HoldingWalletView>, DataProviderError> {
+        route!(self, lookup_token_holders(fingerprint_in, min_amount))
+    }
+
+    async fn lookup_nft_token_holders(
+        &self,
+        policy: &str,
+    ) -> Result<Vec<HoldingWalletView>, DataProviderError> {
+        route!(self, lookup_nft_token_holders(policy))
+    }
+
+    async fn pool_valid(&self, pool_id: &str) -> Result<bool, DataProviderError> {
+        route!(self, pool_valid(pool_id))
+    }
+
+    async fn txhash_spent(&self, txhash: &str) -> Result<bool, DataProviderError> {
+        route!(self, txhash_spent(txhash))
+    }
+
+    async fn addresses_exist(&self, addresses: &Vec<&str>) -> Result<Vec<bool>, DataProviderError> {
+        route!(self, addresses_exist(addresses))
+    }
+
+    async fn tx_history(
+        &self,
+        addresses: &Vec<&str>,
+        slot: Option<u64>,
+    ) -> Result<Vec<TxHistoryListView>, DataProviderError> {
+        route!(self, tx_history(addresses, slot))
+    }
+
+    async fn retrieve_staked_amount(
+        &self,
+        epoch: i32,
+        stake_addr: &str,
+    ) -> Result<BigDecimal, DataProviderError> {
+        route!(self, retrieve_staked_amount(epoch, stake_addr))
+    }
+
+    async fn retrieve_generated_rewards(
+        &self,
+        stake_addr: &str,
+    ) -> Result<Vec<RewardView>, DataProviderError> {
+        route!(self, retrieve_generated_rewards(stake_addr))
+    }
+
+    async fn lookup_token_holders_page(
+        &self,
+        fingerprint_in: &str,
+        min_amount: Option<&i64>,
+        page: &PageRequest,
+    ) -> Result<Page<HoldingWalletView>, DataProviderError> {
+        route!(self, lookup_token_holders_page(fingerprint_in, min_amount, page))
+    }
+
+    async fn lookup_nft_token_holders_page(
+        &self,
+        policy: &str,
+        page: &PageRequest,
+    ) -> Result<Page<HoldingWalletView>, DataProviderError> {
+        route!(self, lookup_nft_token_holders_page(policy, page))
+    }
+
+    async fn tx_history_page(
+        &self,
+        addresses: &Vec<&str>,
+        slot: Option<u64>,
+        page: &PageRequest,
+    ) -> Result<Page<TxHistoryListView>, DataProviderError> {
+        route!(self, tx_history_page(addresses, slot, page))
+    }
+
+    async fn stakers_on_pool_page(
+        &self,
+        pool: &str,
+        epoch: i32,
+        page: &PageRequest,
+    ) -> Result<Page<StakeDelegationView>, DataProviderError> {
+        route!(self, stakers_on_pool_page(pool, epoch, page))
+    }
+
+    async fn retrieve_generated_rewards_page(
+        &self,
+        stake_addr: &str,
+        page: &PageRequest,
+    ) -> Result<Page<RewardView>, DataProviderError> {
+        route!(self, retrieve_generated_rewards_page(stake_addr, page))
+    }
+
+    async fn address_tx_history(
+        &self,
+        addresses: &Vec<&str>,
+        slot: Option<u64>,
+    ) -> Result<Vec<AddressTxView>, DataProviderError> {
+        route!(self, address_tx_history(addresses, slot))
+    }
+
+    async fn stake_address_tx_history(
+        &self,
+        stake_addr: &str,
+        slot: Option<u64>,
+    ) -> Result<Vec<AddressTxView>, DataProviderError> {
+        route!(self, stake_address_tx_history(stake_addr, slot))
+    }
+
+    async fn stake_address_addresses(
+        &self,
+        stake_addr: &str,
+    ) -> Result<Vec<String>, DataProviderError> {
+        route!(self, stake_address_addresses(stake_addr))
+    }
+
+    async fn stake_address_balance(
+        &self,
+        stake_addr: &str,
+    ) -> Result<StakeBalanceView, DataProviderError> {
+        route!(self, stake_address_balance(stake_addr))
+    }
+
+    async fn stake_address_history(
+        &self,
+        stake_addr: &str,
+        slot: Option<u64>,
+    ) -> Result<Vec<TxHistoryListView>, DataProviderError> {
+        route!(self, stake_address_history(stake_addr, slot))
+    }
+
+    async fn tx_included(&self, tx_hash: &str) -> Result<bool, DataProviderError> {
+        route!(self, tx_included(tx_hash))
+    }
+
+    async fn transaction(&self, tx_hash: &str) -> Result<TransactionView, DataProviderError> {
+        route!(self, transaction(tx_hash))
+    }
+
+    async fn utxos_by_refs(
+        &self,
+        refs: &[(TxHash, u32)],
+    ) -> Result<ResolvedUtxos, DataProviderError> {
+        route!(self, utxos_by_refs(refs))
+    }
+
+    fn script_utxos_stream<'a>(
+        &'a self,
+        addr: &'a str,
+    ) -> BoxStream<'a, Result<TransactionUnspentOutput, DataProviderError>> {
+        self.stream_first(move |p| p.script_utxos_stream(addr))
+    }
+
+    fn policy_holders_stream<'a>(
+        &'a self,
+        policy: &'a str,
+    ) -> BoxStream<'a, Result<HoldingWalletView, DataProviderError>> {
+        self.stream_first(move |p| p.policy_holders_stream(policy))
+    }
+
+    fn tx_history_stream<'a>(
+        &'a self,
+        addresses: &'a Vec<&'a str>,
+    ) -> BoxStream<'a, Result<TxHistoryListView, DataProviderError>> {
+        self.stream_first(move |p| p.tx_history_stream(addresses))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, FallbackProvider};
+    use crate::koios::KoiosProvider;
+    use crate::provider::error::DataProviderError;
+    use crate::provider::CardanoDataProvider;
+    use httpmock::prelude::*;
+    use serde_json::json;
+    use std::time::Duration;
+
+    const STAKE_ADDR: &str = "stake1u9jx2en8dp5k56mvd4hx7ur3wfehgatkwau8j7nm037hulctderj8";
+
+    fn koios(server: &MockServer) -> Box<dyn CardanoDataProvider> {
+        Box::new(KoiosProvider::new(crate::koios::Config {
+            url: server.base_url(),
+            api_token: String::new(),
+            network: crate::config::ChainWellKnownInfo::mainnet(),
+        }))
+    }
+
+    async fn tip(server: &MockServer, status: u16, slot: i64) {
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/tip");
+                then.status(status).json_body(json!([{
+                    "hash": "e3c0c1a3c5b5b2a4ac2e3a3f0e6de7b3b2a0c1fcd0cdac1d0bbf2b07d66f3d7e",
+                    "epoch_no": 412,
+                    "abs_slot": slot
+                }]));
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_skips_dead_provider() {
+        let primary = MockServer::start_async().await;
+        let secondary = MockServer::start_async().await;
+        tip(&primary, 503, 0).await;
+        tip(&secondary, 200, 5000).await;
+
+        let fp = FallbackProvider::new(vec![koios(&primary), koios(&secondary)], Config::default());
+        assert!(fp.alive().await);
+        assert_eq!(fp.slot().await.unwrap(), 5000);
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_on_server_error() {
+        let primary = MockServer::start_async().await;
+        let secondary = MockServer::start_async().await;
+        tip(&primary, 200, 5000).await;
+        tip(&secondary, 200, 5000).await;
+        let failing = primary
+            .mock_async(|when, then| {
+                when.method(POST).path("/account_rewards");
+                then.status(500).body("internal error");
+            })
+            .await;
+        secondary
+            .mock_async(|when, then| {
+                when.method(POST).path("/account_rewards");
+                then.status(200).json_body(json!([{
+                    "stake_address": STAKE_ADDR,
+                    "rewards": [{ "earned_epoch": 400, "spendable_epoch": 402, "amount": "42" }]
+                }]));
+            })
+            .await;
+
+        let fp = FallbackProvider::new(vec![koios(&primary), koios(&secondary)], Config::default());
+        let rewards = fp.retrieve_generated_rewards(STAKE_ADDR).await.unwrap();
+        assert_eq!(rewards[0].amount, 42);
+
+        // the failed provider is skipped until the next health check
+        fp.retrieve_generated_rewards(STAKE_ADDR).await.unwrap();
+        failing.assert_hits_async(1).await;
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_keeps_provider_usable() {
+        let primary = MockServer::start_async().await;
+        let secondary = MockServer::start_async().await;
+        tip(&primary, 200, 5000).await;
+        tip(&secondary, 200, 5000).await;
+        let rewards = primary
+            .mock_async(|when, then| {
+                when.method(POST).path("/account_rewards");
+                then.status(200).json_body(json!([]));
+            })
+            .await;
+
+        let fp = FallbackProvider::new(vec![koios(&primary), koios(&secondary)], Config::default());
+        assert!(matches!(
+            fp.utxo_tokens(1, 0).await,
+            Err(DataProviderError::Unsupported(_))
+        ));
+
+        fp.retrieve_generated_rewards(STAKE_ADDR).await.unwrap();
+        rewards.assert_hits_async(1).await;
+    }
+
+    #[tokio::test]
+    async fn test_skips_lagging_provider() {
+        let primary = MockServer::start_async().await;
+        let secondary = MockServer::start_async().await;
+        tip(&primary, 200, 1000).await;
+        tip(&secondary, 200, 5000).await;
+
+        let fp = FallbackProvider::new(
+            vec![koios(&primary), koios(&secondary)],
+            Config {
+                max_slot_lag: Some(100),
+                ..Config::default()
+            },
+        );
+        assert_eq!(fp.slot().await.unwrap(), 5000);
+    }
+
+    #[tokio::test]
+    async fn test_skips_slow_provider() {
+        let primary = MockServer::start_async().await;
+        let secondary = MockServer::start_async().await;
+        primary
+            .mock_async(|when, then| {
+                when.method(GET).path("/tip");
+                then.status(200)
+                    .delay(Duration::from_secs(2))
+                    .json_body(json!([{
+                        "hash": "e3c0c1a3c5b5b2a4ac2e3a3f0e6de7b3b2a0c1fcd0cdac1d0bbf2b07d66f3d7e",
+                        "epoch_no": 412,
+                        "abs_slot": 1000
+                    }]));
+            })
+            .await;
+        tip(&secondary, 200, 5000).await;
+
+        let fp = FallbackProvider::new(
+            vec![koios(&primary), koios(&secondary)],
+            Config {
+                health_check_timeout: Duration::from_millis(200),
+                ..Config::default()
+            },
+        );
+        assert_eq!(fp.slot().await.unwrap(), 5000);
+    }
+
+    #[tokio::test]
+    async fn test_not_found_is_not_retried() {
+        let primary = MockServer::start_async().await;
+        let secondary = MockServer::start_async().await;
+        tip(&primary, 200, 5000).await;
+        tip(&secondary, 200, 5000).await;
+        primary
+            .mock_async(|when, then| {
+                when.method(POST).path("/pool_info");
+                then.status(200).json_body(json!([]));
+            })
+            .await;
+        let untouched = secondary
+            .mock_async(|when, then| {
+                when.method(POST).path("/pool_info");
+                then.status(200).json_body(json!([]));
+            })
+            .await;
+
+        let fp = FallbackProvider::new(vec![koios(&primary), koios(&secondary)], Config::default());
+        assert!(fp.pool_valid("pool1unknown").await.is_err());
+        untouched.assert_hits_async(0).await;
+    }
+}
@@ -0,0 +1,146 @@
+//! Cursor based pagination for listings which can grow without bound.
+//!
+//! A cursor is opaque to the caller: it is the key of the last returned row,
+//! tagged with the kind of key and base64 encoded. Cursors are only valid for
+//! the provider which issued them.
+use base64::{engine::general_purpose, Engine as _};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::error::DataProviderError;
+
+/// Upper bound for the page size, larger limits are reduced to it
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// Page size and position of a paginated request, no cursor requests the first page
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PageRequest {
+    pub limit: usize,
+    pub cursor: Option<String>,
+}
+
+impl PageRequest {
+    pub fn new(limit: usize, cursor: Option<String>) -> Self {
+        PageRequest { limit, cursor }
+    }
+
+    /// requested page size within `1..=MAX_PAGE_SIZE`
+    pub fn limit(&self) -> usize {
+        self.limit.clamp(1, MAX_PAGE_SIZE)
+    }
+
+    /// decodes the cursor, `tag` names the kind of key the caller expects
+    pub(crate) fn key<K: DeserializeOwned>(
+        &self,
+        tag: &str,
+    ) -> Result<Option<K>, DataProviderError> {
+        let cursor = match &self.cursor {
+            Some(cursor) => cursor,
+            None => return Ok(None),
+        };
+        let invalid = || DataProviderError::InvalidCursor(cursor.clone());
+        let decoded = general_purpose::URL_SAFE_NO_PAD
+            .decode(cursor)
+            .map_err(|_| invalid())?;
+        let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+        match decoded.split_once(':') {
+            Some((t, key)) if t == tag => {
+                serde_json::from_str(key).map(Some).map_err(|_| invalid())
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// One page of a listing, `next_cursor` is `None` on the last page
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// Builds a page from at most `limit + 1` rows read after the cursor,
+    /// the additional row only signals that another page follows
+    pub(crate) fn from_rows<K: Serialize>(
+        mut rows: Vec<T>,
+        limit: usize,
+        tag: &str,
+        key: impl Fn(&T) -> K,
+    ) -> Self {
+        let next_cursor = if rows.len() > limit {
+            rows.truncate(limit);
+            rows.last().map(|last| encode_cursor(tag, &key(last)))
+        } else {
+            None
+        };
+        Page {
+            items: rows,
+            next_cursor,
+        }
+    }
+
+    /// Pages through a complete result set, the cursor is an offset. Used by providers
+    /// which can not paginate the query itself.
+    pub(crate) fn from_all(
+        mut items: Vec<T>,
+        request: &PageRequest,
+    ) -> Result<Self, DataProviderError> {
+        let offset = request.key::<usize>(OFFSET)?.unwrap_or(0).min(items.len());
+        let limit = request.limit();
+        let rest = items.split_off(offset);
+        let mut page = Page::from_rows(rest, limit, OFFSET, |_| 0usize);
+        // the key of the last row is its position in the complete result set
+        if page.next_cursor.is_some() {
+            page.next_cursor = Some(encode_cursor(OFFSET, &(offset + limit)));
+        }
+        Ok(page)
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+        }
+    }
+}
+
+const OFFSET: &str = "offset";
+
+fn encode_cursor<K: Serialize>(tag: &str, key: &K) -> String {
+    let key = serde_json::to_string(key).expect("cursor keys are plain values");
+    general_purpose::URL_SAFE_NO_PAD.encode(format!("{tag}:{key}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_through_a_complete_result_set() {
+        let items: Vec<u32> = (0..5).collect();
+
+        let first = Page::from_all(items.clone(), &PageRequest::new(2, None)).unwrap();
+        assert_eq!(first.items, vec![0, 1]);
+        let second =
+            Page::from_all(items.clone(), &PageRequest::new(2, first.next_cursor)).unwrap();
+        assert_eq!(second.items, vec![2, 3]);
+        let last = Page::from_all(items, &PageRequest::new(2, second.next_cursor)).unwrap();
+        assert_eq!(last.items, vec![4]);
+        assert!(last.next_cursor.is_none());
+    }
+
+    #[test]
+    fn rejects_foreign_cursors() {
+        let page = Page::from_rows(vec![(7i64, "a"), (9, "b")], 1, "id", |row| row.0);
+        let request = PageRequest::new(1, page.next_cursor);
+        assert_eq!(request.key::<i64>("id").unwrap(), Some(7));
+        assert!(matches!(
+            request.key::<usize>(OFFSET),
+            Err(DataProviderError::InvalidCursor(_))
+        ));
+        assert!(PageRequest::new(1, Some("%%".to_owned()))
+            .key::<i64>("id")
+            .is_err());
+    }
+}
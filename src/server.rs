@@ -1,13 +1,18 @@
-mod auth;
-mod error;
+pub(crate) mod auth;
+pub(crate) mod error;
 mod examples;
 mod filter;
 mod handler;
 
 pub(crate) mod models;
+pub(crate) mod submit;
+pub(crate) mod tx_cache;
 extern crate pretty_env_logger;
 
+use crate::config::{AyaConfig, ChainWellKnownInfo, ConfigRoot, GrpcConfig};
+use crate::provider::CardanoDataProvider;
 use crate::server::models::LocalTxCache;
+use crate::{DBSyncProvider, DataProvider, DataProviderError};
 
 use self::models::Clients;
 use nonzero_ext::nonzero;
@@ -20,23 +25,141 @@ use rweb::rt::IndexMap;
 use rweb::warp::Filter;
 use rweb::{openapi, openapi_docs};
 use tokio::sync::Mutex;
-use tonic::transport::Server;
+use tonic::transport::{server::Router, Server};
 
 use std::borrow::Cow;
-use std::net::SocketAddr;
+use std::sync::RwLock;
 use std::{collections::HashMap, env, sync::Arc};
 
 const DEFAULT_HOST: &str = "127.0.0.1";
 const DEFAULT_PORT: &str = "4123";
 
-pub async fn serve(dbsync_url: Option<String>) -> Result<(), error::RESTError> {
+/// Providers shared by the REST, websocket and gRPC handlers, set once by `serve`
+static PROVIDERS: RwLock<Option<Providers>> = RwLock::new(None);
+
+#[derive(Clone)]
+struct Providers {
+    provider: Arc<dyn CardanoDataProvider>,
+    dbsync: Option<DBSyncProvider>,
+    network: ChainWellKnownInfo,
+    tx_cache: LocalTxCache,
+    submit_endpoints: Vec<String>,
+    aya: Option<AyaConfig>,
+}
+
+fn providers() -> Providers {
+    PROVIDERS
+        .read()
+        .unwrap()
+        .clone()
+        .expect("providers are set when the server starts")
+}
+
+/// The provider selected by `connectivity.provider`
+pub(crate) fn provider() -> Arc<dyn CardanoDataProvider> {
+    providers().provider
+}
+
+/// The network the providers are connected to
+pub(crate) fn network() -> ChainWellKnownInfo {
+    providers().network
+}
+
+/// Transactions submitted through cdp which are not on-chain yet
+pub(crate) fn tx_cache() -> LocalTxCache {
+    providers().tx_cache
+}
+
+/// submit-api endpoints transactions are submitted to
+pub(crate) fn submit_endpoints() -> Vec<String> {
+    providers().submit_endpoints
+}
+
+/// Aya contracts followed by the gRPC service, fails if the `[aya]` section is missing
+pub(crate) fn aya() -> Result<AyaConfig, DataProviderError> {
+    providers().aya.ok_or_else(|| {
+        DataProviderError::Unsupported(
+            "this request needs the Aya contracts, configure the [aya] section".to_string(),
+        )
+    })
+}
+
+/// DBSync provider for the requests only DBSync can answer, fails if no database is configured
+pub(crate) fn dbsync() -> Result<DataProvider<DBSyncProvider>, DataProviderError> {
+    providers().dbsync.map(DataProvider::new).ok_or_else(|| {
+        DataProviderError::Unsupported(
+            "this request needs a dbsync connection, configure the [dbsync] section".to_string(),
+        )
+    })
+}
+
+/// gRPC services configured by `[grpc]`: the Aya chain follower and the chain query service,
+/// which require a JWT unless `grpc.auth` is false, and the health and reflection services
+async fn grpc_router(config: &GrpcConfig) -> Result<Router, error::RESTError> {
+    use super::grpc::aya_cardano::chain_follower_request_service_server::ChainFollowerRequestServiceServer;
+    use super::grpc::chain_query::proto::chain_query_server::ChainQueryServer;
+    use super::grpc::chain_query::ChainQueryRPCServer;
+    use super::grpc::AyaCardanoRPCServer;
+
+    let interceptor: fn(tonic::Request<()>) -> Result<tonic::Request<()>, tonic::Status> =
+        if config.auth() {
+            super::grpc::auth::authorize
+        } else {
+            log::warn!("gRPC authentication is disabled");
+            Ok
+        };
+
+    let (mut health, health_service) = tonic_health::server::health_reporter();
+    health
+        .set_serving::<ChainFollowerRequestServiceServer<AyaCardanoRPCServer>>()
+        .await;
+    health
+        .set_serving::<ChainQueryServer<ChainQueryRPCServer<dyn CardanoDataProvider>>>()
+        .await;
+
+    let reflection = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(super::grpc::FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+        .build()
+        .map_err(|e| DataProviderError::Custom(format!("gRPC reflection: {e}")))?;
+
+    let mut builder = Server::builder();
+    if let Some(tls) = config.tls()? {
+        builder = builder
+            .tls_config(tls)
+            .map_err(|e| DataProviderError::Custom(format!("gRPC TLS: {e}")))?;
+    }
+
+    Ok(builder
+        .add_service(health_service)
+        .add_service(reflection)
+        .add_service(ChainFollowerRequestServiceServer::with_interceptor(
+            AyaCardanoRPCServer::default(),
+            interceptor,
+        ))
+        .add_service(ChainQueryServer::with_interceptor(
+            ChainQueryRPCServer::new(provider()),
+            interceptor,
+        )))
+}
+
+pub async fn serve(config: &ConfigRoot) -> Result<(), error::RESTError> {
     if env::var_os("RUST_LOG").is_none() {
         env::set_var("RUST_LOG", "info");
     }
 
-    if let Some(url) = dbsync_url {
-        env::set_var("DBSYNC_URL", &url)
-    }
+    let provider = config.provider()?;
+    let dbsync = config.dbsync_provider()?;
+    let network = config.network()?;
+    let tx_cache: LocalTxCache = Arc::new(Mutex::new(HashMap::new()));
+    *PROVIDERS.write().unwrap() = Some(Providers {
+        provider,
+        dbsync,
+        network,
+        tx_cache: tx_cache.clone(),
+        submit_endpoints: config.submit_endpoints(),
+        aya: config.aya.clone(),
+    });
 
     let host: String = env::var("POD_HOST").unwrap_or_else(|_| DEFAULT_HOST.to_string());
     let port = env::var("POD_PORT").unwrap_or_else(|_| DEFAULT_PORT.to_string());
@@ -45,7 +168,12 @@ pub async fn serve(dbsync_url: Option<String>) -> Result<(), error::RESTError> {
     let clients: Clients = Arc::new(Mutex::new(HashMap::new()));
 
     //Transaction Cache
-    let _tx_cache: LocalTxCache = Arc::new(Mutex::new(HashMap::new()));
+    let tx_cache_ttl = config
+        .connectivity
+        .tx_cache_ttl
+        .map(std::time::Duration::from_secs)
+        .unwrap_or(tx_cache::DEFAULT_TTL);
+    tokio::task::spawn(tx_cache::prune_loop(tx_cache, tx_cache_ttl));
 
     //Rate Limitation
     let lim =
@@ -183,28 +311,32 @@ pub async fn serve(dbsync_url: Option<String>) -> Result<(), error::RESTError> {
             "Sec-GPC",
         ]);
 
-    let addr = "0.0.0.0:50051".parse::<SocketAddr>().unwrap();
-    let rpc_server = super::grpc::AyaCardanoRPCServer::default();
+    let grpc = grpc_router(&config.grpc).await?;
+    let grpc_addr = config.grpc.address()?;
 
-    let mut set = vec![]; //tokio::task::JoinSet::new();
-    set.push(tokio::task::spawn(async move {
-            Server::builder()
-                .add_service(super::grpc::aya_cardano::chain_follower_request_service_server::ChainFollowerRequestServiceServer::new(rpc_server))
-                .serve(addr).await.unwrap();}
-        ));
+    let grpc = async move {
+        log::info!("Starting gRPC server on {grpc_addr}");
+        grpc.serve(grpc_addr)
+            .await
+            .map_err(|e| DataProviderError::Custom(format!("gRPC server stopped: {e}")))
+    };
 
     // Start server
-    set.push(tokio::task::spawn(
+    let rest = async move {
         rweb::serve(
             filter
                 .or(openapi_docs(spec))
                 .or(handler::ws(lim, c))
+                .recover(error::handle_rejection)
                 .with(cors),
         )
-        .run(socket),
-    ));
+        .run(socket)
+        .await;
+        Ok::<_, DataProviderError>(())
+    };
 
-    futures::future::join_all(set).await;
+    // a failing gRPC listener stops the REST server as well
+    tokio::try_join!(grpc, rest)?;
 
     Ok(())
 }
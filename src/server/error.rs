@@ -3,6 +3,7 @@ use std::convert::Infallible;
 use thiserror::Error;
 
 use super::models::ErrorResponse;
+use crate::provider::error::DataProviderError;
 
 #[allow(clippy::enum_variant_names)]
 #[derive(Error, Debug)]
@@ -23,11 +24,29 @@ pub enum RESTError {
     RateLimitReachedError,
     #[error("internal error: {:?}", self)]
     InternalError,
+    #[error(transparent)]
+    Provider(#[from] DataProviderError),
 }
 
 impl rweb::warp::reject::Reject for RESTError {}
 
-pub async fn _handle_rejection(err: Rejection) -> std::result::Result<impl Reply, Infallible> {
+/// HTTP status of a provider error
+pub fn status_code(err: &DataProviderError) -> StatusCode {
+    match err {
+        DataProviderError::NotFound(_) => StatusCode::NOT_FOUND,
+        DataProviderError::InvalidInput(_)
+        | DataProviderError::WrongNetwork { .. }
+        | DataProviderError::InvalidCursor(_)
+        | DataProviderError::HexDecoderError(_) => StatusCode::BAD_REQUEST,
+        DataProviderError::Unsupported(_) => StatusCode::NOT_IMPLEMENTED,
+        DataProviderError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+        DataProviderError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+        DataProviderError::BackendUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+pub async fn handle_rejection(err: Rejection) -> std::result::Result<impl Reply, Infallible> {
     let (code, message) = if err.is_not_found() {
         (StatusCode::NOT_FOUND, "Not Found".to_string())
     } else if let Some(e) = err.find::<RESTError>() {
@@ -38,6 +57,7 @@ pub async fn _handle_rejection(err: Rejection) -> std::result::Result<impl Reply
             }
             RESTError::Custom(s) => (StatusCode::INTERNAL_SERVER_ERROR, s.to_string()),
             RESTError::RateLimitReachedError => (StatusCode::TOO_MANY_REQUESTS, e.to_string()),
+            RESTError::Provider(e) => (status_code(e), e.to_string()),
             _ => (StatusCode::BAD_REQUEST, e.to_string()),
         }
     } else if err.find::<rweb::warp::reject::MethodNotAllowed>().is_some() {
@@ -72,3 +92,25 @@ impl From<jsonwebtoken::errors::Error> for RESTError {
         RESTError::Custom(err.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn provider_errors_map_to_status_codes() {
+        let cases = [
+            (DataProviderError::NotFound("tx".to_owned()), 404),
+            (DataProviderError::InvalidInput("policy".to_owned()), 400),
+            (DataProviderError::InvalidCursor("%%".to_owned()), 400),
+            (DataProviderError::Unsupported("utxo_tokens".to_owned()), 501),
+            (DataProviderError::RateLimited("koios".to_owned()), 429),
+            (DataProviderError::Timeout("dbsync".to_owned()), 504),
+            (DataProviderError::BackendUnavailable("carb".to_owned()), 503),
+            (DataProviderError::Custom("other".to_owned()), 500),
+        ];
+        for (err, code) in cases {
+            assert_eq!(status_code(&err).as_u16(), code, "{err}");
+        }
+    }
+}
@@ -6,12 +6,12 @@ use crate::server::handler::handler_rest::aya::{
 };
 
 use super::{handler::handler_websocket::handle_ws_client, models::Clients};
-use handler_rest::info;
+use handler_rest::{info, tx};
 use ratelimit_meter::{DirectRateLimiter, LeakyBucket};
 use rweb::*;
 
 /// REST API
-#[router("/api", services(alive, info, aya))]
+#[router("/api", services(alive, info, aya, tx))]
 //#[header("Authorization", "*")]
 #[openapi(id = "api", description = " REST API")]
 #[cors(origins("*"), methods(get, post, option,), headers("*"), max_age = 600)]
@@ -1,30 +1,52 @@
 pub(crate) mod aya;
 pub(crate) mod info;
+pub(crate) mod tx;
 
 use info::{
     address_exists, handle_asset_for_stake_address, handle_get_asset_for_addresses, is_nft,
     mint_metadata, retrieve_active_pools, token_supply, tx_history, tx_history_discover,
-    utxos_per_addr, retrieve_staked_amount, retrieve_generated_rewards,
+    utxos_per_addr, retrieve_staked_amount, retrieve_generated_rewards, slot_to_time,
+    time_to_slot, epoch_bounds, export_utxos, export_policy_holders, export_tx_history,
+    address_tx_history, stake_address_balance, stake_address_addresses, stake_address_history,
+    utxos_by_refs, decode_datum,
 };
 
 use rweb::*;
+use tx::{pending_txs, submit_tx, track_tx};
 
 /// Info endpoints
 #[router(
     "/info",
     services(
         utxos_per_addr,
+        utxos_by_refs,
+        decode_datum,
         address_exists,
         mint_metadata,
         tx_history_discover,
         tx_history,
+        address_tx_history,
         handle_asset_for_stake_address,
         handle_get_asset_for_addresses,
         retrieve_active_pools,
         is_nft,
         retrieve_staked_amount,
         retrieve_generated_rewards,
+        stake_address_balance,
+        stake_address_addresses,
+        stake_address_history,
+        slot_to_time,
+        time_to_slot,
+        epoch_bounds,
+        export_utxos,
+        export_policy_holders,
+        export_tx_history,
     )
 )]
 #[openapi(id = "api.info", description = "Information Requests")]
 pub async fn info() {}
+
+/// Transaction endpoints
+#[router("/tx", services(submit_tx, track_tx, pending_txs))]
+#[openapi(id = "api.tx", description = "Transactions")]
+pub async fn tx() {}
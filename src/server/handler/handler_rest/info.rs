@@ -1,36 +1,27 @@
+use std::convert::Infallible;
 use std::str::from_utf8;
 
-use crate::dbsync::get_stake_address_utxos_dep;
-use crate::models::{AssetHandle, PoolView};
+use crate::datum;
+use crate::models::{parse_output_ref, AssetHandle, PoolView, ResolvedUtxosView, TxHash};
+use crate::provider::error::DataProviderError;
 use crate::server::error::RESTError;
 use crate::server::filter::with_auth;
-use crate::server::handler::make_error;
+use crate::server::models::{
+    parse_list, AddressHistoryQuery, AddressesQuery, PageQuery, PendingQuery, SlotQuery,
+    TxHistoryQuery,
+};
+use crate::server::tx_cache;
 use crate::{models::TokenInfoView, provider::CardanoDataProvider};
-use crate::{DataProvider, DBSyncProvider, BlockfrostProvider};
-use crate::provider::ProviderType;
 use ::log::debug;
+use cardano_serialization_lib::plutus::PlutusData;
 use cardano_serialization_lib::utils::from_bignum;
 use dcslc::{make_fingerprint, TransactionUnspentOutputs};
+use futures::stream::{BoxStream, StreamExt, TryStreamExt};
 use rweb::*;
+use serde::Serialize;
 use serde_json::json;
-
-fn get_cardano_data_provider() -> Box<dyn CardanoDataProvider> {
-    let provider = std::env::var("PROVIDER").unwrap();
-    match provider.as_str() {
-        "blockfrost" => Box::new(
-            DataProvider::new(
-                BlockfrostProvider::new()
-            )
-        ),
-        "dbsync" => Box::new(
-            DataProvider::new(
-                DBSyncProvider::new(crate::Config {db_path: std::env::var("DBSYNC_URL").unwrap()})
-            )
-        ),
-        // Panic as we never should reach this, config is validated
-        _ => panic!("Provider #{provider} not recognised")
-    }
-}
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
 
 #[get("/address/exist")]
 #[openapi(
@@ -43,11 +34,14 @@ pub async fn address_exists(
     #[query] addresses: String,
     #[filter = "with_auth"] _user_id: String,
 ) -> Result<Json<serde_json::Value>, Rejection> {
-    let mut addresses: Vec<String> = parse_string_vec_from_query(&addresses).unwrap();
+    let mut addresses: Vec<String> = parse_string_vec_from_query(&addresses)?;
     let addresses = addresses.iter_mut().map(|address| &address[..]).collect();
 
-    let dp = get_cardano_data_provider();
-    let result = dp.addresses_exist(&addresses).await.unwrap();
+    let dp = crate::server::provider();
+    let result = dp
+        .addresses_exist(&addresses)
+        .await
+        .map_err(RESTError::from)?;
 
     Ok(rweb::Json::from(json!(result)))
 }
@@ -56,37 +50,134 @@ pub async fn address_exists(
 #[openapi(
     id = "api.info.utxos",
     tags("UTxOs"),
-    summary = "Get UTxOs for an address"
+    summary = "Get UTxOs for an address, `pending=true` applies the transactions submitted through cdp which are not on-chain yet"
 )]
 pub async fn utxos_per_addr(
     address: String,
+    #[query] query: PendingQuery,
     #[filter = "with_auth"] _user_id: String,
 ) -> Result<Json<serde_json::Value>, Rejection> {
-    let dp = get_cardano_data_provider();
+    let dp = crate::server::provider();
 
-    let utxos = dp
+    let mut utxos = dp
         .script_utxos(&address)
         .await
-        .map_err(|e| RESTError::Custom(format!("Could not find UTxOs: {:?}",e)))?;
+        .map_err(RESTError::from)?;
+    if query.pending.unwrap_or(false) {
+        let addr = dcslc::addr_from_str(&address)
+            .map_err(|e| RESTError::from(DataProviderError::InvalidInput(e.to_string())))?;
+        let pending = tx_cache::pending(&crate::server::tx_cache()).await;
+        utxos = tx_cache::apply_pending(&pending, &addr, utxos);
+    }
 
     let result = serde_json::to_value(utxos.to_hex().unwrap())
-        .map_err(|_| RESTError::Custom("db error, could not get utxos".to_string()))?;
+        .map_err(|_| RESTError::Custom("could not encode utxos".to_string()))?;
+    Ok(rweb::Json::from(result))
+}
+
+#[post("/utxos/refs")]
+#[openapi(
+    id = "api.info.utxos",
+    tags("UTxOs"),
+    summary = "Resolve the unspent outputs of a list of output references `txhash#index`, references to unknown or spent outputs are returned as missing. `pending=true` applies the transactions submitted through cdp which are not on-chain yet"
+)]
+pub async fn utxos_by_refs(
+    #[json] refs: Vec<String>,
+    #[query] query: PendingQuery,
+    #[filter = "with_auth"] _user_id: String,
+) -> Result<Json<serde_json::Value>, Rejection> {
+    let dp = crate::server::provider();
+    let refs = refs
+        .iter()
+        .map(|r| parse_output_ref(r).map_err(DataProviderError::InvalidInput))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(RESTError::from)?;
+
+    let mut resolved = dp.utxos_by_refs(&refs).await.map_err(RESTError::from)?;
+    if query.pending.unwrap_or(false) {
+        let pending = tx_cache::pending(&crate::server::tx_cache()).await;
+        resolved = tx_cache::apply_pending_refs(&pending, &refs, resolved);
+    }
+
+    Ok(rweb::Json::from(json!(ResolvedUtxosView::from(&resolved))))
+}
+
+#[get("/datum/{txhash}/{index}/{schema}")]
+#[openapi(
+    id = "api.info.datum",
+    tags("UTxOs"),
+    summary = "Decode the inline or hashed datum of an unspent output with a datum schema, `aya_registration` or `aya_stake`"
+)]
+pub async fn decode_datum(
+    txhash: String,
+    index: u32,
+    schema: String,
+    #[filter = "with_auth"] _user_id: String,
+) -> Result<Json<serde_json::Value>, Rejection> {
+    let schema = datum::schema(&schema)
+        .map_err(DataProviderError::from)
+        .map_err(RESTError::from)?;
+    let hash = TxHash::from_hex(&txhash).map_err(|_| {
+        RESTError::from(DataProviderError::InvalidInput(format!(
+            "invalid transaction hash {txhash}"
+        )))
+    })?;
+    let not_found = |what: &str| {
+        RESTError::from(DataProviderError::NotFound(format!(
+            "{txhash}#{index} {what}"
+        )))
+    };
+
+    let dp = crate::server::provider();
+    let resolved = dp
+        .utxos_by_refs(&[(hash.clone(), index)])
+        .await
+        .map_err(RESTError::from)?;
+    let output = resolved
+        .found
+        .first()
+        .ok_or_else(|| not_found("is not an unspent output"))?
+        .output();
+
+    let data = match (output.plutus_data(), output.data_hash()) {
+        (Some(data), _) => data,
+        (None, Some(data_hash)) => {
+            let datums = dp
+                .find_datums_for_tx(&hash.to_bytes())
+                .await
+                .map_err(RESTError::from)?;
+            let bytes = datums
+                .into_iter()
+                .find(|d| d.hash == data_hash.to_bytes())
+                .ok_or_else(|| not_found("has a datum hash without known datum"))?
+                .bytes;
+            PlutusData::from_bytes(bytes)
+                .map_err(|e| RESTError::from(DataProviderError::from(e)))?
+        }
+        (None, None) => return Err(not_found("has no datum").into()),
+    };
+
+    let decoded = schema
+        .decode(&data)
+        .map_err(DataProviderError::from)
+        .map_err(RESTError::from)?;
+    let result = decoded
+        .to_json(crate::server::network().address_network_id)
+        .map_err(RESTError::from)?;
     Ok(rweb::Json::from(result))
 }
 
 fn parse_string_vec_from_query(query: &str) -> Result<Vec<String>, RESTError> {
     debug!("Q:{:?}", query);
-    let list: Vec<&str> = query.split('=').collect();
-    debug!("1:{:?}", list);
-    let list = list[1].replace("%22", &'"'.to_string());
+    let (_, list) = query.split_once('=').ok_or_else(|| {
+        DataProviderError::InvalidInput(format!("expected a list of values in {query}"))
+    })?;
+    let list = list.replace("%22", &'"'.to_string());
     let list = list.replace("%5D", &']'.to_string());
     let list = list.replace("%5B", &'['.to_string());
     let list = list.replace("%2C", &','.to_string());
     debug!("2:{:?}", list);
-    let list = serde_json::from_str::<Vec<String>>(&list);
-    debug!("Vec: {:?}", list);
-    let list = list.unwrap();
-    Ok(list)
+    Ok(parse_list(&list)?)
 }
 
 #[get("/asset/metadata/{fingerprint}")]
@@ -100,11 +191,11 @@ pub async fn mint_metadata(
     #[filter = "with_auth"] _user_id: String,
 ) -> Result<Json<serde_json::Value>, Rejection> {
 
-    let dp = get_cardano_data_provider();
+    let dp = crate::server::provider();
 
     let metadata: TokenInfoView = match dp.mint_metadata(&fingerprint).await {
         Ok(metadata) => metadata,
-        Err(e) => return Err(RESTError::Custom(e.to_string()).into()),
+        Err(e) => return Err(RESTError::from(e).into()),
     };
     Ok(rweb::Json::from(json!(metadata)))
 }
@@ -120,11 +211,16 @@ pub async fn mint_metadata_policy_assetname(
     assetname: String,
     #[filter = "with_auth"] _user_id: String,
 ) -> Result<Json<serde_json::Value>, Rejection> {
-    let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-        db_path: std::env::var("DBSYNC_URL").unwrap(),
-    }));
-    let fingerprint = make_fingerprint(&policy, &assetname).unwrap();
-    let metadata: TokenInfoView = dp.mint_metadata(&fingerprint).await.unwrap();
+    let dp = crate::server::provider();
+    let fingerprint = make_fingerprint(&policy, &assetname).map_err(|e| {
+        RESTError::from(DataProviderError::InvalidInput(format!(
+            "invalid policy or asset name: {e}"
+        )))
+    })?;
+    let metadata: TokenInfoView = dp
+        .mint_metadata(&fingerprint)
+        .await
+        .map_err(RESTError::from)?;
     Ok(rweb::Json::from(json!(metadata)))
 }
 
@@ -135,17 +231,58 @@ pub async fn mint_metadata_policy_assetname(
     summary = "Retrieve hashes of transactions that involve the provided addresses. Expect list of addresses"
 )]
 pub async fn tx_history(
-    #[query] addresses: String,
-    #[query] slot: String,
+    #[query] query: TxHistoryQuery,
     #[filter = "with_auth"] _user_id: String,
 ) -> Result<Json<serde_json::Value>, Rejection> {
-    let dp = get_cardano_data_provider();
-    let mut addresses: Vec<String> = parse_string_vec_from_query(&addresses).unwrap();
-    let addresses = addresses.iter_mut().map(|address| &address[..]).collect();
+    let dp = crate::server::provider();
+    let history = query_tx_history(dp.as_ref(), &query)
+        .await
+        .map_err(RESTError::from)?;
+    Ok(rweb::Json::from(history))
+}
 
-    let slot = slot.parse::<u64>().ok();
+/// History of the queried addresses up to `slot`, a page of it if a `limit` is given
+async fn query_tx_history(
+    dp: &dyn CardanoDataProvider,
+    query: &TxHistoryQuery,
+) -> Result<serde_json::Value, DataProviderError> {
+    let addresses = query.addresses()?;
+    let addresses = addresses.iter().map(|address| &address[..]).collect();
+
+    Ok(match query.page_request() {
+        Some(page) => json!(dp.tx_history_page(&addresses, query.slot, &page).await?),
+        None => json!(dp.tx_history(&addresses, query.slot).await?),
+    })
+}
 
-    let history = dp.tx_history(&addresses, slot).await.unwrap();
+#[get("/history/transactions/")]
+#[openapi(
+    id = "api.info.history",
+    tags("Transaction History"),
+    summary = "Retrieve transactions of addresses or of a stake address with direction, fee, metadata labels and the net change of lovelace and assets"
+)]
+pub async fn address_tx_history(
+    #[query] query: AddressHistoryQuery,
+    #[filter = "with_auth"] _user_id: String,
+) -> Result<Json<serde_json::Value>, Rejection> {
+    let dp = crate::server::provider();
+    let history = match (&query.addresses, &query.stake_address) {
+        (Some(addresses), None) => {
+            let addresses = addresses
+                .split(',')
+                .map(str::trim)
+                .filter(|a| !a.is_empty())
+                .collect();
+            dp.address_tx_history(&addresses, query.slot).await
+        }
+        (None, Some(stake_address)) => {
+            dp.stake_address_tx_history(stake_address, query.slot).await
+        }
+        _ => Err(DataProviderError::InvalidInput(
+            "expected either addresses or stake_address".to_owned(),
+        )),
+    }
+    .map_err(RESTError::from)?;
 
     Ok(rweb::Json::from(json!(history)))
 }
@@ -160,26 +297,15 @@ pub async fn tx_history_discover(
     hash: String,
     #[filter = "with_auth"] _user_id: String,
 ) -> Result<Json<serde_json::Value>, Rejection> {
-    // Return if provider is blockfrost, BF does not support fingerprint-based requests
-    let provider = std::env::var("PROVIDER").unwrap();
-    if provider.as_str() == "blockfrost" {
-        return make_error(
-            "Fingerprints not supported in blockfrost provider".to_string(),
-            Some(1005),
-            Some("Fingerprints not supported in blockfrost provider"),
-        );
-    }
-
-    let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-        db_path: std::env::var("DBSYNC_URL").unwrap(),
-    }));
+    let dp = crate::server::dbsync().map_err(RESTError::from)?;
     debug!("Try to discover Transaction: {:?}", hash);
-    let tx = crate::dbsync::discover_transaction(dp.provider(), &hash).await;
+    let tx = dp
+        .provider()
+        .discover_transaction(&hash)
+        .await
+        .map_err(RESTError::from)?;
 
-    match tx {
-        Ok(tx) => Ok(rweb::Json::from(json!(tx))),
-        Err(e) => make_error(e.to_string(), None, None),
-    }
+    Ok(rweb::Json::from(json!(tx)))
 }
 
 #[get("/addresses/assets/")]
@@ -193,16 +319,7 @@ pub async fn handle_get_asset_for_addresses(
     #[filter = "with_auth"] _user_id: String,
 ) -> Result<Json<serde_json::Value>, Rejection> {
     debug!("{addresses:?}");
-    let addresses = match parse_string_vec_from_query(&addresses) {
-        Ok(u) => u,
-        Err(e) => {
-            return make_error(
-                e.to_string(),
-                Some(1001),
-                Some("Could not parse addresses from query parameter"),
-            );
-        }
-    };
+    let addresses = parse_string_vec_from_query(&addresses)?;
 
     Ok(rweb::Json::from(json!(
         get_asset_for_addresses(&addresses).await?
@@ -213,12 +330,12 @@ pub(crate) async fn get_asset_for_addresses(
     addresses: &Vec<String>,
 ) -> Result<Vec<AssetHandle>, Rejection> {
     debug!("{addresses:?}");
-    let dp = get_cardano_data_provider();
+    let dp = crate::server::provider();
 
     let mut utxos = TransactionUnspentOutputs::new();
 
     for a in addresses {
-        let us = dp.script_utxos(a).await.unwrap();
+        let us = dp.script_utxos(a).await.map_err(RESTError::from)?;
         utxos.merge(us);
     }
 
@@ -302,49 +419,32 @@ pub async fn handle_asset_for_stake_address(
     #[filter = "with_auth"] _user_id: String,
 ) -> Result<Json<serde_json::Value>, Rejection> {
     debug!("{stake_address:?}");
-    let split = stake_address.split('=').collect::<Vec<&str>>();
-    let bstake_addr = match dcslc::addr_from_str(split[1]) {
-        Ok(s) => s,
-        Err(e) => {
-            return make_error(
-                e.to_string(),
-                Some(1002),
-                Some("The provided stake address is invalid"),
-            );
-        }
-    };
-    let reward_address = match dcslc::get_stakeaddr_from_addr(&bstake_addr) {
-        Ok(r) => r,
-        Err(e) => {
-            return make_error(
-                e.to_string(),
-                Some(1003),
-                Some("The provided address is not a stake address"),
-            );
-        }
-    };
-
-    let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-        db_path: std::env::var("DBSYNC_URL").unwrap(),
-    }));
-    // dp
-    //.wallet_utxos(&reward_address.to_bech32(None).unwrap())
-    let utxos = match get_stake_address_utxos_dep(
-        dp.provider(),
-        &reward_address.to_bech32(None).unwrap(),
-    ) {
-        Ok(u) => u,
-        Err(e) => {
-            return make_error(
-                e.to_string(),
-                Some(2001),
-                Some(&format!(
-                    "Could not retrieve utxos for stake address: {:?}",
-                    reward_address.to_bech32(None).unwrap()
-                )),
-            );
-        }
-    };
+    let (_, stake_address) = stake_address.split_once('=').ok_or_else(|| {
+        RESTError::from(DataProviderError::InvalidInput(
+            "a stake address is required".to_string(),
+        ))
+    })?;
+    let bstake_addr = dcslc::addr_from_str(stake_address).map_err(|e| {
+        RESTError::from(DataProviderError::InvalidInput(format!(
+            "the provided stake address is invalid: {e}"
+        )))
+    })?;
+    let reward_address = dcslc::get_stakeaddr_from_addr(&bstake_addr).map_err(|e| {
+        RESTError::from(DataProviderError::InvalidInput(format!(
+            "the provided address is not a stake address: {e}"
+        )))
+    })?;
+
+    let reward_address = reward_address
+        .to_bech32(None)
+        .map_err(|e| RESTError::from(DataProviderError::from(e)))?;
+
+    let dp = crate::server::dbsync().map_err(RESTError::from)?;
+    let utxos = dp
+        .provider()
+        .stake_address_utxos(&reward_address)
+        .await
+        .map_err(RESTError::from)?;
 
     let mut handles = Vec::<AssetHandle>::new();
     for u in utxos {
@@ -425,8 +525,8 @@ pub async fn retrieve_active_pools(
     page: usize,
     #[filter = "with_auth"] _user_id: String,
 ) -> Result<Json<serde_json::Value>, Rejection> {
-    let dp = get_cardano_data_provider();
-    let pools_page = dp.active_pools(page).await.unwrap();
+    let dp = crate::server::provider();
+    let pools_page = dp.active_pools(page).await.map_err(RESTError::from)?;
     Ok(rweb::Json::from(json!(pools_page)))
 }
 
@@ -440,18 +540,13 @@ pub async fn token_supply(
     fingerprint: String,
     #[filter = "with_auth"] _user_id: String,
 ) -> Result<Json<serde_json::Value>, Rejection> {
-    let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-        db_path: std::env::var("DBSYNC_URL").unwrap(),
-    }));
-    let supply = crate::dbsync::token_supply(dp.provider(), &fingerprint).await;
-    if let Err(e) = &supply {
-        return make_error(
-            format!("Could not get supply for {:?}", e.to_string()),
-            None,
-            None,
-        );
-    }
-    Ok(rweb::Json::from(json!(supply.unwrap())))
+    let dp = crate::server::dbsync().map_err(RESTError::from)?;
+    let supply = dp
+        .provider()
+        .token_supply(&fingerprint)
+        .await
+        .map_err(RESTError::from)?;
+    Ok(rweb::Json::from(json!(supply)))
 }
 
 #[get("/tokens/isNft/")]
@@ -465,45 +560,17 @@ pub async fn is_nft(
     #[filter = "with_auth"] _user_id: String,
 ) -> Result<Json<serde_json::Value>, Rejection> {
 
-    // Return if provider is blockfrost, BF does not support fingerprint-based requests
-    let provider = std::env::var("PROVIDER").unwrap();
-    if provider.as_str() == "blockfrost" {
-        return make_error(
-            "Fingerprints not supported in blockfrost provider".to_string(),
-            Some(1005),
-            Some("Fingerprints not supported in blockfrost provider"),
-        );
-    }
-
-    let f = match parse_string_vec_from_query(&fingerprints) {
-        Ok(u) => u,
-        Err(e) => {
-            return make_error(
-                e.to_string(),
-                Some(1004),
-                Some("Could not parse list of fingerprints from query parameter"),
-            );
-        }
-    };
+    let f = parse_string_vec_from_query(&fingerprints)?;
     debug!("Creating dataprovider instance");
-    let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-        db_path: std::env::var("DBSYNC_URL").unwrap(),
-    }));
+    let dp = crate::server::dbsync().map_err(RESTError::from)?;
     debug!("Try to execute query");
-    let supply = crate::dbsync::is_nft(
-        dp.provider(),
-        &f.iter().map(|n| &**n).collect::<Vec<&str>>()[..],
-    )
-    .await;
+    let supply = dp
+        .provider()
+        .is_nft(&f.iter().map(|n| &**n).collect::<Vec<&str>>()[..])
+        .await;
     debug!("Received query results: {supply:?}");
-    if let Err(e) = &supply {
-        return make_error(
-            format!("Could not get supply for {f:?}, error: {e}"),
-            None,
-            None,
-        );
-    }
-    Ok(rweb::Json::from(json!(supply.unwrap())))
+    let supply = supply.map_err(RESTError::from)?;
+    Ok(rweb::Json::from(json!(supply)))
 }
 
 #[get("/epoch/stake/amount/{stake_addr}/{epoch}")]
@@ -517,7 +584,7 @@ pub async fn retrieve_staked_amount(
     stake_addr: String,
     #[filter = "with_auth"] _user_id: String,
 ) -> Result<Json<serde_json::Value>, Rejection> {
-    let dp = get_cardano_data_provider();
+    let dp = crate::server::provider();
 
     dbg!(epoch.clone());
     dbg!(stake_addr.clone());
@@ -525,7 +592,7 @@ pub async fn retrieve_staked_amount(
     let staked_amount = dp
         .retrieve_staked_amount(epoch, &stake_addr)
         .await
-        .map_err(|_| RESTError::Custom("Couldn't find staked amount".to_string()))?;
+        .map_err(RESTError::from)?;
     dbg!(staked_amount.clone());
 
     Ok(rweb::Json::from(json!(staked_amount)))
@@ -539,18 +606,251 @@ pub async fn retrieve_staked_amount(
 )]
 pub async fn retrieve_generated_rewards(
     stake_addr: String,
+    #[query] page: PageQuery,
     #[filter = "with_auth"] _user_id: String,
 ) -> Result<Json<serde_json::Value>, Rejection> {
-    let dp = get_cardano_data_provider();
+    let dp = crate::server::provider();
+
+    if let Some(page) = page.page_request() {
+        let rewards = dp
+            .retrieve_generated_rewards_page(&stake_addr, &page)
+            .await
+            .map_err(RESTError::from)?;
+        return Ok(rweb::Json::from(json!(rewards)));
+    }
 
     let generated_rewards = dp
         .retrieve_generated_rewards(&stake_addr)
         .await
-        .map_err(|_| RESTError::Custom("Couldn't find generated rewards".to_string()))?;
+        .map_err(RESTError::from)?;
 
     Ok(rweb::Json::from(json!(generated_rewards)))
 }
 
+#[get("/stake/balance/{stake_addr}")]
+#[openapi(
+    id = "api.info.stake",
+    tags("Stake Address"),
+    summary = "Retrieve the lovelace and assets of all addresses of the stake address and its withdrawable rewards"
+)]
+pub async fn stake_address_balance(
+    stake_addr: String,
+    #[filter = "with_auth"] _user_id: String,
+) -> Result<Json<serde_json::Value>, Rejection> {
+    let dp = crate::server::provider();
+    let balance = dp
+        .stake_address_balance(&stake_addr)
+        .await
+        .map_err(RESTError::from)?;
+
+    Ok(rweb::Json::from(json!(balance)))
+}
+
+#[get("/stake/addresses/{stake_addr}")]
+#[openapi(
+    id = "api.info.stake",
+    tags("Stake Address"),
+    summary = "Retrieve all payment addresses associated with the stake address"
+)]
+pub async fn stake_address_addresses(
+    stake_addr: String,
+    #[filter = "with_auth"] _user_id: String,
+) -> Result<Json<serde_json::Value>, Rejection> {
+    let dp = crate::server::provider();
+    let addresses = dp
+        .stake_address_addresses(&stake_addr)
+        .await
+        .map_err(RESTError::from)?;
+
+    Ok(rweb::Json::from(json!(addresses)))
+}
+
+#[get("/history/stake/{stake_addr}")]
+#[openapi(
+    id = "api.info.history",
+    tags("Transaction History"),
+    summary = "Retrieve the transaction history of all addresses associated with the stake address"
+)]
+pub async fn stake_address_history(
+    stake_addr: String,
+    #[query] query: SlotQuery,
+    #[filter = "with_auth"] _user_id: String,
+) -> Result<Json<serde_json::Value>, Rejection> {
+    let dp = crate::server::provider();
+    let history = dp
+        .stake_address_history(&stake_addr, query.slot)
+        .await
+        .map_err(RESTError::from)?;
+
+    Ok(rweb::Json::from(json!(history)))
+}
+
+#[get("/time/slot/{slot}")]
+#[openapi(
+    id = "api.info.time",
+    tags("Time"),
+    summary = "Convert a slot to POSIX time and epoch"
+)]
+pub async fn slot_to_time(
+    slot: u64,
+    #[filter = "with_auth"] _user_id: String,
+) -> Result<Json<serde_json::Value>, Rejection> {
+    let network = crate::server::network();
+    let time = crate::time::slot_to_time(&network, slot).ok_or_else(|| {
+        RESTError::from(DataProviderError::InvalidInput(format!(
+            "the time of slot {slot} is out of range"
+        )))
+    })?;
+    let (epoch, slot_in_epoch) = crate::time::slot_to_epoch(&network, slot);
+    Ok(rweb::Json::from(json!({
+        "slot": slot,
+        "time": time,
+        "epoch": epoch,
+        "slot_in_epoch": slot_in_epoch,
+    })))
+}
+
+#[get("/time/posix/{time}")]
+#[openapi(
+    id = "api.info.time",
+    tags("Time"),
+    summary = "Convert a POSIX time (seconds) to slot and epoch"
+)]
+pub async fn time_to_slot(
+    time: u64,
+    #[filter = "with_auth"] _user_id: String,
+) -> Result<Json<serde_json::Value>, Rejection> {
+    let network = crate::server::network();
+    let slot = crate::time::time_to_slot(&network, time).ok_or_else(|| {
+        RESTError::from(DataProviderError::InvalidInput(format!(
+            "{time} lies before the start of the chain"
+        )))
+    })?;
+    let (epoch, slot_in_epoch) = crate::time::slot_to_epoch(&network, slot);
+    Ok(rweb::Json::from(json!({
+        "slot": slot,
+        "time": time,
+        "epoch": epoch,
+        "slot_in_epoch": slot_in_epoch,
+    })))
+}
+
+#[get("/time/epoch/{epoch}")]
+#[openapi(
+    id = "api.info.time",
+    tags("Time"),
+    summary = "First and last slot and the time span of an epoch"
+)]
+pub async fn epoch_bounds(
+    epoch: u64,
+    #[filter = "with_auth"] _user_id: String,
+) -> Result<Json<serde_json::Value>, Rejection> {
+    let network = crate::server::network();
+    let bounds = crate::time::epoch_bounds(&network, epoch).ok_or_else(|| {
+        RESTError::from(DataProviderError::InvalidInput(format!(
+            "epoch {epoch} is out of range"
+        )))
+    })?;
+    Ok(rweb::Json::from(json!(bounds)))
+}
+
+type NdjsonSender = mpsc::Sender<Result<String, Infallible>>;
+
+/// Responds with the lines received on `rx` as they arrive
+fn ndjson_response(
+    rx: mpsc::Receiver<Result<String, Infallible>>,
+) -> Result<http::Response<rweb::warp::hyper::Body>, http::Error> {
+    http::Response::builder()
+        .header("content-type", "application/x-ndjson")
+        .body(rweb::warp::hyper::Body::wrap_stream(ReceiverStream::new(rx)))
+}
+
+/// Writes one JSON line per item, an error ends the export with an `{"error": ..}` line.
+/// Stops early if the client disconnected.
+async fn write_ndjson<T: Serialize>(
+    mut items: BoxStream<'_, Result<T, DataProviderError>>,
+    tx: NdjsonSender,
+) {
+    while let Some(item) = items.next().await {
+        let line = match item {
+            Ok(item) => serde_json::to_string(&item).map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        match line {
+            Ok(line) => {
+                if tx.send(Ok(line + "\n")).await.is_err() {
+                    return;
+                }
+            }
+            Err(e) => {
+                let _ = tx.send(Ok(json!({ "error": e }).to_string() + "\n")).await;
+                return;
+            }
+        }
+    }
+}
+
+#[get("/export/utxos/{address}")]
+#[openapi(
+    id = "api.info.export",
+    tags("Export"),
+    summary = "Stream all UTxOs of an address as NDJSON, one hex encoded UTxO per line"
+)]
+pub async fn export_utxos(
+    address: String,
+    #[filter = "with_auth"] _user_id: String,
+) -> Result<impl rweb::warp::Reply, http::Error> {
+    let (tx, rx) = mpsc::channel(64);
+    tokio::spawn(async move {
+        let dp = crate::server::provider();
+        let utxos = dp
+            .script_utxos_stream(&address)
+            .map_ok(|utxo| hex::encode(utxo.to_bytes()))
+            .boxed();
+        write_ndjson(utxos, tx).await
+    });
+    ndjson_response(rx)
+}
+
+#[get("/export/holders/{policy}")]
+#[openapi(
+    id = "api.info.export",
+    tags("Export"),
+    summary = "Stream the holders of all assets of a policy as NDJSON"
+)]
+pub async fn export_policy_holders(
+    policy: String,
+    #[filter = "with_auth"] _user_id: String,
+) -> Result<impl rweb::warp::Reply, http::Error> {
+    let (tx, rx) = mpsc::channel(64);
+    tokio::spawn(async move {
+        let dp = crate::server::provider();
+        write_ndjson(dp.policy_holders_stream(&policy), tx).await
+    });
+    ndjson_response(rx)
+}
+
+#[get("/export/history")]
+#[openapi(
+    id = "api.info.export",
+    tags("Export"),
+    summary = "Stream the complete transaction history of the addresses as NDJSON, newest first"
+)]
+pub async fn export_tx_history(
+    #[query] query: AddressesQuery,
+    #[filter = "with_auth"] _user_id: String,
+) -> Result<impl rweb::warp::Reply, Rejection> {
+    let addresses = query.addresses().map_err(RESTError::from)?;
+    let (tx, rx) = mpsc::channel(64);
+    tokio::spawn(async move {
+        let addresses: Vec<&str> = addresses.iter().map(|a| &a[..]).collect();
+        let dp = crate::server::provider();
+        write_ndjson(dp.tx_history_stream(&addresses), tx).await
+    });
+    Ok(ndjson_response(rx)
+        .map_err(|e| RESTError::from(DataProviderError::Custom(e.to_string())))?)
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -559,4 +859,145 @@ mod tests {
         let r = crate::server::handler::handler_rest::info::parse_string_vec_from_query("addresses=[\"addr_test1qqt86eq9972q3qttj6ztje97llasktzfzvhmdccqjlqjaq2cer3t74yn0dm8xqnr7rtwhkqcrpsmphwcf0mlmn39ry6qy6q5t2\",\"addr_test1qpg8ehvgj9zxrx59et72yjn2p02xwsm3l89jwj8ujcj63ujcer3t74yn0dm8xqnr7rtwhkqcrpsmphwcf0mlmn39ry6qw23emu\",\"addr_test1qqdp3cry5vc2gfjljctdu638tvkcqfx40fjunht9hrmru5zcer3t74yn0dm8xqnr7rtwhkqcrpsmphwcf0mlmn39ry6qnaxxgs\",\"addr_test1qr2mw080ujz0unmpn9lx5ftfuewc6htyr6v3a0svul2zgezcer3t74yn0dm8xqnr7rtwhkqcrpsmphwcf0mlmn39ry6qgryf7t\",\"addr_test1qr7tqh7tsg4lut3jv6tsfwlv464m6knjjw90ugyz8uzgr6zcer3t74yn0dm8xqnr7rtwhkqcrpsmphwcf0mlmn39ry6qt0jxzj\",\"addr_test1qrscurjp292sxv24sepj7ghq4ydkkekzaz53zwfswcna6ljcer3t74yn0dm8xqnr7rtwhkqcrpsmphwcf0mlmn39ry6q8pu3l5\",\"addr_test1qqssrphse6qmp9h0ksu5vfmsx99tfl2lc6rhvy2spd5wr86cer3t74yn0dm8xqnr7rtwhkqcrpsmphwcf0mlmn39ry6qw59j4j\",\"addr_test1qqgagc0fy6nm0qe4h8zqxsg952tqjeg7l7j0agd0cx4u25zcer3t74yn0dm8xqnr7rtwhkqcrpsmphwcf0mlmn39ry6qxvept2\"]").unwrap();
         println!("{r:?}");
     }
+
+    #[test]
+    fn invalid_address_lists_are_rejected() {
+        use crate::provider::error::DataProviderError;
+        use crate::server::error::RESTError;
+        use crate::server::models::{AddressesQuery, TxHistoryQuery};
+
+        for query in ["addresses", "addresses=[\"addr1", "addresses="] {
+            let r = super::parse_string_vec_from_query(query);
+            assert!(
+                matches!(
+                    r,
+                    Err(RESTError::Provider(DataProviderError::InvalidInput(_)))
+                ),
+                "{query}"
+            );
+        }
+
+        let query = TxHistoryQuery {
+            addresses: "addr_test1qqt86eq9972q3qttj6ztje97llasktzfzvhmdccqjlqjaq2cer3t74yn0dm8xqnr7rtwhkqcrpsmphwcf0mlmn39ry6qy6q5t2, addr_test1qpg8ehvgj9zxrx59et72yjn2p02xwsm3l89jwj8ujcj63ujcer3t74yn0dm8xqnr7rtwhkqcrpsmphwcf0mlmn39ry6qw23emu".to_owned(),
+            slot: Some(42),
+            limit: None,
+            cursor: None,
+        };
+        assert_eq!(query.addresses().unwrap().len(), 2);
+        assert!(query.page_request().is_none());
+
+        for addresses in ["", "[\"addr1", " , "] {
+            let query = AddressesQuery {
+                addresses: addresses.to_owned(),
+            };
+            assert!(
+                matches!(query.addresses(), Err(DataProviderError::InvalidInput(_))),
+                "{addresses}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn tx_history_honours_the_slot_query() {
+        use crate::blockfrost::{BlockfrostProvider, Config};
+        use crate::server::models::TxHistoryQuery;
+        use httpmock::prelude::*;
+        use serde_json::json;
+
+        const ADDR: &str = "addr1vyqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qavsj8u";
+        const TX_HASH: &str = "1b07f1152e52ce0a9dbb561aa2e2d1750ca3a1a4141150a8bad342947a66a3a6";
+
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path(format!("/addresses/{ADDR}/transactions"))
+                    .query_param("page", "1");
+                then.status(200).json_body(json!([
+                    { "tx_hash": TX_HASH, "tx_index": 1, "block_height": 123456, "block_time": 1635505891 }
+                ]));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path(format!("/addresses/{ADDR}/transactions"));
+                then.status(200).json_body(json!([]));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/txs/{TX_HASH}"));
+                then.status(200).json_body(json!({
+                    "hash": TX_HASH,
+                    "block": "4ea1ba291e8eef538635a53e59fddba7810d1679631cc3aed7c8e6c4091a516a",
+                    "block_height": 123456,
+                    "block_time": 1635505891,
+                    "slot": 95654321,
+                    "index": 1,
+                    "output_amount": [{ "unit": "lovelace", "quantity": "42000000" }],
+                    "fees": "182485",
+                    "deposit": "0",
+                    "size": 433,
+                    "invalid_before": null,
+                    "invalid_hereafter": null,
+                    "utxo_count": 2,
+                    "withdrawal_count": 0,
+                    "mir_cert_count": 0,
+                    "delegation_count": 0,
+                    "stake_cert_count": 0,
+                    "pool_update_count": 0,
+                    "pool_retire_count": 0,
+                    "asset_mint_or_burn_count": 0,
+                    "redeemer_count": 0,
+                    "valid_contract": true
+                }));
+            })
+            .await;
+        let dp = crate::DataProvider::new(BlockfrostProvider::new(Config {
+            project_id: "mainnetTestProject".to_owned(),
+            network_address: server.base_url(),
+            network: crate::config::ChainWellKnownInfo::mainnet(),
+        }));
+        let dp = &dp;
+
+        let history = |slot: u64| {
+            let path = format!("/history/address/?addresses=%5B%22{ADDR}%22%5D&slot={slot}");
+            async move {
+                let query = rweb::warp::test::request()
+                    .path(&path)
+                    .filter(&rweb::warp::query::<TxHistoryQuery>())
+                    .await
+                    .unwrap();
+                assert_eq!(query.slot, Some(slot));
+                super::query_tx_history(dp, &query).await.unwrap()
+            }
+        };
+        assert_eq!(history(95654321).await.as_array().unwrap().len(), 1);
+        assert!(history(95654320).await.as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn ndjson_export_ends_with_the_error() {
+        use crate::provider::error::DataProviderError;
+        use futures::StreamExt;
+
+        let items = futures::stream::iter(vec![
+            Ok(1),
+            Ok(2),
+            Err(DataProviderError::Custom("lost connection".to_owned())),
+            Ok(3),
+        ])
+        .boxed();
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        super::write_ndjson(items, tx).await;
+
+        let mut lines = vec![];
+        while let Some(Ok(line)) = rx.recv().await {
+            lines.push(line);
+        }
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[..2], ["1\n", "2\n"]);
+        assert!(lines[2].starts_with("{\"error\""));
+    }
 }
@@ -0,0 +1,54 @@
+use crate::server::error::RESTError;
+use crate::server::filter::with_auth;
+use crate::server::models::{PendingTxView, SignedTx};
+use crate::server::{submit, tx_cache};
+use rweb::*;
+use serde_json::json;
+
+#[post("/submit")]
+#[openapi(
+    id = "api.tx.submit",
+    tags("Transactions"),
+    summary = "Submit a signed transaction to all configured submit-api endpoints, returns the tx hash and the response of every endpoint"
+)]
+pub async fn submit_tx(
+    #[json] tx: SignedTx,
+    #[filter = "with_auth"] _user_id: String,
+) -> Result<Json<serde_json::Value>, Rejection> {
+    let result = submit::submit_tx(&tx.cbor)
+        .await
+        .map_err(RESTError::from)?;
+
+    Ok(rweb::Json::from(json!(result)))
+}
+
+#[post("/track")]
+#[openapi(
+    id = "api.tx.track",
+    tags("Transactions"),
+    summary = "Track a signed transaction which was submitted elsewhere, its inputs and outputs are applied to UTxO queries with `pending=true` until it is on-chain or expires"
+)]
+pub async fn track_tx(
+    #[json] tx: SignedTx,
+    #[filter = "with_auth"] _user_id: String,
+) -> Result<Json<serde_json::Value>, Rejection> {
+    let tx = tx_cache::decode_tx(&tx.cbor).map_err(RESTError::from)?;
+    let item = tx_cache::track(&crate::server::tx_cache(), tx).await;
+
+    Ok(rweb::Json::from(json!(PendingTxView::from(&item))))
+}
+
+#[get("/pending")]
+#[openapi(
+    id = "api.tx.pending",
+    tags("Transactions"),
+    summary = "List the transactions submitted through cdp which are not on-chain yet"
+)]
+pub async fn pending_txs(
+    #[filter = "with_auth"] _user_id: String,
+) -> Result<Json<serde_json::Value>, Rejection> {
+    let pending = tx_cache::pending(&crate::server::tx_cache()).await;
+    let pending: Vec<PendingTxView> = pending.iter().map(PendingTxView::from).collect();
+
+    Ok(rweb::Json::from(json!(pending)))
+}
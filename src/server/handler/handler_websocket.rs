@@ -129,16 +129,18 @@ async fn client_msg(
             }
             WSRequest::IsNFT(v) => {
                 debug!("IsNFT");
-                let dbs = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-                    db_path: std::env::var("DBSYNC_URL").unwrap(),
-                }));
                 let mut payload = Vec::<bool>::new();
-                for f in &v {
-                    if let Ok(true) = crate::dbsync::check_nft_supply(dbs.provider(), f).await {
-                        payload.push(true);
-                    } else {
-                        payload.push(false);
+                match crate::server::dbsync() {
+                    Ok(dbs) => {
+                        for f in &v {
+                            if let Ok(true) = dbs.provider().check_nft_supply(f).await {
+                                payload.push(true);
+                            } else {
+                                payload.push(false);
+                            }
+                        }
                     }
+                    Err(e) => error!("IsNFT: {e}"),
                 }
                 send_to_client(
                     client_id,
@@ -169,12 +171,10 @@ async fn client_msg(
             WSRequest::MintMetadata(v) => {
                 debug!("MintMetadata");
                 let mut payload = Vec::<TokenInfoView>::new();
-                let dp = crate::DataProvider::new(crate::DBSyncProvider::new(crate::Config {
-                    db_path: std::env::var("DBSYNC_URL").unwrap(),
-                }));
+                let dp = crate::server::provider();
                 for s in &v {
                     if let Ok(response) =
-                        crate::provider::CardanoDataProvider::mint_metadata(&dp, &s).await
+                        crate::provider::CardanoDataProvider::mint_metadata(&*dp, &s).await
                     {
                         payload.push(response)
                     };
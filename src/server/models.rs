@@ -1,12 +1,13 @@
-use cardano_serialization_lib::metadata::AuxiliaryData;
-use cardano_serialization_lib::{Transaction, TransactionBody, TransactionWitnessSet};
+use cardano_serialization_lib::Transaction;
 use rweb::warp::ws::Message;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::time::Instant;
 use std::{collections::HashMap, sync::Arc};
 use tokio::sync::{mpsc, Mutex};
 
 use crate::models::{AssetHandle, TokenInfoView};
+use crate::provider::error::DataProviderError;
 
 #[derive(Serialize, Debug)]
 pub(crate) struct ErrorResponse {
@@ -20,13 +21,127 @@ impl ErrorResponse {
     }
 }
 
+/// `?limit=&cursor=` of paginated endpoints, the complete list is returned without a limit
+#[derive(Deserialize, rweb::Schema, Debug)]
+pub struct PageQuery {
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+}
+
+impl PageQuery {
+    pub fn page_request(&self) -> Option<crate::provider::PageRequest> {
+        self.limit
+            .map(|limit| crate::provider::PageRequest::new(limit, self.cursor.clone()))
+    }
+}
+
+/// `?addresses=["addr1..",..]&slot=&limit=&cursor=` of the transaction history, `addresses` may
+/// also be comma separated, `slot` is the last included slot
+#[derive(Deserialize, rweb::Schema, Debug)]
+pub struct TxHistoryQuery {
+    pub addresses: String,
+    pub slot: Option<u64>,
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+}
+
+impl TxHistoryQuery {
+    pub fn addresses(&self) -> Result<Vec<String>, DataProviderError> {
+        parse_list(&self.addresses)
+    }
+
+    pub fn page_request(&self) -> Option<crate::provider::PageRequest> {
+        self.limit
+            .map(|limit| crate::provider::PageRequest::new(limit, self.cursor.clone()))
+    }
+}
+
+/// `?addresses=["addr1..",..]` of the history export, `addresses` may also be comma separated
+#[derive(Deserialize, rweb::Schema, Debug)]
+pub struct AddressesQuery {
+    pub addresses: String,
+}
+
+impl AddressesQuery {
+    pub fn addresses(&self) -> Result<Vec<String>, DataProviderError> {
+        parse_list(&self.addresses)
+    }
+}
+
+/// Parses a decoded query value holding either a JSON array of strings or a comma separated list
+pub(crate) fn parse_list(value: &str) -> Result<Vec<String>, DataProviderError> {
+    let value = value.trim();
+    let list = if value.starts_with('[') {
+        serde_json::from_str::<Vec<String>>(value)
+            .map_err(|e| DataProviderError::InvalidInput(format!("invalid list {value}: {e}")))?
+    } else {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_owned)
+            .collect()
+    };
+    if list.is_empty() {
+        return Err(DataProviderError::InvalidInput("empty list".to_owned()));
+    }
+    Ok(list)
+}
+
+/// `?addresses=addr1..,addr1..` or `?stake_address=` of the address history,
+/// `slot` is the last included slot
+#[derive(Deserialize, rweb::Schema, Debug)]
+pub struct AddressHistoryQuery {
+    pub addresses: Option<String>,
+    pub stake_address: Option<String>,
+    pub slot: Option<u64>,
+}
+
+/// `?pending=true` applies the transactions submitted through cdp which are not on-chain yet
+#[derive(Deserialize, rweb::Schema, Debug)]
+pub struct PendingQuery {
+    pub pending: Option<bool>,
+}
+
+/// Hex encoded CBOR of a signed transaction
+#[derive(Deserialize, rweb::Schema, Debug)]
+pub struct SignedTx {
+    pub cbor: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PendingTxView {
+    pub tx_hash: String,
+    /// slot from which on the transaction is invalid
+    pub ttl: Option<u64>,
+    /// seconds since the transaction was submitted
+    pub pending_secs: u64,
+}
+
+impl From<&TxCacheItem> for PendingTxView {
+    fn from(item: &TxCacheItem) -> Self {
+        Self {
+            tx_hash: item.tx_hash.clone(),
+            ttl: item.ttl,
+            pending_secs: item.submitted.elapsed().as_secs(),
+        }
+    }
+}
+
+/// `?slot=` of history endpoints, the last included slot
+#[derive(Deserialize, rweb::Schema, Debug)]
+pub struct SlotQuery {
+    pub slot: Option<u64>,
+}
+
+/// Transaction submitted through cdp which is not on-chain yet
 #[derive(Clone)]
 pub struct TxCacheItem {
     pub id: uuid::Uuid,
-    pub tx_body: TransactionBody,
-    pub tx_wittness: TransactionWitnessSet,
-    pub tx_unsigned: Transaction,
-    pub tx_aux: AuxiliaryData,
+    pub tx_hash: String,
+    pub tx: Transaction,
+    pub submitted: Instant,
+    /// slot from which on the transaction is invalid
     pub ttl: Option<u64>,
 }
 
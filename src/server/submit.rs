@@ -0,0 +1,177 @@
+//! Submission of signed transactions to the configured submit-api endpoints
+
+use super::tx_cache;
+use crate::provider::error::DataProviderError;
+use cardano_serialization_lib as csl;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const SUBMIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+lazy_static! {
+    static ref CLIENT: reqwest::Client = reqwest::Client::new();
+}
+
+/// Response of one submit-api endpoint
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SubmitOutcome {
+    pub endpoint: String,
+    pub accepted: bool,
+    /// HTTP status, `None` if the endpoint could not be reached
+    pub status: Option<u16>,
+    /// response body or the connection error
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SubmitResult {
+    pub tx_hash: String,
+    /// true if at least one endpoint accepted the transaction
+    pub accepted: bool,
+    pub outcomes: Vec<SubmitOutcome>,
+}
+
+async fn submit_to(client: &reqwest::Client, endpoint: &str, tx: Vec<u8>) -> SubmitOutcome {
+    let response = client
+        .post(endpoint)
+        .header(reqwest::header::CONTENT_TYPE, "application/cbor")
+        .timeout(SUBMIT_TIMEOUT)
+        .body(tx)
+        .send()
+        .await;
+    match response {
+        Ok(response) => {
+            let status = response.status();
+            SubmitOutcome {
+                endpoint: endpoint.to_owned(),
+                accepted: status.is_success(),
+                status: Some(status.as_u16()),
+                message: response.text().await.unwrap_or_default(),
+            }
+        }
+        Err(e) => SubmitOutcome {
+            endpoint: endpoint.to_owned(),
+            accepted: false,
+            status: None,
+            message: e.to_string(),
+        },
+    }
+}
+
+/// Submits the transaction to all endpoints concurrently, the outcomes are in the order of
+/// `endpoints`
+pub(crate) async fn submit(
+    client: &reqwest::Client,
+    endpoints: &[String],
+    tx_hash: String,
+    tx: Vec<u8>,
+) -> SubmitResult {
+    let outcomes = futures::future::join_all(
+        endpoints
+            .iter()
+            .map(|endpoint| submit_to(client, endpoint, tx.clone())),
+    )
+    .await;
+    SubmitResult {
+        tx_hash,
+        accepted: outcomes.iter().any(|o| o.accepted),
+        outcomes,
+    }
+}
+
+/// Submits the hex encoded signed transaction to the configured endpoints. Once an endpoint
+/// accepted it, the transaction is kept in the tx cache until it is on-chain.
+pub(crate) async fn submit_tx(cbor: &str) -> Result<SubmitResult, DataProviderError> {
+    let tx = tx_cache::decode_tx(cbor)?;
+    let endpoints = crate::server::submit_endpoints();
+    if endpoints.is_empty() {
+        return Err(DataProviderError::Unsupported(
+            "no submit endpoint configured, set connectivity.submit_endpoint_1".to_owned(),
+        ));
+    }
+
+    let tx_hash = csl::utils::hash_transaction(&tx.body()).to_hex();
+    let result = submit(&CLIENT, &endpoints, tx_hash, tx.to_bytes()).await;
+    if result.accepted {
+        tx_cache::track(&crate::server::tx_cache(), tx).await;
+    } else {
+        log::warn!("transaction {} was rejected: {:?}", result.tx_hash, result.outcomes);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+
+    const TX_HASH: &str = "1b07f1152e52ce0a9dbb561aa2e2d1750ca3a1a4141150a8bad342947a66a3a6";
+
+    #[tokio::test]
+    async fn test_submit_fans_out() {
+        let accepting = MockServer::start_async().await;
+        let rejecting = MockServer::start_async().await;
+        let accepted = accepting
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/api/submit/tx")
+                    .header("content-type", "application/cbor")
+                    .body("tx");
+                then.status(202).body(format!("\"{TX_HASH}\""));
+            })
+            .await;
+        rejecting
+            .mock_async(|when, then| {
+                when.method(POST).path("/api/submit/tx");
+                then.status(400).body("BadInputsUTxO");
+            })
+            .await;
+
+        let endpoints = vec![
+            accepting.url("/api/submit/tx"),
+            rejecting.url("/api/submit/tx"),
+            // nothing listens on the discard port
+            "http://127.0.0.1:9/api/submit/tx".to_owned(),
+        ];
+        let result = submit(
+            &reqwest::Client::new(),
+            &endpoints,
+            TX_HASH.to_owned(),
+            b"tx".to_vec(),
+        )
+        .await;
+
+        accepted.assert_async().await;
+        assert!(result.accepted);
+        assert_eq!(result.tx_hash, TX_HASH);
+        assert_eq!(result.outcomes.len(), 3);
+        assert!(result.outcomes[0].accepted);
+        assert_eq!(result.outcomes[0].status, Some(202));
+        assert!(!result.outcomes[1].accepted);
+        assert_eq!(result.outcomes[1].status, Some(400));
+        assert_eq!(result.outcomes[1].message, "BadInputsUTxO");
+        assert_eq!(result.outcomes[2].endpoint, endpoints[2]);
+        assert_eq!(result.outcomes[2].status, None);
+    }
+
+    #[tokio::test]
+    async fn test_submit_rejected_everywhere() {
+        let rejecting = MockServer::start_async().await;
+        rejecting
+            .mock_async(|when, then| {
+                when.method(POST).path("/api/submit/tx");
+                then.status(400).body("ValueNotConserved");
+            })
+            .await;
+
+        let result = submit(
+            &reqwest::Client::new(),
+            &[rejecting.url("/api/submit/tx")],
+            TX_HASH.to_owned(),
+            b"tx".to_vec(),
+        )
+        .await;
+        assert!(!result.accepted);
+    }
+}
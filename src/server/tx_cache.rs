@@ -0,0 +1,343 @@
+//! Transactions submitted through cdp are kept here until they are on-chain or expire.
+//! UTxO queries can apply them to see the outputs as they will be once the pending
+//! transactions are included, so wallets do not spend the same output twice between blocks.
+
+use super::models::{LocalTxCache, TxCacheItem};
+use crate::models::{ResolvedUtxos, TxHash};
+use crate::provider::error::DataProviderError;
+use crate::provider::CardanoDataProvider;
+use cardano_serialization_lib as csl;
+use dcslc::{TransactionUnspentOutput, TransactionUnspentOutputs};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// pending transactions which are not on-chain after this time are dropped
+pub(crate) const DEFAULT_TTL: Duration = Duration::from_secs(20 * 60);
+/// about one block
+const PRUNE_INTERVAL: Duration = Duration::from_secs(20);
+
+type OutputRef = (Vec<u8>, u32);
+
+impl TxCacheItem {
+    pub fn new(tx: csl::Transaction) -> Self {
+        let body = tx.body();
+        Self {
+            id: uuid::Uuid::new_v4(),
+            tx_hash: csl::utils::hash_transaction(&body).to_hex(),
+            ttl: body.ttl_bignum().map(|slot| csl::utils::from_bignum(&slot)),
+            submitted: Instant::now(),
+            tx,
+        }
+    }
+
+    fn hash(&self) -> TxHash {
+        csl::utils::hash_transaction(&self.tx.body())
+    }
+
+    fn expired(&self, slot: Option<u64>, max_age: Duration) -> bool {
+        let invalid = match (self.ttl, slot) {
+            (Some(ttl), Some(slot)) => slot >= ttl,
+            _ => false,
+        };
+        invalid || self.submitted.elapsed() >= max_age
+    }
+}
+
+/// Decodes a hex encoded signed transaction
+pub(crate) fn decode_tx(cbor: &str) -> Result<csl::Transaction, DataProviderError> {
+    csl::Transaction::from_bytes(hex::decode(cbor)?)
+        .map_err(|e| DataProviderError::InvalidInput(format!("invalid transaction: {e}")))
+}
+
+/// Adds a transaction to the cache, a transaction which is already pending keeps its entry
+pub(crate) async fn track(cache: &LocalTxCache, tx: csl::Transaction) -> TxCacheItem {
+    let item = TxCacheItem::new(tx);
+    cache
+        .lock()
+        .await
+        .entry(item.tx_hash.clone())
+        .or_insert(item)
+        .clone()
+}
+
+pub(crate) async fn pending(cache: &LocalTxCache) -> Vec<TxCacheItem> {
+    cache.lock().await.values().cloned().collect()
+}
+
+/// Removes the transactions which are on-chain or expired. Transactions are only dropped by
+/// their age if the provider can neither tell the tip nor whether they are included.
+pub(crate) async fn prune(cache: &LocalTxCache, dp: &dyn CardanoDataProvider, max_age: Duration) {
+    let slot = match dp.slot().await {
+        Ok(slot) => Some(slot as u64),
+        Err(e) => {
+            log::warn!("could not retrieve the tip to expire pending transactions: {e}");
+            None
+        }
+    };
+
+    let mut done = Vec::new();
+    for item in pending(cache).await {
+        if item.expired(slot, max_age) {
+            log::info!("pending transaction {} expired", item.tx_hash);
+            done.push(item.tx_hash);
+            continue;
+        }
+        let included = match dp.tx_included(&item.tx_hash).await {
+            Err(DataProviderError::Unsupported(_)) => outputs_on_chain(dp, &item).await,
+            included => included,
+        };
+        match included {
+            Ok(true) => done.push(item.tx_hash),
+            Ok(false) => {}
+            Err(e) => log::debug!("could not check transaction {}: {e}", item.tx_hash),
+        }
+    }
+
+    let mut cache = cache.lock().await;
+    for tx_hash in done {
+        cache.remove(&tx_hash);
+    }
+}
+
+/// For providers which can not look up transactions: a transaction is on-chain once one of
+/// its outputs resolves
+async fn outputs_on_chain(
+    dp: &dyn CardanoDataProvider,
+    item: &TxCacheItem,
+) -> Result<bool, DataProviderError> {
+    let hash = item.hash();
+    let refs: Vec<(TxHash, u32)> = (0..item.tx.body().outputs().len() as u32)
+        .map(|index| (hash.clone(), index))
+        .collect();
+    Ok(!dp.utxos_by_refs(&refs).await?.found.is_empty())
+}
+
+/// Prunes the cache about once per block
+pub(crate) async fn prune_loop(cache: LocalTxCache, max_age: Duration) {
+    let mut interval = tokio::time::interval(PRUNE_INTERVAL);
+    loop {
+        interval.tick().await;
+        prune(&cache, crate::server::provider().as_ref(), max_age).await;
+    }
+}
+
+fn spent_by(pending: &[TxCacheItem]) -> HashSet<OutputRef> {
+    let mut spent = HashSet::new();
+    for item in pending {
+        let inputs = item.tx.body().inputs();
+        for i in 0..inputs.len() {
+            let input = inputs.get(i);
+            spent.insert((input.transaction_id().to_bytes(), input.index()));
+        }
+    }
+    spent
+}
+
+/// Outputs of the pending transactions which are not spent by another pending transaction
+fn pending_outputs(
+    pending: &[TxCacheItem],
+    spent: &HashSet<OutputRef>,
+) -> Vec<TransactionUnspentOutput> {
+    let mut out = Vec::new();
+    for item in pending {
+        let hash = item.hash();
+        let outputs = item.tx.body().outputs();
+        for index in 0..outputs.len() as u32 {
+            if !spent.contains(&(hash.to_bytes(), index)) {
+                out.push(TransactionUnspentOutput::new(
+                    &csl::TransactionInput::new(&hash, index),
+                    &outputs.get(index as usize),
+                ));
+            }
+        }
+    }
+    out
+}
+
+fn output_ref(utxo: &TransactionUnspentOutput) -> OutputRef {
+    (utxo.input().transaction_id().to_bytes(), utxo.input().index())
+}
+
+/// `utxos` of `address` as they are once the pending transactions are on-chain: outputs spent
+/// by a pending transaction are removed, pending outputs to `address` are added
+pub(crate) fn apply_pending(
+    pending: &[TxCacheItem],
+    address: &csl::address::Address,
+    utxos: TransactionUnspentOutputs,
+) -> TransactionUnspentOutputs {
+    let spent = spent_by(pending);
+    let mut known = HashSet::new();
+    let mut out = TransactionUnspentOutputs::new();
+    for utxo in utxos {
+        let key = output_ref(&utxo);
+        if !spent.contains(&key) {
+            out.add(&utxo);
+        }
+        known.insert(key);
+    }
+    // a transaction stays pending until the next prune, its outputs may already be on-chain
+    for utxo in pending_outputs(pending, &spent) {
+        if utxo.output().address().to_bytes() == address.to_bytes()
+            && !known.contains(&output_ref(&utxo))
+        {
+            out.add(&utxo);
+        }
+    }
+    out
+}
+
+/// `resolved` as it is once the pending transactions are on-chain: references spent by a
+/// pending transaction are missing, references to pending outputs are found
+pub(crate) fn apply_pending_refs(
+    pending: &[TxCacheItem],
+    refs: &[(TxHash, u32)],
+    resolved: ResolvedUtxos,
+) -> ResolvedUtxos {
+    let spent = spent_by(pending);
+    let outputs: HashMap<OutputRef, TransactionUnspentOutput> = resolved
+        .found
+        .into_iter()
+        .chain(pending_outputs(pending, &spent))
+        .map(|utxo| (output_ref(&utxo), utxo))
+        .collect();
+
+    let mut out = ResolvedUtxos {
+        found: Vec::with_capacity(refs.len()),
+        missing: Vec::new(),
+    };
+    for (hash, index) in refs {
+        let key = (hash.to_bytes(), *index);
+        match outputs.get(&key) {
+            Some(utxo) if !spent.contains(&key) => out.found.push(utxo.clone()),
+            _ => out.missing.push((hash.clone(), *index)),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDR: &str = "addr1vyqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qavsj8u";
+    const OTHER_ADDR: &str = "addr1vy3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygs44r503";
+
+    fn hash(byte: u8) -> TxHash {
+        TxHash::from_bytes(vec![byte; 32]).unwrap()
+    }
+
+    fn output(addr: &str, coin: u64) -> csl::TransactionOutput {
+        csl::TransactionOutput::new(
+            &csl::address::Address::from_bech32(addr).unwrap(),
+            &csl::utils::Value::new(&csl::utils::to_bignum(coin)),
+        )
+    }
+
+    fn utxo(tx: &TxHash, index: u32, addr: &str) -> TransactionUnspentOutput {
+        TransactionUnspentOutput::new(&csl::TransactionInput::new(tx, index), &output(addr, 1))
+    }
+
+    /// spends `inputs` and pays 5 ada to ADDR and 3 ada to OTHER_ADDR
+    fn pending_tx(inputs: &[(TxHash, u32)]) -> TxCacheItem {
+        let mut ins = csl::TransactionInputs::new();
+        for (hash, index) in inputs {
+            ins.add(&csl::TransactionInput::new(hash, *index));
+        }
+        let mut outs = csl::TransactionOutputs::new();
+        outs.add(&output(ADDR, 5_000_000));
+        outs.add(&output(OTHER_ADDR, 3_000_000));
+        let body = csl::TransactionBody::new_tx_body(&ins, &outs, &csl::utils::to_bignum(170_000));
+        TxCacheItem::new(csl::Transaction::new(
+            &body,
+            &csl::TransactionWitnessSet::new(),
+            None,
+        ))
+    }
+
+    #[test]
+    fn test_apply_pending() {
+        let item = pending_tx(&[(hash(1), 0)]);
+        let mut utxos = TransactionUnspentOutputs::new();
+        utxos.add(&utxo(&hash(1), 0, ADDR));
+        utxos.add(&utxo(&hash(1), 1, ADDR));
+
+        let address = csl::address::Address::from_bech32(ADDR).unwrap();
+        let applied = apply_pending(&[item.clone()], &address, utxos);
+        assert_eq!(applied.len(), 2);
+        assert_eq!(applied.get(0).input().index(), 1);
+        assert_eq!(applied.get(1).input().transaction_id().to_hex(), item.tx_hash);
+        assert_eq!(applied.get(1).output().amount().coin().to_str(), "5000000");
+    }
+
+    #[test]
+    fn test_apply_pending_included() {
+        let item = pending_tx(&[(hash(1), 0)]);
+        let mut utxos = TransactionUnspentOutputs::new();
+        utxos.add(&utxo(&item.hash(), 0, ADDR));
+
+        let address = csl::address::Address::from_bech32(ADDR).unwrap();
+        let applied = apply_pending(&[item.clone()], &address, utxos);
+        assert_eq!(applied.len(), 1);
+        assert_eq!(applied.get(0).input().transaction_id().to_hex(), item.tx_hash);
+    }
+
+    #[test]
+    fn test_apply_pending_refs() {
+        let first = pending_tx(&[(hash(1), 0)]);
+        // the change of the first transaction is already spent by the second one
+        let second = pending_tx(&[(first.hash(), 0)]);
+        let resolved = ResolvedUtxos {
+            found: vec![utxo(&hash(1), 0, ADDR), utxo(&hash(1), 1, ADDR)],
+            missing: vec![],
+        };
+        let refs = [
+            (hash(1), 0),
+            (hash(1), 1),
+            (first.hash(), 0),
+            (first.hash(), 1),
+            (second.hash(), 0),
+        ];
+
+        let applied = apply_pending_refs(&[first.clone(), second.clone()], &refs, resolved);
+        let found: Vec<OutputRef> = applied.found.iter().map(output_ref).collect();
+        assert_eq!(
+            found,
+            vec![
+                (hash(1).to_bytes(), 1),
+                (first.hash().to_bytes(), 1),
+                (second.hash().to_bytes(), 0)
+            ]
+        );
+        let missing: Vec<OutputRef> = applied
+            .missing
+            .iter()
+            .map(|(tx, index)| (tx.to_bytes(), *index))
+            .collect();
+        assert_eq!(
+            missing,
+            vec![(hash(1).to_bytes(), 0), (first.hash().to_bytes(), 0)]
+        );
+    }
+
+    #[test]
+    fn test_decode_tx() {
+        let item = pending_tx(&[(hash(1), 0)]);
+        let decoded = decode_tx(&hex::encode(item.tx.to_bytes())).unwrap();
+        assert_eq!(TxCacheItem::new(decoded).tx_hash, item.tx_hash);
+        assert!(matches!(
+            decode_tx("84a4"),
+            Err(DataProviderError::InvalidInput(_))
+        ));
+        assert!(decode_tx("not hex").is_err());
+    }
+
+    #[test]
+    fn test_expired() {
+        let mut item = pending_tx(&[(hash(1), 0)]);
+        assert!(!item.expired(Some(100), DEFAULT_TTL));
+        assert!(item.expired(None, Duration::ZERO));
+        item.ttl = Some(100);
+        assert!(!item.expired(Some(99), DEFAULT_TTL));
+        assert!(item.expired(Some(100), DEFAULT_TTL));
+    }
+}
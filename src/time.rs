@@ -0,0 +1,185 @@
+//! Conversions between slots, epochs and POSIX time (seconds) based on the
+//! well-known values of a network. Slots before `shelley_known_slot` use the
+//! Byron slot and epoch lengths, all later slots the Shelley ones.
+use serde::Serialize;
+
+use crate::config::ChainWellKnownInfo;
+
+/// First and last slot of an epoch and the time span it covers,
+/// `end_time` is the start of the following epoch
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct EpochBounds {
+    pub epoch: u64,
+    pub first_slot: u64,
+    pub last_slot: u64,
+    pub start_time: u64,
+    pub end_time: u64,
+}
+
+fn byron_slots_per_epoch(info: &ChainWellKnownInfo) -> u64 {
+    (info.byron_epoch_length / info.byron_slot_length) as u64
+}
+
+fn shelley_slots_per_epoch(info: &ChainWellKnownInfo) -> u64 {
+    (info.shelley_epoch_length / info.shelley_slot_length) as u64
+}
+
+/// epoch in which the Shelley era starts
+fn shelley_start_epoch(info: &ChainWellKnownInfo) -> u64 {
+    (info.shelley_known_slot - info.byron_known_slot) / byron_slots_per_epoch(info)
+}
+
+/// POSIX time at the beginning of `slot`, `None` if it does not fit into 64 bits
+pub fn slot_to_time(info: &ChainWellKnownInfo, slot: u64) -> Option<u64> {
+    if slot < info.shelley_known_slot {
+        (slot - info.byron_known_slot)
+            .checked_mul(info.byron_slot_length as u64)?
+            .checked_add(info.byron_known_time)
+    } else {
+        (slot - info.shelley_known_slot)
+            .checked_mul(info.shelley_slot_length as u64)?
+            .checked_add(info.shelley_known_time)
+    }
+}
+
+/// Slot containing the POSIX `time`, `None` if the time lies before the start of the chain or
+/// the slot does not fit into 64 bits
+pub fn time_to_slot(info: &ChainWellKnownInfo, time: u64) -> Option<u64> {
+    if time >= info.shelley_known_time {
+        let elapsed = time - info.shelley_known_time;
+        info.shelley_known_slot
+            .checked_add(elapsed / info.shelley_slot_length as u64)
+    } else if time >= info.byron_known_time {
+        let elapsed = time - info.byron_known_time;
+        info.byron_known_slot
+            .checked_add(elapsed / info.byron_slot_length as u64)
+    } else {
+        None
+    }
+}
+
+/// Epoch of `slot` and the position of the slot within that epoch
+pub fn slot_to_epoch(info: &ChainWellKnownInfo, slot: u64) -> (u64, u64) {
+    if slot < info.shelley_known_slot {
+        let byron_slot = slot - info.byron_known_slot;
+        let slots_per_epoch = byron_slots_per_epoch(info);
+        (byron_slot / slots_per_epoch, byron_slot % slots_per_epoch)
+    } else {
+        let shelley_slot = slot - info.shelley_known_slot;
+        let slots_per_epoch = shelley_slots_per_epoch(info);
+        (
+            shelley_start_epoch(info) + shelley_slot / slots_per_epoch,
+            shelley_slot % slots_per_epoch,
+        )
+    }
+}
+
+/// Slots and time span of `epoch`, `None` if they do not fit into 64 bits
+pub fn epoch_bounds(info: &ChainWellKnownInfo, epoch: u64) -> Option<EpochBounds> {
+    let shelley_start = shelley_start_epoch(info);
+    let (first_slot, slots_per_epoch) = if epoch < shelley_start {
+        let slots_per_epoch = byron_slots_per_epoch(info);
+        (
+            epoch
+                .checked_mul(slots_per_epoch)?
+                .checked_add(info.byron_known_slot)?,
+            slots_per_epoch,
+        )
+    } else {
+        let slots_per_epoch = shelley_slots_per_epoch(info);
+        (
+            (epoch - shelley_start)
+                .checked_mul(slots_per_epoch)?
+                .checked_add(info.shelley_known_slot)?,
+            slots_per_epoch,
+        )
+    };
+    let next_epoch_slot = first_slot.checked_add(slots_per_epoch)?;
+    Some(EpochBounds {
+        epoch,
+        first_slot,
+        last_slot: next_epoch_slot - 1,
+        start_time: slot_to_time(info, first_slot)?,
+        end_time: slot_to_time(info, next_epoch_slot)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mainnet_byron_shelley_boundary() {
+        let info = ChainWellKnownInfo::mainnet();
+
+        assert_eq!(slot_to_time(&info, 0), Some(1506203091));
+        assert_eq!(slot_to_time(&info, 4492799), Some(1596059071));
+        assert_eq!(slot_to_time(&info, 4492800), Some(1596059091));
+        assert_eq!(slot_to_epoch(&info, 4492799), (207, 21599));
+        assert_eq!(slot_to_epoch(&info, 4492800), (208, 0));
+
+        assert_eq!(time_to_slot(&info, 1506203090), None);
+        assert_eq!(time_to_slot(&info, 1506203091 + 25), Some(1));
+        assert_eq!(time_to_slot(&info, 1596059090), Some(4492799));
+        assert_eq!(time_to_slot(&info, 1596059091), Some(4492800));
+
+        let byron = epoch_bounds(&info, 207).unwrap();
+        assert_eq!((byron.first_slot, byron.last_slot), (4471200, 4492799));
+        assert_eq!(byron.end_time, 1596059091);
+    }
+
+    #[test]
+    fn mainnet_shelley() {
+        let info = ChainWellKnownInfo::mainnet();
+
+        // epoch 365 is the first epoch of the Babbage era
+        assert_eq!(slot_to_time(&info, 72316896), Some(1663883187));
+        assert_eq!(slot_to_epoch(&info, 72316896), (365, 96));
+        assert_eq!(time_to_slot(&info, 1663883187), Some(72316896));
+        assert_eq!(
+            epoch_bounds(&info, 365),
+            Some(EpochBounds {
+                epoch: 365,
+                first_slot: 72316800,
+                last_slot: 72748799,
+                start_time: 1663883091,
+                end_time: 1664315091,
+            })
+        );
+    }
+
+    #[test]
+    fn preprod() {
+        let info = ChainWellKnownInfo::preprod();
+
+        assert_eq!(slot_to_time(&info, 86399), Some(1655769580));
+        assert_eq!(slot_to_time(&info, 86400), Some(1655769600));
+        assert_eq!(slot_to_epoch(&info, 86400), (4, 0));
+        assert_eq!(time_to_slot(&info, 1659225600), Some(3542400));
+        assert_eq!(epoch_bounds(&info, 12).unwrap().first_slot, 3542400);
+        assert_eq!(epoch_bounds(&info, 3).unwrap().last_slot, 86399);
+    }
+
+    #[test]
+    fn preview() {
+        let info = ChainWellKnownInfo::preview();
+
+        assert_eq!(slot_to_time(&info, 0), Some(1666656000));
+        assert_eq!(slot_to_epoch(&info, 86400 * 3 + 5), (3, 5));
+        assert_eq!(epoch_bounds(&info, 1).unwrap().start_time, 1666742400);
+    }
+
+    #[test]
+    fn overflow() {
+        let info = ChainWellKnownInfo::mainnet();
+
+        assert_eq!(slot_to_time(&info, u64::MAX), None);
+        assert_eq!(
+            time_to_slot(&info, u64::MAX),
+            Some(4492800 + (u64::MAX - 1596059091))
+        );
+        assert_eq!(epoch_bounds(&info, u64::MAX), None);
+        // the slots of the epoch fit, its start time does not
+        assert_eq!(epoch_bounds(&info, u64::MAX / 432000), None);
+    }
+}